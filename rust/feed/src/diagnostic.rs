@@ -0,0 +1,236 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Machine readable diagnostics
+//!
+//! Contains a stable representation of findings within a feed so that CI systems and editors
+//! do not need to parse human readable messages. Serialized as JSON a diagnostic looks like:
+//!
+//! ```json
+//! {
+//!   "file": "test.nasl",
+//!   "line": 2,
+//!   "col": 7,
+//!   "severity": "error",
+//!   "message": "unclosed token: ...",
+//!   "code": "unclosed-token"
+//! }
+//! ```
+//!
+//! `line` and `col` start at 1, they are 0 when the finding is not bound to a location within
+//! the file (e.g. a wrong hashsum). `severity` is either `error` or `warning`; `code` is a
+//! kebab-case identifier of the kind of finding that does not change between releases.
+
+use nasl_syntax::SyntaxError;
+use serde::{Deserialize, Serialize};
+
+use crate::verify;
+
+/// Severity of a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The file is broken and must be fixed
+    Error,
+    /// The file is likely not working as intended
+    Warning,
+}
+
+/// A single finding within a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// The affected file
+    pub file: String,
+    /// The line starting at 1 or 0 when unknown
+    pub line: usize,
+    /// The column starting at 1 or 0 when unknown
+    pub col: usize,
+    /// The severity of the finding
+    pub severity: Severity,
+    /// Human readable description of the finding
+    pub message: String,
+    /// Stable identifier of the kind of the finding
+    pub code: String,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic of a SyntaxError found in the given file
+    pub fn from_syntax_error(file: &str, err: &SyntaxError) -> Self {
+        let (line, col) = err.line_column().unwrap_or_default();
+        Self {
+            file: file.to_owned(),
+            line,
+            col,
+            severity: Severity::Error,
+            message: err.to_string(),
+            code: err.kind().code().to_owned(),
+        }
+    }
+
+    /// Creates a diagnostic of a verify error
+    ///
+    /// The file is either the affected file or the sums file when the error is not bound to a
+    /// single file.
+    pub fn from_verify_error(err: &verify::Error) -> Self {
+        use nasl_syntax::LoadError;
+        let sums = || verify::Hasher::Sha256.sum_file().to_owned();
+        let (file, code) = match err {
+            verify::Error::SumsFileCorrupt(h) => (h.sum_file().to_owned(), "sums-file-corrupt"),
+            verify::Error::LoadError(
+                LoadError::Retry(f)
+                | LoadError::NotFound(f)
+                | LoadError::PermissionDenied(f)
+                | LoadError::Dirty(f),
+            ) => (f.clone(), "load-error"),
+            verify::Error::HashInvalid { key, .. } => (key.clone(), "hash-invalid"),
+            verify::Error::BadSignature(_) => (sums(), "bad-signature"),
+            verify::Error::MissingKeyring => (sums(), "missing-keyring"),
+        };
+        Self {
+            file,
+            line: 0,
+            col: 0,
+            severity: Severity::Error,
+            message: err.to_string(),
+            code: code.to_owned(),
+        }
+    }
+}
+
+/// Minimal [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) log
+///
+/// It contains a single run with a result for each diagnostic so that e.g. code scanning of
+/// GitHub can ingest them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SarifLog {
+    /// URI of the used schema
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    /// SARIF version, always 2.1.0
+    pub version: String,
+    /// The runs of the tool, it always contains exactly one run
+    pub runs: Vec<SarifRun>,
+}
+
+/// A single run of a tool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SarifRun {
+    /// The tool that produced the results
+    pub tool: SarifTool,
+    /// The findings
+    pub results: Vec<SarifResult>,
+}
+
+/// Describes the tool that produced the results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SarifTool {
+    /// The tool component
+    pub driver: SarifDriver,
+}
+
+/// The tool component that produced the results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SarifDriver {
+    /// Name of the tool
+    pub name: String,
+}
+
+/// A single finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    /// The code of the diagnostic
+    pub rule_id: String,
+    /// The severity, either `error` or `warning`
+    pub level: Severity,
+    /// The message of the diagnostic
+    pub message: SarifMessage,
+    /// The location of the finding
+    pub locations: Vec<SarifLocation>,
+}
+
+/// A text message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SarifMessage {
+    /// The message
+    pub text: String,
+}
+
+/// A location of a finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    /// The location within a file
+    pub physical_location: SarifPhysicalLocation,
+}
+
+/// A location within a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    /// The affected file
+    pub artifact_location: SarifArtifactLocation,
+    /// The affected region, omitted when the finding concerns the whole file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<SarifRegion>,
+}
+
+/// The location of a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SarifArtifactLocation {
+    /// The path of the file
+    pub uri: String,
+}
+
+/// A region within a file, line and column start at 1
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    /// The line of the finding
+    pub start_line: usize,
+    /// The column of the finding
+    pub start_column: usize,
+}
+
+impl From<&Diagnostic> for SarifResult {
+    fn from(d: &Diagnostic) -> Self {
+        let region = (d.line > 0).then(|| SarifRegion {
+            start_line: d.line,
+            start_column: d.col.max(1),
+        });
+        Self {
+            rule_id: d.code.clone(),
+            level: d.severity,
+            message: SarifMessage {
+                text: d.message.clone(),
+            },
+            locations: vec![SarifLocation {
+                physical_location: SarifPhysicalLocation {
+                    artifact_location: SarifArtifactLocation {
+                        uri: d.file.clone(),
+                    },
+                    region,
+                },
+            }],
+        }
+    }
+}
+
+impl SarifLog {
+    /// Creates a log containing a single run of the given tool with the given diagnostics
+    pub fn new(tool: &str, diagnostics: &[Diagnostic]) -> Self {
+        Self {
+            schema: "https://json.schemastore.org/sarif-2.1.0.json".to_owned(),
+            version: "2.1.0".to_owned(),
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: tool.to_owned(),
+                    },
+                },
+                results: diagnostics.iter().map(SarifResult::from).collect(),
+            }],
+        }
+    }
+}
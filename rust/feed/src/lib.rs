@@ -4,11 +4,16 @@
 
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
+pub mod diagnostic;
+pub mod lint;
 mod oid;
 pub mod transpile;
 mod update;
 pub mod verify;
 
+pub use diagnostic::Diagnostic;
+pub use diagnostic::SarifLog;
+pub use diagnostic::Severity;
 pub use oid::Oid;
 pub use update::feed_version as version;
 pub use update::Error as UpdateError;
@@ -19,4 +24,5 @@ pub use verify::FileNameLoader;
 pub use verify::HashSumNameLoader;
 pub use verify::Hasher;
 pub use verify::NaslFileFinder;
+pub use verify::Report as VerifyReport;
 pub use verify::SignatureChecker;
@@ -0,0 +1,89 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+use nasl_syntax::{Statement, StatementKind, TokenCategory};
+
+use crate::Diagnostic;
+
+use super::Lint;
+
+/// Reports assignments used as a condition, e.g. `if (a = b)` instead of `if (a == b)`
+///
+/// Checked are the conditions of `if`, `while`, `repeat ... until` and `for`. An intended
+/// assignment can be silenced by an additional pair of parentheses: `if ((a = b))`.
+pub struct AssignInCondition;
+
+/// Returns the amount of `(` directly in front of the given byte position ignoring whitespace
+fn open_parens_before(code: &str, position: usize) -> usize {
+    code.get(..position)
+        .unwrap_or_default()
+        .chars()
+        .rev()
+        .filter(|c| !c.is_whitespace())
+        .take_while(|c| *c == '(')
+        .count()
+}
+
+impl Lint for AssignInCondition {
+    fn check(&self, file: &str, code: &str, statement: &Statement) -> Vec<Diagnostic> {
+        let mut results = vec![];
+        statement.walk(&mut |s| {
+            // the amount of parentheses required by the syntax around the condition
+            let (condition, required) = match s.kind() {
+                StatementKind::If(x) => (&x.condition, 1),
+                StatementKind::While(c, _) | StatementKind::Repeat(_, c) => (&**c, 1),
+                StatementKind::For(x) => (&x.condition, 0),
+                _ => return,
+            };
+            if let StatementKind::Assign(TokenCategory::Equal, _, left, _) = condition.kind() {
+                if open_parens_before(code, left.as_token().position.0) <= required {
+                    results.push(super::warning(
+                        file,
+                        left,
+                        "assign-in-condition",
+                        format!("assignment used as condition, did you mean `==`? {condition}"),
+                    ));
+                }
+            }
+        });
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::{check, AssignInCondition};
+
+    fn codes(code: &str) -> Vec<String> {
+        check("test.nasl", code, &[&AssignInCondition])
+            .into_iter()
+            .map(|x| x.code)
+            .collect()
+    }
+
+    #[test]
+    fn assignment_as_condition() {
+        assert_eq!(codes("if (a = 1) b;"), vec!["assign-in-condition"]);
+        assert_eq!(codes("while ( a = 1 ) b;"), vec!["assign-in-condition"]);
+        assert_eq!(
+            codes("repeat b; until (a = 1);"),
+            vec!["assign-in-condition"]
+        );
+        assert_eq!(
+            codes("for (i = 0; i = 1; i++) b;"),
+            vec!["assign-in-condition"]
+        );
+        let results = check("test.nasl", "\nif (a = 1) b;", &[&AssignInCondition]);
+        assert_eq!((results[0].line, results[0].col), (2, 5));
+    }
+
+    #[test]
+    fn comparison_or_double_paren() {
+        assert!(codes("if (a == 1) b;").is_empty());
+        assert!(codes("if ((a = 1)) b;").is_empty());
+        assert!(codes("while (( a = 1 )) b;").is_empty());
+        assert!(codes("for (i = 0; (i = 1); i++) b;").is_empty());
+        assert!(codes("if (a) b = 1;").is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+use std::{
+    cell::RefCell,
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use nasl_interpreter::{interpreter_fn_defined, Loader, NaslFunctionExecuter};
+use nasl_syntax::{IdentifierType, Statement, StatementKind, TokenCategory};
+
+use crate::Diagnostic;
+
+use super::Lint;
+
+/// Reports calls of functions that are neither builtins nor declared
+///
+/// A function is declared when it is either declared within the checked script, within a
+/// script that is included by it or within code given via `declare`, e.g. each script of the
+/// feed. Includes are only resolved when a loader is set.
+///
+/// This catches typos like `scirpt_oid`.
+pub struct UndefinedFunction<'a> {
+    builtins: &'a dyn NaslFunctionExecuter,
+    loader: Option<&'a dyn Loader>,
+    declared: HashSet<String>,
+    /// The functions declared in the last checked file and its includes, identified by the
+    /// file name and the hash of its code
+    in_file: RefCell<Option<(String, u64, HashSet<String>)>>,
+}
+
+fn identifier(stmt: &Statement) -> Option<&str> {
+    match stmt.as_token().category() {
+        TokenCategory::Identifier(IdentifierType::Undefined(x)) => Some(x),
+        _ => None,
+    }
+}
+
+fn declarations(code: &str) -> (HashSet<String>, Vec<String>) {
+    let mut functions = HashSet::new();
+    let mut includes = vec![];
+    for stmt in nasl_syntax::parse(code).filter_map(|x| x.ok()) {
+        stmt.walk(&mut |s| match s.kind() {
+            StatementKind::FunctionDeclaration(f) => {
+                if let TokenCategory::Identifier(IdentifierType::Undefined(x)) = f.name.category() {
+                    functions.insert(x.to_owned());
+                }
+            }
+            StatementKind::Include(x) => match x.as_token().category() {
+                TokenCategory::String(x) => includes.push(x.to_owned()),
+                TokenCategory::Data(x) => includes.push(x.iter().map(|x| *x as char).collect()),
+                _ => {}
+            },
+            _ => {}
+        });
+    }
+    (functions, includes)
+}
+
+impl<'a> UndefinedFunction<'a> {
+    /// Creates a new lint based on the given builtin functions
+    pub fn new(builtins: &'a dyn NaslFunctionExecuter) -> Self {
+        Self {
+            builtins,
+            loader: None,
+            declared: HashSet::new(),
+            in_file: RefCell::new(None),
+        }
+    }
+
+    /// Sets the loader used to resolve includes
+    pub fn with_loader(mut self, loader: &'a dyn Loader) -> Self {
+        self.loader = Some(loader);
+        self
+    }
+
+    /// Adds each function declared in the given code
+    pub fn declare(&mut self, code: &str) {
+        self.declared.extend(declarations(code).0);
+    }
+
+    /// Returns the functions declared in the code and in each of its includes
+    fn declared_in(&self, code: &str) -> HashSet<String> {
+        let (mut functions, mut includes) = declarations(code);
+        let mut loaded = HashSet::new();
+        while let Some(include) = includes.pop() {
+            if !loaded.insert(include.clone()) {
+                continue;
+            }
+            if let Some(code) = self.loader.and_then(|l| l.load(&include).ok()) {
+                let (f, i) = declarations(&code);
+                functions.extend(f);
+                includes.extend(i);
+            }
+        }
+        functions
+    }
+}
+
+impl<'a> Lint for UndefinedFunction<'a> {
+    fn check(&self, file: &str, code: &str, statement: &Statement) -> Vec<Diagnostic> {
+        let mut calls = vec![];
+        statement.walk(&mut |s| {
+            if let StatementKind::Call(_) = s.kind() {
+                if let Some(name) = identifier(s) {
+                    if !self.builtins.nasl_fn_defined(name)
+                        && !interpreter_fn_defined(name)
+                        && !self.declared.contains(name)
+                    {
+                        calls.push((name, s));
+                    }
+                }
+            }
+        });
+        if calls.is_empty() {
+            return vec![];
+        }
+        // only done on the first miss within a file as it requires parsing the script and its
+        // includes, the following statements of the same file reuse the result
+        let mut hasher = DefaultHasher::new();
+        code.hash(&mut hasher);
+        let hash = hasher.finish();
+        let mut in_file = self.in_file.borrow_mut();
+        let declared = match in_file.as_ref() {
+            Some((cached, h, declared)) if cached == file && *h == hash => declared,
+            _ => {
+                &in_file
+                    .insert((file.to_owned(), hash, self.declared_in(code)))
+                    .2
+            }
+        };
+        calls
+            .into_iter()
+            .filter(|(name, _)| !declared.contains(*name))
+            .map(|(name, s)| {
+                super::warning(
+                    file,
+                    s,
+                    "undefined-function",
+                    format!("{name} is neither a builtin nor a declared function"),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use nasl_interpreter::{nasl_std_functions, LoadError, Loader};
+
+    use crate::lint::{check, UndefinedFunction};
+
+    struct Includes(HashMap<String, String>);
+
+    impl Loader for Includes {
+        fn load(&self, key: &str) -> Result<String, LoadError> {
+            self.0
+                .get(key)
+                .cloned()
+                .ok_or_else(|| LoadError::NotFound(key.to_owned()))
+        }
+
+        fn root_path(&self) -> Result<String, LoadError> {
+            Ok(String::default())
+        }
+    }
+
+    #[test]
+    fn misspelled_builtin() {
+        let builtins = nasl_std_functions();
+        let lint = UndefinedFunction::new(&builtins);
+        let results = check("test.nasl", "a = scirpt_oid();", &[&lint]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].code, "undefined-function");
+        assert_eq!((results[0].line, results[0].col), (1, 5));
+    }
+
+    #[test]
+    fn defined_functions() {
+        let builtins = nasl_std_functions();
+        let loader = Includes(HashMap::from([
+            ("a.inc".to_owned(), "include('b.inc');".to_owned()),
+            ("b.inc".to_owned(), "function from_b() {}".to_owned()),
+        ]));
+        let mut lint = UndefinedFunction::new(&builtins).with_loader(&loader);
+        lint.declare("function from_feed() { return 1; }");
+        let code = r#"
+        include("a.inc");
+        script_oid("1.2.3");
+        local();
+        from_b();
+        from_feed();
+        function local() { return 1; }
+        "#;
+        assert_eq!(check("test.nasl", code, &[&lint]), vec![]);
+    }
+
+    #[test]
+    fn interpreter_builtins() {
+        let builtins = nasl_std_functions();
+        let lint = UndefinedFunction::new(&builtins);
+        let code = r#"
+        a = eval_arithmetic("1 + 2");
+        b = array_map(f: "local", array: make_list(1));
+        c = get_time_remaining();
+        "#;
+        assert_eq!(check("test.nasl", code, &[&lint]), vec![]);
+    }
+
+    #[test]
+    fn includes_are_loaded_once_per_file() {
+        use std::cell::Cell;
+
+        struct Counting(Cell<usize>);
+
+        impl Loader for Counting {
+            fn load(&self, key: &str) -> Result<String, LoadError> {
+                self.0.set(self.0.get() + 1);
+                match key {
+                    "a.inc" => Ok("function from_a() {}".to_owned()),
+                    _ => Err(LoadError::NotFound(key.to_owned())),
+                }
+            }
+
+            fn root_path(&self) -> Result<String, LoadError> {
+                Ok(String::default())
+            }
+        }
+
+        let builtins = nasl_std_functions();
+        let loader = Counting(Cell::new(0));
+        let lint = UndefinedFunction::new(&builtins).with_loader(&loader);
+        let code = "include('a.inc');\nfrom_a();\nfrom_a();\nmissing();\nmissing();";
+        assert_eq!(check("a.nasl", code, &[&lint]).len(), 2);
+        assert_eq!(loader.0.get(), 1);
+        assert_eq!(check("b.nasl", code, &[&lint]).len(), 2);
+        assert_eq!(loader.0.get(), 2);
+        // a changed file of the same name is parsed again
+        let changed = "missing();\nfunction missing() {}";
+        assert_eq!(check("b.nasl", changed, &[&lint]), vec![]);
+    }
+}
@@ -0,0 +1,72 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Lints NASL scripts of a feed
+//!
+//! A lint walks through the statements of a script and reports suspicious code as a
+//! [Diagnostic](crate::Diagnostic) with the severity warning.
+//!
+//! # Example
+//!
+//! ```
+//! let code = "function a() { return 1; display('dead'); }";
+//! let lints: &[&dyn feed::lint::Lint] = &[
+//!     &feed::lint::UnreachableCode,
+//!     &feed::lint::AssignInCondition,
+//! ];
+//! let results = feed::lint::check("test.nasl", code, lints);
+//! assert_eq!(results.len(), 1);
+//! assert_eq!(results[0].code, "unreachable-code");
+//! ```
+mod condition;
+mod function;
+mod unreachable;
+
+use nasl_syntax::Statement;
+
+use crate::{Diagnostic, Severity};
+
+pub use condition::AssignInCondition;
+pub use function::UndefinedFunction;
+pub use unreachable::UnreachableCode;
+
+/// Checks a top level statement of a script
+pub trait Lint {
+    /// Returns the findings within the given statement
+    ///
+    /// The code is the whole script the statement got parsed from; the positions of the tokens
+    /// are byte positions within it.
+    fn check(&self, file: &str, code: &str, statement: &Statement) -> Vec<Diagnostic>;
+}
+
+/// Runs each lint on each statement of the given code
+///
+/// Syntax errors are returned as diagnostics as well.
+pub fn check(file: &str, code: &str, lints: &[&dyn Lint]) -> Vec<Diagnostic> {
+    let mut results = vec![];
+    for stmt in nasl_syntax::parse(code) {
+        match stmt {
+            Ok(stmt) => {
+                for lint in lints {
+                    results.extend(lint.check(file, code, &stmt));
+                }
+            }
+            Err(e) => results.push(Diagnostic::from_syntax_error(file, &e)),
+        }
+    }
+    results
+}
+
+/// Creates a warning pointing to the given statement
+fn warning(file: &str, statement: &Statement, code: &str, message: String) -> Diagnostic {
+    let (line, col) = statement.as_token().line_column;
+    Diagnostic {
+        file: file.to_owned(),
+        line,
+        col,
+        severity: Severity::Warning,
+        message,
+        code: code.to_owned(),
+    }
+}
@@ -0,0 +1,92 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+use nasl_syntax::{Statement, StatementKind};
+
+use crate::Diagnostic;
+
+use super::Lint;
+
+/// Reports statements within a block that follow a `return`, `exit`, `break` or `continue`
+///
+/// Only the first unreachable statement of a block is reported. Statements that are only
+/// conditionally skipped, e.g. after an `if` containing a `return`, are not reported.
+pub struct UnreachableCode;
+
+fn exit_name(kind: &StatementKind) -> Option<&'static str> {
+    match kind {
+        StatementKind::Return(_) => Some("return"),
+        StatementKind::Exit(_) => Some("exit"),
+        StatementKind::Break(_) => Some("break"),
+        StatementKind::Continue(_) => Some("continue"),
+        _ => None,
+    }
+}
+
+impl Lint for UnreachableCode {
+    fn check(&self, file: &str, _: &str, statement: &Statement) -> Vec<Diagnostic> {
+        let mut results = vec![];
+        statement.walk(&mut |s| {
+            if let StatementKind::Block(stmts) = s.kind() {
+                let mut exit = None;
+                for stmt in stmts {
+                    if matches!(stmt.kind(), StatementKind::NoOp) {
+                        continue;
+                    }
+                    if let Some(name) = exit {
+                        results.push(super::warning(
+                            file,
+                            stmt,
+                            "unreachable-code",
+                            format!("statement is unreachable after {name}: {stmt}"),
+                        ));
+                        break;
+                    }
+                    exit = exit_name(stmt.kind());
+                }
+            }
+        });
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::{check, UnreachableCode};
+
+    #[test]
+    fn dead_statement_after_return() {
+        let code = r#"
+        function test(a) {
+            return a;
+            display(a);
+            display(a);
+        }
+        while (TRUE) { break; a = 1; }
+        foreach x(y) { exit(0); ; }
+        "#;
+        let results = check("test.nasl", code, &[&UnreachableCode]);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].code, "unreachable-code");
+        assert_eq!((results[0].line, results[0].col), (4, 13));
+        assert_eq!((results[1].line, results[1].col), (7, 31));
+    }
+
+    #[test]
+    fn conditionally_reachable() {
+        let code = r#"
+        function test(a) {
+            if (a) {
+                return 1;
+            } else {
+                a = 2;
+            }
+            if (a) return 1;
+            display(a);
+            return a;
+        }
+        "#;
+        assert_eq!(check("test.nasl", code, &[&UnreachableCode]), vec![]);
+    }
+}
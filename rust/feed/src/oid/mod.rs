@@ -57,8 +57,8 @@ where
     fn single(&self, key: String) -> Result<String, update::ErrorKind> {
         let code = self.loader.load(key.as_ref())?;
         for stmt in nasl_syntax::parse(&code) {
-            if let StatementKind::If(_, stmts, _, _) = stmt?.kind() {
-                if let StatementKind::Block(x) = stmts.kind() {
+            if let StatementKind::If(stmts) = stmt?.kind() {
+                if let StatementKind::Block(x) = stmts.then.kind() {
                     for stmt in x {
                         if let Some(oid) = Self::script_oid(stmt) {
                             return Ok(oid);
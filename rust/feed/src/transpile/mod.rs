@@ -170,10 +170,10 @@ impl<'a> FunctionNameMatcher<'a> {
                     false
                 }
             }
-            StatementKind::FunctionDeclaration(id, ..) => {
+            StatementKind::FunctionDeclaration(f) => {
                 if let nasl_syntax::TokenCategory::Identifier(
                     nasl_syntax::IdentifierType::Undefined(ref x),
-                ) = id.category()
+                ) = f.name.category()
                 {
                     self.name.map(|y| x == y).unwrap_or(true)
                 } else {
@@ -216,13 +216,13 @@ impl<'a> Matcher for FunctionNameMatcher<'a> {
                     .count();
                 (named, anon)
             }
-            StatementKind::FunctionDeclaration(_, p, _block) => {
+            StatementKind::FunctionDeclaration(f) => {
                 let anon = {
                     // we don't know how many anon parameter an declared method is using.
                     // Theoretically we could guess by checking _block for _FC_ANON_ARGS and return
                     // the given indices number when available
                     //
-                    // let fcta = _block.find(&|x| {
+                    // let fcta = f.body.find(&|x| {
                     //     use nasl_syntax::{IdentifierType as IT, Token, TokenCategory as TC};
                     //     matches!(
                     //         x,
@@ -246,7 +246,8 @@ impl<'a> Matcher for FunctionNameMatcher<'a> {
                     // than wrongly changed anon parameter.
                     0
                 };
-                let named = p
+                let named = f
+                    .parameters
                     .children()
                     .iter()
                     .filter_map(|p| match p.kind() {
@@ -396,8 +397,8 @@ impl CodeReplacer {
             Replace::Name(name) => match s.kind() {
                 // TODO introduce a id method on statement so that one has not
                 // to differentiate when renaming
-                StatementKind::FunctionDeclaration(n, ..) => {
-                    self.replace_range_with_offset(name, &n.position);
+                StatementKind::FunctionDeclaration(f) => {
+                    self.replace_range_with_offset(name, &f.name.position);
                     Ok(())
                 }
                 StatementKind::Call(..) | StatementKind::Exit(..) | StatementKind::Include(..) => {
@@ -408,8 +409,8 @@ impl CodeReplacer {
             },
             Replace::Parameter(params) => {
                 let parameter = match s.kind() {
-                    StatementKind::FunctionDeclaration(_, stmt, ..)
-                    | StatementKind::Call(stmt)
+                    StatementKind::FunctionDeclaration(f) => &f.parameters,
+                    StatementKind::Call(stmt)
                     | StatementKind::Exit(stmt, ..)
                     | StatementKind::Include(stmt, ..) => stmt,
                     _ => return Err(ReplaceError::Unsupported(r.clone(), s.clone())),
@@ -504,10 +505,10 @@ impl CodeReplacer {
         }
 
         if let Some((pos, np)) = match s.kind() {
-            StatementKind::FunctionDeclaration(_, args, _) => {
-                let rp = args.end();
+            StatementKind::FunctionDeclaration(f) => {
+                let rp = f.parameters.end();
 
-                calculate_fn_decl(p, args.children().is_empty()).map(|x| (rp.position, x))
+                calculate_fn_decl(p, f.parameters.children().is_empty()).map(|x| (rp.position, x))
             }
             StatementKind::Call(args) => {
                 let rp = args.end();
@@ -562,30 +563,30 @@ impl CodeReplacer {
             };
             Some(np)
         }
-        match s.kind() {
-            // TODO change params from Vec<Statement> to a struct either to make it easier to identify start and end
-            StatementKind::FunctionDeclaration(_, params, _) | StatementKind::Call(params)
-                if i <= params.children().len() || i == 0 =>
-            {
-                let get = &params.children().get(i);
-                let index_exits = get.iter().map(|t| t.position()).next();
-                let np = if index_exits.is_some() {
-                    calculate_known_index(s, p)
-                } else {
-                    calculate_unknown_index(s, p, params.children())
-                };
+        let params = match s.kind() {
+            StatementKind::FunctionDeclaration(f) => &f.parameters,
+            StatementKind::Call(params) => params,
+            _ => return,
+        };
+        // TODO change params from Vec<Statement> to a struct either to make it easier to identify start and end
+        if i <= params.children().len() || i == 0 {
+            let get = &params.children().get(i);
+            let index_exits = get.iter().map(|t| t.position()).next();
+            let np = if index_exits.is_some() {
+                calculate_known_index(s, p)
+            } else {
+                calculate_unknown_index(s, p, params.children())
+            };
 
-                if let Some(stringus) = np {
-                    let position = index_exits.unwrap_or_else(|| {
-                        // TODO reduct on empty
-                        params.end().position
-                    });
-                    let new_position = self.range_with_offset(&position);
-                    let before = &self.code[new_position.0..new_position.1];
-                    self.replace_range(&new_position, &format!("{stringus}{before}"), &position);
-                }
+            if let Some(stringus) = np {
+                let position = index_exits.unwrap_or_else(|| {
+                    // TODO reduct on empty
+                    params.end().position
+                });
+                let new_position = self.range_with_offset(&position);
+                let before = &self.code[new_position.0..new_position.1];
+                self.replace_range(&new_position, &format!("{stringus}{before}"), &position);
             }
-            _ => {}
         }
     }
 
@@ -617,15 +618,13 @@ impl CodeReplacer {
         self.replace_range(&new_position, "", &new_position);
     }
     fn remove_indexed_parameter(&mut self, s: &Statement, i: usize) {
-        match s.kind() {
-            StatementKind::FunctionDeclaration(_, stmts, ..) | StatementKind::Call(stmts) => {
-                let children = stmts.children();
-
-                if let Some(x) = children.get(i) {
-                    self.remove_parameter(children, i, x)
-                }
-            }
-            _ => {}
+        let children = match s.kind() {
+            StatementKind::FunctionDeclaration(f) => f.parameters.children(),
+            StatementKind::Call(stmts) => stmts.children(),
+            _ => return,
+        };
+        if let Some(x) = children.get(i) {
+            self.remove_parameter(children, i, x)
         }
     }
 
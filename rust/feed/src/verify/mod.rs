@@ -313,6 +313,58 @@ impl<'a, R: Read> HashSumNameLoader<'a, R> {
     }
 }
 
+/// Contains the outcome of verifying each entry of a sums file
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// Amount of successfully verified files
+    pub verified: usize,
+    /// Errors of each file that failed to verify
+    pub errors: Vec<Error>,
+}
+
+impl Report {
+    /// Returns true when no file failed to verify
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns a process exit code for this report
+    ///
+    /// It is 0 when each file got verified otherwise it is the amount of failed files capped at
+    /// 255 so that it does not wrap around to success.
+    pub fn exit_code(&self) -> i32 {
+        self.errors.len().min(255) as i32
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "verified: {} files; failed: {} files",
+            self.verified,
+            self.errors.len()
+        )
+    }
+}
+
+impl<'a, R: Read> HashSumNameLoader<'a, R> {
+    /// Verifies each entry of the sums file and aggregates the results
+    ///
+    /// In difference to iterating it does not stop on the first error but collects the errors
+    /// of each entry.
+    pub fn verify_all(self) -> Report {
+        let mut report = Report::default();
+        for item in self {
+            match item.and_then(|x| x.verify()) {
+                Ok(()) => report.verified += 1,
+                Err(e) => report.errors.push(e),
+            }
+        }
+        report
+    }
+}
+
 /// Defines a file name loader to load filenames
 pub trait FileNameLoader {
     /// Returns the next filename
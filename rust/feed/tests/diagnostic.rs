@@ -0,0 +1,87 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+#[cfg(test)]
+mod test {
+    use feed::{Diagnostic, Severity, VerifyError};
+
+    #[test]
+    fn syntax_error_as_json() {
+        let code = "a = 1;\ncalled(me;\n";
+        let diagnostics = nasl_syntax::parse(code)
+            .filter_map(|x| x.err())
+            .map(|e| Diagnostic::from_syntax_error("test.nasl", &e))
+            .collect::<Vec<_>>();
+        let json = serde_json::to_string(&diagnostics).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let array = value.as_array().expect("diagnostics should be an array");
+        assert_eq!(array.len(), 1);
+        let diagnostic = array[0].as_object().unwrap();
+        let mut keys = diagnostic.keys().map(|x| x as &str).collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec!["code", "col", "file", "line", "message", "severity"]
+        );
+        assert_eq!(diagnostic["file"], "test.nasl");
+        assert_eq!(diagnostic["line"], 2);
+        assert_eq!(diagnostic["col"], 7);
+        assert_eq!(diagnostic["severity"], "error");
+        assert_eq!(diagnostic["code"], "unclosed-token");
+    }
+
+    #[test]
+    fn verify_error() {
+        let err = VerifyError::HashInvalid {
+            expected: "0".to_owned(),
+            actual: "1".to_owned(),
+            key: "test.nasl".to_owned(),
+        };
+        let diagnostic = Diagnostic::from_verify_error(&err);
+        assert_eq!(diagnostic.file, "test.nasl");
+        assert_eq!((diagnostic.line, diagnostic.col), (0, 0));
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.code, "hash-invalid");
+        assert_eq!(diagnostic.message, err.to_string());
+    }
+
+    #[test]
+    fn sarif() {
+        let code = "a = 1;\ncalled(me;\n";
+        let diagnostics = nasl_syntax::parse(code)
+            .filter_map(|x| x.err())
+            .map(|e| Diagnostic::from_syntax_error("test.nasl", &e))
+            .collect::<Vec<_>>();
+        let log = feed::SarifLog::new("scannerctl", &diagnostics);
+        let value = serde_json::to_value(log).unwrap();
+        assert_eq!(value["version"], "2.1.0");
+        assert!(value["$schema"].as_str().unwrap().contains("sarif-2.1.0"));
+        let runs = value["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0]["tool"]["driver"]["name"], "scannerctl");
+        let results = runs[0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "unclosed-token");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[0]["message"]["text"], diagnostics[0].message);
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "test.nasl");
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(location["region"]["startColumn"], 7);
+    }
+
+    #[test]
+    fn sarif_without_region() {
+        let err = VerifyError::HashInvalid {
+            expected: "0".to_owned(),
+            actual: "1".to_owned(),
+            key: "test.nasl".to_owned(),
+        };
+        let log = feed::SarifLog::new("scannerctl", &[Diagnostic::from_verify_error(&err)]);
+        let value = serde_json::to_value(log).unwrap();
+        let location = &value["runs"][0]["results"][0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "test.nasl");
+        assert!(location.get("region").is_none());
+    }
+}
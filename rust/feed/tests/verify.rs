@@ -0,0 +1,53 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io::BufReader};
+
+    use feed::{HashSumNameLoader, Hasher, VerifyError};
+    use nasl_interpreter::FSPluginLoader;
+
+    #[test]
+    fn verify_all_aggregates_failures() {
+        let root = std::env::temp_dir().join(format!("feed-verify-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let valid = b"display('valid');";
+        fs::write(root.join("valid.nasl"), valid).unwrap();
+        fs::write(root.join("modified.nasl"), "display('modified');").unwrap();
+        let hash = Hasher::Sha256
+            .hash(&mut BufReader::new(&valid[..]), "valid.nasl")
+            .unwrap();
+        fs::write(
+            root.join("sha256sums"),
+            format!("{hash}  valid.nasl\n{hash}  modified.nasl\n{hash}  missing.nasl\n"),
+        )
+        .unwrap();
+
+        let loader = FSPluginLoader::new(&root);
+        let verifier = HashSumNameLoader::sha256(&loader).expect("sha256sums should be available");
+        let report = verifier.verify_all();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(report.verified, 1);
+        assert_eq!(report.errors.len(), 2);
+        assert!(matches!(
+            &report.errors[0],
+            VerifyError::HashInvalid { key, .. } if key == "modified.nasl"
+        ));
+        assert!(matches!(&report.errors[1], VerifyError::LoadError(_)));
+        assert!(!report.is_success());
+        assert_eq!(report.exit_code(), 2);
+    }
+
+    #[test]
+    fn exit_code_is_capped() {
+        let report = feed::VerifyReport {
+            verified: 0,
+            errors: vec![VerifyError::MissingKeyring; 256],
+        };
+        assert_eq!(report.exit_code(), 255);
+        assert_eq!(feed::VerifyReport::default().exit_code(), 0);
+    }
+}
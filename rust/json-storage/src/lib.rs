@@ -148,6 +148,13 @@ where
                     .filter(move |x| x.key == s)
                     .map(|x| storage::Field::KB(x.clone()))
             }),
+            storage::Retrieve::KBPattern(s) => Box::new({
+                let kbs = self.kbs.lock().map_err(StorageError::from)?;
+                let kbs = kbs.clone();
+                kbs.into_iter()
+                    .filter(move |x| storage::kb_key_matches(&s, &x.key))
+                    .map(storage::Field::KB)
+            }),
         })
     }
 
@@ -25,4 +25,24 @@ pub struct Scan {
     pub scan_preferences: Vec<ScanPreference>,
     /// List of VTs to execute for the target
     pub vts: Vec<VT>,
+    #[cfg_attr(
+        feature = "serde_support",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    /// Information about the feed the scan was created with
+    pub metadata: Option<ScanMetadata>,
+}
+
+/// Information about the feed a scan was created with
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde_support",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+pub struct ScanMetadata {
+    /// Version of the feed as set in plugin_feed_info.inc
+    pub feed_version: String,
+    /// Hash of the sums file of the feed, which covers each file of the feed
+    pub feed_hash: String,
 }
@@ -0,0 +1,150 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+use aes::{
+    cipher::{BlockCipher, BlockDecrypt, BlockEncrypt, KeyInit},
+    Aes128, Aes192, Aes256,
+};
+
+use crate::NaslFunction;
+use nasl_builtin_utils::error::FunctionErrorKind;
+use nasl_builtin_utils::{Context, Register};
+use nasl_syntax::NaslValue;
+
+use super::{get_data, get_key, get_len, Crypt};
+
+/// Base function for en- and decrypting Electronic Codebook (ECB) mode
+fn ecb<D>(register: &Register, crypt: Crypt) -> Result<NaslValue, FunctionErrorKind>
+where
+    D: BlockCipher + BlockEncrypt + BlockDecrypt + KeyInit,
+{
+    // Get Arguments
+    let key = get_key(register)?;
+    let data = get_data(register)?;
+    let cipher = D::new_from_slice(key).map_err(|e| {
+        FunctionErrorKind::WrongArgument(format!("key of length {}: {e}", key.len()))
+    })?;
+    let block_size = D::block_size();
+
+    // Mode Encrypt or Decrypt
+    match crypt {
+        Crypt::Encrypt => {
+            let mut result = data.to_vec();
+            // fill the last block with zeroes
+            let padding = (block_size - result.len() % block_size) % block_size;
+            result.resize(result.len() + padding, 0);
+            for block in result.chunks_mut(block_size) {
+                cipher.encrypt_block(block.into());
+            }
+            Ok(result.into())
+        }
+        Crypt::Decrypt => {
+            // length for encrypted data
+            let len = match get_len(register)? {
+                Some(x) => x,
+                None => data.len(),
+            };
+
+            // len should not be more than the length of the data
+            if len > data.len() {
+                return Err((
+                    "len",
+                    format!("<={:?}", data.len()).as_str(),
+                    len.to_string().as_str(),
+                )
+                    .into());
+            }
+            if data.len() % block_size != 0 {
+                return Err(FunctionErrorKind::WrongArgument(format!(
+                    "data length must be a multiple of {block_size} but is {}",
+                    data.len()
+                )));
+            }
+            let mut result = data.to_vec();
+            for block in result.chunks_mut(block_size) {
+                cipher.decrypt_block(block.into());
+            }
+            result.truncate(len);
+            Ok(result.into())
+        }
+    }
+}
+
+/// NASL function to encrypt data with aes128 ecb.
+///
+/// This function expects 2 named arguments key and data either in a string or data type.
+/// - The data is divided into blocks of 16 bytes. The last block is filled so it also has 16 bytes.
+///   Currently the data is filled with zeroes. Therefore the length of the encrypted data must be
+///   known for decryption. If no length is given, the last block is decrypted as a whole.
+/// - The key must have a length of 16 bytes
+fn aes128_ecb_encrypt(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    ecb::<Aes128>(register, Crypt::Encrypt)
+}
+
+/// NASL function to decrypt data with aes128 ecb.
+///
+/// This function expects 3 named arguments key and data either in a string or data type. The
+/// len argument is a number.
+/// - The data must be a multiple of 16 bytes. If no length is given, the last block is decrypted
+///   as a whole.
+/// - The key must have a length of 16 bytes
+fn aes128_ecb_decrypt(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    ecb::<Aes128>(register, Crypt::Decrypt)
+}
+
+/// NASL function to encrypt data with aes192 ecb.
+///
+/// This function expects 2 named arguments key and data either in a string or data type.
+/// - The data is divided into blocks of 16 bytes. The last block is filled so it also has 16 bytes.
+///   Currently the data is filled with zeroes. Therefore the length of the encrypted data must be
+///   known for decryption. If no length is given, the last block is decrypted as a whole.
+/// - The key must have a length of 24 bytes
+fn aes192_ecb_encrypt(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    ecb::<Aes192>(register, Crypt::Encrypt)
+}
+
+/// NASL function to decrypt data with aes192 ecb.
+///
+/// This function expects 3 named arguments key and data either in a string or data type. The
+/// len argument is a number.
+/// - The data must be a multiple of 16 bytes. If no length is given, the last block is decrypted
+///   as a whole.
+/// - The key must have a length of 24 bytes
+fn aes192_ecb_decrypt(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    ecb::<Aes192>(register, Crypt::Decrypt)
+}
+
+/// NASL function to encrypt data with aes256 ecb.
+///
+/// This function expects 2 named arguments key and data either in a string or data type.
+/// - The data is divided into blocks of 16 bytes. The last block is filled so it also has 16 bytes.
+///   Currently the data is filled with zeroes. Therefore the length of the encrypted data must be
+///   known for decryption. If no length is given, the last block is decrypted as a whole.
+/// - The key must have a length of 32 bytes
+fn aes256_ecb_encrypt(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    ecb::<Aes256>(register, Crypt::Encrypt)
+}
+
+/// NASL function to decrypt data with aes256 ecb.
+///
+/// This function expects 3 named arguments key and data either in a string or data type. The
+/// len argument is a number.
+/// - The data must be a multiple of 16 bytes. If no length is given, the last block is decrypted
+///   as a whole.
+/// - The key must have a length of 32 bytes
+fn aes256_ecb_decrypt(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    ecb::<Aes256>(register, Crypt::Decrypt)
+}
+
+pub fn lookup(key: &str) -> Option<NaslFunction> {
+    match key {
+        "aes128_ecb_encrypt" => Some(aes128_ecb_encrypt),
+        "aes128_ecb_decrypt" => Some(aes128_ecb_decrypt),
+        "aes192_ecb_encrypt" => Some(aes192_ecb_encrypt),
+        "aes192_ecb_decrypt" => Some(aes192_ecb_decrypt),
+        "aes256_ecb_encrypt" => Some(aes256_ecb_encrypt),
+        "aes256_ecb_decrypt" => Some(aes256_ecb_decrypt),
+        _ => None,
+    }
+}
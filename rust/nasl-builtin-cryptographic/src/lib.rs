@@ -12,11 +12,13 @@ pub mod aes_cbc;
 pub mod aes_ccm;
 pub mod aes_cmac;
 pub mod aes_ctr;
+pub mod aes_ecb;
 pub mod aes_gcm;
 pub mod aes_gmac;
 pub mod des;
 pub mod hash;
 pub mod hmac;
+pub mod rsa;
 
 enum Crypt {
     Encrypt,
@@ -31,11 +33,13 @@ pub(crate) fn lookup(function_name: &str) -> Option<NaslFunction> {
         .or_else(|| hmac::lookup(function_name))
         .or_else(|| aes_cbc::lookup(function_name))
         .or_else(|| aes_ctr::lookup(function_name))
+        .or_else(|| aes_ecb::lookup(function_name))
         .or_else(|| aes_gcm::lookup(function_name))
         .or_else(|| aes_cmac::lookup(function_name))
         .or_else(|| aes_gmac::lookup(function_name))
         .or_else(|| hash::lookup(function_name))
         .or_else(|| des::lookup(function_name))
+        .or_else(|| rsa::lookup(function_name))
 }
 
 pub struct Cryptographic;
@@ -0,0 +1,334 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+use aes::{
+    cipher::{
+        block_padding::Pkcs7, BlockCipher, BlockDecrypt, BlockDecryptMut, KeyInit, KeyIvInit,
+    },
+    Aes128, Aes192, Aes256,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use cbc::Decryptor;
+use des::TdesEde3;
+use md5::{Digest, Md5};
+use rsa::{
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey},
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    traits::{PrivateKeyParts, PublicKeyParts},
+    BigUint, Oaep, Pkcs1v15Encrypt, Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey,
+};
+use sha1::Sha1;
+use sha2::{Sha256, Sha384, Sha512};
+
+use crate::NaslFunction;
+use nasl_builtin_utils::error::FunctionErrorKind;
+use nasl_builtin_utils::{Context, ContextType, Register};
+use nasl_syntax::{NaslDict, NaslValue};
+
+use super::{get_data, get_required_named_data};
+
+/// Padding schemes supported for en- and decryption
+enum Padding {
+    /// No padding, the data is en- or decrypted as is
+    Raw,
+    /// PKCS#1 v1.5
+    Pkcs1,
+    /// OAEP with SHA-256 as hash and within MGF1
+    Oaep,
+}
+
+/// Returns true when the optional named argument is set to TRUE
+fn get_flag(register: &Register, key: &str) -> bool {
+    match register.named(key) {
+        Some(ContextType::Value(NaslValue::String(x))) => x == "TRUE",
+        Some(ContextType::Value(x)) => bool::from(x.clone()),
+        _ => false,
+    }
+}
+
+/// Get the padding scheme or error.
+///
+/// The optional `oaep` argument selects OAEP when it is TRUE. Otherwise the required `pad`
+/// argument selects PKCS#1 v1.5 when it is TRUE and no padding when it is FALSE. Like in the
+/// original implementation `pad` may also be given as the string "TRUE" or "FALSE".
+fn get_padding(register: &Register) -> Result<Padding, FunctionErrorKind> {
+    if get_flag(register, "oaep") {
+        return Ok(Padding::Oaep);
+    }
+    match register.named("pad") {
+        Some(ContextType::Value(NaslValue::Boolean(x))) => {
+            Ok(if *x { Padding::Pkcs1 } else { Padding::Raw })
+        }
+        Some(ContextType::Value(NaslValue::Number(x))) => Ok(if *x != 0 {
+            Padding::Pkcs1
+        } else {
+            Padding::Raw
+        }),
+        Some(ContextType::Value(NaslValue::String(x))) => Ok(if x == "TRUE" {
+            Padding::Pkcs1
+        } else {
+            Padding::Raw
+        }),
+        Some(x) => Err(("pad", "TRUE or FALSE", format!("{:?}", x).as_str()).into()),
+        _ => Err(("pad").into()),
+    }
+}
+
+/// Get the required named argument as big endian unsigned integer.
+fn get_biguint(register: &Register, key: &str) -> Result<BigUint, FunctionErrorKind> {
+    get_required_named_data(register, key).map(BigUint::from_bytes_be)
+}
+
+/// Get the optional passphrase argument, a missing passphrase is empty.
+fn get_passphrase(register: &Register) -> Result<&[u8], FunctionErrorKind> {
+    match register.named("passphrase") {
+        None | Some(ContextType::Value(NaslValue::Null)) => Ok(&[]),
+        Some(_) => get_required_named_data(register, "passphrase"),
+    }
+}
+
+/// Derives the key of an encrypted PEM like `EVP_BytesToKey` with MD5 and one iteration.
+fn derive_pem_key(passphrase: &[u8], salt: &[u8], len: usize) -> Vec<u8> {
+    let mut key = Vec::with_capacity(len + Md5::output_size());
+    let mut block = Vec::new();
+    while key.len() < len {
+        let mut hasher = Md5::new();
+        hasher.update(&block);
+        hasher.update(passphrase);
+        hasher.update(salt);
+        block = hasher.finalize().to_vec();
+        key.extend_from_slice(&block);
+    }
+    key.truncate(len);
+    key
+}
+
+/// Decrypts the body of an encrypted PEM with the given block cipher in CBC mode.
+fn decrypt_pem_body<C>(
+    body: &[u8],
+    passphrase: &[u8],
+    iv: &[u8],
+    key_len: usize,
+) -> Result<Vec<u8>, FunctionErrorKind>
+where
+    C: BlockCipher + BlockDecrypt + KeyInit,
+{
+    // the first 8 bytes of the iv are used as salt
+    let key = derive_pem_key(passphrase, &iv[..8], key_len);
+    Decryptor::<C>::new_from_slices(&key, iv)
+        .map_err(|_| FunctionErrorKind::WrongArgument("private key: invalid DEK-Info".into()))?
+        .decrypt_padded_vec_mut::<Pkcs7>(body)
+        .map_err(|_| FunctionErrorKind::WrongArgument("private key: wrong passphrase".into()))
+}
+
+/// Decrypts the DER of a private key in the traditional encrypted PEM format of OpenSSL.
+///
+/// The cipher is given in the `DEK-Info` header, supported are AES-128-CBC, AES-192-CBC,
+/// AES-256-CBC and DES-EDE3-CBC.
+fn decrypt_pem(
+    dek_info: &str,
+    body: &[u8],
+    passphrase: &[u8],
+) -> Result<Vec<u8>, FunctionErrorKind> {
+    let invalid = || FunctionErrorKind::WrongArgument("private key: invalid DEK-Info".into());
+    let (cipher, iv) = dek_info.split_once(',').ok_or_else(invalid)?;
+    let iv = hex::decode(iv.trim()).map_err(|_| invalid())?;
+    if iv.len() < 8 {
+        return Err(invalid());
+    }
+    match cipher.trim() {
+        "AES-128-CBC" => decrypt_pem_body::<Aes128>(body, passphrase, &iv, 16),
+        "AES-192-CBC" => decrypt_pem_body::<Aes192>(body, passphrase, &iv, 24),
+        "AES-256-CBC" => decrypt_pem_body::<Aes256>(body, passphrase, &iv, 32),
+        "DES-EDE3-CBC" => decrypt_pem_body::<TdesEde3>(body, passphrase, &iv, 24),
+        x => Err(FunctionErrorKind::WrongArgument(format!(
+            "private key: unsupported cipher {x}"
+        ))),
+    }
+}
+
+/// Parses a PEM encoded private key in either PKCS#1 or PKCS#8 format.
+///
+/// A PKCS#1 key that is encrypted in the traditional format of OpenSSL, containing a `Proc-Type`
+/// and `DEK-Info` header, is decrypted with the passphrase.
+fn parse_private_key(pem: &[u8], passphrase: &[u8]) -> Result<RsaPrivateKey, FunctionErrorKind> {
+    let pem = std::str::from_utf8(pem)
+        .map_err(|e| FunctionErrorKind::WrongArgument(format!("private key: {e}")))?;
+    if pem.contains("Proc-Type: 4,ENCRYPTED") {
+        let dek_info = pem
+            .lines()
+            .find_map(|x| x.strip_prefix("DEK-Info:"))
+            .ok_or_else(|| FunctionErrorKind::WrongArgument("private key: no DEK-Info".into()))?;
+        let body: String = pem
+            .lines()
+            .map(str::trim)
+            .skip_while(|x| !x.is_empty())
+            .take_while(|x| !x.starts_with("-----END"))
+            .collect();
+        let body = STANDARD
+            .decode(body)
+            .map_err(|e| FunctionErrorKind::WrongArgument(format!("private key: {e}")))?;
+        let der = decrypt_pem(dek_info, &body, passphrase)?;
+        return RsaPrivateKey::from_pkcs1_der(&der)
+            .map_err(|e| FunctionErrorKind::WrongArgument(format!("private key: {e}")));
+    }
+    if pem.contains("BEGIN ENCRYPTED PRIVATE KEY") {
+        return Err(FunctionErrorKind::WrongArgument(
+            "private key: encrypted PKCS#8 keys are not supported".into(),
+        ));
+    }
+    RsaPrivateKey::from_pkcs1_pem(pem)
+        .or_else(|_| RsaPrivateKey::from_pkcs8_pem(pem))
+        .map_err(|e| FunctionErrorKind::WrongArgument(format!("private key: {e}")))
+}
+
+/// Parses a PEM encoded public key in either PKCS#1 or SubjectPublicKeyInfo format.
+fn parse_public_key(pem: &[u8]) -> Result<RsaPublicKey, FunctionErrorKind> {
+    let pem = std::str::from_utf8(pem)
+        .map_err(|e| FunctionErrorKind::WrongArgument(format!("key: {e}")))?;
+    RsaPublicKey::from_pkcs1_pem(pem)
+        .or_else(|_| RsaPublicKey::from_public_key_pem(pem))
+        .map_err(|_| FunctionErrorKind::WrongArgument("key: not a PEM encoded RSA key".into()))
+}
+
+/// Converts the data into a number that is smaller than the modulus for raw en- and decryption.
+fn raw_input(data: &[u8], n: &BigUint) -> Result<BigUint, FunctionErrorKind> {
+    let x = BigUint::from_bytes_be(data);
+    if &x >= n {
+        return Err(FunctionErrorKind::WrongArgument(
+            "data: too large for the modulus".into(),
+        ));
+    }
+    Ok(x)
+}
+
+/// NASL function to encrypt data with a RSA public key.
+///
+/// This function expects 4 named arguments and an optional one:
+/// - data: the data to encrypt either in a string or data type.
+/// - n: the modulus as big endian data.
+/// - e: the public exponent as big endian data.
+/// - pad: TRUE for PKCS#1 v1.5 padding, FALSE for no padding.
+/// - oaep: optional, when TRUE OAEP with SHA-256 is used instead of `pad`.
+///
+/// Returns the encrypted data with the length of the modulus.
+fn rsa_public_encrypt(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let data = get_data(register)?;
+    let n = get_biguint(register, "n")?;
+    let e = get_biguint(register, "e")?;
+    let padding = get_padding(register)?;
+    let key = RsaPublicKey::new(n, e)
+        .map_err(|e| FunctionErrorKind::WrongArgument(format!("public key: {e}")))?;
+    let mut rng = rand::thread_rng();
+    let result = match padding {
+        Padding::Raw => {
+            let c = raw_input(data, key.n())?.modpow(key.e(), key.n());
+            let c = c.to_bytes_be();
+            let mut result = vec![0; key.size() - c.len()];
+            result.extend(c);
+            return Ok(result.into());
+        }
+        Padding::Pkcs1 => key.encrypt(&mut rng, Pkcs1v15Encrypt, data),
+        Padding::Oaep => key.encrypt(&mut rng, Oaep::new::<Sha256>(), data),
+    };
+    result
+        .map(|x| x.into())
+        .map_err(|e| FunctionErrorKind::WrongArgument(format!("data: {e}")))
+}
+
+/// NASL function to decrypt data with a RSA private key.
+///
+/// This function expects 5 named arguments and an optional one:
+/// - data: the data to decrypt either in a string or data type.
+/// - n: the modulus as big endian data.
+/// - e: the public exponent as big endian data.
+/// - d: the private exponent as big endian data.
+/// - pad: TRUE for PKCS#1 v1.5 padding, FALSE for no padding.
+/// - oaep: optional, when TRUE OAEP with SHA-256 is used instead of `pad`.
+///
+/// Without padding the leading zeros of the decrypted data are removed.
+fn rsa_private_decrypt(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let data = get_data(register)?;
+    let n = get_biguint(register, "n")?;
+    let e = get_biguint(register, "e")?;
+    let d = get_biguint(register, "d")?;
+    let padding = get_padding(register)?;
+    let key = RsaPrivateKey::from_components(n, e, d, vec![])
+        .map_err(|e| FunctionErrorKind::WrongArgument(format!("private key: {e}")))?;
+    let result = match padding {
+        Padding::Raw => {
+            let m = raw_input(data, key.n())?.modpow(key.d(), key.n());
+            return Ok(m.to_bytes_be().into());
+        }
+        Padding::Pkcs1 => key.decrypt(Pkcs1v15Encrypt, data),
+        Padding::Oaep => key.decrypt(Oaep::new::<Sha256>(), data),
+    };
+    result
+        .map(|x| x.into())
+        .map_err(|e| FunctionErrorKind::WrongArgument(format!("data: {e}")))
+}
+
+/// NASL function to sign a digest with a RSA private key using PKCS#1 v1.5 padding.
+///
+/// This function expects 2 named arguments and optional ones:
+/// - data: the digest to sign either in a string or data type, it is signed as is.
+/// - priv: the PEM encoded private key in PKCS#1 or PKCS#8 format.
+/// - passphrase: optional, the passphrase of an encrypted private key.
+/// - hash: optional, the hash algorithm the digest was created with, one of `sha1`, `sha256`,
+///   `sha384` or `sha512`. Defaults to `sha1`.
+fn rsa_sign(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let data = get_data(register)?;
+    let key = parse_private_key(
+        get_required_named_data(register, "priv")?,
+        get_passphrase(register)?,
+    )?;
+    let hash = match register.named("hash") {
+        Some(ContextType::Value(NaslValue::String(x))) => x.to_lowercase(),
+        None | Some(ContextType::Value(NaslValue::Null)) => "sha1".to_owned(),
+        Some(x) => return Err(("hash", "string", format!("{:?}", x).as_str()).into()),
+    };
+    let scheme = match hash.as_str() {
+        "sha1" => Pkcs1v15Sign::new::<Sha1>(),
+        "sha256" => Pkcs1v15Sign::new::<Sha256>(),
+        "sha384" => Pkcs1v15Sign::new::<Sha384>(),
+        "sha512" => Pkcs1v15Sign::new::<Sha512>(),
+        x => return Err(("hash", "sha1, sha256, sha384 or sha512", x).into()),
+    };
+    key.sign(scheme, data)
+        .map(|x| x.into())
+        .map_err(|e| FunctionErrorKind::WrongArgument(format!("data: {e}")))
+}
+
+/// NASL function to extract the components of a RSA key.
+///
+/// This function expects the named argument key containing a PEM encoded public or private key
+/// and the optional passphrase of an encrypted private key.
+///
+/// Returns an array with the modulus `n` and the public exponent `e` as big endian data. For
+/// private keys the private exponent `d` is contained as well.
+fn rsa_key_components(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let pem = get_required_named_data(register, "key")?;
+    let mut result = NaslDict::new();
+    let private = parse_private_key(pem, get_passphrase(register)?).ok();
+    let public = match &private {
+        Some(x) => x.to_public_key(),
+        None => parse_public_key(pem)?,
+    };
+    result.insert("n".to_owned(), public.n().to_bytes_be().into());
+    result.insert("e".to_owned(), public.e().to_bytes_be().into());
+    if let Some(x) = private {
+        result.insert("d".to_owned(), x.d().to_bytes_be().into());
+    }
+    Ok(NaslValue::Dict(result))
+}
+
+pub fn lookup(key: &str) -> Option<NaslFunction> {
+    match key {
+        "rsa_public_encrypt" => Some(rsa_public_encrypt),
+        "rsa_private_decrypt" => Some(rsa_private_decrypt),
+        "rsa_sign" => Some(rsa_sign),
+        "rsa_key_components" => Some(rsa_key_components),
+        _ => None,
+    }
+}
@@ -39,6 +39,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn aes128_cbc_nist() {
+        // NIST SP 800-38A F.2.1
+        let code = r#"
+        key = hexstr_to_data("2b7e151628aed2a6abf7158809cf4f3c");
+        data = hexstr_to_data("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        iv = hexstr_to_data("000102030405060708090a0b0c0d0e0f");
+        aes128_cbc_encrypt(key: key, data: data, iv: iv);
+        "#;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.next();
+        parser.next();
+        parser.next();
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Data(
+                decode_hex("7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2")
+                    .unwrap()
+            )))
+        );
+    }
+
+    #[test]
+    fn aes128_cbc_wrong_key_length() {
+        let code = r#"
+        aes128_cbc_encrypt(key: "too short", data: "data", iv: "0123456789abcdef");
+        "#;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert!(matches!(
+            parser.next(),
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::FunctionCallError(FunctionError {
+                    kind: FunctionErrorKind::WrongArgument(_),
+                    ..
+                }),
+                ..
+            }))
+        ));
+    }
+
     #[test]
     fn aes192_cbc_crypt() {
         let code = r#"
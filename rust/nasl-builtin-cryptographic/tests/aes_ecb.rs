@@ -0,0 +1,85 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+mod helper;
+#[cfg(test)]
+mod tests {
+
+    use super::helper::decode_hex;
+    use nasl_interpreter::*;
+
+    #[test]
+    fn aes128_ecb_crypt() {
+        // NIST SP 800-38A F.1.1
+        let code = r#"
+        key = hexstr_to_data("2b7e151628aed2a6abf7158809cf4f3c");
+        data = hexstr_to_data("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        crypt = aes128_ecb_encrypt(key: key, data: data);
+        aes128_ecb_decrypt(key: key, data: crypt);
+        "#;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.next();
+        parser.next();
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Data(
+                decode_hex("3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf")
+                    .unwrap()
+            )))
+        );
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Data(
+                decode_hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")
+                    .unwrap()
+            )))
+        );
+    }
+
+    #[test]
+    fn aes256_ecb_crypt() {
+        // NIST SP 800-38A F.1.5
+        let code = r#"
+        key = hexstr_to_data("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4");
+        data = hexstr_to_data("6bc1bee22e409f96e93d7e117393172a");
+        aes256_ecb_encrypt(key: key, data: data);
+        "#;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.next();
+        parser.next();
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Data(
+                decode_hex("f3eed1bdb5d2a03c064b5a7e3db181f8").unwrap()
+            )))
+        );
+    }
+
+    #[test]
+    fn aes128_ecb_wrong_key_length() {
+        let code = r#"
+        aes128_ecb_encrypt(key: "too short", data: "data");
+        "#;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert!(matches!(
+            parser.next(),
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::FunctionCallError(FunctionError {
+                    kind: FunctionErrorKind::WrongArgument(_),
+                    ..
+                }),
+                ..
+            }))
+        ));
+    }
+}
@@ -2,6 +2,8 @@
 //
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
+mod preferences;
+
 use std::str::FromStr;
 
 use nasl_builtin_utils::{Context, FunctionErrorKind, Register};
@@ -271,10 +273,12 @@ impl nasl_builtin_utils::NaslFunctionExecuter for Description {
         register: &Register,
         context: &Context,
     ) -> Option<nasl_builtin_utils::NaslResult> {
-        lookup(name).map(|x| x(register, context))
+        lookup(name)
+            .or_else(|| preferences::lookup(name))
+            .map(|x| x(register, context))
     }
 
     fn nasl_fn_defined(&self, name: &str) -> bool {
-        lookup(name).is_some()
+        lookup(name).or_else(|| preferences::lookup(name)).is_some()
     }
 }
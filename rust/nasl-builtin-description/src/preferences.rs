@@ -0,0 +1,85 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Defines the functions to read the preferences of a script as configured for the scan
+
+use nasl_builtin_utils::{
+    get_optional_named_parameter, Context, FunctionErrorKind, NaslFunction, Register,
+};
+use nasl_syntax::NaslValue;
+use storage::item::{NvtPreference, PreferenceType};
+
+/// Returns the preference identified by the arguments
+///
+/// A preference is identified either by its id, given as number or as `id` parameter, or by its
+/// name. Returns None when there is no such preference.
+fn find_preference<'a>(
+    register: &Register,
+    context: &'a Context,
+) -> Result<Option<&'a NvtPreference>, FunctionErrorKind> {
+    let id = match get_optional_named_parameter(register, "id")? {
+        None => None,
+        Some(NaslValue::Number(x)) => Some(*x),
+        Some(x) => return Err(("Number", x).into()),
+    };
+    let preferences = context.preferences();
+    let positional = register.positional().first();
+    let id = match (positional, id) {
+        (Some(NaslValue::Number(x)), None) => Some(*x),
+        _ => id,
+    };
+    match (positional, id) {
+        (_, Some(id)) => Ok(preferences
+            .iter()
+            .find(|x| x.id().map(i64::from) == Some(id))),
+        (Some(NaslValue::Null), None) | (None, None) => {
+            Err(FunctionErrorKind::MissingPositionalArguments {
+                expected: 1,
+                got: 0,
+            })
+        }
+        (Some(name), None) => {
+            let name = name.to_string();
+            Ok(preferences.iter().find(|x| x.name() == name))
+        }
+    }
+}
+
+/// Returns the value of a preference of the script
+///
+/// The preference is either identified by its name or by its id. The content of a preference of
+/// type file is returned as data, all other values as string. Returns NULL when there is no such
+/// preference.
+fn script_get_preference(
+    register: &Register,
+    context: &Context,
+) -> Result<NaslValue, FunctionErrorKind> {
+    Ok(match find_preference(register, context)? {
+        Some(x) if x.class() == PreferenceType::File => x.default().as_bytes().to_vec().into(),
+        Some(x) => NaslValue::String(x.default().to_owned()),
+        None => NaslValue::Null,
+    })
+}
+
+/// Returns the content of a preference of type file as data
+///
+/// Returns NULL when there is no such preference or it is not of type file.
+fn script_get_preference_file_content(
+    register: &Register,
+    context: &Context,
+) -> Result<NaslValue, FunctionErrorKind> {
+    Ok(match find_preference(register, context)? {
+        Some(x) if x.class() == PreferenceType::File => x.default().as_bytes().to_vec().into(),
+        _ => NaslValue::Null,
+    })
+}
+
+/// Returns found function for key or None when not found
+pub fn lookup(key: &str) -> Option<NaslFunction> {
+    match key {
+        "script_get_preference" => Some(script_get_preference),
+        "script_get_preference_file_content" => Some(script_get_preference_file_content),
+        _ => None,
+    }
+}
@@ -0,0 +1,80 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+#[cfg(test)]
+mod tests {
+    use nasl_interpreter::*;
+    use storage::item::NvtPreference;
+
+    fn preferences() -> Vec<NvtPreference> {
+        vec![
+            ("1", "Report timeout", "entry", "30").into(),
+            ("2", "Use SSL", "checkbox", "yes").into(),
+            (
+                "3",
+                "Client certificate",
+                "file",
+                "-----BEGIN CERTIFICATE-----",
+            )
+                .into(),
+        ]
+    }
+
+    #[test]
+    fn script_get_preference() {
+        let code = r#"
+        script_get_preference(1);
+        script_get_preference(id: 2);
+        script_get_preference("Report timeout");
+        script_get_preference("Use SSL", id: 1);
+        script_get_preference("Use SSL", id: NULL);
+        script_get_preference(3);
+        script_get_preference(4);
+        script_get_preference("Unknown");
+        script_get_preference();
+        "#;
+        let binding = ContextFactory::default().preferences(preferences());
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok("30".into())));
+        assert_eq!(parser.next(), Some(Ok("yes".into())));
+        assert_eq!(parser.next(), Some(Ok("30".into())));
+        assert_eq!(parser.next(), Some(Ok("30".into())));
+        assert_eq!(parser.next(), Some(Ok("yes".into())));
+        assert_eq!(
+            parser.next(),
+            Some(Ok(b"-----BEGIN CERTIFICATE-----".to_vec().into()))
+        );
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn script_get_preference_file_content() {
+        let code = r#"
+        script_get_preference_file_content(3);
+        script_get_preference_file_content("Client certificate");
+        script_get_preference_file_content(1);
+        script_get_preference_file_content(4);
+        "#;
+        let binding = ContextFactory::default().preferences(preferences());
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        let content = NaslValue::Data(b"-----BEGIN CERTIFICATE-----".to_vec());
+        assert_eq!(parser.next(), Some(Ok(content.clone())));
+        assert_eq!(parser.next(), Some(Ok(content)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+    }
+
+    #[test]
+    fn without_preferences() {
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser =
+            CodeInterpreter::new("script_get_preference(1);", Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+    }
+}
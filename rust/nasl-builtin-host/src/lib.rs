@@ -72,12 +72,21 @@ fn nasl_get_host_ip(
     Ok(NaslValue::String(ip.to_string()))
 }
 
+/// Returns the port the script runs for or NULL when it does not run for a specific port
+fn nasl_get_port(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    Ok(register
+        .port()
+        .map(|x| NaslValue::Number(x.into()))
+        .unwrap_or(NaslValue::Null))
+}
+
 /// Returns found function for key or None when not found
 fn lookup(key: &str) -> Option<NaslFunction> {
     match key {
         "get_host_name" => Some(get_host_name),
         "get_host_names" => Some(get_host_names),
         "get_host_ip" => Some(nasl_get_host_ip),
+        "get_port" => Some(nasl_get_port),
         _ => None,
     }
 }
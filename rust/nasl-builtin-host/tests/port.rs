@@ -0,0 +1,52 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+#[cfg(test)]
+mod tests {
+    use nasl_interpreter::*;
+
+    #[test]
+    fn get_port() {
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new("get_port();", Register::default(), &context);
+        parser.set_port(Some(443));
+        assert_eq!(parser.port(), Some(443));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(443))));
+    }
+
+    #[test]
+    fn port_per_interpreter() {
+        let code = "get_port();";
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut https = CodeInterpreter::new(code, Register::default(), &context);
+        let mut http = CodeInterpreter::new(code, Register::default(), &context);
+        let mut unset = CodeInterpreter::new(code, Register::default(), &context);
+        https.set_port(Some(443));
+        http.set_port(Some(80));
+        assert_eq!(https.next(), Some(Ok(NaslValue::Number(443))));
+        assert_eq!(http.next(), Some(Ok(NaslValue::Number(80))));
+        assert_eq!(unset.next(), Some(Ok(NaslValue::Null)));
+    }
+
+    #[test]
+    fn forks_inherit_port() {
+        let code = r#"
+        set_kb_item(name: "test", value: 1);
+        set_kb_item(name: "test", value: 2);
+        a = get_kb_item("test");
+        get_port();
+        "#;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        parser.set_port(Some(22));
+        let results: Vec<_> = parser.filter_map(|x| x.ok()).collect();
+        assert_eq!(
+            results[results.len() - 2..],
+            [NaslValue::Number(22), NaslValue::Number(22)]
+        );
+    }
+}
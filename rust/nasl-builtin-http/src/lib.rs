@@ -5,7 +5,7 @@
 //! Defines NASL functions to perform HTTP/2 request.
 // TODO: implement http functions once socket handling is available
 
-use nasl_builtin_utils::{Context, ContextType, FunctionErrorKind, Register};
+use nasl_builtin_utils::{get_named_argument, Context, ContextType, FunctionErrorKind, Register};
 use nasl_syntax::NaslValue;
 
 use h2::client;
@@ -288,10 +288,7 @@ impl NaslHttp {
             _ => String::new(),
         };
 
-        let port = match register.named("port") {
-            Some(ContextType::Value(NaslValue::Number(x))) => *x as u16,
-            _ => 0u16,
-        };
+        let port = get_named_argument::<Option<u16>>(register, "port")?.unwrap_or_default();
 
         let ip_str: String = match ctx.target() {
             x if !x.is_empty() => x.to_string(),
@@ -542,3 +539,27 @@ impl nasl_builtin_utils::NaslFunctionExecuter for NaslHttp {
         NaslHttp::lookup(name).is_some()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nasl_interpreter::{CodeInterpreter, ContextFactory, Register};
+
+    #[test]
+    fn port_out_of_range() {
+        let code = r#"
+        h = http2_handle();
+        http2_get(handle: h, port: 70000, item: "/");
+        "#;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert!(matches!(parser.next(), Some(Ok(_))));
+        match parser.next() {
+            Some(Err(e)) => assert_eq!(
+                e.to_string(),
+                "argument `port` of http2_get at line 3: Expected a number between 0 and 65535 but got 70000"
+            ),
+            x => panic!("expected an invalid argument error, got {x:?}"),
+        }
+    }
+}
@@ -2,36 +2,40 @@
 //
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
-use std::time::{SystemTime, UNIX_EPOCH};
-
-use nasl_builtin_utils::{error::FunctionErrorKind, get_named_parameter, NaslFunction};
+use indexmap::IndexMap;
+use nasl_builtin_utils::{
+    error::FunctionErrorKind, get_named_parameter, get_optional_named_parameter, NaslFunction,
+};
 use storage::{Field, Kb, Retrieve};
 
-use nasl_builtin_utils::{Context, Register};
+use nasl_builtin_utils::{Context, ContextType, Register};
 use nasl_syntax::NaslValue;
 
+/// Returns the unix timestamp when a KB item expires based on the optional `expires` argument
+///
+/// The legacy name `expire` is accepted as well.
+fn expires(register: &Register, c: &Context) -> Result<Option<u64>, FunctionErrorKind> {
+    let expires = match get_optional_named_parameter(register, "expires")? {
+        None => get_optional_named_parameter(register, "expire")?,
+        x => x,
+    };
+    match expires {
+        Some(NaslValue::Number(x)) if *x >= 0 => Ok(Some(*x)),
+        None => Ok(None),
+        Some(x) => Err(FunctionErrorKind::Diagnostic(
+            format!("expected expires to be a non-negative number but is {x}."),
+            None,
+        )),
+    }
+    // the storage decides if an entry is expired, so its clock is used instead of the system time
+    .map(|x| x.map(|seconds| c.dispatcher().now().saturating_add(seconds as u64)))
+}
+
 /// NASL function to set a knowledge base
 fn set_kb_item(register: &Register, c: &Context) -> Result<NaslValue, FunctionErrorKind> {
     let name = get_named_parameter(register, "name", true)?;
     let value = get_named_parameter(register, "value", true)?;
-    let expires = match get_named_parameter(register, "expires", false) {
-        Ok(NaslValue::Number(x)) => Some(*x),
-        Ok(NaslValue::Exit(0)) => None,
-        Ok(x) => {
-            return Err(FunctionErrorKind::Diagnostic(
-                format!("expected expires to be a number but is {x}."),
-                None,
-            ))
-        }
-        Err(e) => return Err(e),
-    }
-    .map(|seconds| {
-        let start = SystemTime::now();
-        match start.duration_since(UNIX_EPOCH) {
-            Ok(x) => x.as_secs() + seconds as u64,
-            Err(_) => 0,
-        }
-    });
+    let expires = expires(register, c)?;
     c.dispatcher()
         .dispatch(
             c.key(),
@@ -45,6 +49,26 @@ fn set_kb_item(register: &Register, c: &Context) -> Result<NaslValue, FunctionEr
         .map_err(|e| e.into())
 }
 
+/// NASL function to replace all values of a knowledge base item
+///
+/// Unlike `set_kb_item` the previous values of the item are removed before the new value is set.
+fn replace_kb_item(register: &Register, c: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let name = get_named_parameter(register, "name", true)?;
+    let value = get_named_parameter(register, "value", true)?;
+    let expires = expires(register, c)?;
+    c.dispatcher()
+        .dispatch_replace(
+            c.key(),
+            Field::KB(Kb {
+                key: name.to_string(),
+                value: value.clone().as_primitive(),
+                expire: expires,
+            }),
+        )
+        .map(|_| NaslValue::Null)
+        .map_err(|e| e.into())
+}
+
 /// NASL function to get a knowledge base
 fn get_kb_item(register: &Register, c: &Context) -> Result<NaslValue, FunctionErrorKind> {
     match register.positional() {
@@ -68,11 +92,135 @@ fn get_kb_item(register: &Register, c: &Context) -> Result<NaslValue, FunctionEr
     }
 }
 
+/// NASL function to get all knowledge base items whose keys match a pattern
+///
+/// The pattern is anchored and supports `*` as the only wildcard. Returns an array with the
+/// matching keys and their values; keys with multiple values contain a list of all values. The
+/// keys are in the order the storage returns them.
+fn get_kb_list(register: &Register, c: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    match register.positional() {
+        [x] => {
+            let mut values: IndexMap<String, Vec<NaslValue>> = IndexMap::new();
+            let fields = c
+                .retriever()
+                .retrieve(c.key(), Retrieve::KBPattern(x.to_string()))?;
+            for field in fields {
+                if let Field::KB(kb) = field {
+                    values.entry(kb.key).or_default().push(kb.value.into());
+                }
+            }
+            let result = values
+                .into_iter()
+                .map(|(k, mut v)| match v.len() {
+                    1 => (k, v.remove(0)),
+                    _ => (k, NaslValue::Array(v)),
+                })
+                .collect();
+            Ok(NaslValue::Dict(result))
+        }
+        x => Err(FunctionErrorKind::Diagnostic(
+            format!("expected one positional argument but got: {}", x.len()),
+            None,
+        )),
+    }
+}
+
+/// The transport of a plain TCP connection, see `get_port_transport`
+const ENCAPS_IP: i64 = 1;
+
+/// Returns the name of a transport encapsulation
+fn encaps_name(encaps: i64) -> String {
+    match encaps {
+        0 => "auto".to_owned(),
+        ENCAPS_IP => "IP".to_owned(),
+        2 => "SSLv23".to_owned(),
+        3 => "SSLv2".to_owned(),
+        4 => "SSLv3".to_owned(),
+        5 => "TLSv1".to_owned(),
+        6 => "TLSv11".to_owned(),
+        7 => "TLSv12".to_owned(),
+        8 => "TLSv13".to_owned(),
+        9 => "TLScustom".to_owned(),
+        x => format!("[unknown transport layer - code {x} (0x{x:x})]"),
+    }
+}
+
+/// Returns the port given as the only positional argument or None when it is no valid port
+fn port_argument(register: &Register) -> Result<Option<u16>, FunctionErrorKind> {
+    match register.positional() {
+        [NaslValue::Number(x)] => Ok(u16::try_from(*x).ok()),
+        [_] => Ok(None),
+        x => Err(FunctionErrorKind::Diagnostic(
+            format!("expected one positional argument but got: {}", x.len()),
+            None,
+        )),
+    }
+}
+
+/// Returns the first value of the knowledge base item with the given name
+fn first_kb_value(c: &Context, name: String) -> Result<Option<NaslValue>, FunctionErrorKind> {
+    let fields = c.retriever().retrieve(c.key(), Retrieve::KB(name))?;
+    Ok(fields.into_iter().find_map(|x| match x {
+        Field::KB(kb) => Some(kb.value.into()),
+        Field::NVT(_) | Field::NotusAdvisory(_) => None,
+    }))
+}
+
+/// NASL function to get the transport encapsulation of a port stored by find_service
+///
+/// Returns ENCAPS_IP when no encapsulation is stored for the port. With `asstring` the name of
+/// the encapsulation is returned instead of the number, NULL for an invalid port.
+fn get_port_transport(register: &Register, c: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let port = match port_argument(register)? {
+        Some(x) => x,
+        None => return Ok(NaslValue::Null),
+    };
+    let encaps = match first_kb_value(c, format!("Transports/TCP/{port}"))? {
+        Some(NaslValue::Number(x)) if x >= 0 => x,
+        _ => ENCAPS_IP,
+    };
+    let asstring = match register.named("asstring") {
+        Some(ContextType::Value(x)) => bool::from(x.clone()),
+        _ => false,
+    };
+    Ok(if asstring {
+        NaslValue::String(encaps_name(encaps))
+    } else {
+        NaslValue::Number(encaps)
+    })
+}
+
+/// NASL function to check whether no service was identified on a port
+///
+/// A service is known when find_service stored its name as `Known/<ipproto>/<port>`; `ipproto`
+/// defaults to tcp. Returns NULL for an invalid port.
+fn service_is_unknown(register: &Register, c: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let port = match port_argument(register)? {
+        Some(x) => x,
+        None => return Ok(NaslValue::Null),
+    };
+    let ipproto = match register.named("ipproto") {
+        Some(ContextType::Value(NaslValue::String(x))) => x.to_owned(),
+        None => "tcp".to_owned(),
+        _ => return Err(("String", "Invalid ipproto value").into()),
+    };
+    let service = first_kb_value(c, format!("Known/{ipproto}/{port}"))?;
+    Ok(NaslValue::Boolean(match service {
+        Some(NaslValue::String(x)) => x.is_empty() || x == "unknown",
+        Some(NaslValue::Null) | None => true,
+        Some(_) => false,
+    }))
+}
+
 /// Returns found function for key or None when not found
 pub fn lookup(key: &str) -> Option<NaslFunction> {
     match key {
         "set_kb_item" => Some(set_kb_item),
+        "replace_kb_item" => Some(replace_kb_item),
         "get_kb_item" => Some(get_kb_item),
+        "get_kb_list" => Some(get_kb_list),
+        "get_port_transport" => Some(get_port_transport),
+        "service_is_unknown" => Some(service_is_unknown),
         _ => None,
     }
 }
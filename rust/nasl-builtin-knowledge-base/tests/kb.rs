@@ -36,4 +36,238 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok(NaslValue::Number(1))));
         assert!(matches!(parser.next(), Some(Err(_))));
     }
+
+    #[test]
+    fn get_kb_list() {
+        let code = r#"
+        set_kb_item(name: "Ports/tcp/22", value: 1);
+        set_kb_item(name: "Ports/tcp/80", value: 1);
+        set_kb_item(name: "Ports/udp/53", value: 1);
+        set_kb_item(name: "Host/Ports/tcp/443", value: 1);
+        set_kb_item(name: "hosts", value: "foo");
+        set_kb_item(name: "hosts", value: "bar");
+        get_kb_list("Ports/tcp/*");
+        get_kb_list("Ports/*/53");
+        get_kb_list("Ports/icmp/*");
+        get_kb_list("Ports/tcp/22");
+        get_kb_list("Ports/tcp/2");
+        get_kb_list("hosts");
+        "#;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let results: Vec<_> = CodeInterpreter::new(code, register, &context).collect();
+        let dict = |x: &[(&str, NaslValue)]| {
+            Ok(NaslValue::Dict(
+                x.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+            ))
+        };
+        assert_eq!(
+            results[6],
+            dict(&[("Ports/tcp/22", 1.into()), ("Ports/tcp/80", 1.into())])
+        );
+        assert_eq!(results[7], dict(&[("Ports/udp/53", 1.into())]));
+        assert_eq!(results[8], dict(&[]));
+        assert_eq!(results[9], dict(&[("Ports/tcp/22", 1.into())]));
+        assert_eq!(results[10], dict(&[]));
+        assert_eq!(
+            results[11],
+            dict(&[("hosts", NaslValue::Array(vec!["foo".into(), "bar".into()]))])
+        );
+    }
+
+    #[test]
+    fn get_kb_list_in_retrieval_order() {
+        use storage::{Field, Retrieve, Retriever};
+
+        let code = r#"
+        set_kb_item(name: "Ports/tcp/22", value: 1);
+        set_kb_item(name: "Ports/tcp/80", value: 1);
+        set_kb_item(name: "Ports/tcp/443", value: 1);
+        set_kb_item(name: "Ports/tcp/8080", value: 1);
+        set_kb_item(name: "Ports/tcp/80", value: 2);
+        get_kb_list("Ports/tcp/*");
+        "#;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let result = CodeInterpreter::new(code, Register::default(), &context).last();
+        let Some(Ok(NaslValue::Dict(result))) = result else {
+            panic!("expected a dict, got {result:?}");
+        };
+        let mut expected: Vec<String> = vec![];
+        for field in binding
+            .storage
+            .retrieve(
+                context.key(),
+                Retrieve::KBPattern("Ports/tcp/*".to_string()),
+            )
+            .unwrap()
+        {
+            if let Field::KB(kb) = field {
+                if !expected.contains(&kb.key) {
+                    expected.push(kb.key);
+                }
+            }
+        }
+        assert_eq!(result.keys().cloned().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn get_port_transport() {
+        let code = r#"
+        set_kb_item(name: "Transports/TCP/443", value: 2);
+        get_port_transport(443);
+        get_port_transport(443, asstring: TRUE);
+        get_port_transport(80);
+        get_port_transport(80, asstring: TRUE);
+        get_port_transport(70000);
+        "#;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let results: Vec<_> = CodeInterpreter::new(code, register, &context).collect();
+        assert_eq!(results[1], Ok(NaslValue::Number(2)));
+        assert_eq!(results[2], Ok(NaslValue::String("SSLv23".to_owned())));
+        assert_eq!(results[3], Ok(NaslValue::Number(1)));
+        assert_eq!(results[4], Ok(NaslValue::String("IP".to_owned())));
+        assert_eq!(results[5], Ok(NaslValue::Null));
+    }
+
+    #[test]
+    fn service_is_unknown() {
+        let code = r#"
+        set_kb_item(name: "Known/tcp/80", value: "www");
+        set_kb_item(name: "Known/udp/53", value: "dns");
+        service_is_unknown(80);
+        service_is_unknown(22);
+        service_is_unknown(53);
+        service_is_unknown(53, ipproto: "udp");
+        service_is_unknown();
+        "#;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let results: Vec<_> = CodeInterpreter::new(code, register, &context).collect();
+        assert_eq!(results[2], Ok(NaslValue::Boolean(false)));
+        assert_eq!(results[3], Ok(NaslValue::Boolean(true)));
+        assert_eq!(results[4], Ok(NaslValue::Boolean(true)));
+        assert_eq!(results[5], Ok(NaslValue::Boolean(false)));
+        assert!(results[6].is_err());
+    }
+
+    #[test]
+    fn replace_kb_item() {
+        let code = r#"
+        set_kb_item(name: "test", value: 1);
+        set_kb_item(name: "test", value: 2);
+        replace_kb_item(name: "test", value: 3);
+        get_kb_list("test");
+        replace_kb_item(name: "absent", value: 4);
+        get_kb_item("absent");
+        replace_kb_item(name: "test");
+        "#;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Dict(
+                [("test".to_owned(), NaslValue::Number(3))].into()
+            )))
+        );
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(4))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn set_kb_item_expires() {
+        use std::sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        };
+
+        let code = r#"
+        set_kb_item(name: "transient", value: 1, expires: 10);
+        set_kb_item(name: "transient", value: 2, expire: 20);
+        set_kb_item(name: "persistent", value: 3, expires: NULL);
+        get_kb_list("transient");
+        get_kb_list("transient");
+        get_kb_item("persistent");
+        get_kb_list("transient");
+        get_kb_item("persistent");
+        set_kb_item(name: "negative", value: 1, expires: -1);
+        "#;
+        let elapsed = Arc::new(AtomicU64::new(0));
+        let clock = elapsed.clone();
+        // the clock of the storage is far behind the system time, the expiry must be based on it
+        let storage = storage::DefaultDispatcher::default()
+            .with_clock(Arc::new(move || 1000 + clock.load(Ordering::SeqCst)));
+        let binding = ContextFactory::new(
+            nasl_syntax::NoOpLoader::default(),
+            nasl_syntax::logger::DefaultLogger::default(),
+            storage,
+        );
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        let values = |x: Option<Result<NaslValue, InterpretError>>| match x {
+            Some(Ok(NaslValue::Dict(x))) => match x.get("transient") {
+                Some(NaslValue::Array(x)) => {
+                    let mut x = x.clone();
+                    x.sort_by_key(|x| i64::from(x));
+                    x
+                }
+                Some(x) => vec![x.clone()],
+                None => vec![],
+            },
+            x => panic!("expected a dict but got {x:?}"),
+        };
+        assert_eq!(values(parser.next()), vec![1.into(), 2.into()]);
+        elapsed.store(15, Ordering::SeqCst);
+        assert_eq!(values(parser.next()), vec![2.into()]);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(3))));
+        elapsed.store(25, Ordering::SeqCst);
+        assert_eq!(values(parser.next()), vec![]);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(3))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn replace_kb_item_expires() {
+        use std::sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        };
+
+        let code = r#"
+        set_kb_item(name: "transient", value: 1);
+        replace_kb_item(name: "transient", value: 2, expires: 10);
+        get_kb_item("transient");
+        get_kb_item("transient");
+        replace_kb_item(name: "transient", value: 3, expires: -1);
+        "#;
+        let elapsed = Arc::new(AtomicU64::new(0));
+        let clock = elapsed.clone();
+        let storage = storage::DefaultDispatcher::default()
+            .with_clock(Arc::new(move || 1000 + clock.load(Ordering::SeqCst)));
+        let binding = ContextFactory::new(
+            nasl_syntax::NoOpLoader::default(),
+            nasl_syntax::logger::DefaultLogger::default(),
+            storage,
+        );
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(2))));
+        elapsed.store(10, Ordering::SeqCst);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
 }
@@ -5,23 +5,24 @@
 //! Defines NASL miscellaneous functions
 
 use std::{
-    collections::HashMap,
     fs::File,
     io::{Read, Write},
     thread,
     time::{self, Duration, UNIX_EPOCH},
 };
 
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine as _};
 use chrono::{
     self, DateTime, Datelike, FixedOffset, Local, LocalResult, Offset, TimeZone, Timelike, Utc,
 };
-use nasl_syntax::NaslValue;
+use nasl_syntax::{bytes_to_str, NaslDict, NaslValue};
 
 use flate2::{
     read::GzDecoder, read::ZlibDecoder, write::GzEncoder, write::ZlibEncoder, Compression,
 };
 use nasl_builtin_utils::{error::FunctionErrorKind, resolve_positional_arguments, NaslFunction};
 use nasl_builtin_utils::{Context, ContextType, Register};
+use storage::item::{NvtPreference, PreferenceType};
 
 #[inline]
 #[cfg(unix)]
@@ -35,8 +36,26 @@ pub fn random_impl() -> Result<i64, FunctionErrorKind> {
 }
 
 /// NASL function to get random number
-fn rand(_: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
-    random_impl().map(NaslValue::Number)
+///
+/// Uses the seeded generator of the context when there is one.
+fn rand(_: &Register, context: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    match context.next_random() {
+        Some(x) => Ok(NaslValue::Number(x)),
+        None => random_impl().map(NaslValue::Number),
+    }
+}
+
+/// NASL function to reseed the random number generator
+///
+/// Following `rand` calls return a deterministic sequence for the given seed. Returns the
+/// previous seed, either of a former `set_seed` call or the initial seed of the context, or NULL
+/// when the generator was not seeded before.
+fn set_seed(register: &Register, context: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let seed = first_positional_number(register)?;
+    Ok(match context.set_seed(seed as u64) {
+        Some(previous) => NaslValue::Number(previous as i64),
+        None => NaslValue::Null,
+    })
 }
 
 /// NASL function to get host byte order
@@ -44,6 +63,123 @@ fn get_byte_order(_: &Register, _: &Context) -> Result<NaslValue, FunctionErrorK
     Ok(NaslValue::Boolean(cfg!(target_endian = "little")))
 }
 
+/// Returns the first positional argument as a number
+fn first_positional_number(register: &Register) -> Result<i64, FunctionErrorKind> {
+    match register.positional().first() {
+        Some(NaslValue::Number(x)) => Ok(*x),
+        x => Err(("0", "numeric", x).into()),
+    }
+}
+
+/// NASL function to convert a 16 bit number from host to network byte order
+fn htons(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let x = first_positional_number(register)?;
+    Ok(NaslValue::Number((x as u16).to_be() as i64))
+}
+
+/// NASL function to convert a 32 bit number from host to network byte order
+fn htonl(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let x = first_positional_number(register)?;
+    Ok(NaslValue::Number((x as u32).to_be() as i64))
+}
+
+/// NASL function to convert a 16 bit number from network to host byte order
+fn ntohs(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let x = first_positional_number(register)?;
+    Ok(NaslValue::Number(u16::from_be(x as u16) as i64))
+}
+
+/// NASL function to convert a 32 bit number from network to host byte order
+fn ntohl(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let x = first_positional_number(register)?;
+    Ok(NaslValue::Number(u32::from_be(x as u32) as i64))
+}
+
+/// Returns N bytes of the named argument blob starting at the named argument pos.
+///
+/// Returns an error when pos is negative or when there are not enough bytes left.
+fn blob_bytes<const N: usize>(register: &Register) -> Result<[u8; N], FunctionErrorKind> {
+    let blob = match register.named("blob") {
+        Some(ContextType::Value(NaslValue::Data(x))) => x.clone(),
+        Some(ContextType::Value(NaslValue::String(x))) => x.as_bytes().to_vec(),
+        x => return Err(("blob", "data", x).into()),
+    };
+    let pos = match register.named("pos") {
+        None => 0,
+        Some(ContextType::Value(NaslValue::Number(x))) => *x,
+        x => return Err(("pos", "numeric", x).into()),
+    };
+    usize::try_from(pos)
+        .ok()
+        .and_then(|pos| blob.get(pos..pos.checked_add(N)?))
+        .and_then(|x| x.try_into().ok())
+        .ok_or_else(|| {
+            FunctionErrorKind::WrongArgument(format!(
+                "pos {pos} is out of range for {N} bytes within a blob of length {}",
+                blob.len()
+            ))
+        })
+}
+
+/// NASL function to read a big endian 16 bit number from blob at pos
+fn getword(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let bytes = blob_bytes::<2>(register)?;
+    Ok(NaslValue::Number(u16::from_be_bytes(bytes) as i64))
+}
+
+/// NASL function to read a big endian 32 bit number from blob at pos
+fn getdword(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let bytes = blob_bytes::<4>(register)?;
+    Ok(NaslValue::Number(u32::from_be_bytes(bytes) as i64))
+}
+
+/// Returns true when the optional named argument little_endian is set
+fn little_endian_flag(register: &Register) -> bool {
+    match register.named("little_endian") {
+        Some(ContextType::Value(x)) => bool::from(x.clone()),
+        _ => false,
+    }
+}
+
+/// NASL function to pack a number into 2 bytes
+///
+/// The bytes are in big endian order unless the named argument little_endian is set to TRUE.
+/// Values exceeding 16 bit are masked, only the lower 16 bit are packed.
+fn mkword(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let x = first_positional_number(register)? as u16;
+    Ok(NaslValue::Data(match little_endian_flag(register) {
+        true => x.to_le_bytes().to_vec(),
+        false => x.to_be_bytes().to_vec(),
+    }))
+}
+
+/// NASL function to pack a number into 4 bytes
+///
+/// The bytes are in big endian order unless the named argument little_endian is set to TRUE.
+/// Values exceeding 32 bit are masked, only the lower 32 bit are packed.
+fn mkdword(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let x = first_positional_number(register)? as u32;
+    Ok(NaslValue::Data(match little_endian_flag(register) {
+        true => x.to_le_bytes().to_vec(),
+        false => x.to_be_bytes().to_vec(),
+    }))
+}
+
+/// NASL function to insert a zero byte after each byte of the named argument in
+///
+/// This is mostly used to transform ASCII into UTF-16LE.
+fn insert_hexzeros(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let data = match register.named("in") {
+        Some(ContextType::Value(NaslValue::Null)) => return Ok(NaslValue::Null),
+        Some(ContextType::Value(NaslValue::Data(x))) => x.clone(),
+        Some(ContextType::Value(NaslValue::String(x))) => x.as_bytes().to_vec(),
+        x => return Err(("in", "data", x).into()),
+    };
+    Ok(NaslValue::Data(
+        data.into_iter().flat_map(|x| [x, 0]).collect(),
+    ))
+}
+
 /// NASL function to convert given number to string
 fn dec2str(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
     match register.named("num") {
@@ -95,6 +231,129 @@ fn nasl_typeof(register: &Register, _: &Context) -> Result<NaslValue, FunctionEr
     }
 }
 
+/// Converts the given unnamed argument to an integer.
+///
+/// Strings are parsed like `strtol` with a base of 0: `"0x1F"` is 31, `"  42abc"` is 42 and
+/// non-numeric strings are 0.
+fn int(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    match register.positional().first() {
+        Some(x) => Ok(NaslValue::Number(x.to_number())),
+        None => Err(FunctionErrorKind::MissingPositionalArguments {
+            expected: 1,
+            got: 0,
+        }),
+    }
+}
+
+/// Returns the value of the variable with the given name.
+///
+/// The variable is looked up in the context of the caller so that the name can be computed at
+/// runtime. Returns NULL when there is no such variable.
+fn get_var(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let name = match register.positional().first() {
+        Some(NaslValue::String(x)) => x,
+        Some(NaslValue::Data(x)) => &bytes_to_str(x),
+        x => return Err(("0", "string", x).into()),
+    };
+    // the last context is created for this call, the one before belongs to the caller
+    let caller = register.index().saturating_sub(2);
+    match register.named_at(caller, name) {
+        Some(ContextType::Value(x)) => Ok(x.clone()),
+        _ => Ok(NaslValue::Null),
+    }
+}
+
+/// Parts of variable names that indicate credentials, their values are not revealed
+const SENSITIVE_NAMES: [&str; 5] = ["pass", "secret", "token", "private", "credential"];
+
+/// Returns true when the name of a variable indicates that it contains credentials
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SENSITIVE_NAMES.iter().any(|x| name.contains(x))
+}
+
+/// Returns the credentials in all encodings they are looked for
+///
+/// A credential is either the value of a preference of type password of the running script or
+/// the string or data value of a variable with a sensitive name. It is looked for as is, hex
+/// encoded in lower and upper case and base64 encoded.
+fn encoded_secrets<'a>(
+    variables: impl Iterator<Item = (&'a str, &'a ContextType)>,
+    preferences: &[NvtPreference],
+) -> Vec<Vec<u8>> {
+    let preferences = preferences
+        .iter()
+        .filter(|x| x.class() == PreferenceType::Password)
+        .map(|x| x.default().as_bytes().to_vec());
+    variables
+        .filter(|(name, _)| is_sensitive(name))
+        .filter_map(|(_, value)| match value {
+            ContextType::Value(x @ (NaslValue::String(_) | NaslValue::Data(_))) => {
+                Some(Vec::<u8>::from(x))
+            }
+            _ => None,
+        })
+        .chain(preferences)
+        .filter(|x| !x.is_empty())
+        .flat_map(|x| {
+            let hex: String = x.iter().map(|b| format!("{b:02x}")).collect();
+            // without padding it also matches the padded encoding
+            let base64 = STANDARD_NO_PAD.encode(&x);
+            [
+                hex.to_uppercase().into_bytes(),
+                hex.into_bytes(),
+                base64.into_bytes(),
+                x,
+            ]
+        })
+        .collect()
+}
+
+/// Returns true when the value or one of its elements contains one of the given secrets
+fn contains_secret(value: &NaslValue, secrets: &[Vec<u8>]) -> bool {
+    match value {
+        NaslValue::String(_) | NaslValue::Data(_) => {
+            let bytes = Vec::<u8>::from(value);
+            secrets
+                .iter()
+                .any(|s| bytes.windows(s.len()).any(|x| x == s.as_slice()))
+        }
+        NaslValue::Array(x) => x.iter().any(|x| contains_secret(x, secrets)),
+        NaslValue::Dict(x) => x.values().any(|x| contains_secret(x, secrets)),
+        _ => false,
+    }
+}
+
+/// Returns a dict of all variables visible in the context of the caller ordered by their names.
+///
+/// Functions are represented by their signature, e.g. `function(a, b)`. Values of variables
+/// whose name indicates a credential, e.g. `password`, are replaced by `<redacted>`. So are
+/// values that contain the value of a password preference of the script or of such a variable,
+/// as is, hex or base64 encoded, e.g. `hexstr(password)`. Other transformations of a
+/// credential, like a hash or a substring, are not detected.
+fn get_variables(register: &Register, c: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    // the last context is created for this call, the one before belongs to the caller
+    let caller = register.index().saturating_sub(2);
+    let mut variables: Vec<_> = register.visible_at(caller).into_iter().collect();
+    variables.sort_by_key(|(name, _)| *name);
+    let secrets = encoded_secrets(variables.iter().map(|(k, v)| (*k, *v)), c.preferences());
+    let result: NaslDict = variables
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                ContextType::Function(params, _) => {
+                    NaslValue::String(format!("function({})", params.join(", ")))
+                }
+                ContextType::Value(_) if is_sensitive(name) => "<redacted>".into(),
+                ContextType::Value(x) if contains_secret(x, &secrets) => "<redacted>".into(),
+                ContextType::Value(x) => x.clone(),
+            };
+            (name.to_owned(), value)
+        })
+        .collect();
+    Ok(NaslValue::Dict(result))
+}
+
 /// Returns true when the given unnamed argument is null.
 fn isnull(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
     let positional = register.positional();
@@ -220,11 +479,11 @@ fn mktime(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKi
     }
 }
 
-fn create_localtime_map<T>(date: chrono::DateTime<T>) -> HashMap<String, NaslValue>
+fn create_localtime_map<T>(date: chrono::DateTime<T>) -> NaslDict
 where
     T: chrono::TimeZone,
 {
-    HashMap::from([
+    NaslDict::from([
         ("sec".to_string(), NaslValue::from(date.second() as i64)),
         ("min".to_string(), NaslValue::from(date.minute() as i64)),
         ("hour".to_string(), NaslValue::from(date.hour() as i64)),
@@ -320,10 +579,23 @@ fn dump_ctxt(register: &Register, _: &Context) -> Result<NaslValue, FunctionErro
 fn lookup(key: &str) -> Option<NaslFunction> {
     match key {
         "rand" => Some(rand),
+        "set_seed" => Some(set_seed),
         "get_byte_order" => Some(get_byte_order),
+        "htons" => Some(htons),
+        "htonl" => Some(htonl),
+        "ntohs" => Some(ntohs),
+        "ntohl" => Some(ntohl),
+        "getword" => Some(getword),
+        "getdword" => Some(getdword),
+        "mkword" => Some(mkword),
+        "mkdword" => Some(mkdword),
+        "insert_hexzeros" => Some(insert_hexzeros),
         "dec2str" => Some(dec2str),
         "typeof" => Some(nasl_typeof),
         "isnull" => Some(isnull),
+        "int" => Some(int),
+        "get_var" => Some(get_var),
+        "get_variables" => Some(get_variables),
         "unixtime" => Some(unixtime),
         "localtime" => Some(localtime),
         "mktime" => Some(mktime),
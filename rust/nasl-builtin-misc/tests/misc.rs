@@ -28,6 +28,46 @@ mod tests {
         assert_ne!(first, second);
     }
 
+    #[test]
+    fn set_seed() {
+        let code = r###"
+        set_seed(42);
+        rand();
+        rand();
+        set_seed(42);
+        rand();
+        rand();
+        set_seed(7);
+        "###;
+        let run = || {
+            let binding = ContextFactory::default();
+            let context = binding.build(Default::default(), Default::default());
+            CodeInterpreter::new(code, Register::default(), &context)
+                .filter_map(|x| x.ok())
+                .collect::<Vec<_>>()
+        };
+        let results = run();
+        assert_eq!(results[0], NaslValue::Null);
+        assert_eq!(results[1..3], results[4..6]);
+        assert_ne!(results[1], results[2]);
+        assert_eq!(results[3], NaslValue::Number(42));
+        assert_eq!(results[6], NaslValue::Number(42));
+        assert_eq!(results, run());
+    }
+
+    #[test]
+    fn initial_seed() {
+        let code = r###"
+        rand();
+        set_seed(1);
+        "###;
+        let binding = ContextFactory::default().seed(23);
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert!(matches!(parser.next(), Some(Ok(NaslValue::Number(_)))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(23))));
+    }
+
     #[test]
     fn get_byte_order() {
         let code = r###"
@@ -94,6 +134,118 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok(NaslValue::Boolean(true))));
     }
 
+    #[test]
+    fn int() {
+        let code = r###"
+        int("0x1F");
+        int("  42abc");
+        int("-7");
+        int("abc");
+        int(23);
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(31))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(42))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(-7))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(0))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(23))));
+    }
+
+    #[test]
+    fn get_var() {
+        let code = r###"
+        foo_1 = 42;
+        i = 1;
+        get_var("foo_" + i);
+        get_var("foo_2");
+        function f() {
+            local_var bar;
+            bar = "local";
+            return get_var("bar");
+        }
+        f();
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.next();
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(42))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(NaslValue::String("local".into()))));
+    }
+
+    #[test]
+    fn get_variables() {
+        let code = r###"
+        a = 1;
+        password = "secret";
+        hex = "0x" + hexstr(password);
+        encoded = "c2VjcmV0";
+        list = make_list(1, "my secret");
+        hash = "e5e9fa1ba31ecd1ae84f75caaa474f3a";
+        login = "admin:" + script_get_preference("Login password");
+        typed = "hunter2";
+        encoded_login = "aHVudGVyMg";
+        other = script_get_preference("Login name");
+        function f(x) {
+            local_var b;
+            b = "local";
+            return get_variables();
+        }
+        f(x: 2);
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory {
+            preferences: vec![
+                ("1", "Login name", "entry", "admin").into(),
+                ("2", "Login password", "password", "hunter2").into(),
+            ],
+            ..Default::default()
+        };
+        let context = binding.build(Default::default(), Default::default());
+        let parser = CodeInterpreter::new(code, register, &context);
+        let result = parser.last();
+        let vars = match result {
+            Some(Ok(NaslValue::Dict(x))) => x,
+            x => panic!("expected a dict, got {x:?}"),
+        };
+        assert_eq!(vars.get("a"), Some(&NaslValue::Number(1)));
+        assert_eq!(vars.get("b"), Some(&NaslValue::String("local".into())));
+        assert_eq!(vars.get("x"), Some(&NaslValue::Number(2)));
+        assert_eq!(
+            vars.get("password"),
+            Some(&NaslValue::String("<redacted>".into()))
+        );
+        assert_eq!(
+            vars.get("f"),
+            Some(&NaslValue::String("function(x)".into()))
+        );
+        let mut names: Vec<_> = vars.keys().cloned().collect();
+        names.sort();
+        assert_eq!(vars.keys().cloned().collect::<Vec<_>>(), names);
+        assert_eq!(vars.get("other"), Some(&NaslValue::String("admin".into())));
+        for name in ["hex", "encoded", "list", "login", "typed", "encoded_login"] {
+            assert_eq!(
+                vars.get(name),
+                Some(&NaslValue::String("<redacted>".into())),
+                "{name}"
+            );
+        }
+        // only the credential itself and its hex and base64 encodings are detected
+        assert_eq!(
+            vars.get("hash"),
+            Some(&NaslValue::String(
+                "e5e9fa1ba31ecd1ae84f75caaa474f3a".into()
+            ))
+        );
+    }
+
     #[test]
     fn unixtime() {
         let code = r###"
@@ -294,4 +446,83 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok(false.into()))); // is a a function
         assert_eq!(parser.next(), Some(Ok(false.into()))); // is the value of a a function
     }
+
+    #[test]
+    fn byte_order_round_trip() {
+        let code = r###"
+        ntohs(htons(0x1234));
+        ntohl(htonl(0x12345678));
+        htons(0x1234) == 0x1234;
+        htonl(0x12345678) == 0x12345678;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(parser.next(), Some(Ok(0x1234.into())));
+        assert_eq!(parser.next(), Some(Ok(0x12345678.into())));
+        let big_endian = cfg!(target_endian = "big");
+        assert_eq!(parser.next(), Some(Ok(big_endian.into())));
+        assert_eq!(parser.next(), Some(Ok(big_endian.into())));
+    }
+
+    #[test]
+    fn getword_getdword() {
+        let code = r###"
+        blob = raw_string(0x00, 0x12, 0x34, 0x56, 0x78);
+        getword(blob: blob, pos: 1);
+        getdword(blob: blob, pos: 1);
+        getword(blob: blob);
+        getword(blob: blob, pos: 4);
+        getdword(blob: blob, pos: 2);
+        getword(blob: blob, pos: -1);
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(0x1234.into())));
+        assert_eq!(parser.next(), Some(Ok(0x12345678.into())));
+        assert_eq!(parser.next(), Some(Ok(0x0012.into())));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn mkword_mkdword() {
+        let code = r###"
+        mkword(0x1234);
+        mkword(0x1234, little_endian: TRUE);
+        mkdword(0x12345678);
+        mkdword(0x12345678, little_endian: TRUE);
+        mkword(0x123456);
+        getword(blob: mkword(0x1234));
+        getdword(blob: mkdword(0x12345678));
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(parser.next(), Some(Ok(vec![0x12, 0x34].into())));
+        assert_eq!(parser.next(), Some(Ok(vec![0x34, 0x12].into())));
+        assert_eq!(parser.next(), Some(Ok(vec![0x12, 0x34, 0x56, 0x78].into())));
+        assert_eq!(parser.next(), Some(Ok(vec![0x78, 0x56, 0x34, 0x12].into())));
+        assert_eq!(parser.next(), Some(Ok(vec![0x34, 0x56].into())));
+        assert_eq!(parser.next(), Some(Ok(0x1234.into())));
+        assert_eq!(parser.next(), Some(Ok(0x12345678.into())));
+    }
+
+    #[test]
+    fn insert_hexzeros() {
+        let code = r###"
+        insert_hexzeros(in: "ab");
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(parser.next(), Some(Ok(vec![0x61, 0, 0x62, 0].into())));
+    }
 }
@@ -5,8 +5,9 @@
 //! Defines NASL packet forgery functions
 
 use std::{
-    net::{Ipv4Addr, SocketAddr},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6},
     str::FromStr,
+    time::{Duration, Instant},
 };
 
 use super::raw_ip_utils::{get_interface_by_local_ip, get_source_ip, islocalhost};
@@ -19,12 +20,13 @@ use nasl_builtin_utils::{
 use nasl_syntax::{logger::NaslLogger, NaslValue};
 
 use pcap::Capture;
+use pnet::datalink::interfaces;
 use pnet::packet::{
     self,
     ethernet::EthernetPacket,
     icmp::*,
     ip::{IpNextHeaderProtocol, IpNextHeaderProtocols},
-    ipv4::{checksum, MutableIpv4Packet},
+    ipv4::{checksum, Ipv4Flags, MutableIpv4Packet},
     tcp::{TcpOption, TcpOptionNumbers, TcpPacket, *},
     udp::UdpPacket,
     Packet, PrimitiveValues,
@@ -103,6 +105,26 @@ fn safe_copy_from_slice(
     Ok(())
 }
 
+/// Returns the payload of the IP datagram within the buffer
+///
+/// The payload ends at the total length of the datagram or at the end of the buffer. Returns None
+/// when the buffer ends within the IP header, so that no field beyond the data is read.
+fn ip_payload(buf: &[u8]) -> Option<&[u8]> {
+    let ip = packet::ipv4::Ipv4Packet::new(buf)?;
+    let start = ip.get_header_length() as usize * 4;
+    let end = (ip.get_total_length() as usize).max(start).min(buf.len());
+    buf.get(start..end)
+}
+
+/// Returns the TCP segment of the IP datagram within the buffer
+///
+/// Returns None when the buffer ends within the IP or the TCP header.
+fn tcp_segment(buf: &[u8]) -> Option<TcpPacket<'_>> {
+    ip_payload(buf)
+        .and_then(TcpPacket::new)
+        .filter(|tcp| tcp.get_data_offset() as usize * 4 <= tcp.packet().len())
+}
+
 /// Forge an IP datagram inside the block of data. It takes following arguments:
 ///  
 /// - data: is the payload.
@@ -374,9 +396,11 @@ fn get_ip_element(register: &Register, _configs: &Context) -> Result<NaslValue,
         }
     };
 
-    let pkt = packet::ipv4::Ipv4Packet::new(&buf).ok_or_else(|| {
-        FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-    })?;
+    let pkt = match packet::ipv4::Ipv4Packet::new(&buf) {
+        Some(pkt) if pkt.get_header_length() as usize * 4 <= buf.len() => pkt,
+        // the buffer ends within the IP header
+        _ => return Ok(NaslValue::Null),
+    };
 
     match register.named("element") {
         Some(ContextType::Value(NaslValue::String(e))) => match e.as_str() {
@@ -569,6 +593,85 @@ fn insert_ip_options(
     Ok(NaslValue::Data(new_pkt.packet().to_vec()))
 }
 
+/// Splits the payload of an IP datagram into fragments. Its arguments are:
+///
+/// - packet: is the IP datagram to fragment
+/// - mtu: is the maximum size of a fragment including the IP header
+///
+/// Each fragment gets a copy of the IP header, including its options. The payload of each fragment
+/// but the last one is a multiple of 8 bytes and has the more fragments flag set. The fragment
+/// offset is given in 8 bytes units and continues the offset of the given datagram, so that a
+/// fragment can be fragmented again. A datagram whose payload fits into the mtu is returned as is.
+///
+/// Returns an array of IP datagrams.
+fn forge_ip_fragments(
+    register: &Register,
+    _configs: &Context,
+) -> Result<NaslValue, FunctionErrorKind> {
+    let buf = match register.named("packet") {
+        Some(ContextType::Value(NaslValue::Data(d))) => d.clone(),
+        _ => {
+            return Err(FunctionErrorKind::from("packet"));
+        }
+    };
+    let mtu = match register.named("mtu") {
+        Some(ContextType::Value(NaslValue::Number(x))) => *x,
+        _ => {
+            return Err(FunctionErrorKind::from("mtu"));
+        }
+    };
+
+    let (ip, payload) = match (packet::ipv4::Ipv4Packet::new(&buf), ip_payload(&buf)) {
+        (Some(ip), Some(payload)) => (ip, payload),
+        _ => {
+            return Err(FunctionErrorKind::WrongArgument(
+                "packet is not a valid IP datagram".to_string(),
+            ))
+        }
+    };
+    let hl = ip.get_header_length() as usize * 4;
+    // the payload of a fragment must be a multiple of 8 bytes
+    let chunk = usize::try_from(mtu).unwrap_or_default().saturating_sub(hl) / 8 * 8;
+    if chunk == 0 {
+        return Err(FunctionErrorKind::WrongArgument(format!(
+            "mtu {mtu} does not fit the IP header of {hl} bytes and 8 bytes of data"
+        )));
+    }
+    if payload.len() <= chunk {
+        return Ok(NaslValue::Array(vec![NaslValue::Data(buf)]));
+    }
+
+    let offset = ip.get_fragment_offset() as usize;
+    let flags = ip.get_flags();
+    let last = payload.len().div_ceil(chunk) - 1;
+    if offset + last * chunk / 8 > IP_OFFMASK as usize {
+        return Err(FunctionErrorKind::WrongArgument(
+            "fragment offset exceeds the maximum of the IP header".to_string(),
+        ));
+    }
+
+    let mut fragments = vec![];
+    for (i, data) in payload.chunks(chunk).enumerate() {
+        let mut fragment = vec![0u8; hl + data.len()];
+        safe_copy_from_slice(&mut fragment, 0, hl, &buf, 0, hl)?;
+        safe_copy_from_slice(&mut fragment, hl, hl + data.len(), data, 0, data.len())?;
+        let mut pkt = MutableIpv4Packet::new(&mut fragment).ok_or_else(|| {
+            FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
+        })?;
+        pkt.set_total_length((hl + data.len()) as u16);
+        pkt.set_fragment_offset((offset + i * chunk / 8) as u16);
+        // the last fragment keeps the flags of the datagram, so that the more fragments flag stays
+        // set when a fragment in the middle is fragmented again
+        if i < last {
+            pkt.set_flags(flags | Ipv4Flags::MoreFragments);
+        }
+        let sum = checksum(&pkt.to_immutable());
+        pkt.set_checksum(sum);
+        fragments.push(NaslValue::Data(fragment));
+    }
+    Ok(NaslValue::Array(fragments))
+}
+
 /// Fills an IP datagram with TCP data. Note that the ip_p field is not updated. It returns the modified IP datagram. Its arguments are:
 ///  
 /// - data: is the TCP data payload.
@@ -720,12 +823,11 @@ fn get_tcp_element(
         }
     };
 
-    let ip = packet::ipv4::Ipv4Packet::new(&buf).ok_or_else(|| {
-        FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-    })?;
-    let tcp = packet::tcp::TcpPacket::new(ip.payload()).ok_or_else(|| {
-        FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-    })?;
+    let tcp = match tcp_segment(&buf) {
+        Some(tcp) => tcp,
+        // the buffer ends within the IP or TCP header
+        None => return Ok(NaslValue::Null),
+    };
 
     match register.named("element") {
         Some(ContextType::Value(NaslValue::String(el))) => match el.as_str() {
@@ -765,12 +867,11 @@ fn get_tcp_option(register: &Register, _configs: &Context) -> Result<NaslValue,
         }
     };
 
-    let ip = packet::ipv4::Ipv4Packet::new(&buf).ok_or_else(|| {
-        FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-    })?;
-    let tcp = packet::tcp::TcpPacket::new(ip.payload()).ok_or_else(|| {
-        FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-    })?;
+    let tcp = match tcp_segment(&buf) {
+        Some(tcp) => tcp,
+        // the buffer ends within the IP or TCP header
+        None => return Ok(NaslValue::Null),
+    };
 
     let mut max_seg: i64 = 0;
     let mut window: i64 = 0;
@@ -1599,12 +1700,11 @@ fn get_udp_element(
         }
     };
 
-    let ip = packet::ipv4::Ipv4Packet::new(&buf).ok_or_else(|| {
-        FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-    })?;
-    let udp = packet::udp::UdpPacket::new(ip.payload()).ok_or_else(|| {
-        FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-    })?;
+    let udp = match ip_payload(&buf).and_then(UdpPacket::new) {
+        Some(udp) => udp,
+        // the buffer ends within the IP or UDP header
+        None => return Ok(NaslValue::Null),
+    };
 
     match register.named("element") {
         Some(ContextType::Value(NaslValue::String(el))) => match el.as_str() {
@@ -1737,12 +1837,11 @@ fn get_icmp_element(
         }
     };
 
-    let ip = packet::ipv4::Ipv4Packet::new(&buf).ok_or_else(|| {
-        FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-    })?;
-    let icmp = packet::icmp::IcmpPacket::new(ip.payload()).ok_or_else(|| {
-        FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-    })?;
+    let icmp = match ip_payload(&buf).and_then(IcmpPacket::new) {
+        Some(icmp) => icmp,
+        // the buffer ends within the IP or ICMP header
+        None => return Ok(NaslValue::Null),
+    };
 
     match register.named("element") {
         Some(ContextType::Value(NaslValue::String(el))) => match el.as_str() {
@@ -2165,6 +2264,128 @@ fn nasl_tcp_ping(register: &Register, configs: &Context) -> Result<NaslValue, Fu
     Ok(NaslValue::Null)
 }
 
+/// Returns the capture timeout in milliseconds of the given value in seconds
+fn pcap_timeout(value: Option<&ContextType>) -> Result<i32, FunctionErrorKind> {
+    match value {
+        Some(ContextType::Value(NaslValue::Number(x))) => x
+            .checked_mul(1000)
+            .and_then(|x| i32::try_from(x).ok())
+            .filter(|x| *x >= 0)
+            .ok_or_else(|| ("Integer", "Invalid timeout value").into()),
+        None => Ok(DEFAULT_TIMEOUT),
+        _ => Err(("Integer", "Invalid timeout value").into()),
+    }
+}
+
+/// The arguments send_packet and send_v6packet have in common
+#[derive(Debug, PartialEq, Eq)]
+struct SendOptions {
+    /// Captures an answer after each packet
+    use_pcap: bool,
+    /// BPF filter used for the answers
+    filter: String,
+    /// Time to wait for an answer in milliseconds
+    timeout: i32,
+    /// Length each packet is cut to, 0 sends the whole packet
+    length: usize,
+}
+
+impl SendOptions {
+    fn new(register: &Register) -> Result<Self, FunctionErrorKind> {
+        let use_pcap = match register.named("pcap_active") {
+            Some(ContextType::Value(NaslValue::Boolean(x))) => *x,
+            None => true,
+            _ => return Err(("Boolean", "Invalid pcap_active value").into()),
+        };
+        let filter = match register.named("pcap_filter") {
+            Some(ContextType::Value(NaslValue::String(x))) => x.to_string(),
+            None => String::new(),
+            _ => return Err(("String", "Invalid pcap_filter value").into()),
+        };
+        let length = match register.named("length") {
+            Some(ContextType::Value(NaslValue::Number(x))) => usize::try_from(*x).unwrap_or(0),
+            None => 0,
+            _ => return Err(("Number", "Invalid length value").into()),
+        };
+        Ok(Self {
+            use_pcap,
+            filter,
+            timeout: pcap_timeout(register.named("pcap_timeout"))?,
+            length,
+        })
+    }
+
+    /// Returns the part of the packet that is sent
+    fn cut<'a>(&self, packet: &'a [u8]) -> &'a [u8] {
+        match self.length {
+            0 => packet,
+            x => &packet[..x.min(packet.len())],
+        }
+    }
+}
+
+/// Sends each packet and returns the first answer
+///
+/// The socket address of a packet is returned by destination, which rejects packets that must
+/// not be sent. When capturing is enabled the next packet matching the filter is captured on
+/// iface after each sent packet, the remaining packets are only sent while there is no answer.
+fn send_packets<F>(
+    name: &str,
+    configs: &Context,
+    options: &SendOptions,
+    soc: &Socket,
+    iface: Option<pcap::Device>,
+    packets: &[NaslValue],
+    mut destination: F,
+) -> Result<NaslValue, FunctionErrorKind>
+where
+    F: FnMut(&[u8]) -> Result<socket2::SockAddr, FunctionErrorKind>,
+{
+    // The capture is opened before sending to not miss an answer.
+    let mut capture_dev = match iface.filter(|_| options.use_pcap) {
+        Some(iface) => {
+            let mut capture = match Capture::from_device(iface) {
+                Ok(c) => match c.promisc(true).timeout(options.timeout).open() {
+                    Ok(capture) => capture,
+                    Err(e) => return custom_error!("{}", format!("{name}: {e}")),
+                },
+                Err(e) => return custom_error!("{}", format!("{name}: {e}")),
+            };
+            if let Err(e) = capture.filter(&options.filter, true) {
+                return custom_error!("{}", format!("{name}: {e}"));
+            }
+            Some(capture)
+        }
+        None => None,
+    };
+
+    for pkt in packets {
+        let packet_raw = match pkt {
+            NaslValue::Data(data) => data as &[u8],
+            _ => return Err(("Data", "Invalid packet").into()),
+        };
+        let sockaddr = destination(packet_raw)?;
+        match soc.send_to(options.cut(packet_raw), &sockaddr) {
+            Ok(b) => {
+                configs.logger().debug(&format!("Sent {} bytes", b));
+            }
+            Err(e) => {
+                return Err(FunctionErrorKind::Diagnostic(
+                    format!("{name}: {e}"),
+                    Some(NaslValue::Null),
+                ));
+            }
+        }
+
+        if let Some(capture_dev) = capture_dev.as_mut() {
+            if let Ok(packet) = capture_dev.next_packet() {
+                return Ok(NaslValue::Data(packet.data.to_vec()));
+            }
+        }
+    }
+    Ok(NaslValue::Null)
+}
+
 /// Send a list of packets, passed as unnamed arguments, with the option to listen to the answers.
 ///  
 /// The arguments are:
@@ -2178,25 +2399,9 @@ fn nasl_send_packet(
     register: &Register,
     configs: &Context,
 ) -> Result<NaslValue, FunctionErrorKind> {
-    let use_pcap = match register.named("pcap_active") {
-        Some(ContextType::Value(NaslValue::Boolean(x))) => *x,
-        None => true,
-        _ => return Err(("Boolean", "Invalid pcap_active value").into()),
-    };
+    let options = SendOptions::new(register)?;
 
-    let filter = match register.named("pcap_filter") {
-        Some(ContextType::Value(NaslValue::String(x))) => x.to_string(),
-        None => String::new(),
-        _ => return Err(("String", "Invalid pcap_filter value").into()),
-    };
-
-    let timeout = match register.named("pcap_timeout") {
-        Some(ContextType::Value(NaslValue::Number(x))) => *x as i32 * 1000i32, // to milliseconds
-        None => DEFAULT_TIMEOUT,
-        _ => return Err(("Integer", "Invalid timeout value").into()),
-    };
-
-    let mut allow_broadcast = match register.named("allow_broadcast") {
+    let allow_broadcast = match register.named("allow_broadcast") {
         Some(ContextType::Value(NaslValue::Boolean(x))) => *x,
         None => false,
         _ => return Err(("Boolean", "Invalid allow_broadcast value").into()),
@@ -2215,88 +2420,190 @@ fn nasl_send_packet(
             e
         )));
     };
-
-    let _dflt_packet_sz = match register.named("length") {
-        Some(ContextType::Value(NaslValue::Number(x))) => *x,
-        None => 0,
-        _ => return Err(("Number", "Invalid length value").into()),
-    };
+    if allow_broadcast {
+        if let Err(err) = soc.set_broadcast(true) {
+            return custom_error!("Not possible to set broadcast soc option: {}", err);
+        }
+    }
 
     // Get the iface name, to set the capture device.
     let target_ip = get_host_ip(configs)?;
-    let local_ip = get_source_ip(target_ip, 50000u16)?;
-    let iface = get_interface_by_local_ip(local_ip)?;
-
-    let mut capture_dev = match Capture::from_device(iface) {
-        Ok(c) => match c.promisc(true).timeout(timeout).open() {
-            Ok(capture) => capture,
-            Err(e) => return custom_error!("send_packet: {}", e),
+    let iface = if options.use_pcap {
+        Some(get_interface_by_local_ip(get_source_ip(
+            target_ip, 50000u16,
+        )?)?)
+    } else {
+        None
+    };
+
+    send_packets(
+        "send_packet",
+        configs,
+        &options,
+        &soc,
+        iface,
+        positional,
+        |packet_raw| {
+            let packet = packet::ipv4::Ipv4Packet::new(packet_raw).ok_or_else(|| {
+                FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
+            })?;
+            let destination = packet.get_destination();
+            // No broadcast destination and dst ip address inside the IP packet
+            // differs from target IP, is consider a malicious or buggy script.
+            if destination != target_ip && !(allow_broadcast && destination.is_broadcast()) {
+                return Err(FunctionErrorKind::Dirty(
+                    format!("send_packet: malicious or buggy script is trying to send packet to {} instead of designated target {}",
+                            destination, target_ip)
+                ));
+            }
+            Ok(SocketAddr::new(IpAddr::V4(destination), 0).into())
         },
-        Err(e) => return custom_error!("send_packet: {}", e),
-    };
+    )
+}
 
-    for pkt in positional.iter() {
-        let packet_raw = match pkt {
-            NaslValue::Data(data) => data as &[u8],
-            _ => return Err(("Data", "Invalid packet").into()),
-        };
-        let packet = packet::ipv4::Ipv4Packet::new(packet_raw).ok_or_else(|| {
-            FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-        })?;
+/// Returns true for unicast link-local addresses (fe80::/10), which are only unique per link
+fn is_link_local(addr: &Ipv6Addr) -> bool {
+    addr.segments()[0] & 0xffc0 == 0xfe80
+}
 
-        if allow_broadcast {
-            if let Err(err) = soc.set_broadcast(true) {
-                return custom_error!("Not possible to set broadcast soc option: {}", err);
-            }
-            // We allow broadcast, only if the dst ip inside the packet is the broadcast
-            allow_broadcast = packet.get_destination().is_broadcast();
-        }
+/// The network interface IPv6 packets are sent on
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Scope {
+    index: u32,
+    name: String,
+}
 
-        // No broadcast destination and dst ip address inside the IP packet
-        // differs from target IP, is consider a malicious or buggy script.
-        if packet.get_destination() != target_ip && !allow_broadcast {
-            return Err(FunctionErrorKind::Dirty(
-                format!("send_packet: malicious or buggy script is trying to send packet to {} instead of designated target {}",
-                        packet.get_destination(), target_ip)
-            ));
-        }
+/// Returns the interface of the scope_id argument, given either by its name or by its index
+fn scope_id(value: Option<&ContextType>) -> Result<Option<Scope>, FunctionErrorKind> {
+    let interface = match value {
+        None => return Ok(None),
+        Some(ContextType::Value(NaslValue::String(name))) => interfaces()
+            .into_iter()
+            .find(|x| x.name == *name)
+            .ok_or_else(|| format!("scope_id: no interface named {name}")),
+        Some(ContextType::Value(NaslValue::Number(index))) => interfaces()
+            .into_iter()
+            .find(|x| x.index as i64 == *index)
+            .ok_or_else(|| format!("scope_id: no interface with index {index}")),
+        _ => return Err(("String or Integer", "Invalid scope_id value").into()),
+    };
+    interface
+        .map(|x| {
+            Some(Scope {
+                index: x.index,
+                name: x.name,
+            })
+        })
+        .map_err(FunctionErrorKind::WrongArgument)
+}
 
-        let sock_str = format!("{}:{}", &packet.get_destination().to_string().as_str(), 0);
-        let sockaddr = match SocketAddr::from_str(&sock_str) {
-            Ok(addr) => socket2::SockAddr::from(addr),
-            Err(e) => {
-                return Err(FunctionErrorKind::Diagnostic(
-                    format!("send_packet: {}", e),
-                    Some(NaslValue::Null),
-                ));
-            }
-        };
+/// Returns the socket address to send an IPv6 packet to
+///
+/// A link-local destination requires a scope, as the same address may exist on each link.
+fn ipv6_destination(
+    destination: Ipv6Addr,
+    scope: Option<&Scope>,
+) -> Result<SocketAddrV6, FunctionErrorKind> {
+    match scope {
+        Some(scope) => Ok(SocketAddrV6::new(destination, 0, 0, scope.index)),
+        None if is_link_local(&destination) => Err(FunctionErrorKind::WrongArgument(format!(
+            "link-local address {destination} requires a scope_id"
+        ))),
+        None => Ok(SocketAddrV6::new(destination, 0, 0, 0)),
+    }
+}
 
-        match soc.send_to(packet_raw, &sockaddr) {
-            Ok(b) => {
-                configs.logger().debug(&format!("Sent {} bytes", b));
-            }
-            Err(e) => {
-                return Err(FunctionErrorKind::Diagnostic(
-                    format!("send_packet: {}", e),
-                    Some(NaslValue::Null),
-                ));
-            }
-        }
+/// Send a list of IPv6 packets, passed as unnamed arguments, with the option to listen to the
+/// answers.
+///
+/// - length: default length of each every packet, if a packet does not fit, its actual size is
+///   taken instead
+/// - pcap_active: option to capture the answers, TRUE by default
+/// - pcap_filter: BPF filter used for the answers
+/// - pcap_timeout: time to wait for the answers in seconds, 5 by default
+/// - allow_multicast: allows multicast destinations, FALSE by default
+/// - scope_id: name or index of the interface to send on, required for link-local targets
+fn nasl_send_v6packet(
+    register: &Register,
+    configs: &Context,
+) -> Result<NaslValue, FunctionErrorKind> {
+    let options = SendOptions::new(register)?;
 
-        if use_pcap {
-            let p = match capture_dev.filter(&filter, true) {
-                Ok(_) => capture_dev.next_packet(),
-                Err(e) => Err(pcap::Error::PcapError(e.to_string())),
-            };
+    let allow_multicast = match register.named("allow_multicast") {
+        Some(ContextType::Value(NaslValue::Boolean(x))) => *x,
+        None => false,
+        _ => return Err(("Boolean", "Invalid allow_multicast value").into()),
+    };
 
-            match p {
-                Ok(packet) => return Ok(NaslValue::Data(packet.data.to_vec())),
-                Err(_) => return Ok(NaslValue::Null),
-            };
+    let scope = scope_id(register.named("scope_id"))?;
+
+    let positional = register.positional();
+    if positional.is_empty() {
+        return Ok(NaslValue::Null);
+    }
+
+    let target_ip = match get_host_ip(configs)? {
+        IpAddr::V6(ip) => ip,
+        ip => {
+            return Err(FunctionErrorKind::WrongArgument(format!(
+                "send_v6packet: target {ip} is not an IPv6 address"
+            )))
+        }
+    };
+    // verify the scope before any socket is opened
+    ipv6_destination(target_ip, scope.as_ref())?;
+
+    let soc = match Socket::new_raw(
+        Domain::IPV6,
+        socket2::Type::RAW,
+        Some(Protocol::from(IPPROTO_RAW)),
+    ) {
+        Ok(s) => s,
+        Err(e) => {
+            return custom_error!("send_v6packet: not possible to create a raw socket: {}", e)
+        }
+    };
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    if let Some(scope) = &scope {
+        if let Err(e) = soc.bind_device(Some(scope.name.as_bytes())) {
+            return custom_error!("send_v6packet: not possible to bind to the scope: {}", e);
         }
     }
-    Ok(NaslValue::Null)
+
+    // The interface of the scope is used as capture device, otherwise the one of the route to
+    // the target.
+    let iface = match &scope {
+        _ if !options.use_pcap => None,
+        Some(scope) => Some(pcap::Device::from(scope.name.as_str())),
+        None => Some(get_interface_by_local_ip(get_source_ip(
+            IpAddr::V6(target_ip),
+            50000u16,
+        )?)?),
+    };
+
+    send_packets(
+        "send_v6packet",
+        configs,
+        &options,
+        &soc,
+        iface,
+        positional,
+        |packet_raw| {
+            let packet = packet::ipv6::Ipv6Packet::new(packet_raw).ok_or_else(|| {
+                FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
+            })?;
+            let destination = packet.get_destination();
+            // A destination that differs from the target and is no allowed multicast address is
+            // considered a malicious or buggy script.
+            if destination != target_ip && !(allow_multicast && destination.is_multicast()) {
+                return Err(FunctionErrorKind::Dirty(
+                    format!("send_v6packet: malicious or buggy script is trying to send packet to {} instead of designated target {}",
+                            destination, target_ip)
+                ));
+            }
+            Ok(ipv6_destination(destination, scope.as_ref())?.into())
+        },
+    )
 }
 
 /// This function is the same as send_capture().
@@ -2308,32 +2615,164 @@ fn nasl_pcap_next(register: &Register, configs: &Context) -> Result<NaslValue, F
     nasl_send_capture(register, configs)
 }
 
+/// Sends a forged IPv4 or IPv6 packet to its destination, which must be the target
+fn send_to_target(packet_raw: &[u8], target_ip: IpAddr) -> Result<usize, FunctionErrorKind> {
+    let (soc, destination) = match packet_raw.first().map(|x| x >> 4) {
+        Some(4) => {
+            let packet = packet::ipv4::Ipv4Packet::new(packet_raw).ok_or_else(|| {
+                FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
+            })?;
+            let soc = new_raw_socket()?;
+            if let Err(e) = soc.set_header_included(true) {
+                return custom_error!("send_capture: not possible to create a raw socket: {}", e);
+            };
+            let destination = packet.get_destination();
+            (soc, SocketAddr::new(IpAddr::V4(destination), 0))
+        }
+        Some(6) => {
+            let packet = packet::ipv6::Ipv6Packet::new(packet_raw).ok_or_else(|| {
+                FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
+            })?;
+            let soc = match Socket::new_raw(
+                Domain::IPV6,
+                socket2::Type::RAW,
+                Some(Protocol::from(IPPROTO_RAW)),
+            ) {
+                Ok(s) => s,
+                Err(e) => {
+                    return custom_error!(
+                        "send_capture: not possible to create a raw socket: {}",
+                        e
+                    )
+                }
+            };
+            let destination = ipv6_destination(packet.get_destination(), None)?;
+            (soc, SocketAddr::V6(destination))
+        }
+        _ => return Err(("IPv4 or IPv6 packet", "Invalid packet").into()),
+    };
+
+    // A destination that differs from the target is considered a malicious or buggy script.
+    if destination.ip() != target_ip {
+        return Err(FunctionErrorKind::Dirty(
+            format!("send_capture: malicious or buggy script is trying to send packet to {} instead of designated target {}",
+                    destination.ip(), target_ip)
+        ));
+    }
+
+    soc.send_to(packet_raw, &socket2::SockAddr::from(destination))
+        .map_err(|e| {
+            FunctionErrorKind::Diagnostic(format!("send_capture: {}", e), Some(NaslValue::Null))
+        })
+}
+
+/// The addresses and, for TCP and UDP, the ports of the source and destination of an IP packet
+type Endpoints = ((IpAddr, Option<u16>), (IpAddr, Option<u16>));
+
+/// Returns the source and destination of an IPv4 or IPv6 packet
+fn endpoints(packet: &[u8]) -> Option<Endpoints> {
+    let (source, destination, protocol, payload) = match packet.first()? >> 4 {
+        4 => {
+            let ip = packet::ipv4::Ipv4Packet::new(packet)?;
+            (
+                IpAddr::V4(ip.get_source()),
+                IpAddr::V4(ip.get_destination()),
+                ip.get_next_level_protocol(),
+                ip.payload().to_vec(),
+            )
+        }
+        6 => {
+            let ip = packet::ipv6::Ipv6Packet::new(packet)?;
+            (
+                IpAddr::V6(ip.get_source()),
+                IpAddr::V6(ip.get_destination()),
+                ip.get_next_header(),
+                ip.payload().to_vec(),
+            )
+        }
+        _ => return None,
+    };
+    let ports = match protocol {
+        IpNextHeaderProtocols::Tcp => {
+            TcpPacket::new(&payload).map(|x| (x.get_source(), x.get_destination()))
+        }
+        IpNextHeaderProtocols::Udp => {
+            UdpPacket::new(&payload).map(|x| (x.get_source(), x.get_destination()))
+        }
+        _ => None,
+    };
+    Some((
+        (source, ports.map(|x| x.0)),
+        (destination, ports.map(|x| x.1)),
+    ))
+}
+
+/// Returns the first captured packet until the deadline that answers the sent packet
+///
+/// An answer is sent from the destination to the source of the sent packet, for TCP and UDP
+/// the ports are swapped as well. This skips the sent packet itself, which is seen by the
+/// capture device as well, e.g. on the loopback interface. Without a sent packet, or when it
+/// is no IP packet, the first captured packet is returned.
+///
+/// Read timeouts of next are retried until the deadline passed, any other error ends the
+/// capture.
+fn first_reply<F>(sent: Option<&[u8]>, deadline: Instant, mut next: F) -> Option<Vec<u8>>
+where
+    F: FnMut() -> Result<Vec<u8>, pcap::Error>,
+{
+    let expected = sent
+        .and_then(endpoints)
+        .map(|(source, destination)| (destination, source));
+    while Instant::now() < deadline {
+        match next() {
+            Ok(packet) if expected.is_some() && endpoints(&packet) != expected => continue,
+            Ok(packet) => return Some(packet),
+            Err(pcap::Error::TimeoutExpired) => continue,
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
 /// Read the next packet.
+///
+/// When a packet is given, it is sent to the target after the capture has been opened, so that
+/// a fast reply is not missed, and the first reply matching the filter is returned.
 ///  
+/// - packet: forged IPv4 or IPv6 packet to send to the target, by default nothing is sent
 /// - interface: network interface name, by default NASL will try to find the best one
-/// - pcap_filter: BPF filter, by default it listens to everything
-/// - timeout: timeout in seconds, 5 by default
+/// - pcap_filter or filter: BPF filter, by default it listens to everything
+/// - timeout or pcap_timeout: timeout in seconds, 5 by default
 fn nasl_send_capture(
     register: &Register,
     configs: &Context,
 ) -> Result<NaslValue, FunctionErrorKind> {
+    let packet = match register.named("packet") {
+        Some(ContextType::Value(NaslValue::Data(x))) => Some(x.as_slice()),
+        None => None,
+        _ => return Err(("Data", "Invalid packet").into()),
+    };
+
     let interface = match register.named("interface") {
         Some(ContextType::Value(NaslValue::String(x))) => x.to_string(),
         None => String::new(),
         _ => return Err(("String", "Invalid interface value").into()),
     };
 
-    let filter = match register.named("pcap_filter") {
+    let filter = match register
+        .named("pcap_filter")
+        .or_else(|| register.named("filter"))
+    {
         Some(ContextType::Value(NaslValue::String(x))) => x.to_string(),
         None => String::new(),
         _ => return Err(("String", "Invalid pcap_filter value").into()),
     };
 
-    let timeout = match register.named("pcap_timeout") {
-        Some(ContextType::Value(NaslValue::Number(x))) => *x as i32 * 1000i32, // to milliseconds
-        None => DEFAULT_TIMEOUT,
-        _ => return Err(("Integer", "Invalid timeout value").into()),
-    };
+    let timeout = pcap_timeout(
+        register
+            .named("pcap_timeout")
+            .or_else(|| register.named("timeout")),
+    )?;
 
     // Get the iface name, to set the capture device.
     let target_ip = get_host_ip(configs)?;
@@ -2344,28 +2783,33 @@ fn nasl_send_capture(
     }
 
     let mut capture_dev = match Capture::from_device(iface) {
-        Ok(c) => match c.promisc(true).timeout(timeout).open() {
+        Ok(c) => match c.promisc(true).immediate_mode(true).timeout(timeout).open() {
             Ok(capture) => capture,
             Err(e) => return custom_error!("send_capture: {}", e),
         },
         Err(e) => return custom_error!("send_capture: {}", e),
     };
+    if capture_dev.filter(&filter, true).is_err() {
+        return Ok(NaslValue::Null);
+    }
 
-    let p = match capture_dev.filter(&filter, true) {
-        Ok(_) => capture_dev.next_packet(),
-        Err(e) => Err(pcap::Error::PcapError(e.to_string())),
-    };
+    let deadline = Instant::now() + Duration::from_millis(timeout.max(0) as u64);
+    if let Some(packet) = packet {
+        let b = send_to_target(packet, target_ip)?;
+        configs.logger().debug(&format!("Sent {} bytes", b));
+    }
 
-    match p {
-        Ok(packet) => {
-            // Remove all from lower layer
-            let frame = EthernetPacket::new(packet.data).ok_or_else(|| {
-                FunctionErrorKind::Dirty("No possible to create a packet from buffer".to_string())
-            })?;
-            return Ok(NaslValue::Data(frame.payload().to_vec()));
+    let reply = first_reply(packet, deadline, || {
+        let frame = capture_dev.next_packet()?;
+        // Remove all from lower layer
+        match EthernetPacket::new(frame.data) {
+            Some(frame) => Ok(frame.payload().to_vec()),
+            None => Err(pcap::Error::PcapError(
+                "No possible to create a packet from buffer".to_string(),
+            )),
         }
-        Err(_) => Ok(NaslValue::Null),
-    }
+    });
+    Ok(reply.map(NaslValue::Data).unwrap_or(NaslValue::Null))
 }
 
 /// Returns a NaslVars with all predefined variables which must be expose to nasl script
@@ -2429,6 +2873,7 @@ pub fn lookup(key: &str) -> Option<NaslFunction> {
         "get_ip_element" => Some(get_ip_element),
         "dump_ip_packet" => Some(dump_ip_packet),
         "insert_ip_options" => Some(insert_ip_options),
+        "forge_ip_fragments" => Some(forge_ip_fragments),
         "forge_tcp_packet" => Some(forge_tcp_packet),
         "get_tcp_element" => Some(get_tcp_element),
         "get_tcp_option" => Some(get_tcp_option),
@@ -2445,8 +2890,159 @@ pub fn lookup(key: &str) -> Option<NaslFunction> {
         "forge_igmp_packet" => Some(forge_igmp_packet),
         "tcp_ping" => Some(nasl_tcp_ping),
         "send_packet" => Some(nasl_send_packet),
+        "send_v6packet" => Some(nasl_send_v6packet),
         "pcap_next" => Some(nasl_pcap_next),
         "send_capture" => Some(nasl_send_capture),
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::Ipv6Addr,
+        time::{Duration, Instant},
+    };
+
+    use nasl_builtin_utils::{ContextType, FunctionErrorKind, Register};
+    use nasl_syntax::NaslValue;
+    use pnet::{
+        datalink::interfaces,
+        packet::{ip::IpNextHeaderProtocols, ipv4::MutableIpv4Packet, udp::MutableUdpPacket},
+    };
+
+    use super::{first_reply, ipv6_destination, scope_id, Scope, SendOptions, DEFAULT_TIMEOUT};
+
+    #[test]
+    fn scope_id_arguments() {
+        let lo = interfaces().into_iter().find(|x| x.is_loopback()).unwrap();
+        let expected = Some(Scope {
+            index: lo.index,
+            name: lo.name.clone(),
+        });
+        let value = |x: NaslValue| ContextType::Value(x);
+        assert_eq!(scope_id(None), Ok(None));
+        assert_eq!(
+            scope_id(Some(&value(NaslValue::String(lo.name.clone())))),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            scope_id(Some(&value(NaslValue::Number(lo.index as i64)))),
+            Ok(expected)
+        );
+        assert!(matches!(
+            scope_id(Some(&value(NaslValue::String("no such interface".into())))),
+            Err(FunctionErrorKind::WrongArgument(_))
+        ));
+        assert!(matches!(
+            scope_id(Some(&value(NaslValue::Boolean(true)))),
+            Err(FunctionErrorKind::WrongArgument(_))
+        ));
+    }
+
+    #[test]
+    fn link_local_requires_scope() {
+        let scope = Scope {
+            index: 2,
+            name: "eth0".to_owned(),
+        };
+        let link_local: Ipv6Addr = "fe80::1".parse().unwrap();
+        assert_eq!(
+            ipv6_destination(link_local, None),
+            Err(FunctionErrorKind::WrongArgument(
+                "link-local address fe80::1 requires a scope_id".to_owned()
+            ))
+        );
+        assert_eq!(
+            ipv6_destination(link_local, Some(&scope)).map(|x| x.scope_id()),
+            Ok(2)
+        );
+        assert_eq!(
+            ipv6_destination(Ipv6Addr::LOCALHOST, None).map(|x| x.scope_id()),
+            Ok(0)
+        );
+    }
+
+    /// Returns an IPv4 UDP packet
+    fn udp(source: [u8; 4], destination: [u8; 4], ports: (u16, u16)) -> Vec<u8> {
+        let mut packet = vec![0; 28];
+        let mut ip = MutableIpv4Packet::new(&mut packet).unwrap();
+        ip.set_version(4);
+        ip.set_header_length(5);
+        ip.set_total_length(28);
+        ip.set_next_level_protocol(IpNextHeaderProtocols::Udp);
+        ip.set_source(source.into());
+        ip.set_destination(destination.into());
+        let mut udp = MutableUdpPacket::new(&mut packet[20..]).unwrap();
+        udp.set_source(ports.0);
+        udp.set_destination(ports.1);
+        udp.set_length(8);
+        packet
+    }
+
+    #[test]
+    fn reply_timeout() {
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(50);
+        let mut reads = 0;
+        let sent = udp([127, 0, 0, 1], [127, 0, 0, 2], (5000, 53));
+        let reply = first_reply(Some(&sent), deadline, || {
+            reads += 1;
+            std::thread::sleep(Duration::from_millis(10));
+            Err(pcap::Error::TimeoutExpired)
+        });
+        assert_eq!(reply, None);
+        assert!(reads > 1);
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[test]
+    fn reply_matches_swapped_endpoints() {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let sent = udp([127, 0, 0, 1], [127, 0, 0, 2], (5000, 53));
+        let answer = udp([127, 0, 0, 2], [127, 0, 0, 1], (53, 5000));
+        let mut captured = vec![
+            Ok(answer.clone()),
+            // answers another port
+            Ok(udp([127, 0, 0, 2], [127, 0, 0, 1], (53, 5001))),
+            Ok(sent.clone()),
+        ];
+        let reply = first_reply(Some(&sent), deadline, || captured.pop().unwrap());
+        assert_eq!(reply, Some(answer));
+
+        let reply = first_reply(None, deadline, || Ok(vec![1, 2, 3]));
+        assert_eq!(reply, Some(vec![1, 2, 3]));
+        let reply = first_reply(None, deadline, || Err(pcap::Error::NoMorePackets));
+        assert_eq!(reply, None);
+    }
+
+    #[test]
+    fn send_options() {
+        let options = |args: Vec<(&str, NaslValue)>| {
+            let mut register = Register::default();
+            for (key, value) in args {
+                register.add_local(key, ContextType::Value(value));
+            }
+            SendOptions::new(&register)
+        };
+        assert_eq!(
+            options(vec![]),
+            Ok(SendOptions {
+                use_pcap: true,
+                filter: String::new(),
+                timeout: DEFAULT_TIMEOUT,
+                length: 0,
+            })
+        );
+        let sent = options(vec![
+            ("pcap_timeout", NaslValue::Number(2)),
+            ("length", NaslValue::Number(2)),
+        ])
+        .unwrap();
+        assert_eq!(sent.timeout, 2000);
+        assert_eq!(sent.cut(&[1, 2, 3]), &[1, 2]);
+        assert_eq!(sent.cut(&[1]), &[1]);
+        assert!(options(vec![("pcap_timeout", NaslValue::Number(i64::MAX))]).is_err());
+        assert!(options(vec![("pcap_timeout", NaslValue::Number(-1))]).is_err());
+    }
+}
@@ -299,6 +299,181 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fragments() {
+        let code = r###"
+        ip_packet = forge_ip_packet(ip_v : 4,
+                     ip_hl : 5,
+                     ip_tos : 0,
+                     ip_id : 1234,
+                     ip_p : 0x11,
+                     ip_ttl : 255,
+                     ip_off : 0,
+                     ip_src : 192.168.0.1,
+                     ip_dst : 192.168.0.12,
+                     data: crap(length: 3000));
+        forge_ip_fragments(packet: ip_packet, mtu: 1500);
+        forge_ip_fragments(packet: ip_packet, mtu: 3020);
+        forge_ip_fragments(packet: ip_packet, mtu: 27);
+        "###;
+        let register = Register::default();
+        let mut binding = ContextFactory::default();
+        binding.functions.push_executer(nasl_builtin_raw_ip::RawIp);
+
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        let packet = match parser.next() {
+            Some(Ok(NaslValue::Data(x))) => x,
+            x => panic!("expected an IP datagram, got {x:?}"),
+        };
+        let fragments = match parser.next() {
+            Some(Ok(NaslValue::Array(x))) => x,
+            x => panic!("expected an array of fragments, got {x:?}"),
+        };
+        let mut payload = vec![];
+        let mut fields = vec![];
+        for fragment in fragments {
+            let NaslValue::Data(fragment) = fragment else {
+                panic!("expected a fragment, got {fragment:?}");
+            };
+            // total length, more fragments flag and fragment offset in 8 bytes units
+            let length = u16::from_be_bytes([fragment[2], fragment[3]]);
+            let flags_offset = u16::from_be_bytes([fragment[6], fragment[7]]);
+            fields.push((length, flags_offset & 0x2000 != 0, flags_offset & 0x1fff));
+            assert_eq!(fragment[..2], packet[..2]);
+            assert_eq!(fragment[4..6], packet[4..6]);
+            payload.extend_from_slice(&fragment[20..]);
+        }
+        assert_eq!(
+            fields,
+            vec![(1500, true, 0), (1500, true, 185), (60, false, 370)]
+        );
+        assert_eq!(payload, packet[20..]);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Array(vec![NaslValue::Data(packet)])))
+        );
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn truncated_buffers() {
+        let code = r###"
+        short = raw_string(0x45, 0, 0, 20);
+        get_ip_element(ip: short, element: "ip_src");
+        # the header length of 60 bytes exceeds the buffer
+        long_header = raw_string(0x4f, 0, 0, 20, 0, 0, 0, 0, 64, 6, 0, 0,
+                                 192, 168, 0, 1, 192, 168, 0, 12);
+        get_ip_element(ip: long_header, element: "ip_src");
+        get_tcp_element(tcp: long_header, element: "th_sport");
+        # the payload ends after two bytes
+        ip = raw_string(0x45, 0, 0, 22, 0, 0, 0, 0, 64, 6, 0, 0,
+                        192, 168, 0, 1, 192, 168, 0, 12, 0, 80);
+        get_tcp_element(tcp: ip, element: "th_sport");
+        get_tcp_option(tcp: ip, option: 2);
+        get_udp_element(udp: ip, element: "uh_sport");
+        get_icmp_element(icmp: ip, element: "icmp_id");
+        # the data offset of 60 bytes exceeds the TCP segment
+        tcp = raw_string(0x45, 0, 0, 40, 0, 0, 0, 0, 64, 6, 0, 0,
+                         192, 168, 0, 1, 192, 168, 0, 12,
+                         0, 80, 0, 80, 0, 0, 0, 0, 0, 0, 0, 0, 0xf0, 2, 0, 0, 0, 0, 0, 0);
+        get_tcp_element(tcp: tcp, element: "th_data");
+        "###;
+        let register = Register::default();
+        let mut binding = ContextFactory::default();
+        binding.functions.push_executer(nasl_builtin_raw_ip::RawIp);
+
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+    }
+
+    /// An IPv6 datagram from and to ::1 containing an UDP header
+    const IPV6_UDP: &str = r###"
+        packet = raw_string(0x60, 0, 0, 0, 0, 8, 17, 64,
+                            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+                            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+                            0x13, 0x88, 0, 80, 0, 8, 0, 0);
+        "###;
+
+    #[test]
+    fn send_v6packet_link_local_without_scope() {
+        let code = format!("{IPV6_UDP} send_v6packet(packet, pcap_active: FALSE);");
+        let register = Register::default();
+        let mut binding = ContextFactory::default();
+        binding.functions.push_executer(nasl_builtin_raw_ip::RawIp);
+
+        let context = binding.build(Default::default(), "fe80::1".to_string());
+        let mut parser = CodeInterpreter::new(&code, register, &context);
+        parser.next();
+        match parser.next() {
+            Some(Err(e)) => assert!(e
+                .to_string()
+                .contains("link-local address fe80::1 requires a scope_id")),
+            x => panic!("expected an error, got {x:?}"),
+        }
+    }
+
+    #[cfg(feature = "loopback-tests")]
+    #[test]
+    fn send_v6packet_loopback() {
+        let code = format!("{IPV6_UDP} send_v6packet(packet, pcap_active: FALSE);");
+        let register = Register::default();
+        let mut binding = ContextFactory::default();
+        binding.functions.push_executer(nasl_builtin_raw_ip::RawIp);
+
+        let context = binding.build(Default::default(), "::1".to_string());
+        let mut parser = CodeInterpreter::new(&code, register, &context);
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+    }
+
+    #[cfg(feature = "loopback-tests")]
+    #[test]
+    fn send_capture_loopback() {
+        let code = r#"
+            ip_packet = forge_ip_packet(ip_v : 4,
+                     ip_hl : 5,
+                     ip_tos : 0,
+                     ip_len : 20,
+                     ip_id : 1234,
+                     ip_p : 0x01, #IPPROTO_ICMP
+                     ip_ttl : 255,
+                     ip_off : 0,
+                     ip_src : 127.0.0.1,
+                     ip_dst : 127.0.0.1);
+            icmp = forge_icmp_packet(ip: ip_packet,
+                     icmp_type: 8,
+                     icmp_code: 0,
+                     icmp_seq:   1,
+                     icmp_id:   1,
+                     data: "1234");
+            reply = send_capture(packet: icmp, filter: "icmp[icmptype] = icmp-echoreply", timeout: 2);
+            get_icmp_element(icmp: reply, element: "icmp_type");
+        "#;
+        let register = Register::default();
+        let mut binding = ContextFactory::default();
+        binding.functions.push_executer(nasl_builtin_raw_ip::RawIp);
+
+        let context = binding.build(Default::default(), "127.0.0.1".to_string());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.next();
+        parser.next();
+        assert!(matches!(parser.next(), Some(Ok(NaslValue::Data(_)))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(0))));
+    }
+
     #[test]
     #[should_panic]
     fn copy_from_slice_panic() {
@@ -7,28 +7,32 @@
 //! In NASL a array is actually a dictionary capable of having not just indexable keys
 //! while lists are standard arrays.
 
-use std::collections::HashMap;
-
 use nasl_builtin_utils::error::FunctionErrorKind;
 
 use nasl_builtin_utils::{Context, NaslFunction, Register};
-use nasl_syntax::NaslValue;
+use nasl_syntax::{NaslDict, NaslValue};
 
 use nasl_builtin_utils::resolve_positional_arguments;
 
 /// NASL function to create a dictionary out of an even number of arguments
 ///
-/// Each uneven arguments out of positional arguments are used as keys while each even even argument is used a value.
-/// When there is an uneven number of elements the last key will be dropped, as there is no corresponding value.
-/// So `make_array(1, 0, 1)` will return the same response as `make_array(1, 0)`.
+/// Each uneven arguments out of positional arguments are used as keys while each even argument is used a value.
+/// Keys are converted to strings the same way as indices of a subscript assignment, so
+/// `make_array(1, "a")` is equal to `a[1] = "a"` on a dict. The dict keeps the keys in the order they
+/// are given; when a key is repeated it keeps its first position and the last value wins.
+/// An uneven number of arguments is an error, as there is no corresponding value to the last key.
 fn make_array(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
     let positional = resolve_positional_arguments(register);
-    let mut values = HashMap::new();
-    for (idx, val) in positional.iter().enumerate() {
-        if idx % 2 == 1 {
-            values.insert(positional[idx - 1].to_string(), val.clone());
-        }
+    if positional.len() % 2 == 1 {
+        return Err(FunctionErrorKind::MissingPositionalArguments {
+            expected: positional.len() + 1,
+            got: positional.len(),
+        });
     }
+    let values: NaslDict = positional
+        .chunks_exact(2)
+        .map(|x| (x[0].to_string(), x[1].clone()))
+        .collect();
     Ok(values.into())
 }
 
@@ -38,7 +42,7 @@ fn nasl_make_list(register: &Register) -> Result<Vec<NaslValue>, FunctionErrorKi
     let mut values = Vec::<NaslValue>::new();
     for val in arr.iter() {
         match val {
-            NaslValue::Dict(x) => values.extend(x.values().cloned().collect::<Vec<NaslValue>>()),
+            NaslValue::Dict(_) => values.extend(Vec::<NaslValue>::from(val.clone())),
             NaslValue::Array(x) => values.extend(x.clone()),
             NaslValue::Null => {}
             x => values.push(x.clone()),
@@ -60,6 +64,9 @@ fn nasl_sort(register: &Register, _: &Context) -> Result<NaslValue, FunctionErro
 }
 
 /// Returns an array with the keys of a dict
+///
+/// The keys of a dict are returned in the order they were inserted, which is the order a foreach
+/// loop iterates over its values.
 fn keys(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
     let positional = resolve_positional_arguments(register);
     let mut keys = Vec::<NaslValue>::new();
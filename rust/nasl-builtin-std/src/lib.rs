@@ -5,8 +5,10 @@
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
 
-use nasl_builtin_utils::{Context, NaslFunctionRegister, NaslVarRegister, Register};
-use storage::{ContextKey, DefaultDispatcher};
+use nasl_builtin_utils::{
+    Capabilities, Context, Limits, NaslFunctionRegister, NaslVarRegister, Register,
+};
+use storage::{item::NvtPreference, ContextKey, DefaultDispatcher};
 mod array;
 
 /// The description builtin function
@@ -76,7 +78,8 @@ pub fn nasl_std_functions() -> nasl_builtin_utils::NaslFunctionRegister {
         .push_register(nasl_builtin_host::Host)
         .push_register(nasl_builtin_http::NaslHttp::default())
         .push_register(nasl_builtin_cryptographic::Cryptographic)
-        .push_register(nasl_builtin_description::Description);
+        .push_register(nasl_builtin_description::Description)
+        .push_register(nasl_builtin_unsafe::Unsafe::default());
     builder = add_ssh(builder);
     builder = add_raw_ip(builder);
     builder.build()
@@ -177,6 +180,14 @@ pub struct ContextFactory<Loader, Logger, Storage> {
     pub logger: Logger,
     /// The functions available to the nasl script.
     pub functions: NaslFunctionRegister,
+    /// The initial seed of the random number generator of each built context.
+    pub seed: Option<u64>,
+    /// The size limits of values created by the scripts.
+    pub limits: Limits,
+    /// The access to the host system granted to the scripts.
+    pub capabilities: Capabilities,
+    /// The preferences of the scripts as configured for the scan.
+    pub preferences: Vec<NvtPreference>,
 }
 
 impl Default
@@ -192,6 +203,10 @@ impl Default
             logger: Default::default(),
             functions: nasl_std_functions(),
             storage: DefaultDispatcher::default(),
+            seed: None,
+            limits: Limits::default(),
+            capabilities: Capabilities::default(),
+            preferences: vec![],
         }
     }
 }
@@ -211,6 +226,10 @@ where
             loader,
             logger,
             functions: nasl_std_functions(),
+            seed: None,
+            limits: Limits::default(),
+            capabilities: Capabilities::default(),
+            preferences: vec![],
         }
     }
 
@@ -224,6 +243,10 @@ where
             loader,
             logger: Default::default(),
             functions: nasl_std_functions(),
+            seed: None,
+            limits: Limits::default(),
+            capabilities: Capabilities::default(),
+            preferences: vec![],
         }
     }
 
@@ -233,9 +256,39 @@ where
         self
     }
 
+    /// Sets the initial seed of the random number generator of each built context.
+    ///
+    /// Without a seed `rand` reads from the operating system.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the size limits of values created by the scripts.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the access to the host system granted to the scripts.
+    ///
+    /// Without it scripts can neither execute local commands nor access local files.
+    pub fn capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Sets the preferences of the scripts as configured for the scan.
+    ///
+    /// The `default` of each preference is used as its value.
+    pub fn preferences(mut self, preferences: Vec<NvtPreference>) -> Self {
+        self.preferences = preferences;
+        self
+    }
+
     /// Creates a new Context with the shared loader, logger and function register
     pub fn build(&self, key: ContextKey, target: String) -> Context {
-        Context::new(
+        let context = Context::new(
             key,
             target,
             self.storage.as_dispatcher(),
@@ -244,6 +297,13 @@ where
             &self.logger,
             &self.functions,
         )
+        .with_limits(self.limits)
+        .with_capabilities(self.capabilities.clone())
+        .with_preferences(self.preferences.clone());
+        match self.seed {
+            Some(seed) => context.with_seed(seed),
+            None => context,
+        }
     }
 }
 
@@ -4,17 +4,15 @@
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
     use nasl_builtin_std::ContextFactory;
     use nasl_builtin_utils::Register;
     use nasl_interpreter::CodeInterpreter;
-    use nasl_syntax::NaslValue;
+    use nasl_syntax::{NaslDict, NaslValue};
     macro_rules! make_dict {
         ($($key:expr => $val:expr),*) => {
             {
                 #[allow(unused_mut)]
-                let mut result: HashMap<String, nasl_syntax::NaslValue> = HashMap::new();
+                let mut result = NaslDict::new();
                 $(
                    let key: String = format!("{}", $key);
                    let value: nasl_syntax::NaslValue = $val.into();
@@ -30,8 +28,7 @@ mod tests {
     fn make_array() {
         let code = r###"
         make_array(1, 0, 2, 1);
-        make_array(1, 0, 2, 1, 1);
-        make_array(1);
+        make_array("a", "x", "b", "y", "a", "z");
         make_array();
         "###;
         let register = Register::default();
@@ -39,11 +36,77 @@ mod tests {
         let context = binding.build(Default::default(), Default::default());
         let mut parser = CodeInterpreter::new(code, register, &context);
         assert_eq!(parser.next(), Some(Ok(make_dict!(1 => 0i64, 2 => 1i64))));
-        assert_eq!(parser.next(), Some(Ok(make_dict!(1 => 0i64, 2 => 1i64))));
-        assert_eq!(parser.next(), Some(Ok(make_dict!())));
+        assert_eq!(parser.next(), Some(Ok(make_dict!("a" => "z", "b" => "y"))));
         assert_eq!(parser.next(), Some(Ok(make_dict!())));
     }
 
+    #[test]
+    fn make_array_keeps_insertion_order() {
+        let code = r###"
+        make_array("b", 1, "c", 2, "a", 3, "c", 4);
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        match parser.next() {
+            Some(Ok(NaslValue::Dict(x))) => {
+                let entries: Vec<_> = x.iter().map(|(k, v)| (k.as_str(), v.clone())).collect();
+                assert_eq!(
+                    entries,
+                    vec![("b", 1.into()), ("c", 4.into()), ("a", 3.into())]
+                );
+            }
+            x => panic!("expected a dict, got {x:?}"),
+        }
+    }
+
+    #[test]
+    fn make_array_uneven_arguments() {
+        let code = r###"
+        make_array(1, 0, 2, 1, 1);
+        make_array(1);
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        match parser.next() {
+            Some(Err(e)) => assert!(e.to_string().contains("expected 6 arguments but got 5")),
+            x => panic!("expected an error, got {x:?}"),
+        }
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn make_array_keys_as_subscript() {
+        let code = r###"
+        a = make_array(1, "n", 2, "b", raw_string(0x41), "d");
+        b = make_array();
+        b[1] = "n";
+        b[2] = "b";
+        b[raw_string(0x41)] = "d";
+        b;
+        a["1"] + a["2"] + a["A"];
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(make_dict!(1 => "n", 2 => "b", "A" => "d")))
+        );
+        for _ in 0..4 {
+            parser.next();
+        }
+        assert_eq!(
+            parser.next(),
+            Some(Ok(make_dict!(1 => "n", 2 => "b", "A" => "d")))
+        );
+        assert_eq!(parser.next(), Some(Ok("nbd".into())));
+    }
+
     #[test]
     fn make_list() {
         let code = r#"
@@ -154,7 +217,7 @@ mod tests {
         let code = r###"
         l = [1,2,3,4,5];
         max_index(l);
-        max_index(make_array(1,2,3,4,5,6,7));
+        max_index(make_array(1,2,3,4,5,6));
         max_index(make_list(1, 0));
         max_index(make_list());
         "###;
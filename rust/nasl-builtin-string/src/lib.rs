@@ -6,11 +6,12 @@
 
 use core::fmt::Write;
 use nasl_builtin_utils::{
-    resolve_positional_arguments, Context, ContextType, FunctionErrorKind, NaslFunction, Register,
+    resolve_positional_arguments, to_nasl_result, Context, ContextType, FromNaslValue,
+    FunctionErrorKind, NaslFunction, NaslResult, Register,
 };
 use std::num::ParseIntError;
 
-use nasl_syntax::NaslValue;
+use nasl_syntax::{bytes_to_str, NaslValue};
 
 /// Decodes given string as hex and returns the result as a byte array
 pub fn decode_hex(s: &str) -> Result<Vec<u8>, ParseIntError> {
@@ -71,7 +72,7 @@ fn write_nasl_string(s: &mut String, value: &NaslValue) -> Result<(), FunctionEr
     match value {
         NaslValue::String(x) => write!(s, "{x}"),
         NaslValue::Data(x) => {
-            let x = x.iter().map(|x| *x as char).collect::<String>();
+            let x = bytes_to_str(x);
             write!(s, "{x}")
         }
         NaslValue::Number(x) => {
@@ -124,11 +125,11 @@ fn write_nasl_string_value(s: &mut String, value: &NaslValue) -> Result<(), Func
             Ok(())
         }
         NaslValue::String(x) => write!(s, "{}", x),
-        NaslValue::Number(x) => write!(s, "{}", x),
+        NaslValue::Number(x) | NaslValue::Exit(x) => write!(s, "{}", x),
         NaslValue::Boolean(x) => write!(s, "{}", *x as i32),
         NaslValue::AttackCategory(x) => write!(s, "{}", *x as i32),
         NaslValue::Data(x) => {
-            let x = x.iter().map(|x| *x as char).collect::<String>();
+            let x = bytes_to_str(x);
             write!(s, "{}", x)
         }
         _ => Ok(()),
@@ -136,38 +137,45 @@ fn write_nasl_string_value(s: &mut String, value: &NaslValue) -> Result<(), Func
     .map_err(|e| e.into())
 }
 
+/// Converts the first positional argument to upper or lower case
+///
+/// Only the ASCII letters are converted by default so that the result does not depend on the
+/// locale or on the Unicode case mappings, e.g. the Turkish dotted capital I is kept as it is.
+/// When the named argument `unicode` is TRUE the Unicode case mappings are used instead. Data is
+/// converted byte-wise into a string before.
+fn convert_case(register: &Register, upper: bool) -> Result<NaslValue, FunctionErrorKind> {
+    let unicode = match register.named("unicode") {
+        Some(ContextType::Value(NaslValue::Boolean(x))) => *x,
+        None => false,
+        _ => return Err(("Boolean", "Invalid unicode value").into()),
+    };
+    let positional = resolve_positional_arguments(register);
+    let s = match positional.first() {
+        Some(NaslValue::String(x)) => x.to_owned(),
+        Some(NaslValue::Data(x)) => bytes_to_str(x),
+        _ => return Ok(NaslValue::Null),
+    };
+    Ok(match (upper, unicode) {
+        (true, true) => s.to_uppercase(),
+        (true, false) => s.to_ascii_uppercase(),
+        (false, true) => s.to_lowercase(),
+        (false, false) => s.to_ascii_lowercase(),
+    }
+    .into())
+}
+
 /// NASL function to return uppercase equivalent of a given string
 ///
 /// If this function retrieves anything but a string it returns NULL
 fn toupper(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
-    let positional = resolve_positional_arguments(register);
-    Ok(match positional.first() {
-        Some(NaslValue::String(x)) => x.to_uppercase().into(),
-        Some(NaslValue::Data(x)) => x
-            .iter()
-            .map(|x| *x as char)
-            .collect::<String>()
-            .to_uppercase()
-            .into(),
-        _ => NaslValue::Null,
-    })
+    convert_case(register, true)
 }
 
 /// NASL function to return lowercase equivalent of a given string
 ///
 /// If this function retrieves anything but a string it returns NULL
 fn tolower(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
-    let positional = resolve_positional_arguments(register);
-    Ok(match positional.first() {
-        Some(NaslValue::String(x)) => x.to_lowercase().into(),
-        Some(NaslValue::Data(x)) => x
-            .iter()
-            .map(|x| *x as char)
-            .collect::<String>()
-            .to_lowercase()
-            .into(),
-        _ => NaslValue::Null,
-    })
+    convert_case(register, false)
 }
 
 /// NASL function to return the length of string
@@ -266,14 +274,39 @@ fn data_to_hexstr(register: &Register, _: &Context) -> Result<NaslValue, Functio
     }
 }
 
+/// NASL function to decode UTF-8 encoded byte data into a string.
+///
+/// The first positional argument must be byte data, all other positional arguments are ignored.
+/// Unlike the conversion of data into a string, which maps each byte to a char, multi byte
+/// sequences are decoded into a single char. On invalid sequences NULL is returned unless the
+/// named argument `lossy` is TRUE, then they are replaced by U+FFFD.
+fn data_to_utf8(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+    let lossy = match register.named("lossy") {
+        Some(ContextType::Value(NaslValue::Boolean(x))) => *x,
+        None => false,
+        _ => return Err(("Boolean", "Invalid lossy value").into()),
+    };
+    match resolve_positional_arguments(register).first() {
+        Some(NaslValue::Data(x)) if lossy => Ok(String::from_utf8_lossy(x).into_owned().into()),
+        Some(NaslValue::Data(x)) => Ok(std::str::from_utf8(x)
+            .map(|x| x.into())
+            .unwrap_or(NaslValue::Null)),
+        Some(x) => Err(("first positional argument", "data", x.to_string().as_str()).into()),
+        None => Err("0".into()),
+    }
+}
+
 /// NASL function to return a buffer of required length with repeated occurrences of a specified string
 ///
 /// Length argument is required and can be a named argument or a positional argument.
 /// Data argument is an optional named argument and is taken to be "X" if not provided.
-fn crap(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+/// The data is repeated until the buffer has the given length, the last repetition is cut off
+/// when the length is not a multiple of the length of data.
+fn crap(register: &Register, context: &Context) -> Result<NaslValue, FunctionErrorKind> {
     let data = match register.named("data") {
-        None => "X",
-        Some(ContextType::Value(NaslValue::String(x))) => x,
+        None => b"X".to_vec(),
+        Some(ContextType::Value(NaslValue::String(x))) => x.as_bytes().to_vec(),
+        Some(ContextType::Value(NaslValue::Data(x))) => x.clone(),
         Some(x) => {
             let ek = match x {
                 ContextType::Value(a) => ("data", "string", a).into(),
@@ -282,19 +315,27 @@ fn crap(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind
             return Err(ek);
         }
     };
-    match register.named("length") {
+    let length = match register.named("length") {
         None => {
             let positional = resolve_positional_arguments(register);
             match positional.first() {
-                Some(NaslValue::Number(x)) => Ok(NaslValue::String(data.repeat(*x as usize))),
-                x => Err(("0", "numeric", x).into()),
+                Some(NaslValue::Number(x)) => *x,
+                x => return Err(("0", "numeric", x).into()),
             }
         }
-        Some(ContextType::Value(NaslValue::Number(x))) => {
-            Ok(NaslValue::String(data.repeat(*x as usize)))
-        }
-        x => Err(("length", "numeric", x).into()),
+        Some(ContextType::Value(NaslValue::Number(x))) => *x,
+        x => return Err(("length", "numeric", x).into()),
+    };
+    let length = usize::try_from(length)
+        .map_err(|_| FunctionErrorKind::from(("length", "positive", &NaslValue::Number(length))))?;
+    if data.is_empty() && length > 0 {
+        return Err(("data", "not empty", "empty").into());
     }
+    // check before allocating so that huge lengths do not exhaust memory
+    context.limits().check_string_length(length)?;
+    Ok(NaslValue::Data(
+        data.into_iter().cycle().take(length).collect(),
+    ))
 }
 
 /// NASL function to remove trailing whitespaces from a string
@@ -315,29 +356,43 @@ fn chomp(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKin
     }
 }
 
+/// Returns the positional argument at index converted into the required type
+///
+/// A missing argument is converted like NULL, so it is only valid for optional types.
+fn positional_argument<T: FromNaslValue>(
+    positional: &[NaslValue],
+    index: usize,
+) -> Result<T, FunctionErrorKind> {
+    T::from_nasl_value(positional.get(index).unwrap_or(&NaslValue::Null))
+}
+
+/// Returns the index of needle within haystack starting at offset or -1 when it is not found
+///
+/// The index is relative to the offset.
+fn find_index(
+    haystack: String,
+    needle: String,
+    offset: Option<usize>,
+) -> Result<i64, FunctionErrorKind> {
+    Ok(haystack
+        .get(offset.unwrap_or_default()..)
+        .and_then(|x| x.find(&needle))
+        .map(|x| x as i64)
+        .unwrap_or(-1))
+}
+
 /// NASL function to lookup position of a substring within a string
 ///
 /// The first positional argument is the *string* to search through.
 /// The second positional argument is the *string* to search for.
 /// The optional third positional argument is an *int* containing an offset from where to start the search.
-fn stridx(register: &Register, _: &Context) -> Result<NaslValue, FunctionErrorKind> {
+fn stridx(register: &Register, _: &Context) -> NaslResult {
     let positional = resolve_positional_arguments(register);
-    let haystack = match positional.first() {
-        Some(NaslValue::String(x)) => x,
-        x => return Err(("0", "string", x).into()),
-    };
-    let needle = match positional.get(1) {
-        Some(NaslValue::String(x)) => x,
-        x => return Err(("1", "string", x).into()),
-    };
-    let offset = match positional.get(2) {
-        Some(NaslValue::Number(x)) => *x as usize,
-        _ => 0_usize,
-    };
-    Ok(match &haystack[offset..].find(needle) {
-        Some(index) => NaslValue::Number(*index as i64),
-        None => NaslValue::Number(-1),
-    })
+    to_nasl_result(find_index(
+        positional_argument(&positional, 0)?,
+        positional_argument(&positional, 1)?,
+        positional_argument(&positional, 2)?,
+    ))
 }
 
 /// NASL function to display any number of NASL values
@@ -364,6 +419,7 @@ pub fn lookup(key: &str) -> Option<NaslFunction> {
         "display" => Some(display),
         "hexstr_to_data" => Some(hexstr_to_data),
         "data_to_hexstr" => Some(data_to_hexstr),
+        "data_to_utf8" => Some(data_to_utf8),
         _ => None,
     }
 }
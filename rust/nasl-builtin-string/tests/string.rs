@@ -76,6 +76,27 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok("HALLO".into())));
     }
     #[test]
+    fn case_folding() {
+        let code = r###"
+        tolower("TITLE İı");
+        tolower("TITLE İı", unicode: TRUE);
+        toupper("title iı");
+        toupper("title iı", unicode: TRUE);
+        tolower(raw_string(0x41, 0xc4));
+        tolower("A", unicode: 1);
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(parser.next(), Some(Ok("title İı".into())));
+        assert_eq!(parser.next(), Some(Ok("title i\u{307}ı".into())));
+        assert_eq!(parser.next(), Some(Ok("TITLE Iı".into())));
+        assert_eq!(parser.next(), Some(Ok("TITLE II".into())));
+        assert_eq!(parser.next(), Some(Ok("a\u{c4}".into())));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+    #[test]
     fn strlen() {
         let code = r###"
         strlen(0x7B);
@@ -106,6 +127,21 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok("1231Hallo".into())));
     }
 
+    #[test]
+    fn string_of_exit_value() {
+        let code = r#"
+        string(rc);
+        string("rc: ", rc);
+        "#;
+        let register =
+            Register::root_initial(&[("rc".to_owned(), ContextType::Value(NaslValue::Exit(2)))]);
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(parser.next(), Some(Ok("2".into())));
+        assert_eq!(parser.next(), Some(Ok("rc: 2".into())));
+    }
+
     #[test]
     fn substr() {
         let code = r#"
@@ -128,14 +164,40 @@ mod tests {
         crap(5);
         crap(length: 5);
         crap(data: "ab", length: 5);
+        crap(data: "abc", length: 6);
+        crap(0);
+        crap(data: "", length: 1);
         "#;
         let register = Register::default();
         let binding = ContextFactory::default();
         let context = binding.build(Default::default(), Default::default());
         let mut parser = CodeInterpreter::new(code, register, &context);
-        assert_eq!(parser.next(), Some(Ok("XXXXX".into())));
-        assert_eq!(parser.next(), Some(Ok("XXXXX".into())));
-        assert_eq!(parser.next(), Some(Ok("ababababab".into())));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Data(b"XXXXX".to_vec()))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Data(b"XXXXX".to_vec()))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Data(b"ababa".to_vec()))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Data(b"abcabc".to_vec()))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Data(vec![]))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn crap_size_limit() {
+        let code = r#"
+        crap(4);
+        crap(5);
+        crap(data: "ab", length: 5);
+        crap(-1);
+        "#;
+        let binding = ContextFactory::default().limits(Limits {
+            max_string_length: 4,
+            ..Default::default()
+        });
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Data(b"XXXX".to_vec()))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
     }
 
     #[test]
@@ -165,6 +227,9 @@ mod tests {
         stridx("blahabc", "abc", 4);
         stridx("blahabc", "abc", 3);
         stridx("blahbc", "abc", 2);
+        stridx('blahabc', "abc");
+        stridx("abc", "c", 4);
+        stridx("abc", "c", -1);
         "#;
         let register = Register::default();
         let binding = ContextFactory::default();
@@ -176,6 +241,9 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok(0_i64.into())));
         assert_eq!(parser.next(), Some(Ok(1_i64.into())));
         assert_eq!(parser.next(), Some(Ok((-1_i64).into())));
+        assert_eq!(parser.next(), Some(Ok(4_i64.into())));
+        assert_eq!(parser.next(), Some(Ok((-1_i64).into())));
+        assert!(matches!(parser.next(), Some(Err(_))));
     }
 
     #[test]
@@ -213,4 +281,26 @@ mod tests {
             )))
         );
     }
+
+    #[test]
+    fn data_to_utf8() {
+        let code = r#"
+        data_to_utf8(raw_string(0x47, 0x72, 0xc3, 0xbc, 0xc3, 0x9f, 0x65));
+        data_to_utf8(raw_string(0x61, 0xff, 0x62));
+        data_to_utf8(raw_string(0x61, 0xff, 0x62), lossy: TRUE);
+        data_to_utf8(raw_string(0x61, 0xc3), lossy: FALSE);
+        data_to_utf8(raw_string());
+        data_to_utf8("text");
+        "#;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(parser.next(), Some(Ok("Grüße".into())));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok("a\u{fffd}b".into())));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok("".into())));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
 }
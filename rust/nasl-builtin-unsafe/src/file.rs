@@ -0,0 +1,130 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Defines NASL functions to read local files within the configured file root.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::{Component, Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+
+use nasl_builtin_utils::{error::FunctionErrorKind, Context, ContextType, NaslResult, Register};
+use nasl_syntax::NaslValue;
+
+/// The files opened by a script
+#[derive(Default)]
+struct Handles {
+    next: i64,
+    files: HashMap<i64, File>,
+}
+
+/// Contains the files opened by `file_open` until they are closed via `file_close`
+#[derive(Default)]
+pub(crate) struct Files {
+    handles: Mutex<Handles>,
+}
+
+impl Files {
+    fn lock(&self) -> MutexGuard<Handles> {
+        // a poisoned lock only means that another thread panicked while holding it, the
+        // handles themselves are still usable.
+        self.handles.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Closes all open files and returns the amount of closed files
+    pub(crate) fn clear(&self) -> Option<usize> {
+        let mut handles = self.lock();
+        if handles.files.is_empty() {
+            return None;
+        }
+        let result = handles.files.len();
+        handles.files.clear();
+        Some(result)
+    }
+}
+
+/// Resolves the given name within the file root of the context.
+///
+/// Relative names are resolved against the root, absolute names must point into the root.
+/// Names containing `..` are rejected as well as names that leave the root via symbolic links.
+fn resolve(context: &Context, name: &str) -> Result<PathBuf, FunctionErrorKind> {
+    let root = context.capabilities().file_root.as_ref().ok_or_else(|| {
+        FunctionErrorKind::PermissionDenied("local file access requires a file root".to_owned())
+    })?;
+    let path = Path::new(name);
+    if path.components().any(|x| x == Component::ParentDir) {
+        return Err(FunctionErrorKind::PermissionDenied(format!(
+            "{name} must not contain .."
+        )));
+    }
+    let root = root.canonicalize()?;
+    let path = root.join(path).canonicalize()?;
+    if !path.starts_with(&root) {
+        return Err(FunctionErrorKind::PermissionDenied(format!(
+            "{name} is outside of {}",
+            root.display()
+        )));
+    }
+    Ok(path)
+}
+
+/// Get the named argument as positive number
+fn get_positive_number(register: &Register, key: &str) -> Result<i64, FunctionErrorKind> {
+    match register.named(key) {
+        Some(ContextType::Value(NaslValue::Number(x))) if *x >= 0 => Ok(*x),
+        x => Err((key, "a positive number", x).into()),
+    }
+}
+
+/// Opens a file within the file root for reading.
+///
+/// It takes the named arguments name and mode; only the mode `r` is supported. Returns the
+/// file descriptor.
+pub(crate) fn file_open(files: &Files, register: &Register, context: &Context) -> NaslResult {
+    let name = match register.named("name") {
+        Some(ContextType::Value(NaslValue::String(x))) => x.to_owned(),
+        Some(ContextType::Value(NaslValue::Data(x))) => x.iter().map(|&b| b as char).collect(),
+        x => return Err(("name", "string", x).into()),
+    };
+    match register.named("mode") {
+        Some(ContextType::Value(NaslValue::String(x))) if x == "r" => {}
+        x => return Err(("mode", "r", x).into()),
+    }
+    let file = File::open(resolve(context, &name)?)?;
+    let mut handles = files.lock();
+    handles.next += 1;
+    let fd = handles.next;
+    handles.files.insert(fd, file);
+    Ok(NaslValue::Number(fd))
+}
+
+/// Reads up to length bytes from the file descriptor fp.
+pub(crate) fn file_read(files: &Files, register: &Register, _: &Context) -> NaslResult {
+    let fd = get_positive_number(register, "fp")?;
+    let length = get_positive_number(register, "length")?;
+    let mut handles = files.lock();
+    let file = handles.files.get_mut(&fd).ok_or_else(|| {
+        FunctionErrorKind::WrongArgument(format!("file descriptor {fd} is not open"))
+    })?;
+    let mut result = Vec::new();
+    file.by_ref().take(length as u64).read_to_end(&mut result)?;
+    Ok(NaslValue::Data(result))
+}
+
+/// Closes the file descriptor given as the first positional argument.
+pub(crate) fn file_close(files: &Files, register: &Register, _: &Context) -> NaslResult {
+    let fd = match register.positional().first() {
+        Some(NaslValue::Number(x)) if *x >= 0 => *x,
+        x => return Err(("0", "a positive number", x).into()),
+    };
+    match files.lock().files.remove(&fd) {
+        Some(_) => Ok(NaslValue::Number(0)),
+        None => Err(FunctionErrorKind::WrongArgument(format!(
+            "file descriptor {fd} is not open"
+        ))),
+    }
+}
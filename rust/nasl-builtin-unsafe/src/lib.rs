@@ -0,0 +1,129 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Defines NASL functions that access the host system.
+//!
+//! They are only usable when the corresponding capability is granted to the context, otherwise
+//! they return a `FunctionErrorKind::PermissionDenied`.
+
+mod file;
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use nasl_builtin_utils::{error::FunctionErrorKind, Context, ContextType, NaslResult, Register};
+use nasl_syntax::NaslValue;
+
+/// Looks up the given command in PATH unless it already contains a path
+fn find_command(cmd: &str) -> Option<PathBuf> {
+    if cmd.contains('/') {
+        let path = PathBuf::from(cmd);
+        return path.is_file().then_some(path);
+    }
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(cmd))
+            .find(|path| path.is_file())
+    })
+}
+
+/// Runs a command on the host system and returns its output.
+///
+/// It takes the named arguments:
+/// - cmd: the command to run, it is looked up in PATH unless it contains a path.
+/// - argv: the arguments of the command, argv[0] is the name of the program and not passed on.
+/// - cd: optional, when TRUE the working directory is changed to the directory of the command.
+///
+/// Requires the `allow_local_commands` capability.
+fn pread(register: &Register, context: &Context) -> NaslResult {
+    if !context.capabilities().allow_local_commands {
+        return Err(FunctionErrorKind::PermissionDenied(
+            "pread requires local commands to be allowed".to_owned(),
+        ));
+    }
+    if !matches!(
+        register.named("drop_privileges_user"),
+        None | Some(ContextType::Value(NaslValue::Null))
+    ) {
+        return Err(FunctionErrorKind::WrongArgument(
+            "drop_privileges_user is not supported".to_owned(),
+        ));
+    }
+    let cmd = match register.named("cmd") {
+        Some(ContextType::Value(NaslValue::String(x))) => x.to_owned(),
+        Some(ContextType::Value(NaslValue::Data(x))) => x.iter().map(|&b| b as char).collect(),
+        x => return Err(("cmd", "string", x).into()),
+    };
+    let argv = match register.named("argv") {
+        Some(ContextType::Value(NaslValue::Array(x))) => x.iter().map(|x| x.to_string()).collect(),
+        Some(ContextType::Value(NaslValue::Null)) | None => vec![],
+        x => return Err(("argv", "array", x).into()),
+    };
+    let cd = match register.named("cd") {
+        Some(ContextType::Value(x)) => bool::from(x.clone()),
+        _ => false,
+    };
+    let path = find_command(&cmd)
+        .ok_or_else(|| FunctionErrorKind::WrongArgument(format!("command {cmd} not found")))?;
+    let mut command = Command::new(&path);
+    command.args(argv.iter().skip(1));
+    if cd {
+        if let Some(dir) = path.parent().filter(|x| x != &Path::new("")) {
+            command.current_dir(dir);
+        }
+    }
+    let output = command.output()?;
+    Ok(NaslValue::Data(output.stdout))
+}
+
+/// A function of Unsafe
+type UnsafeFunction = fn(&Unsafe, &Register, &Context) -> NaslResult;
+
+/// The unsafe builtin functions
+///
+/// Contains the files opened by the script.
+#[derive(Default)]
+pub struct Unsafe {
+    files: file::Files,
+}
+
+impl Unsafe {
+    /// Returns found function for key or None when not found
+    fn lookup(key: &str) -> Option<UnsafeFunction> {
+        match key {
+            "pread" => Some(|_, register, context| pread(register, context)),
+            "file_open" => {
+                Some(|x, register, context| file::file_open(&x.files, register, context))
+            }
+            "file_read" => {
+                Some(|x, register, context| file::file_read(&x.files, register, context))
+            }
+            "file_close" => {
+                Some(|x, register, context| file::file_close(&x.files, register, context))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl nasl_builtin_utils::NaslFunctionExecuter for Unsafe {
+    fn nasl_fn_cache_clear(&self) -> Option<usize> {
+        self.files.clear()
+    }
+
+    fn nasl_fn_execute(
+        &self,
+        name: &str,
+        register: &Register,
+        context: &Context,
+    ) -> Option<nasl_builtin_utils::NaslResult> {
+        Unsafe::lookup(name).map(|x| x(self, register, context))
+    }
+
+    fn nasl_fn_defined(&self, name: &str) -> bool {
+        Unsafe::lookup(name).is_some()
+    }
+}
@@ -0,0 +1,93 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use nasl_interpreter::*;
+
+    fn file_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("nasl-file-{name}-{}", std::process::id()));
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("sub").join("allowed.txt"), "hello world").unwrap();
+        root
+    }
+
+    fn is_permission_denied(result: Option<Result<NaslValue, InterpretError>>) -> bool {
+        matches!(
+            result,
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::FunctionCallError(FunctionError {
+                    kind: FunctionErrorKind::PermissionDenied(_),
+                    ..
+                }),
+                ..
+            }))
+        )
+    }
+
+    #[test]
+    fn file_read() {
+        let root = file_root("read");
+        let code = format!(
+            r###"
+        fd = file_open(name: "sub/allowed.txt", mode: "r");
+        file_read(fp: fd, length: 5);
+        file_read(fp: fd, length: 100);
+        file_close(fd);
+        fd = file_open(name: "{}", mode: "r");
+        file_read(fp: fd, length: 5);
+        file_close(fd);
+        "###,
+            root.join("sub").join("allowed.txt").display()
+        );
+        let register = Register::default();
+        let binding = ContextFactory::default().capabilities(Capabilities {
+            file_root: Some(root.clone()),
+            ..Default::default()
+        });
+        let context = binding.build(Default::default(), Default::default());
+        let results: Vec<_> = CodeInterpreter::new(&code, register, &context).collect();
+        fs::remove_dir_all(&root).unwrap();
+        assert!(matches!(results[0], Ok(NaslValue::Number(_))));
+        assert_eq!(results[1], Ok(NaslValue::Data("hello".into())));
+        assert_eq!(results[2], Ok(NaslValue::Data(" world".into())));
+        assert_eq!(results[3], Ok(NaslValue::Number(0)));
+        assert_eq!(results[5], Ok(NaslValue::Data("hello".into())));
+        assert_eq!(results[6], Ok(NaslValue::Number(0)));
+    }
+
+    #[test]
+    fn file_open_outside_of_root() {
+        let root = file_root("outside");
+        let code = r###"
+        file_open(name: "sub/../sub/allowed.txt", mode: "r");
+        file_open(name: "../../etc/passwd", mode: "r");
+        file_open(name: "/etc/passwd", mode: "r");
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default().capabilities(Capabilities {
+            file_root: Some(root.join("sub")),
+            ..Default::default()
+        });
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        let results: Vec<_> = (0..3).map(|_| parser.next()).collect();
+        fs::remove_dir_all(&root).unwrap();
+        assert!(results.into_iter().all(is_permission_denied));
+    }
+
+    #[test]
+    fn file_open_disabled_by_default() {
+        let code = r###"
+        file_open(name: "/etc/passwd", mode: "r");
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert!(is_permission_denied(parser.next()));
+    }
+}
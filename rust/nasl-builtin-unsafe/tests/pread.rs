@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+#[cfg(test)]
+mod tests {
+    use nasl_interpreter::*;
+
+    #[test]
+    fn pread() {
+        let code = r###"
+        pread(cmd: "echo", argv: make_list("echo", "hello", "world"));
+        pread(cmd: "pwd", argv: make_list("pwd"), cd: TRUE);
+        pread(cmd: "does-not-exist", argv: make_list("does-not-exist"));
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default().capabilities(Capabilities {
+            allow_local_commands: true,
+            ..Default::default()
+        });
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Data("hello world\n".into())))
+        );
+        assert!(matches!(
+            parser.next(),
+            Some(Ok(NaslValue::Data(x))) if x.ends_with(b"bin\n")
+        ));
+        assert!(matches!(
+            parser.next(),
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::FunctionCallError(FunctionError {
+                    kind: FunctionErrorKind::WrongArgument(_),
+                    ..
+                }),
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn pread_denied_by_default() {
+        let code = r###"
+        pread(cmd: "echo", argv: make_list("echo", "hello"));
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert!(matches!(
+            parser.next(),
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::FunctionCallError(FunctionError {
+                    kind: FunctionErrorKind::PermissionDenied(_),
+                    ..
+                }),
+                ..
+            }))
+        ));
+    }
+}
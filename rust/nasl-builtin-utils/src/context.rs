@@ -5,9 +5,11 @@
 //! Defines the context used within the interpreter and utilized by the builtin functions
 
 use nasl_syntax::{logger::NaslLogger, Loader, NaslValue, Statement};
-use storage::{ContextKey, Dispatcher, Retriever};
+use storage::{item::NvtPreference, ContextKey, Dispatcher, Retriever};
 
-use crate::lookup_keys::FC_ANON_ARGS;
+use std::{cell::RefCell, path::PathBuf, sync::Mutex};
+
+use crate::{lookup_keys::FC_ANON_ARGS, FunctionErrorKind, SeededRandom};
 
 /// Contexts are responsible to locate, add and delete everything that is declared within a NASL plugin
 
@@ -117,6 +119,8 @@ impl From<HashMap<String, NaslValue>> for ContextType {
 #[derive(Clone)]
 pub struct Register {
     blocks: Vec<NaslContext>,
+    /// The port the script runs for, e.g. when a script is executed once per open port
+    port: Option<u16>,
 }
 
 impl Register {
@@ -124,6 +128,7 @@ impl Register {
     pub fn new() -> Self {
         Self {
             blocks: vec![NaslContext::default()],
+            port: None,
         }
     }
 
@@ -137,7 +142,20 @@ impl Register {
             defined,
             ..Default::default()
         };
-        Self { blocks: vec![root] }
+        Self {
+            blocks: vec![root],
+            port: None,
+        }
+    }
+
+    /// Returns the port the script runs for
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Sets the port the script runs for
+    pub fn set_port(&mut self, port: Option<u16>) {
+        self.port = port;
     }
 
     /// Returns the next index
@@ -152,6 +170,7 @@ impl Register {
             parent: Some(parent_id),
             id: self.index(),
             defined,
+            ..Default::default()
         };
         self.blocks.push(result);
     }
@@ -165,6 +184,7 @@ impl Register {
             parent: Some(0),
             id: self.index(),
             defined,
+            ..Default::default()
         };
         self.blocks.push(result);
     }
@@ -177,15 +197,55 @@ impl Register {
             .map(|(_, val)| val)
     }
 
+    /// Finds a named ContextType starting at the context of the given index
+    ///
+    /// This allows builtin functions to look up variables of the calling context, which is the
+    /// context before the one created for the function call.
+    pub fn named_at<'a>(&'a self, index: usize, name: &'a str) -> Option<&'a ContextType> {
+        self.blocks
+            .get(index)
+            .and_then(|x| x.named(self, name))
+            .map(|(_, val)| val)
+    }
+
+    /// Returns all definitions visible from the context of the given index
+    ///
+    /// Definitions of inner contexts shadow the ones of outer contexts with the same name.
+    /// Positional arguments of function calls are omitted.
+    pub fn visible_at(&self, index: usize) -> HashMap<&str, &ContextType> {
+        let mut result = HashMap::new();
+        let mut current = self.blocks.get(index);
+        while let Some(ctx) = current {
+            for (name, value) in &ctx.defined {
+                if name != FC_ANON_ARGS {
+                    result.entry(name.as_str()).or_insert(value);
+                }
+            }
+            current = ctx.parent.and_then(|x| self.blocks.get(x));
+        }
+        result
+    }
+
     /// Finds a named ContextType with index
     pub fn index_named<'a>(&'a self, name: &'a str) -> Option<(usize, &ContextType)> {
         self.blocks.last().and_then(|x| x.named(self, name))
     }
 
+    /// Adds a named parameter to the context of the given index
+    ///
+    /// A new name may shadow the definition a cached lookup resolved to, therefore it is removed
+    /// from the lookup caches.
+    fn add_named(&mut self, idx: usize, name: &str, value: ContextType) {
+        if self.blocks[idx].add_named(name, value) {
+            for block in &mut self.blocks {
+                block.lookup.get_mut().remove(name);
+            }
+        }
+    }
+
     /// Adds a named parameter to the root context
     pub fn add_global(&mut self, name: &str, value: ContextType) {
-        let global = &mut self.blocks[0];
-        global.add_named(name, value);
+        self.add_named(0, name, value);
     }
 
     /// Adds a named parameter to a specified context
@@ -193,14 +253,13 @@ impl Register {
         if idx >= self.blocks.len() {
             panic!("The given index should be retrieved by named_value. Therefore this should not happen.");
         } else {
-            let ctx = &mut self.blocks[idx];
-            ctx.add_named(name, value);
+            self.add_named(idx, name, value);
         }
     }
     /// Adds a named parameter to the last context
     pub fn add_local(&mut self, name: &str, value: ContextType) {
-        if let Some(last) = self.blocks.last_mut() {
-            last.add_named(name, value);
+        if !self.blocks.is_empty() {
+            self.add_named(self.blocks.len() - 1, name, value);
         }
     }
 
@@ -296,12 +355,17 @@ pub struct NaslContext {
     id: usize,
     /// The defined values/ functions.
     defined: Named,
+    /// Caches the ids of the parent contexts names were found in
+    ///
+    /// The parents of a context do not change while it exists, so that an entry stays valid
+    /// until the name gets defined in another context.
+    lookup: RefCell<HashMap<String, usize>>,
 }
 
 impl NaslContext {
-    /// Adds a named parameter to the context
-    fn add_named(&mut self, name: &str, value: ContextType) {
-        self.defined.insert(name.to_owned(), value);
+    /// Adds a named parameter to the context, returns true when the name is new
+    fn add_named(&mut self, name: &str, value: ContextType) -> bool {
+        self.defined.insert(name.to_owned(), value).is_none()
     }
 
     /// Retrieves a definition by name
@@ -309,18 +373,77 @@ impl NaslContext {
         &'a self,
         registrat: &'a Register,
         name: &'a str,
-    ) -> Option<(usize, &ContextType)> {
+    ) -> Option<(usize, &'a ContextType)> {
         // first check local
-        match self.defined.get(name) {
-            Some(ctx) => Some((self.id, ctx)),
-            None => match self.parent {
-                Some(parent) => registrat.blocks[parent].named(registrat, name),
-                None => None,
-            },
+        if let Some(ctx) = self.defined.get(name) {
+            return Some((self.id, ctx));
+        }
+        let cached = self.lookup.borrow().get(name).copied();
+        if let Some(ctx) = cached.and_then(|id| registrat.blocks[id].defined.get(name)) {
+            return cached.map(|id| (id, ctx));
+        }
+        let result = match self.parent {
+            Some(parent) => registrat.blocks[parent].named(registrat, name),
+            None => None,
+        };
+        if let Some((id, _)) = result {
+            self.lookup.borrow_mut().insert(name.to_owned(), id);
         }
+        result
     }
 }
 
+/// The default maximum amount of elements an array may grow to
+pub const DEFAULT_MAX_ARRAY_SIZE: usize = 1 << 20;
+
+/// The default maximum length of a string or data in bytes
+pub const DEFAULT_MAX_STRING_LENGTH: usize = 1 << 26;
+
+/// Limits the size of values a script can create to prevent memory exhaustion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum length of a string or data in bytes
+    pub max_string_length: usize,
+    /// The maximum amount of elements of an array
+    pub max_array_size: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_string_length: DEFAULT_MAX_STRING_LENGTH,
+            max_array_size: DEFAULT_MAX_ARRAY_SIZE,
+        }
+    }
+}
+
+impl Limits {
+    /// Returns an error when the given length exceeds the maximum string length
+    pub fn check_string_length(&self, length: usize) -> Result<(), FunctionErrorKind> {
+        if length > self.max_string_length {
+            Err(FunctionErrorKind::TooLarge {
+                size: length,
+                max: self.max_string_length,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Capabilities that allow a script to access the host system
+///
+/// Everything is denied by default so that scripts cannot escape the sandbox of the interpreter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Allows scripts to execute local commands, e.g. via `pread`
+    pub allow_local_commands: bool,
+    /// The directory scripts may read files from, e.g. via `file_open`
+    ///
+    /// When None scripts cannot access local files.
+    pub file_root: Option<PathBuf>,
+}
+
 /// Configurations
 ///
 /// This struct includes all objects that a nasl function requires.
@@ -341,6 +464,16 @@ pub struct Context<'a> {
     logger: &'a dyn NaslLogger,
     /// Default logger.
     executor: &'a dyn super::NaslFunctionExecuter,
+    /// Seeded random number generator, when None the random functions use the OS
+    random: Mutex<Option<SeededRandom>>,
+    /// Size limits of created values
+    limits: Limits,
+    /// Access to the host system granted to the script
+    capabilities: Capabilities,
+    /// Calls of user defined functions must pass each parameter
+    strict_calls: bool,
+    /// Preferences of the script as configured for the scan
+    preferences: Vec<NvtPreference>,
 }
 
 impl<'a> Context<'a> {
@@ -362,9 +495,85 @@ impl<'a> Context<'a> {
             loader,
             logger,
             executor,
+            random: Mutex::new(None),
+            limits: Limits::default(),
+            capabilities: Capabilities::default(),
+            strict_calls: false,
+            preferences: vec![],
         }
     }
 
+    /// Sets the size limits of values created by the script
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Get the size limits of values created by the script
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Sets the access to the host system granted to the script
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Get the access to the host system granted to the script
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Requires calls of user defined functions to pass each parameter as named argument
+    ///
+    /// NASL sets parameters that are not passed to NULL; in strict mode such a call is an error
+    /// instead. This helps to find calls that miss an argument, e.g. in tests of a feed.
+    pub fn with_strict_calls(mut self, strict_calls: bool) -> Self {
+        self.strict_calls = strict_calls;
+        self
+    }
+
+    /// Returns true when calls of user defined functions must pass each parameter
+    pub fn strict_calls(&self) -> bool {
+        self.strict_calls
+    }
+
+    /// Sets the preferences of the script as configured for the scan
+    ///
+    /// The `default` of each preference contains the value to be used; for preferences of type
+    /// file it contains the content of the file.
+    pub fn with_preferences(mut self, preferences: Vec<NvtPreference>) -> Self {
+        self.preferences = preferences;
+        self
+    }
+
+    /// Get the preferences of the script as configured for the scan
+    pub fn preferences(&self) -> &[NvtPreference] {
+        &self.preferences
+    }
+
+    /// Sets the initial seed of the random number generator
+    ///
+    /// Without a seed random numbers are read from the operating system. A script can replace
+    /// the seed at any time via `set_seed`.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.set_seed(seed);
+        self
+    }
+
+    /// Reseeds the random number generator and returns the previous seed if there was any
+    pub fn set_seed(&self, seed: u64) -> Option<u64> {
+        let mut random = self.random.lock().unwrap_or_else(|e| e.into_inner());
+        random.replace(SeededRandom::new(seed)).map(|x| x.seed())
+    }
+
+    /// Returns the next random number when the context is seeded
+    pub fn next_random(&self) -> Option<i64> {
+        let mut random = self.random.lock().unwrap_or_else(|e| e.into_inner());
+        random.as_mut().map(|x| x.next_i64())
+    }
+
     /// Executes a function by name
     ///
     /// Returns None when the function was not found.
@@ -417,3 +626,39 @@ impl From<&ContextType> for NaslValue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ContextType, Register};
+
+    fn named(register: &Register, name: &str) -> Option<ContextType> {
+        register.named(name).cloned()
+    }
+
+    #[test]
+    fn lookup_resolves_nearest_scope() {
+        let mut register = Register::default();
+        register.add_global("a", 1.into());
+        register.create_child(Default::default());
+        register.create_child(Default::default());
+        // caches the global within both children
+        assert_eq!(named(&register, "a"), Some(1.into()));
+        register.add_global("a", 2.into());
+        assert_eq!(named(&register, "a"), Some(2.into()));
+        // shadows the cached global
+        register.add_to_index(1, "a", 3.into());
+        assert_eq!(named(&register, "a"), Some(3.into()));
+        register.add_local("a", 4.into());
+        assert_eq!(named(&register, "a"), Some(4.into()));
+        register.drop_last();
+        assert_eq!(named(&register, "a"), Some(3.into()));
+        register.drop_last();
+        assert_eq!(named(&register, "a"), Some(2.into()));
+        // a new context at a reused index does not inherit the cache
+        register.create_root_child(Default::default());
+        assert_eq!(register.index_named("a").map(|(i, _)| i), Some(0));
+        register.create_child([("a".to_owned(), 5.into())].into());
+        assert_eq!(named(&register, "a"), Some(5.into()));
+        assert_eq!(named(&register, "b"), None);
+    }
+}
@@ -0,0 +1,307 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Defines the conversion of arguments into common rust types and of return values back
+//!
+//! Unlike the lenient `From` implementations of [NaslValue] a conversion fails when the value
+//! does not fit the requested type, e.g. a number that is out of range of a port.
+
+use nasl_syntax::{bytes_to_str, NaslValue};
+
+use crate::{FunctionErrorKind, NaslResult};
+
+/// Converts a NaslValue argument into a rust type
+pub trait FromNaslValue: Sized {
+    /// Returns the converted value or an error when the value does not fit
+    fn from_nasl_value(value: &NaslValue) -> Result<Self, FunctionErrorKind>;
+}
+
+impl FromNaslValue for i64 {
+    fn from_nasl_value(value: &NaslValue) -> Result<Self, FunctionErrorKind> {
+        match value {
+            NaslValue::Number(x) => Ok(*x),
+            NaslValue::Boolean(x) => Ok(*x as i64),
+            x => Err(("Number", x).into()),
+        }
+    }
+}
+
+/// Implements the conversion of a number into an integer type with range checking
+macro_rules! from_number {
+    ($($type:ty),*) => {
+        $(
+            impl FromNaslValue for $type {
+                fn from_nasl_value(value: &NaslValue) -> Result<Self, FunctionErrorKind> {
+                    let x = i64::from_nasl_value(value)?;
+                    <$type>::try_from(x).map_err(|_| {
+                        FunctionErrorKind::WrongArgument(format!(
+                            "Expected a number between {} and {} but got {x}",
+                            <$type>::MIN,
+                            <$type>::MAX
+                        ))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+from_number!(u8, u16, u32, u64, usize, i32);
+
+impl FromNaslValue for bool {
+    fn from_nasl_value(value: &NaslValue) -> Result<Self, FunctionErrorKind> {
+        Ok(value.clone().into())
+    }
+}
+
+impl FromNaslValue for String {
+    fn from_nasl_value(value: &NaslValue) -> Result<Self, FunctionErrorKind> {
+        match value {
+            NaslValue::String(x) => Ok(x.clone()),
+            NaslValue::Data(x) => Ok(bytes_to_str(x)),
+            NaslValue::Number(x) => Ok(x.to_string()),
+            x => Err(("String", x).into()),
+        }
+    }
+}
+
+impl FromNaslValue for Vec<u8> {
+    fn from_nasl_value(value: &NaslValue) -> Result<Self, FunctionErrorKind> {
+        match value {
+            NaslValue::String(x) => Ok(x.as_bytes().to_vec()),
+            NaslValue::Data(x) => Ok(x.clone()),
+            x => Err(("Data", x).into()),
+        }
+    }
+}
+
+/// A missing or NULL value is converted into None
+impl<T: FromNaslValue> FromNaslValue for Option<T> {
+    fn from_nasl_value(value: &NaslValue) -> Result<Self, FunctionErrorKind> {
+        match value {
+            NaslValue::Null | NaslValue::Exit(0) => Ok(None),
+            x => T::from_nasl_value(x).map(Some),
+        }
+    }
+}
+
+/// Converts a rust type into the NaslValue returned by a builtin
+pub trait ToNaslValue {
+    /// Returns the value as NaslValue
+    fn to_nasl_value(self) -> NaslValue;
+}
+
+impl ToNaslValue for NaslValue {
+    fn to_nasl_value(self) -> NaslValue {
+        self
+    }
+}
+
+impl ToNaslValue for i64 {
+    fn to_nasl_value(self) -> NaslValue {
+        NaslValue::Number(self)
+    }
+}
+
+impl ToNaslValue for usize {
+    fn to_nasl_value(self) -> NaslValue {
+        NaslValue::Number(self as i64)
+    }
+}
+
+impl ToNaslValue for bool {
+    fn to_nasl_value(self) -> NaslValue {
+        NaslValue::Boolean(self)
+    }
+}
+
+impl ToNaslValue for String {
+    fn to_nasl_value(self) -> NaslValue {
+        NaslValue::String(self)
+    }
+}
+
+impl ToNaslValue for &str {
+    fn to_nasl_value(self) -> NaslValue {
+        NaslValue::String(self.to_owned())
+    }
+}
+
+impl ToNaslValue for Vec<u8> {
+    fn to_nasl_value(self) -> NaslValue {
+        NaslValue::Data(self)
+    }
+}
+
+/// The elements are returned as array
+impl<T: ToNaslValue> ToNaslValue for Vec<T> {
+    fn to_nasl_value(self) -> NaslValue {
+        NaslValue::Array(self.into_iter().map(|x| x.to_nasl_value()).collect())
+    }
+}
+
+/// None is returned as NULL
+impl<T: ToNaslValue> ToNaslValue for Option<T> {
+    fn to_nasl_value(self) -> NaslValue {
+        match self {
+            Some(x) => x.to_nasl_value(),
+            None => NaslValue::Null,
+        }
+    }
+}
+
+/// Wraps the result of a builtin with a plain return type into a NaslResult
+pub fn to_nasl_result<T: ToNaslValue>(result: Result<T, FunctionErrorKind>) -> NaslResult {
+    result.map(ToNaslValue::to_nasl_value)
+}
+
+/// Formats the value like `to_string` but renders numbers in the given radix
+///
+/// Digits above 9 are lowercase letters, negative numbers are prefixed with `-`.
+/// Values that are not numbers are formatted like `to_string`.
+/// A radix that is not in the range from 2 to 36 is a wrong argument.
+pub fn to_string_radix(value: &NaslValue, radix: u32) -> Result<String, FunctionErrorKind> {
+    if !(2..=36).contains(&radix) {
+        return Err(FunctionErrorKind::WrongArgument(format!(
+            "radix must be between 2 and 36, got {radix}"
+        )));
+    }
+    let x = match value {
+        NaslValue::Number(x) => *x,
+        _ => return Ok(value.to_string()),
+    };
+    let mut rest = x.unsigned_abs();
+    let mut digits = vec![];
+    loop {
+        let digit = (rest % radix as u64) as u32;
+        digits.push(char::from_digit(digit, radix).unwrap_or('0'));
+        rest /= radix as u64;
+        if rest == 0 {
+            break;
+        }
+    }
+    if x < 0 {
+        digits.push('-');
+    }
+    Ok(digits.iter().rev().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use nasl_syntax::NaslValue;
+
+    use super::{FromNaslValue, ToNaslValue};
+    use crate::FunctionErrorKind;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(u16::from_nasl_value(&NaslValue::Number(443)), Ok(443));
+        assert_eq!(u16::from_nasl_value(&NaslValue::Number(65535)), Ok(65535));
+        assert!(matches!(
+            u16::from_nasl_value(&NaslValue::Number(65536)),
+            Err(FunctionErrorKind::WrongArgument(_))
+        ));
+        assert!(u16::from_nasl_value(&NaslValue::Number(-1)).is_err());
+        assert_eq!(usize::from_nasl_value(&NaslValue::Number(0)), Ok(0));
+        assert!(usize::from_nasl_value(&NaslValue::Number(-1)).is_err());
+        assert!(usize::from_nasl_value(&"1".into()).is_err());
+        assert_eq!(i64::from_nasl_value(&NaslValue::Boolean(true)), Ok(1));
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(
+            Vec::<u8>::from_nasl_value(&"abc".into()),
+            Ok(b"abc".to_vec())
+        );
+        assert_eq!(
+            Vec::<u8>::from_nasl_value(&NaslValue::Data(vec![0, 255])),
+            Ok(vec![0, 255])
+        );
+        assert!(Vec::<u8>::from_nasl_value(&NaslValue::Number(1)).is_err());
+        assert_eq!(
+            String::from_nasl_value(&NaslValue::Data(b"abc".to_vec())),
+            Ok("abc".to_owned())
+        );
+        // like the conversion of NaslValue each byte is a char
+        assert_eq!(
+            String::from_nasl_value(&NaslValue::Data(vec![0x61, 0xe4])),
+            Ok("a\u{e4}".to_owned())
+        );
+    }
+
+    #[test]
+    fn optional() {
+        assert_eq!(Option::<u16>::from_nasl_value(&NaslValue::Null), Ok(None));
+        assert_eq!(
+            Option::<u16>::from_nasl_value(&NaslValue::Exit(0)),
+            Ok(None)
+        );
+        assert_eq!(
+            Option::<u16>::from_nasl_value(&NaslValue::Number(22)),
+            Ok(Some(22))
+        );
+        assert!(Option::<u16>::from_nasl_value(&NaslValue::Number(-22)).is_err());
+    }
+
+    #[test]
+    fn return_values() {
+        assert_eq!(1i64.to_nasl_value(), NaslValue::Number(1));
+        assert_eq!(true.to_nasl_value(), NaslValue::Boolean(true));
+        assert_eq!("a".to_nasl_value(), NaslValue::String("a".to_owned()));
+        assert_eq!(vec![0u8, 1].to_nasl_value(), NaslValue::Data(vec![0, 1]));
+        assert_eq!(
+            vec![vec![0u8]].to_nasl_value(),
+            NaslValue::Array(vec![NaslValue::Data(vec![0])])
+        );
+        assert_eq!(None::<i64>.to_nasl_value(), NaslValue::Null);
+        assert_eq!(Some(2i64).to_nasl_value(), NaslValue::Number(2));
+    }
+
+    #[test]
+    fn builtin_result() {
+        fn reverse(data: Vec<u8>) -> Result<Vec<u8>, FunctionErrorKind> {
+            Ok(data.into_iter().rev().collect())
+        }
+        let argument = Vec::<u8>::from_nasl_value(&"ab".into()).unwrap();
+        assert_eq!(
+            super::to_nasl_result(reverse(argument)),
+            Ok(NaslValue::Data(b"ba".to_vec()))
+        );
+        let failed: Result<Vec<u8>, _> = Err(FunctionErrorKind::Dirty("failed".to_owned()));
+        assert!(super::to_nasl_result(failed).is_err());
+    }
+
+    #[test]
+    fn to_string_radix() {
+        let cases = [
+            (16, 255, "ff"),
+            (16, -255, "-ff"),
+            (16, 0, "0"),
+            (2, 5, "101"),
+            (2, -5, "-101"),
+            (2, 0, "0"),
+            (10, 42, "42"),
+            (10, -42, "-42"),
+            (10, 0, "0"),
+            (16, i64::MIN, "-8000000000000000"),
+        ];
+        for (radix, number, expected) in cases {
+            assert_eq!(
+                super::to_string_radix(&NaslValue::Number(number), radix),
+                Ok(expected.to_owned())
+            );
+        }
+        assert_eq!(
+            super::to_string_radix(&"ff".into(), 16),
+            Ok("ff".to_owned())
+        );
+        for radix in [0, 1, 37] {
+            assert!(matches!(
+                super::to_string_radix(&NaslValue::Number(1), radix),
+                Err(FunctionErrorKind::WrongArgument(_))
+            ));
+        }
+    }
+}
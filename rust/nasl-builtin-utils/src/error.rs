@@ -40,6 +40,24 @@ pub enum FunctionErrorKind {
     /// There is a deeper problem
     /// An example would be that there is no free memory left in the system
     Dirty(String),
+    /// The function requires a capability that was not granted to the script
+    PermissionDenied(String),
+    /// A named argument could not be converted into the type required by the function
+    InvalidArgument {
+        /// The name of the argument
+        name: String,
+        /// The reason why the conversion failed
+        reason: String,
+    },
+    /// A value would exceed the configured size limit
+    TooLarge {
+        /// The requested size
+        size: usize,
+        /// The maximum allowed size
+        max: usize,
+    },
+    /// The function panicked, contains the panic message
+    Panic(String),
 }
 
 impl From<GeneralErrorType> for FunctionErrorKind {
@@ -64,6 +82,14 @@ impl Display for FunctionErrorKind {
             FunctionErrorKind::Diagnostic(x, _) => write!(f, "{x}"),
             FunctionErrorKind::GeneralError(x) => write!(f, "{x}"),
             FunctionErrorKind::Dirty(x) => write!(f, "{x}"),
+            FunctionErrorKind::PermissionDenied(x) => write!(f, "permission denied: {x}"),
+            FunctionErrorKind::InvalidArgument { name, reason } => {
+                write!(f, "argument `{name}`: {reason}")
+            }
+            FunctionErrorKind::TooLarge { size, max } => {
+                write!(f, "size {size} exceeds the maximum of {max}")
+            }
+            FunctionErrorKind::Panic(x) => write!(f, "panicked: {x}"),
         }
     }
 }
@@ -5,12 +5,19 @@
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
 pub mod context;
+pub mod convert;
 pub mod error;
 pub mod lookup_keys;
+pub mod random;
 use std::collections::HashMap;
 
-pub use context::{Context, ContextType, Register};
+pub use context::{
+    Capabilities, Context, ContextType, Limits, Register, DEFAULT_MAX_ARRAY_SIZE,
+    DEFAULT_MAX_STRING_LENGTH,
+};
+pub use convert::{to_nasl_result, to_string_radix, FromNaslValue, ToNaslValue};
 pub use error::FunctionErrorKind;
+pub use random::SeededRandom;
 
 /// The result of a function call.
 pub type NaslResult = Result<nasl_syntax::NaslValue, FunctionErrorKind>;
@@ -94,6 +101,44 @@ pub fn get_named_parameter<'a>(
         },
     }
 }
+
+/// gets an optional named parameter
+///
+/// Unlike [get_named_parameter] a parameter that is explicitly set to NULL is treated as absent,
+/// matching the NASL semantic of optional parameters. Returns None in both cases so that the
+/// caller can use the default behavior.
+pub fn get_optional_named_parameter<'a>(
+    registrat: &'a Register,
+    key: &'a str,
+) -> Result<Option<&'a nasl_syntax::NaslValue>, FunctionErrorKind> {
+    match get_named_parameter(registrat, key, false)? {
+        nasl_syntax::NaslValue::Exit(0) | nasl_syntax::NaslValue::Null => Ok(None),
+        x => Ok(Some(x)),
+    }
+}
+/// gets a named argument converted into the required type
+///
+/// A missing argument is only valid when the type is optional. When the conversion fails the
+/// error contains the name of the argument.
+pub fn get_named_argument<T: FromNaslValue>(
+    registrat: &Register,
+    key: &str,
+) -> Result<T, FunctionErrorKind> {
+    let value = get_named_parameter(registrat, key, false)?;
+    T::from_nasl_value(value).map_err(|reason| match value {
+        nasl_syntax::NaslValue::Exit(0) => {
+            FunctionErrorKind::MissingArguments(vec![key.to_owned()])
+        }
+        _ => FunctionErrorKind::InvalidArgument {
+            name: key.to_owned(),
+            reason: match reason {
+                FunctionErrorKind::WrongArgument(x) => x,
+                x => x.to_string(),
+            },
+        },
+    })
+}
+
 /// Holds registered NaslFunctionExecuter and executes them in order of registration.
 #[derive(Default)]
 pub struct NaslFunctionRegister {
@@ -138,6 +183,13 @@ impl NaslFunctionExecuter for NaslFunctionRegister {
         }
         false
     }
+
+    fn nasl_fn_cache_clear(&self) -> Option<usize> {
+        self.executor
+            .iter()
+            .filter_map(|x| x.nasl_fn_cache_clear())
+            .reduce(|a, b| a + b)
+    }
 }
 
 #[derive(Default)]
@@ -277,4 +329,46 @@ mod test {
             Some(Ok(3.into()))
         );
     }
+
+    #[test]
+    fn optional_named_parameter() {
+        use nasl_syntax::NaslValue;
+        let mut register = crate::Register::default();
+        register.add_local("null", NaslValue::Null.into());
+        register.add_local("number", 1.into());
+        let optional = |key| crate::get_optional_named_parameter(&register, key).unwrap();
+        assert_eq!(optional("absent"), None);
+        assert_eq!(optional("null"), None);
+        assert_eq!(optional("number"), Some(&NaslValue::Number(1)));
+    }
+
+    #[test]
+    fn named_argument() {
+        use crate::FunctionErrorKind;
+        let mut register = crate::Register::default();
+        register.add_local("port", 70000.into());
+        register.add_local("data", "a".into());
+        assert_eq!(
+            crate::get_named_argument(&register, "data"),
+            Ok(b"a".to_vec())
+        );
+        assert_eq!(crate::get_named_argument(&register, "port"), Ok(70000i64));
+        assert_eq!(
+            crate::get_named_argument::<Option<u16>>(&register, "absent"),
+            Ok(None)
+        );
+        assert_eq!(
+            crate::get_named_argument::<u16>(&register, "absent"),
+            Err(FunctionErrorKind::MissingArguments(vec![
+                "absent".to_owned()
+            ]))
+        );
+        match crate::get_named_argument::<u16>(&register, "port") {
+            Err(e @ FunctionErrorKind::InvalidArgument { .. }) => assert_eq!(
+                e.to_string(),
+                "argument `port`: Expected a number between 0 and 65535 but got 70000"
+            ),
+            x => panic!("expected an invalid argument error, got {x:?}"),
+        }
+    }
 }
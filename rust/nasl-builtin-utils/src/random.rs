@@ -0,0 +1,36 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Defines a seedable random number generator for reproducible runs
+
+/// A seedable pseudo random number generator based on splitmix64
+///
+/// It is not suitable for cryptographic purposes; it exists so that e.g. fuzzing runs can be
+/// reproduced by using the same seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeededRandom {
+    seed: u64,
+    state: u64,
+}
+
+impl SeededRandom {
+    /// Creates a new generator with the given seed
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// Returns the seed the generator was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the next random number
+    pub fn next_i64(&mut self) -> i64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (z ^ (z >> 31)) as i64
+    }
+}
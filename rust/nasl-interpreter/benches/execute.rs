@@ -0,0 +1,90 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nasl_interpreter::{CodeInterpreter, ContextFactory, Register};
+
+/// Executes the code and panics on the first error
+fn execute(code: &str) {
+    let binding = ContextFactory::default();
+    let context = binding.build(Default::default(), Default::default());
+    let interpreter = CodeInterpreter::new(code, Register::default(), &context);
+    for result in interpreter {
+        if let Err(err) = result {
+            panic!("Unexpected error: {err}");
+        }
+    }
+}
+
+pub fn arithmetic_loop_benchmark(c: &mut Criterion) {
+    let code = r#"
+    x = 0;
+    for (i = 0; i < 10000; i++) {
+        x = (x + i * 3) % 65536;
+        x = x ^ (i << 2);
+    }
+    "#;
+    c.bench_function("arithmetic loop", |b| b.iter(|| execute(black_box(code))));
+}
+
+pub fn array_loop_benchmark(c: &mut Criterion) {
+    let code = r#"
+    a = make_list();
+    for (i = 0; i < 1000; i++) a[i] = i;
+    sum = 0;
+    foreach x (a) sum += x;
+    "#;
+    c.bench_function("array loop", |b| b.iter(|| execute(black_box(code))));
+}
+
+pub fn array_read_benchmark(c: &mut Criterion) {
+    let code = r#"
+    a = make_list();
+    for (i = 0; i < 1000; i++) a[i] = i;
+    sum = 0;
+    for (i = 0; i < 1000; i++) sum += a[i];
+    "#;
+    c.bench_function("array read", |b| b.iter(|| execute(black_box(code))));
+}
+
+pub fn deep_scope_benchmark(c: &mut Criterion) {
+    // reads a global variable from within 32 nested blocks
+    let mut code = "a = 1; sum = 0;".to_owned();
+    code.push_str(&"{ ".repeat(32));
+    code.push_str("for (i = 0; i < 1000; i++) sum += a;");
+    code.push_str(&" }".repeat(32));
+    c.bench_function("deep scope", |b| b.iter(|| execute(black_box(&code))));
+}
+
+pub fn function_call_benchmark(c: &mut Criterion) {
+    let code = r#"
+    function add(a, b) { return a + b; }
+    x = 0;
+    for (i = 0; i < 1000; i++) x = add(a: x, b: i);
+    "#;
+    c.bench_function("function calls", |b| b.iter(|| execute(black_box(code))));
+}
+
+pub fn regex_match_benchmark(c: &mut Criterion) {
+    let code = r#"
+    banner = "SSH-2.0-OpenSSH_8.9p1 Ubuntu-3ubuntu0.1";
+    found = 0;
+    for (i = 0; i < 1000; i++) {
+        if (banner =~ "^SSH-[0-9.]+-OpenSSH_[0-9.]+p[0-9]+") found++;
+        if (banner !~ "Debian") found++;
+    }
+    "#;
+    c.bench_function("regex match", |b| b.iter(|| execute(black_box(code))));
+}
+
+criterion_group!(
+    benches,
+    arithmetic_loop_benchmark,
+    array_loop_benchmark,
+    array_read_benchmark,
+    deep_scope_benchmark,
+    function_call_benchmark,
+    regex_match_benchmark
+);
+criterion_main!(benches);
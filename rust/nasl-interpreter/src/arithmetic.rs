@@ -0,0 +1,178 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Defines builtin functions that need the interpreter itself
+
+use std::time::{Duration, Instant};
+
+use nasl_builtin_utils::{Context, FunctionErrorKind, NaslFunctionExecuter, NaslResult, Register};
+use nasl_syntax::{
+    bytes_to_str, IdentifierType, NaslValue, Statement, StatementKind, TokenCategory,
+};
+
+use crate::Interpreter;
+
+/// Returns true when the statement is a number, a boolean or an operator
+fn is_arithmetic(statement: &Statement) -> bool {
+    match statement.kind() {
+        StatementKind::Primitive => matches!(
+            statement.as_token().category(),
+            TokenCategory::Number(_)
+                | TokenCategory::Identifier(IdentifierType::True)
+                | TokenCategory::Identifier(IdentifierType::False)
+        ),
+        StatementKind::Operator(..) => true,
+        _ => false,
+    }
+}
+
+/// Evaluates the given unnamed argument as an arithmetic expression.
+///
+/// Only numbers, booleans and operators are allowed; calls, assignments and variables are
+/// rejected so that the expression cannot have side effects. It is evaluated within the timeout of
+/// the calling interpreter.
+fn eval_arithmetic(
+    register: &Register,
+    context: &Context,
+    timeout: Option<(Duration, Instant)>,
+) -> NaslResult {
+    let code = match register.positional().first() {
+        Some(NaslValue::String(x)) => x.to_owned(),
+        Some(NaslValue::Data(x)) => bytes_to_str(x),
+        x => return Err(("0", "string", x).into()),
+    };
+    let mut statements = vec![];
+    for statement in nasl_syntax::parse(&format!("{code};")) {
+        let statement = statement.map_err(|e| FunctionErrorKind::WrongArgument(e.to_string()))?;
+        if !matches!(statement.kind(), StatementKind::NoOp | StatementKind::EoF) {
+            statements.push(statement);
+        }
+    }
+    let statement = match &statements[..] {
+        [x] => x,
+        _ => {
+            return Err(FunctionErrorKind::WrongArgument(format!(
+                "expected a single expression: {code}"
+            )))
+        }
+    };
+    let mut rejected = None;
+    statement.walk(&mut |s| {
+        if rejected.is_none() && !is_arithmetic(s) {
+            rejected = Some(s);
+        }
+    });
+    if let Some(s) = rejected {
+        return Err(FunctionErrorKind::WrongArgument(format!(
+            "only numbers, booleans and operators are allowed, got: {s}"
+        )));
+    }
+    let mut interpreter = Interpreter::new(Register::default(), context);
+    interpreter.timeout = timeout;
+    interpreter
+        .resolve(statement)
+        .map_err(|e| FunctionErrorKind::WrongArgument(e.to_string()))
+}
+
+/// Builtin functions that evaluate NASL code and therefore depend on the interpreter
+///
+/// They are always available and looked up after the functions of the context.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Arithmetic {
+    timeout: Option<(Duration, Instant)>,
+}
+
+impl Arithmetic {
+    /// Creates the functions for an interpreter with the given timeout and the instant it ends
+    pub fn new(timeout: Option<(Duration, Instant)>) -> Self {
+        Self { timeout }
+    }
+}
+
+impl NaslFunctionExecuter for Arithmetic {
+    fn nasl_fn_execute(
+        &self,
+        name: &str,
+        register: &Register,
+        context: &Context,
+    ) -> Option<NaslResult> {
+        match name {
+            "eval_arithmetic" => Some(eval_arithmetic(register, context, self.timeout)),
+            _ => None,
+        }
+    }
+
+    fn nasl_fn_defined(&self, name: &str) -> bool {
+        name == "eval_arithmetic"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nasl_builtin_utils::FunctionErrorKind;
+
+    use crate::*;
+
+    #[test]
+    fn eval_arithmetic() {
+        let code = r###"
+        eval_arithmetic("1+2*3");
+        eval_arithmetic("(1 + 2) * 3 == 9");
+        eval_arithmetic("-4 >> 1");
+        eval_arithmetic("foo()");
+        eval_arithmetic("a = 1");
+        eval_arithmetic("a + 1");
+        eval_arithmetic("1; 2");
+        eval_arithmetic("1 / 0");
+        eval_arithmetic("1 % 0");
+        eval_arithmetic("1 << 64");
+        eval_arithmetic("2 ** 40");
+        eval_arithmetic("9223372036854775807 + 1");
+        "###;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(7))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Boolean(true))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Number(-2))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        for _ in 0..5 {
+            assert!(matches!(
+                parser.next(),
+                Some(Err(InterpretError {
+                    kind: InterpretErrorKind::FunctionCallError(FunctionError {
+                        kind: FunctionErrorKind::WrongArgument(_),
+                        ..
+                    }),
+                    ..
+                }))
+            ));
+        }
+    }
+
+    #[test]
+    fn eval_arithmetic_within_timeout() {
+        use std::time::Duration;
+
+        let code = r###"eval_arithmetic("1 + " + (1 + usleep(100000)));"###;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        parser.set_timeout(Some(Duration::from_millis(50)));
+        match parser.next() {
+            Some(Err(InterpretError {
+                kind:
+                    InterpretErrorKind::FunctionCallError(FunctionError {
+                        kind: FunctionErrorKind::WrongArgument(x),
+                        ..
+                    }),
+                ..
+            })) => assert!(x.contains("timeout"), "{x}"),
+            x => panic!("expected a timeout, got {x:?}"),
+        }
+    }
+}
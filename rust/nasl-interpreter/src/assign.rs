@@ -2,11 +2,14 @@
 //
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
-use std::collections::HashMap;
+use nasl_syntax::{bytes_to_str, AssignOrder, NaslDict, Statement, TokenCategory};
 
-use nasl_syntax::{AssignOrder, Statement, TokenCategory};
-
-use crate::{error::InterpretError, interpreter::InterpretResult, Interpreter};
+use crate::{
+    error::InterpretError,
+    interpreter::{check_length, InterpretResult},
+    operator::arithmetic,
+    Interpreter,
+};
 use nasl_builtin_utils::ContextType;
 use nasl_syntax::NaslValue;
 use nasl_syntax::StatementKind::*;
@@ -23,8 +26,17 @@ pub(crate) trait AssignExtension {
     ) -> InterpretResult;
 }
 
-fn prepare_array(idx: &NaslValue, left: NaslValue) -> (usize, Vec<NaslValue>) {
-    let idx = i64::from(idx) as usize;
+fn prepare_array(
+    idx: &NaslValue,
+    left: NaslValue,
+    max: usize,
+) -> Result<(usize, Vec<NaslValue>), InterpretError> {
+    let index = i64::from(idx);
+    // reject before allocating so that e.g. `a[-1]` or `a[1000000000]` do not exhaust memory
+    let idx = match usize::try_from(index) {
+        Ok(idx) if idx < max => idx,
+        _ => return Err(InterpretError::invalid_index(index, max)),
+    };
     let mut arr: Vec<NaslValue> = match left {
         NaslValue::Array(x) => x,
         _ => {
@@ -35,10 +47,37 @@ fn prepare_array(idx: &NaslValue, left: NaslValue) -> (usize, Vec<NaslValue>) {
     for _ in arr.len()..idx + 1 {
         arr.push(NaslValue::Null)
     }
-    (idx, arr)
+    Ok((idx, arr))
+}
+
+/// Returns the number of a value that is incremented or decremented
+///
+/// Null is treated as 0 and booleans as 0 or 1 so that uninitialized counters work; other
+/// values, like strings, are not coerced but return an error.
+fn incrementable(stmt: &Statement, value: &NaslValue) -> Result<i64, InterpretError> {
+    match value {
+        NaslValue::Number(x) => Ok(*x),
+        NaslValue::Null | NaslValue::Boolean(_) => Ok(i64::from(value)),
+        _ => Err(InterpretError::unsupported(stmt, "number")),
+    }
+}
+
+/// Returns the operator of a compound assignment, e.g. `+` for `+=`
+fn compound_operator(category: &TokenCategory) -> Option<TokenCategory> {
+    Some(match category {
+        TokenCategory::PlusEqual => TokenCategory::Plus,
+        TokenCategory::MinusEqual => TokenCategory::Minus,
+        TokenCategory::StarEqual => TokenCategory::Star,
+        TokenCategory::SlashEqual => TokenCategory::Slash,
+        TokenCategory::PercentEqual => TokenCategory::Percent,
+        TokenCategory::LessLessEqual => TokenCategory::LessLess,
+        TokenCategory::GreaterGreaterEqual => TokenCategory::GreaterGreater,
+        TokenCategory::GreaterGreaterGreaterEqual => TokenCategory::GreaterGreaterGreater,
+        _ => return None,
+    })
 }
 
-fn prepare_dict(left: NaslValue) -> HashMap<String, NaslValue> {
+fn prepare_dict(left: NaslValue) -> NaslDict {
     match left {
         NaslValue::Array(x) => x
             .into_iter()
@@ -46,13 +85,22 @@ fn prepare_dict(left: NaslValue) -> HashMap<String, NaslValue> {
             .map(|(i, v)| (i.to_string(), v))
             .collect(),
         NaslValue::Dict(x) => x,
-        NaslValue::Null => HashMap::new(),
-        x => HashMap::from([("0".to_string(), x)]),
+        NaslValue::Null => NaslDict::new(),
+        x => NaslDict::from([("0".to_string(), x)]),
     }
 }
 
 impl<'a> Interpreter<'a> {
     fn save(&mut self, idx: usize, key: &str, value: NaslValue) {
+        if let Some(watches) = self.watches.get(key) {
+            let old = match self.register().named_at(idx, key) {
+                Some(ContextType::Value(x)) => x.clone(),
+                _ => NaslValue::Null,
+            };
+            for watch in watches {
+                watch(&old, &value);
+            }
+        }
         self.register_mut()
             .add_to_index(idx, key, ContextType::Value(value));
     }
@@ -76,25 +124,25 @@ impl<'a> Interpreter<'a> {
         left: NaslValue,
         right: &NaslValue,
         return_original: &AssignOrder,
-        result: impl Fn(&NaslValue, &NaslValue) -> NaslValue,
-    ) -> NaslValue {
+        result: impl Fn(&NaslValue, &NaslValue) -> InterpretResult,
+    ) -> InterpretResult {
         let mut dict = prepare_dict(left);
-        match return_original {
+        Ok(match return_original {
             AssignOrder::ReturnAssign => {
                 let original = dict.get(&idx).unwrap_or(&NaslValue::Null).clone();
-                let result = result(&original, right);
+                let result = result(&original, right)?;
                 dict.insert(idx, result);
                 self.save(ridx, key, NaslValue::Dict(dict));
                 original
             }
             AssignOrder::AssignReturn => {
                 let original = dict.get(&idx).unwrap_or(&NaslValue::Null);
-                let result = result(original, right);
+                let result = result(original, right)?;
                 dict.insert(idx, result.clone());
                 self.save(ridx, key, NaslValue::Dict(dict));
                 result
             }
-        }
+        })
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -106,24 +154,24 @@ impl<'a> Interpreter<'a> {
         left: NaslValue,
         right: &NaslValue,
         return_original: &AssignOrder,
-        result: impl Fn(&NaslValue, &NaslValue) -> NaslValue,
-    ) -> NaslValue {
-        let (idx, mut arr) = prepare_array(idx, left);
-        match return_original {
+        result: impl Fn(&NaslValue, &NaslValue) -> InterpretResult,
+    ) -> InterpretResult {
+        let (idx, mut arr) = prepare_array(idx, left, self.max_array_size)?;
+        Ok(match return_original {
             AssignOrder::ReturnAssign => {
                 let orig = arr[idx].clone();
-                let result = result(&orig, right);
+                let result = result(&orig, right)?;
                 arr[idx] = result;
                 self.save(ridx, key, NaslValue::Array(arr));
                 orig
             }
             AssignOrder::AssignReturn => {
-                let result = result(&arr[idx], right);
+                let result = result(&arr[idx], right)?;
                 arr[idx] = result.clone();
                 self.save(ridx, key, NaslValue::Array(arr));
                 result
             }
-        }
+        })
     }
 
     fn store_return(
@@ -131,7 +179,7 @@ impl<'a> Interpreter<'a> {
         key: &str,
         lookup: Option<NaslValue>,
         right: &NaslValue,
-        result: impl Fn(&NaslValue, &NaslValue) -> NaslValue,
+        result: impl Fn(&NaslValue, &NaslValue) -> InterpretResult,
     ) -> InterpretResult {
         self.dynamic_return(key, &AssignOrder::AssignReturn, lookup, right, result)
     }
@@ -142,12 +190,12 @@ impl<'a> Interpreter<'a> {
         order: &AssignOrder,
         lookup: Option<NaslValue>,
         right: &NaslValue,
-        result: impl Fn(&NaslValue, &NaslValue) -> NaslValue,
+        result: impl Fn(&NaslValue, &NaslValue) -> InterpretResult,
     ) -> InterpretResult {
         let (ridx, left) = self.named_value(key)?;
         let result = match lookup {
             None => {
-                let result = result(&left, right);
+                let result = result(&left, right)?;
                 self.save(ridx, key, result.clone());
                 match order {
                     AssignOrder::AssignReturn => result,
@@ -156,17 +204,17 @@ impl<'a> Interpreter<'a> {
             }
             Some(idx) => match idx {
                 NaslValue::String(idx) => {
-                    self.handle_dict(ridx, key, idx, left, right, order, result)
+                    self.handle_dict(ridx, key, idx, left, right, order, result)?
                 }
                 NaslValue::Data(idx) => {
-                    let idx = idx.into_iter().map(|x| x as char).collect();
-                    self.handle_dict(ridx, key, idx, left, right, order, result)
+                    let idx = bytes_to_str(&idx);
+                    self.handle_dict(ridx, key, idx, left, right, order, result)?
                 }
                 _ => match left {
                     NaslValue::Dict(_) => {
-                        self.handle_dict(ridx, key, idx.to_string(), left, right, order, result)
+                        self.handle_dict(ridx, key, idx.to_string(), left, right, order, result)?
                     }
-                    _ => self.handle_array(ridx, key, &idx, left, right, order, result),
+                    _ => self.handle_array(ridx, key, &idx, left, right, order, result)?,
                 },
             },
         };
@@ -177,7 +225,7 @@ impl<'a> Interpreter<'a> {
         order: &AssignOrder,
         key: &str,
         lookup: Option<NaslValue>,
-        result: impl Fn(&NaslValue, &NaslValue) -> NaslValue,
+        result: impl Fn(&NaslValue, &NaslValue) -> InterpretResult,
     ) -> InterpretResult {
         self.dynamic_return(key, order, lookup, &NaslValue::Null, result)
     }
@@ -204,53 +252,35 @@ impl<'a> AssignExtension for Interpreter<'a> {
         };
         let val = self.resolve(right)?;
         match category {
-            TokenCategory::Equal => self.store_return(&key, lookup, &val, |_, right| right.clone()),
-            TokenCategory::PlusEqual => self.store_return(&key, lookup, &val, |left, right| {
-                NaslValue::Number(i64::from(left) + i64::from(right))
-            }),
-            TokenCategory::MinusEqual => self.store_return(&key, lookup, &val, |left, right| {
-                NaslValue::Number(i64::from(left) - i64::from(right))
-            }),
-            TokenCategory::SlashEqual => self.store_return(&key, lookup, &val, |left, right| {
-                NaslValue::Number(i64::from(left) / i64::from(right))
-            }),
-            TokenCategory::StarEqual => self.store_return(&key, lookup, &val, |left, right| {
-                NaslValue::Number(i64::from(left) * i64::from(right))
-            }),
-            TokenCategory::GreaterGreaterEqual => {
-                self.store_return(&key, lookup, &val, |left, right| {
-                    NaslValue::Number(i64::from(left) >> i64::from(right))
-                })
+            TokenCategory::Equal => {
+                self.store_return(&key, lookup, &val, |_, right| Ok(right.clone()))
             }
-            TokenCategory::LessLessEqual => self.store_return(&key, lookup, &val, |left, right| {
-                NaslValue::Number(i64::from(left) << i64::from(right))
+            // wraps around on overflow like the C implementation
+            TokenCategory::PlusPlus => self.without_right(order, &key, lookup, |value, _| {
+                let x = incrementable(left, value)?;
+                Ok(NaslValue::Number(x.checked_add(1).unwrap_or(i64::MIN)))
             }),
-            TokenCategory::GreaterGreaterGreaterEqual => {
-                self.store_return(&key, lookup, &val, |left, right| {
-                    // get rid of minus sign
-                    let left = i64::from(left) as u32;
-                    let right = i64::from(right) as u32;
-                    NaslValue::Number((left << right) as i64)
-                })
-            }
-            TokenCategory::PercentEqual => self.store_return(&key, lookup, &val, |left, right| {
-                NaslValue::Number(i64::from(left) % i64::from(right))
+            TokenCategory::MinusMinus => self.without_right(order, &key, lookup, |value, _| {
+                let x = incrementable(left, value)?;
+                Ok(NaslValue::Number(x.checked_sub(1).unwrap_or(i64::MAX)))
             }),
-            TokenCategory::PlusPlus => self.without_right(order, &key, lookup, |left, _| {
-                NaslValue::Number(i64::from(left) + 1)
-            }),
-            TokenCategory::MinusMinus => self.without_right(order, &key, lookup, |left, _| {
-                NaslValue::Number(i64::from(left) - 1)
-            }),
-
-            cat => Err(InterpretError::wrong_category(cat)),
+            // compound assignments behave like their operator, e.g. `a += b` like `a = a + b`
+            cat => match compound_operator(cat).as_ref().and_then(arithmetic) {
+                Some(op) => {
+                    let max = self.ctxconfigs.limits().max_string_length;
+                    self.store_return(&key, lookup, &val, |left, right| {
+                        op(left.clone(), Some(right.clone())).and_then(|x| check_length(x, max))
+                    })
+                }
+                None => Err(InterpretError::wrong_category(cat)),
+            },
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use nasl_syntax::NaslDict;
 
     use crate::*;
     #[test]
@@ -281,13 +311,97 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok(22.into())));
         assert_eq!(parser.next(), Some(Ok(5.into())));
         assert_eq!(parser.next(), Some(Ok(20.into())));
-        assert_eq!(parser.next(), Some(Ok(80.into())));
-        assert_eq!(parser.next(), Some(Ok(0.into())));
-        assert_eq!(parser.next(), Some(Ok(0.into())));
-        assert_eq!(parser.next(), Some(Ok(2.into())));
-        assert_eq!(parser.next(), Some(Ok(2.into())));
-        assert_eq!(parser.next(), Some(Ok(0.into())));
+        assert_eq!(parser.next(), Some(Ok(5.into())));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert_eq!(parser.next(), Some(Ok(3.into())));
+        assert_eq!(parser.next(), Some(Ok(3.into())));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+    }
+    #[test]
+    fn plus_equal_concatenates() {
+        let code = r###"
+        s = "a";
+        s += "b";
+        d = 'a';
+        d += "b";
+        n = 1;
+        n += 2;
+        a[0] = "x";
+        a[0] += 1;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(parser.next(), Some(Ok("a".into())));
+        assert_eq!(parser.next(), Some(Ok("ab".into())));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Data("a".into()))));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Data("ab".into()))));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert_eq!(parser.next(), Some(Ok(3.into())));
+        assert_eq!(parser.next(), Some(Ok("x".into())));
+        assert_eq!(parser.next(), Some(Ok("x1".into())));
+    }
+
+    #[test]
+    fn compound_assignment_like_operator() {
+        let code = r###"
+        s = "abcb";
+        s - "b";
+        s -= "b";
+        d = 'abcb';
+        d -= "b";
+        n = 7;
+        n * 3;
+        n *= 3;
+        n % 4;
+        n %= 4;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok("acb".into())));
+        assert_eq!(parser.next(), Some(Ok("acb".into())));
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Data("acb".into()))));
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(21.into())));
+        assert_eq!(parser.next(), Some(Ok(21.into())));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
     }
+
+    #[test]
+    fn increment_decrement() {
+        let code = r###"
+        a = 9223372036854775807;
+        a++;
+        a;
+        a--;
+        a;
+        ++b;
+        s = "a";
+        s++;
+        --s;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        assert_eq!(parser.next(), Some(Ok(i64::MAX.into())));
+        assert_eq!(parser.next(), Some(Ok(i64::MAX.into())));
+        assert_eq!(parser.next(), Some(Ok(i64::MIN.into())));
+        assert_eq!(parser.next(), Some(Ok(i64::MIN.into())));
+        assert_eq!(parser.next(), Some(Ok(i64::MAX.into())));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert_eq!(parser.next(), Some(Ok("a".into())));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
     #[test]
     fn arrays() {
         let code = r###"
@@ -314,10 +428,10 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok(22.into())));
         assert_eq!(parser.next(), Some(Ok(5.into())));
         assert_eq!(parser.next(), Some(Ok(20.into())));
-        assert_eq!(parser.next(), Some(Ok(80.into())));
-        assert_eq!(parser.next(), Some(Ok(0.into())));
-        assert_eq!(parser.next(), Some(Ok(0.into())));
-        assert_eq!(parser.next(), Some(Ok(2.into())));
+        assert_eq!(parser.next(), Some(Ok(5.into())));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert_eq!(parser.next(), Some(Ok(3.into())));
     }
     #[test]
     fn implicit_extend() {
@@ -379,7 +493,7 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok(12.into())));
         assert_eq!(
             parser.next(),
-            Some(Ok(NaslValue::Dict(HashMap::from([(
+            Some(Ok(NaslValue::Dict(NaslDict::from([(
                 "hi".to_owned(),
                 12.into()
             )]))))
@@ -401,4 +515,160 @@ mod tests {
             Some(Ok(NaslValue::Array(vec![1.into(), 2.into(), 3.into()])))
         );
     }
+
+    #[test]
+    fn array_read() {
+        let code = r###"
+        a = [1, 2, 3];
+        a[1];
+        a[3];
+        a[-1];
+        i = 0;
+        a[i++];
+        i;
+        d["k"] = "v";
+        d["k"];
+        d["missing"];
+        n[0];
+        x = 1;
+        x[0];
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(2.into())));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        parser.next();
+        assert_eq!(parser.next(), Some(Ok("v".into())));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        parser.next();
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn invalid_array_index() {
+        let code = r###"
+        a[-1] = 1;
+        a[1000000000] = 1;
+        b[3] = 1;
+        b[4] = 1;
+        b;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, register, &context);
+        parser.set_max_array_size(4);
+        let is_invalid_index = |r: Option<Result<NaslValue, InterpretError>>, expected: i64| match r
+        {
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::InvalidIndex { index, max: 4 },
+                ..
+            })) => index == expected,
+            _ => false,
+        };
+        assert!(is_invalid_index(parser.next(), -1));
+        assert!(is_invalid_index(parser.next(), 1000000000));
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert!(is_invalid_index(parser.next(), 4));
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Array(vec![
+                NaslValue::Null,
+                NaslValue::Null,
+                NaslValue::Null,
+                1.into()
+            ])))
+        );
+    }
+
+    #[test]
+    fn string_length_limit() {
+        let code = r###"
+        a = "aaaa";
+        a += "b";
+        a + "bb";
+        a += "bb";
+        "###;
+        let binding = ContextFactory::default().limits(Limits {
+            max_string_length: 6,
+            ..Default::default()
+        });
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok("aaaa".into())));
+        assert_eq!(parser.next(), Some(Ok("aaaab".into())));
+        assert!(matches!(
+            parser.next(),
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::TooLarge { size: 7, max: 6 },
+                ..
+            }))
+        ));
+        assert!(matches!(
+            parser.next(),
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::TooLarge { size: 7, max: 6 },
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn array_size_limit_of_context() {
+        let code = r###"
+        a[3] = 1;
+        a[4] = 1;
+        "###;
+        let binding = ContextFactory::default().limits(Limits {
+            max_array_size: 4,
+            ..Default::default()
+        });
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        assert!(matches!(
+            parser.next(),
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::InvalidIndex { index: 4, max: 4 },
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn array_size_limit_of_lists() {
+        let code = r###"
+        a = [1, 2, 3, 4];
+        a = [1, 2, 3, 4, 5];
+        a = make_list(1, 2, 3, 4);
+        a = make_list(a, 5);
+        "###;
+        let binding = ContextFactory::default().limits(Limits {
+            max_array_size: 4,
+            ..Default::default()
+        });
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        let too_large = |r: Option<Result<NaslValue, InterpretError>>| {
+            matches!(
+                r,
+                Some(Err(InterpretError {
+                    kind: InterpretErrorKind::TooLarge { size: 5, max: 4 },
+                    ..
+                }))
+            )
+        };
+        assert!(matches!(parser.next(), Some(Ok(NaslValue::Array(x))) if x.len() == 4));
+        assert!(too_large(parser.next()));
+        assert!(matches!(parser.next(), Some(Ok(NaslValue::Array(x))) if x.len() == 4));
+        assert!(too_large(parser.next()));
+    }
 }
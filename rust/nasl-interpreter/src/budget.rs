@@ -0,0 +1,88 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Defines builtin functions that query the budget of the interpreter
+
+use std::time::Instant;
+
+use nasl_builtin_utils::{Context, NaslFunctionExecuter, NaslResult, Register};
+use nasl_syntax::NaslValue;
+
+/// Returns the remaining milliseconds until the script exceeds its timeout
+///
+/// Returns 0 when the timeout is already exceeded and -1 when the script has no timeout.
+fn get_time_remaining(deadline: Option<Instant>) -> NaslResult {
+    Ok(match deadline {
+        Some(deadline) => {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            NaslValue::Number(remaining.as_millis().try_into().unwrap_or(i64::MAX))
+        }
+        None => NaslValue::Number(-1),
+    })
+}
+
+/// Builtin functions that depend on the budget of the calling interpreter
+///
+/// They are always available and looked up after the functions of the context.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Budget {
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    /// Creates the functions for an interpreter whose timeout ends at deadline
+    pub fn new(deadline: Option<Instant>) -> Self {
+        Self { deadline }
+    }
+}
+
+impl NaslFunctionExecuter for Budget {
+    fn nasl_fn_execute(
+        &self,
+        name: &str,
+        _register: &Register,
+        _context: &Context,
+    ) -> Option<NaslResult> {
+        match name {
+            "get_time_remaining" => Some(get_time_remaining(self.deadline)),
+            _ => None,
+        }
+    }
+
+    fn nasl_fn_defined(&self, name: &str) -> bool {
+        name == "get_time_remaining"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::*;
+
+    fn remaining(interpreter: &mut Interpreter) -> i64 {
+        match interpreter.run_all("get_time_remaining();").pop() {
+            Some(Ok(NaslValue::Number(x))) => x,
+            x => panic!("expected a number, got: {x:?}"),
+        }
+    }
+
+    #[test]
+    fn get_time_remaining() {
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(Register::default(), &context);
+        assert_eq!(remaining(&mut interpreter), -1);
+
+        interpreter.set_timeout(Some(Duration::from_secs(10)));
+        let first = remaining(&mut interpreter);
+        assert!(first > 9000 && first <= 10000, "{first}");
+        std::thread::sleep(Duration::from_millis(50));
+        let second = remaining(&mut interpreter);
+        assert!(second <= first - 50, "{first} {second}");
+
+        interpreter.set_timeout(None);
+        assert_eq!(remaining(&mut interpreter), -1);
+    }
+}
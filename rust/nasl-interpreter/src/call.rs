@@ -6,14 +6,42 @@ use nasl_builtin_utils::lookup_keys::FC_ANON_ARGS;
 use nasl_syntax::{Statement, StatementKind::*, Token};
 
 use crate::{
+    arithmetic::Arithmetic,
+    budget::Budget,
     error::{FunctionError, InterpretError},
-    interpreter::{InterpretResult, RunSpecific},
+    higher_order::HigherOrder,
+    interpreter::{check_size, InterpretResult, RunSpecific},
     Interpreter,
 };
 
-use nasl_builtin_utils::ContextType;
+use nasl_builtin_utils::{ContextType, FunctionErrorKind, NaslFunctionExecuter};
 use nasl_syntax::NaslValue;
-use std::collections::HashMap;
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+/// Returns the message of a caught panic
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(x) => *x,
+        Err(payload) => match payload.downcast_ref::<&str>() {
+            Some(x) => x.to_string(),
+            None => "unknown reason".to_owned(),
+        },
+    }
+}
+
+/// Returns true when the interpreter itself defines a builtin function called name
+///
+/// Those functions, e.g. `eval_arithmetic` or `array_map`, depend on the calling interpreter and
+/// are available in addition to the functions of the context.
+pub fn interpreter_fn_defined(name: &str) -> bool {
+    Arithmetic::default().nasl_fn_defined(name)
+        || Budget::default().nasl_fn_defined(name)
+        || HigherOrder.nasl_fn_defined(name)
+}
 
 /// Is a trait to handle function calls within nasl.
 pub(crate) trait CallExtension {
@@ -44,14 +72,45 @@ impl<'a> CallExtension for Interpreter<'a> {
             FC_ANON_ARGS.to_owned(),
             ContextType::Value(NaslValue::Array(position)),
         );
+        // named arguments a user defined function is called with, only needed for strict calls
+        let passed: Option<HashSet<String>> = if self.ctxconfigs.strict_calls() {
+            Some(named.keys().cloned().collect())
+        } else {
+            None
+        };
         self.register_mut().create_root_child(named);
-        let result = match self.ctxconfigs.nasl_fn_execute(name, self.register()) {
+        // A panicking builtin fails the call instead of the whole process. Builtins only get
+        // shared references; the scope of the call is dropped below as on any other error.
+        let builtin = catch_unwind(AssertUnwindSafe(|| {
+            self.ctxconfigs
+                .nasl_fn_execute(name, self.register())
+                .or_else(|| {
+                    Arithmetic::new(self.timeout).nasl_fn_execute(
+                        name,
+                        self.register(),
+                        self.ctxconfigs,
+                    )
+                })
+                .or_else(|| {
+                    Budget::new(self.timeout.map(|(_, deadline)| deadline)).nasl_fn_execute(
+                        name,
+                        self.register(),
+                        self.ctxconfigs,
+                    )
+                })
+        }))
+        .unwrap_or_else(|e| Some(Err(FunctionErrorKind::Panic(panic_message(e)))));
+        let result = match builtin {
             Some(r) => {
                 if let Ok(NaslValue::Fork(mut x)) = r {
                     Ok(if let Some(r) = x.pop() {
                         // this is a proposal for the case that the caller is immediately executing
                         // if not the position needs to be reset
-                        if self.index == 0 {
+                        //
+                        // Each instance, not just the root one, can fork so that multiple forks
+                        // result in each combination of values. While an instance is still
+                        // replaying to its own fork position it must not fork again.
+                        if self.skip_until_return().is_none() {
                             let position = self.position().current_init_statement();
                             for i in x {
                                 tracing::trace!(return_value=?i, return_position=?self.position(), interpreter_position=?position, "creating interpreter instance" );
@@ -64,7 +123,7 @@ impl<'a> CallExtension for Interpreter<'a> {
                         } else {
                             tracing::trace!(
                                 index = self.index,
-                                "we only allow expanding of executions (fork) on root instance"
+                                "we do not allow expanding of executions (fork) while replaying"
                             );
                         }
                         tracing::trace!(return_value=?r, "returning interpreter instance" );
@@ -74,15 +133,32 @@ impl<'a> CallExtension for Interpreter<'a> {
                     })
                 } else {
                     r.map_err(|x| FunctionError::new(name, x).into())
+                        .and_then(|x| check_size(x, self.max_array_size))
                 }
             }
+            None if HigherOrder.nasl_fn_defined(name) => self
+                .higher_order(name)
+                .unwrap_or_else(|| Err(InterpretError::not_found(name))),
             None => {
                 let found = self
                     .register()
                     .named(name)
                     .ok_or_else(|| InterpretError::not_found(name))?
                     .clone();
+                let missing = match (&found, passed) {
+                    (ContextType::Function(params, _), Some(passed)) => params
+                        .iter()
+                        .filter(|p| !passed.contains(*p))
+                        .cloned()
+                        .collect(),
+                    _ => vec![],
+                };
                 match found {
+                    ContextType::Function(..) if !missing.is_empty() => Err(FunctionError::new(
+                        name,
+                        FunctionErrorKind::MissingArguments(missing),
+                    )
+                    .into()),
                     ContextType::Function(params, stmt) => {
                         // prepare default values
                         for p in params {
@@ -95,7 +171,11 @@ impl<'a> CallExtension for Interpreter<'a> {
                                 Some(_) => {}
                             }
                         }
-                        match self.resolve(&stmt)? {
+                        // loops of the caller do not enclose the statements of the function
+                        let loops = std::mem::take(&mut self.loops);
+                        let result = self.resolve(&stmt);
+                        self.loops = loops;
+                        match result? {
                             NaslValue::Return(x) => Ok(*x),
                             a => Ok(a),
                         }
@@ -111,8 +191,58 @@ impl<'a> CallExtension for Interpreter<'a> {
 
 #[cfg(test)]
 mod tests {
+    use nasl_builtin_utils::NaslResult;
+
     use crate::*;
 
+    struct Panicking;
+
+    impl NaslFunctionExecuter for Panicking {
+        fn nasl_fn_execute(
+            &self,
+            name: &str,
+            register: &Register,
+            _context: &Context,
+        ) -> Option<NaslResult> {
+            match name {
+                "out_of_bounds" => Some(Ok(register.positional()[3].clone())),
+                _ => None,
+            }
+        }
+
+        fn nasl_fn_defined(&self, name: &str) -> bool {
+            name == "out_of_bounds"
+        }
+    }
+
+    #[test]
+    fn panicking_builtin_is_an_error() {
+        let code = r###"
+        a = 1;
+        out_of_bounds(a);
+        a + 1;
+        "###;
+        let mut binding = ContextFactory::default();
+        binding.functions.push_executer(Panicking);
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(1.into())));
+        match parser.next() {
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::FunctionCallError(FunctionError { function, kind }),
+                ..
+            })) => {
+                assert_eq!(function, "out_of_bounds");
+                assert!(
+                    matches!(&kind, FunctionErrorKind::Panic(x) if x.contains("out of bounds")),
+                    "{kind:?}"
+                );
+            }
+            x => panic!("expected a function error, got: {x:?}"),
+        }
+        assert_eq!(parser.next(), Some(Ok(2.into())));
+    }
+
     #[test]
     fn default_null_on_user_defined_functions() {
         let code = r###"
@@ -131,4 +261,135 @@ mod tests {
         assert_eq!(parser.next(), Some(Ok(3.into())));
         assert_eq!(parser.next(), Some(Ok(1.into())));
     }
+
+    #[test]
+    fn strict_calls_require_each_parameter() {
+        let code = r###"
+        function add(a, b) {
+            return a + b;
+        }
+        add(a: 1, b: 2);
+        add(a: 1);
+        add(1, 2);
+        "###;
+        let binding = ContextFactory::default();
+        let context = binding
+            .build(Default::default(), Default::default())
+            .with_strict_calls(true);
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(3.into())));
+        match parser.next() {
+            Some(Err(e)) => assert_eq!(e.kind.to_string(), "add: missing arguments: b"),
+            x => panic!("expected missing argument error, got {x:?}"),
+        }
+        // positional arguments are not bound to parameters
+        match parser.next() {
+            Some(Err(e)) => assert_eq!(e.kind.to_string(), "add: missing arguments: a, b"),
+            x => panic!("expected missing argument error, got {x:?}"),
+        }
+    }
+
+    #[test]
+    fn fork_runs_remaining_statements_per_value() {
+        let code = r###"
+        set_kb_item(name: "Ports/tcp", value: 80);
+        set_kb_item(name: "Ports/tcp", value: 443);
+        port = get_kb_item("Ports/tcp");
+        port + 1;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let parser = CodeInterpreter::new(code, register, &context);
+        let results = parser.collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                Ok(NaslValue::Null),
+                Ok(NaslValue::Null),
+                Ok(443.into()),
+                Ok(80.into()),
+                Ok(444.into()),
+                Ok(81.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn forked_instances_fork_again() {
+        let code = r###"
+        set_kb_item(name: "Ports/tcp", value: 80);
+        set_kb_item(name: "Ports/tcp", value: 443);
+        set_kb_item(name: "Host/ip", value: "a");
+        set_kb_item(name: "Host/ip", value: "b");
+        port = get_kb_item("Ports/tcp");
+        ip = get_kb_item("Host/ip");
+        ip + port;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let parser = CodeInterpreter::new(code, register, &context);
+        let mut results = parser
+            .skip(10)
+            .filter_map(|x| x.ok())
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        results.sort();
+        assert_eq!(results, vec!["a443", "a80", "b443", "b80"]);
+    }
+
+    #[test]
+    fn invalid_argument_names_argument_and_line() {
+        struct Ports;
+        impl NaslFunctionExecuter for Ports {
+            fn nasl_fn_execute(
+                &self,
+                name: &str,
+                register: &Register,
+                _: &Context,
+            ) -> Option<nasl_builtin_utils::NaslResult> {
+                match name {
+                    "open_sock_tcp" => Some(
+                        nasl_builtin_utils::get_named_argument::<u16>(register, "port")
+                            .map(|x| NaslValue::Number(x as i64)),
+                    ),
+                    _ => None,
+                }
+            }
+
+            fn nasl_fn_defined(&self, name: &str) -> bool {
+                name == "open_sock_tcp"
+            }
+        }
+        let code = r###"
+        open_sock_tcp(port: 22);
+        open_sock_tcp(port: "ssh");
+        "###;
+        let storage = storage::DefaultDispatcher::default();
+        let loader = nasl_syntax::NoOpLoader::default();
+        let logger = nasl_syntax::logger::DefaultLogger::default();
+        let context = Context::new(
+            storage::ContextKey::FileName("test".to_owned()),
+            "localhost".into(),
+            &storage,
+            &storage,
+            &loader,
+            &logger,
+            &Ports,
+        );
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(22.into())));
+        match parser.next() {
+            Some(Err(e)) => {
+                assert_eq!(e.line(), 3);
+                assert_eq!(
+                    e.to_string(),
+                    "argument `port` of open_sock_tcp at line 3: Expected Number but got ssh"
+                );
+            }
+            x => panic!("expected an invalid argument error, got {x:?}"),
+        }
+    }
 }
@@ -48,19 +48,17 @@ pub(crate) trait DeclareVariableExtension {
 
 impl<'a> DeclareVariableExtension for Interpreter<'a> {
     fn declare_variable(&mut self, scope: &Token, stmts: &[Statement]) -> InterpretResult {
+        let global = match scope.category() {
+            TokenCategory::Identifier(nasl_syntax::IdentifierType::GlobalVar) => true,
+            TokenCategory::Identifier(nasl_syntax::IdentifierType::LocalVar) => false,
+            cat => return Err(InterpretError::wrong_category(cat)),
+        };
         let mut add = |key: &str| {
             let value = ContextType::Value(NaslValue::Null);
-            match scope.category() {
-                TokenCategory::Identifier(nasl_syntax::IdentifierType::GlobalVar) => {
-                    self.register_mut().add_global(key, value)
-                }
-                TokenCategory::Identifier(nasl_syntax::IdentifierType::LocalVar) => {
-                    self.register_mut().add_local(key, value)
-                }
-                _ => unreachable!(
-                    "{} should not be identified as an declare statement",
-                    scope.category()
-                ),
+            if global {
+                self.register_mut().add_global(key, value)
+            } else {
+                self.register_mut().add_local(key, value)
             }
         };
 
@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
-use std::{fmt::Display, io};
+use std::{fmt::Display, io, time::Duration};
 
 use nasl_builtin_utils::error::FunctionErrorKind;
 use nasl_syntax::{Statement, SyntaxError, TokenCategory};
@@ -31,7 +31,12 @@ impl FunctionError {
 
 impl Display for FunctionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.function, self.kind)
+        match &self.kind {
+            FunctionErrorKind::InvalidArgument { name, reason } => {
+                write!(f, "argument `{name}` of {}: {reason}", self.function)
+            }
+            kind => write!(f, "{}: {kind}", self.function),
+        }
     }
 }
 
@@ -79,6 +84,26 @@ pub enum InterpretErrorKind {
     IOError(io::ErrorKind),
     /// An error occurred while calling a built-in function.
     FunctionCallError(FunctionError),
+    /// An array index is either negative or exceeds the maximum array size.
+    InvalidIndex {
+        /// The given index
+        index: i64,
+        /// The maximum amount of elements an array may have
+        max: usize,
+    },
+    /// A string or data exceeds the maximum length.
+    TooLarge {
+        /// The length of the value
+        size: usize,
+        /// The maximum length
+        max: usize,
+    },
+    /// A break or continue is not within a loop or within no loop with the given label.
+    NotInLoop(String),
+    /// The script ran longer than its timeout.
+    Timeout(Duration),
+    /// An arithmetic operation is not defined for the given numbers, e.g. a division by zero.
+    Arithmetic(String),
 }
 
 impl Display for InterpretErrorKind {
@@ -109,12 +134,45 @@ impl Display for InterpretErrorKind {
             InterpretErrorKind::FMTError(e) => write!(f, "{e}"),
             InterpretErrorKind::IOError(e) => write!(f, "{e}"),
             InterpretErrorKind::FunctionCallError(e) => write!(f, "{e}"),
+            InterpretErrorKind::InvalidIndex { index, max } => {
+                write!(
+                    f,
+                    "index {index} must be between 0 and {}",
+                    max.saturating_sub(1)
+                )
+            }
+            InterpretErrorKind::TooLarge { size, max } => {
+                write!(f, "size {size} exceeds the maximum of {max}")
+            }
+            InterpretErrorKind::NotInLoop(e) => write!(f, "{e} is not within a matching loop"),
+            InterpretErrorKind::Timeout(e) => {
+                write!(
+                    f,
+                    "script exceeded its timeout of {} seconds",
+                    e.as_secs_f64()
+                )
+            }
+            InterpretErrorKind::Arithmetic(e) => write!(f, "{e} is not defined"),
         }
     }
 }
 
 impl Display for InterpretError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the position of the call is more helpful than the statement for an invalid argument
+        if let InterpretErrorKind::FunctionCallError(FunctionError {
+            function,
+            kind: FunctionErrorKind::InvalidArgument { name, reason },
+        }) = &self.kind
+        {
+            if self.origin.is_some() {
+                return write!(
+                    f,
+                    "argument `{name}` of {function} at line {}: {reason}",
+                    self.line()
+                );
+            }
+        }
         write!(
             f,
             "{}{}",
@@ -214,6 +272,31 @@ impl InterpretError {
             None,
         )
     }
+    /// When an array index is negative or exceeds the maximum array size
+    pub fn invalid_index(index: i64, max: usize) -> Self {
+        Self::new(InterpretErrorKind::InvalidIndex { index, max }, None)
+    }
+
+    /// When a string or data exceeds the maximum length
+    pub fn too_large(size: usize, max: usize) -> Self {
+        Self::new(InterpretErrorKind::TooLarge { size, max }, None)
+    }
+
+    /// When a break or continue is used outside of a matching loop
+    pub fn not_in_loop(statement: &str) -> Self {
+        Self::new(InterpretErrorKind::NotInLoop(statement.to_owned()), None)
+    }
+
+    /// When the script ran longer than the given timeout
+    pub fn timeout(timeout: Duration) -> Self {
+        Self::new(InterpretErrorKind::Timeout(timeout), None)
+    }
+
+    /// When an arithmetic operation like a division by zero is not defined
+    pub fn arithmetic(expression: &str) -> Self {
+        Self::new(InterpretErrorKind::Arithmetic(expression.to_owned()), None)
+    }
+
     /// When a given regex is not parseable
     pub fn unparse_regex(rx: &str) -> Self {
         Self::new(InterpretErrorKind::InvalidRegex(rx.to_owned()), None)
@@ -278,6 +361,10 @@ impl From<FunctionError> for InterpretError {
             | FunctionErrorKind::Infallible(_)
             | FunctionErrorKind::WrongArgument(_)
             | FunctionErrorKind::Dirty(_)
+            | FunctionErrorKind::PermissionDenied(_)
+            | FunctionErrorKind::TooLarge { .. }
+            | FunctionErrorKind::Panic(_)
+            | FunctionErrorKind::InvalidArgument { .. }
             | FunctionErrorKind::Diagnostic(_, _) => {
                 Self::new(InterpretErrorKind::FunctionCallError(fe), None)
             }
@@ -1,12 +1,22 @@
 //! Contains implementations of Interpreter that handle the simulation of forking methods for the
 //! caller.
 
-use nasl_syntax::Statement;
+use std::time::Duration;
+
+use nasl_syntax::{NaslValue, Statement};
 
 use crate::interpreter::InterpretResult;
 
 /// To allow closures we use a heap stored statement consumer
 pub type StatementConsumer = Box<dyn Fn(&Statement)>;
+/// To allow closures we use a heap stored statement observer, it gets the scope depth as well
+pub type StatementObserver = Box<dyn Fn(&Statement, usize)>;
+/// To allow closures we use a heap stored breakpoint handler, it returns false to stop
+pub type BreakpointHandler = Box<dyn Fn(&Statement, &crate::Register) -> bool>;
+/// To allow closures we use a heap stored watch callback, it gets the old and the new value
+pub type WatchCallback = Box<dyn Fn(&NaslValue, &NaslValue)>;
+/// To allow closures we use a heap stored exit hook
+pub type ExitHook = Box<dyn Fn()>;
 /// Uses given code to return results based on that.
 pub struct CodeInterpreter<'a, 'b> {
     lexer: nasl_syntax::Lexer<'b>,
@@ -14,6 +24,8 @@ pub struct CodeInterpreter<'a, 'b> {
     statement: Option<Statement>,
     /// call back function for Statements before they get interpret
     pub statement_cb: Option<StatementConsumer>,
+    exit_hooks: Vec<ExitHook>,
+    finished: bool,
 }
 
 impl<'a, 'b> CodeInterpreter<'a, 'b> {
@@ -48,6 +60,8 @@ impl<'a, 'b> CodeInterpreter<'a, 'b> {
             interpreter,
             statement: None,
             statement_cb: None,
+            exit_hooks: vec![],
+            finished: false,
         }
     }
 
@@ -80,6 +94,34 @@ impl<'a, 'b> CodeInterpreter<'a, 'b> {
         result
     }
 
+    /// Registers a hook that is called once when the execution of the script ends
+    ///
+    /// The execution ends when all statements are executed by all forked runs or the
+    /// CodeInterpreter is dropped, e.g. because the caller stopped on exit or an error.
+    pub fn on_exit<F>(&mut self, hook: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.exit_hooks.push(Box::new(hook));
+    }
+
+    /// Finishes the execution
+    ///
+    /// Clears cached handles, like open sessions, of the function executor and calls the
+    /// registered exit hooks. It is only done once.
+    fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        if let Some(amount) = self.interpreter.ctxconfigs.executor().nasl_fn_cache_clear() {
+            tracing::trace!(amount, "cleared cached handles on exit");
+        }
+        for hook in &self.exit_hooks {
+            hook();
+        }
+    }
+
     fn next_statement(&mut self) -> Option<InterpretResult> {
         self.statement = None;
         match self.lexer.next() {
@@ -96,6 +138,86 @@ impl<'a, 'b> CodeInterpreter<'a, 'b> {
         }
     }
 
+    /// Sets the maximum amount of elements an array may grow to on assignment
+    ///
+    /// See `Interpreter::set_max_array_size`.
+    pub fn set_max_array_size(&mut self, max: usize) {
+        self.interpreter.set_max_array_size(max);
+    }
+
+    /// Sets how `==` and `!=` compare values of different types
+    ///
+    /// See `Interpreter::set_comparison`.
+    pub fn set_comparison(&mut self, comparison: crate::Comparison) {
+        self.interpreter.set_comparison(comparison);
+    }
+
+    /// Sets an observer that is called before each statement, including nested ones, is executed
+    ///
+    /// See `Interpreter::set_observer`.
+    pub fn set_observer<F>(&mut self, observer: F)
+    where
+        F: Fn(&Statement, usize) + 'static,
+    {
+        self.interpreter.set_observer(observer);
+    }
+
+    /// Adds a breakpoint on the given line, starting at 1
+    ///
+    /// See `Interpreter::add_breakpoint`.
+    pub fn add_breakpoint(&mut self, line: usize) {
+        self.interpreter.add_breakpoint(line);
+    }
+
+    /// Removes the breakpoint on the given line
+    pub fn remove_breakpoint(&mut self, line: usize) {
+        self.interpreter.remove_breakpoint(line);
+    }
+
+    /// Sets the handler that is called when a breakpoint is reached
+    ///
+    /// See `Interpreter::set_breakpoint_handler`.
+    pub fn set_breakpoint_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(&Statement, &crate::Register) -> bool + 'static,
+    {
+        self.interpreter.set_breakpoint_handler(handler);
+    }
+
+    /// Adds a callback that is called on each assignment to the variable with the given name
+    ///
+    /// See `Interpreter::add_watch`.
+    pub fn add_watch<F>(&mut self, name: &str, callback: F)
+    where
+        F: Fn(&NaslValue, &NaslValue) + 'static,
+    {
+        self.interpreter.add_watch(name, callback);
+    }
+
+    /// Sets the port the script runs for
+    ///
+    /// See `Interpreter::set_port`.
+    pub fn set_port(&mut self, port: Option<u16>) {
+        self.interpreter.set_port(port);
+    }
+
+    /// Returns the port the current run is executed for
+    pub fn port(&self) -> Option<u16> {
+        self.interpreter.port()
+    }
+
+    /// Sets the maximum wall-clock time the script may run, None for no limit
+    ///
+    /// See `Interpreter::set_timeout`.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.interpreter.set_timeout(timeout);
+    }
+
+    /// Returns the maximum wall-clock time the script may run
+    pub fn timeout(&self) -> Option<Duration> {
+        self.interpreter.timeout()
+    }
+
     /// Returns the Register of the underlying Interpreter
     pub fn register(&self) -> &crate::Register {
         self.interpreter.register()
@@ -106,23 +228,39 @@ impl<'a, 'b> Iterator for CodeInterpreter<'a, 'b> {
     type Item = InterpretResult;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(stmt) = self.statement.as_ref() {
+        let result = if let Some(stmt) = self.statement.as_ref() {
             match self.interpreter.next_interpreter() {
                 Some(inter) => Some(inter.retry_resolve(stmt, 5)),
                 None => self.next_statement(),
             }
         } else {
             self.next_statement()
+        };
+        // an exit only ends the current run, forked runs may still use the cached handles
+        if result.is_none() {
+            self.finish();
         }
+        result
+    }
+}
+
+impl<'a, 'b> Drop for CodeInterpreter<'a, 'b> {
+    fn drop(&mut self) {
+        self.finish();
     }
 }
 
 #[cfg(test)]
 mod rests {
+    use std::{cell::Cell, cell::RefCell, net::UdpSocket, rc::Rc};
+
+    use nasl_builtin_utils::{Context, NaslFunctionExecuter};
+    use nasl_syntax::NaslValue;
+
+    use crate::{CodeInterpreter, ContextFactory, Register};
+
     #[test]
     fn code_interpreter() {
-        use crate::{CodeInterpreter, ContextFactory, Register};
-        use nasl_syntax::NaslValue;
         let register = Register::default();
         let context_builder = ContextFactory::default();
         let context = context_builder.build(Default::default(), Default::default());
@@ -138,4 +276,117 @@ mod rests {
         let results = interpreter.filter_map(|x| x.ok()).collect::<Vec<_>>();
         assert_eq!(results, vec![NaslValue::Null; 4]);
     }
+
+    /// Opens UDP sockets on localhost and closes them when the cache is cleared
+    #[derive(Default, Clone)]
+    struct Sockets(Rc<RefCell<Vec<UdpSocket>>>);
+
+    impl NaslFunctionExecuter for Sockets {
+        fn nasl_fn_execute(
+            &self,
+            name: &str,
+            _: &Register,
+            _: &Context,
+        ) -> Option<nasl_builtin_utils::NaslResult> {
+            match name {
+                "open_sock_udp" => Some(
+                    UdpSocket::bind("127.0.0.1:0")
+                        .map(|x| self.0.borrow_mut().push(x))
+                        .map(|_| NaslValue::Number(self.0.borrow().len() as i64))
+                        .map_err(Into::into),
+                ),
+                _ => None,
+            }
+        }
+
+        fn nasl_fn_defined(&self, name: &str) -> bool {
+            name == "open_sock_udp"
+        }
+
+        fn nasl_fn_cache_clear(&self) -> Option<usize> {
+            let amount = self.0.borrow_mut().drain(..).count();
+            (amount > 0).then_some(amount)
+        }
+    }
+
+    type DefaultFactory = ContextFactory<
+        nasl_syntax::NoOpLoader,
+        nasl_syntax::logger::DefaultLogger,
+        storage::DefaultDispatcher,
+    >;
+
+    fn socket_factory() -> (Sockets, DefaultFactory) {
+        let sockets = Sockets::default();
+        let mut factory = ContextFactory::default();
+        factory.functions.push_executer(sockets.clone());
+        (sockets, factory)
+    }
+
+    #[test]
+    fn clear_handles_on_exit() {
+        let (sockets, factory) = socket_factory();
+        let context = factory.build(Default::default(), Default::default());
+        let code = r#"
+            open_sock_udp();
+            exit(0);
+            open_sock_udp();
+        "#;
+        let called = Rc::new(Cell::new(0));
+        let mut interpreter = CodeInterpreter::new(code, Register::default(), &context);
+        let hook_called = called.clone();
+        interpreter.on_exit(move || hook_called.set(hook_called.get() + 1));
+        assert_eq!(interpreter.next(), Some(Ok(NaslValue::Number(1))));
+        assert_eq!(interpreter.next(), Some(Ok(NaslValue::Exit(0))));
+        assert_eq!(sockets.0.borrow().len(), 1);
+        drop(interpreter);
+        assert!(sockets.0.borrow().is_empty());
+        assert_eq!(called.get(), 1);
+    }
+
+    #[test]
+    fn clear_handles_of_all_forks() {
+        let (sockets, factory) = socket_factory();
+        let context = factory.build(Default::default(), Default::default());
+        let code = r#"
+            set_kb_item(name: "Ports/udp", value: 1);
+            set_kb_item(name: "Ports/udp", value: 2);
+            port = get_kb_item("Ports/udp");
+            open_sock_udp();
+            exit(0);
+        "#;
+        let called = Rc::new(Cell::new(0));
+        let mut interpreter = CodeInterpreter::new(code, Register::default(), &context);
+        let hook_called = called.clone();
+        interpreter.on_exit(move || hook_called.set(hook_called.get() + 1));
+        let mut exits = 0;
+        for result in interpreter.by_ref() {
+            if let Ok(NaslValue::Exit(_)) = result {
+                exits += 1;
+                // the exit of one fork does not close the sockets of the others
+                assert_eq!(sockets.0.borrow().len(), 2);
+                assert_eq!(called.get(), 0);
+            }
+        }
+        assert_eq!(exits, 2);
+        assert!(sockets.0.borrow().is_empty());
+        assert_eq!(called.get(), 1);
+        drop(interpreter);
+        assert_eq!(called.get(), 1);
+    }
+
+    #[test]
+    fn clear_handles_on_drop() {
+        let (sockets, factory) = socket_factory();
+        let context = factory.build(Default::default(), Default::default());
+        let code = r#"
+            open_sock_udp();
+            a = b();
+        "#;
+        let mut interpreter = CodeInterpreter::new(code, Register::default(), &context);
+        assert!(interpreter.next().unwrap().is_ok());
+        assert!(interpreter.next().unwrap().is_err());
+        assert_eq!(sockets.0.borrow().len(), 1);
+        drop(interpreter);
+        assert!(sockets.0.borrow().is_empty());
+    }
 }
@@ -0,0 +1,421 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Defines builtin functions that call user defined functions
+
+use std::collections::HashMap;
+
+use nasl_builtin_utils::{lookup_keys::FC_ANON_ARGS, ContextType, FunctionErrorKind};
+use nasl_syntax::{NaslDict, NaslValue, Statement};
+
+use crate::{error::FunctionError, interpreter::InterpretResult, InterpretError, Interpreter};
+
+/// A user defined function passed by name to a higher order builtin
+struct Callback {
+    params: Vec<String>,
+    body: Statement,
+}
+
+impl<'a> Interpreter<'a> {
+    /// Returns the user defined function called name
+    fn callback(&self, name: &NaslValue) -> Result<Callback, FunctionErrorKind> {
+        let NaslValue::String(name) = name else {
+            return Err(("1", "the name of a function", name).into());
+        };
+        match self.register().named(name) {
+            Some(ContextType::Function(params, body)) => Ok(Callback {
+                params: params.clone(),
+                body: body.clone(),
+            }),
+            _ => Err(FunctionErrorKind::WrongArgument(format!(
+                "{name} is not a defined function"
+            ))),
+        }
+    }
+
+    /// Calls the user defined function with the given arguments
+    ///
+    /// The arguments are passed to the parameters of the function in the order of declaration
+    /// as well as positional arguments. The remaining parameters are NULL. The function runs on
+    /// this interpreter, so it shares its global variables, timeout and limits.
+    fn call_back(&mut self, callback: &Callback, args: &[&NaslValue]) -> InterpretResult {
+        let mut named: HashMap<String, ContextType> = callback
+            .params
+            .iter()
+            .map(|p| (p.to_owned(), NaslValue::Null.into()))
+            .collect();
+        for (param, arg) in callback.params.iter().zip(args) {
+            named.insert(param.to_owned(), (*arg).clone().into());
+        }
+        named.insert(
+            FC_ANON_ARGS.to_owned(),
+            NaslValue::Array(args.iter().map(|&x| x.clone()).collect()).into(),
+        );
+        self.register_mut().create_root_child(named);
+        // loops of the caller do not enclose the statements of the function
+        let loops = std::mem::take(&mut self.loops);
+        let result = self.resolve(&callback.body);
+        self.loops = loops;
+        self.register_mut().drop_last();
+        match result? {
+            NaslValue::Return(x) => Ok(*x),
+            x => Ok(x),
+        }
+    }
+
+    /// Executes the higher order builtin called name or returns None when there is none
+    ///
+    /// The arguments are taken from the current scope. Errors of the user defined function are
+    /// returned as they are, errors of the arguments as error of the builtin.
+    pub(crate) fn higher_order(&mut self, name: &str) -> Option<InterpretResult> {
+        let function: HigherOrderFn = lookup(name)?;
+        let args = self.register().positional().to_vec();
+        Some(function(self, &args).map_err(|e| match e {
+            HigherOrderError::Argument(kind) => FunctionError::new(name, kind).into(),
+            HigherOrderError::Callback(e) => *e,
+        }))
+    }
+}
+
+/// Is returned by a higher order builtin
+enum HigherOrderError {
+    /// The arguments passed to the builtin are invalid
+    Argument(FunctionErrorKind),
+    /// The user defined function failed
+    Callback(Box<InterpretError>),
+}
+
+impl From<FunctionErrorKind> for HigherOrderError {
+    fn from(e: FunctionErrorKind) -> Self {
+        Self::Argument(e)
+    }
+}
+
+impl From<InterpretError> for HigherOrderError {
+    fn from(e: InterpretError) -> Self {
+        Self::Callback(Box::new(e))
+    }
+}
+
+type HigherOrderFn = fn(&mut Interpreter, &[NaslValue]) -> Result<NaslValue, HigherOrderError>;
+
+/// Applies a user defined function to each element of an array.
+///
+/// Expects the array and the name of the function as positional arguments. Returns a new array
+/// containing the results; for arrays with keys the keys are kept.
+fn array_map(
+    interpreter: &mut Interpreter,
+    args: &[NaslValue],
+) -> Result<NaslValue, HigherOrderError> {
+    let (collection, name) = match args {
+        [collection, name] => (collection, name),
+        x => {
+            return Err(FunctionErrorKind::MissingPositionalArguments {
+                expected: 2,
+                got: x.len(),
+            }
+            .into())
+        }
+    };
+    let callback = interpreter.callback(name)?;
+    match collection {
+        NaslValue::Array(x) => {
+            let mut result = Vec::with_capacity(x.len());
+            for v in x {
+                result.push(interpreter.call_back(&callback, &[v])?);
+            }
+            Ok(NaslValue::Array(result))
+        }
+        NaslValue::Dict(x) => {
+            let mut result = NaslDict::with_capacity(x.len());
+            for (k, v) in x {
+                result.insert(k.to_owned(), interpreter.call_back(&callback, &[v])?);
+            }
+            Ok(NaslValue::Dict(result))
+        }
+        x => Err(FunctionErrorKind::from(("0", "array", x)).into()),
+    }
+}
+
+/// Combines the elements of an array into a single value using a user defined function.
+///
+/// Expects the array, the name of the function and the initial value as positional arguments.
+/// The function is called with the accumulated value and the element and its result is used as
+/// the accumulated value for the next element. Returns the initial value for an empty array.
+/// Arrays with keys are combined in the order the keys were inserted.
+fn array_reduce(
+    interpreter: &mut Interpreter,
+    args: &[NaslValue],
+) -> Result<NaslValue, HigherOrderError> {
+    let (collection, name, initial) = match args {
+        [collection, name, initial] => (collection, name, initial),
+        x => {
+            return Err(FunctionErrorKind::MissingPositionalArguments {
+                expected: 3,
+                got: x.len(),
+            }
+            .into())
+        }
+    };
+    let callback = interpreter.callback(name)?;
+    let elements: Vec<&NaslValue> = match collection {
+        NaslValue::Array(x) => x.iter().collect(),
+        NaslValue::Dict(x) => x.values().collect(),
+        x => return Err(FunctionErrorKind::from(("0", "array", x)).into()),
+    };
+    let mut acc = initial.clone();
+    for x in elements {
+        acc = interpreter.call_back(&callback, &[&acc, x])?;
+    }
+    Ok(acc)
+}
+
+/// Selects the elements of an array for which a user defined function returns TRUE.
+///
+/// Expects the array and the name of the function as positional arguments. Returns a new array
+/// containing the selected elements; for arrays with keys the keys are kept.
+fn array_filter(
+    interpreter: &mut Interpreter,
+    args: &[NaslValue],
+) -> Result<NaslValue, HigherOrderError> {
+    let (collection, name) = match args {
+        [collection, name] => (collection, name),
+        x => {
+            return Err(FunctionErrorKind::MissingPositionalArguments {
+                expected: 2,
+                got: x.len(),
+            }
+            .into())
+        }
+    };
+    let callback = interpreter.callback(name)?;
+    let mut keep = |x: &NaslValue| interpreter.call_back(&callback, &[x]).map(bool::from);
+    match collection {
+        NaslValue::Array(x) => {
+            let mut result = Vec::with_capacity(x.len());
+            for v in x {
+                if keep(v)? {
+                    result.push(v.clone());
+                }
+            }
+            Ok(NaslValue::Array(result))
+        }
+        NaslValue::Dict(x) => {
+            let mut result = NaslDict::new();
+            for (k, v) in x {
+                if keep(v)? {
+                    result.insert(k.to_owned(), v.clone());
+                }
+            }
+            Ok(NaslValue::Dict(result))
+        }
+        x => Err(FunctionErrorKind::from(("0", "array", x)).into()),
+    }
+}
+
+/// Returns found function for key or None when not found
+fn lookup(key: &str) -> Option<HigherOrderFn> {
+    match key {
+        "array_map" => Some(array_map),
+        "array_reduce" => Some(array_reduce),
+        "array_filter" => Some(array_filter),
+        _ => None,
+    }
+}
+
+/// Builtin functions that call user defined functions and therefore depend on the interpreter
+///
+/// They are always available and executed by the calling interpreter, so that the user defined
+/// functions share its variables, timeout and limits.
+pub struct HigherOrder;
+
+impl HigherOrder {
+    /// Returns true when name is a higher order builtin
+    pub fn nasl_fn_defined(&self, name: &str) -> bool {
+        lookup(name).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nasl_syntax::NaslDict;
+
+    use crate::*;
+
+    #[test]
+    fn array_map() {
+        let code = r###"
+        function double(x) {
+            return x * 2;
+        }
+        function add(x, y) {
+            if (isnull(y)) return x + 1;
+            return x + y;
+        }
+        array_map(make_list(1, 2, 3), "double");
+        array_map(make_array("a", 1, "b", 2), "double");
+        array_map(make_list(1, 2), "add");
+        array_map(make_list(), "double");
+        array_map(make_list(1), "undefined");
+        array_map(1, "double");
+        array_map(make_list(1));
+        "###;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Array(vec![2.into(), 4.into(), 6.into()])))
+        );
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Dict(NaslDict::from([
+                ("a".to_owned(), 2.into()),
+                ("b".to_owned(), 4.into())
+            ]))))
+        );
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Array(vec![2.into(), 3.into()])))
+        );
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Array(vec![]))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn callbacks_run_on_the_calling_interpreter() {
+        let code = r###"
+        count = 0;
+        function counted(x) {
+            count++;
+            return x;
+        }
+        function equals_one(x) {
+            return x == "1";
+        }
+        function remaining(x) {
+            return get_time_remaining();
+        }
+        array_map(make_list(1, 2, 3), "counted");
+        count;
+        array_filter(make_list(1, "1"), "equals_one");
+        array_map(make_list(1), "remaining");
+        "###;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(Register::default(), &context);
+        interpreter.set_comparison(Comparison::Strict);
+        interpreter.set_timeout(Some(std::time::Duration::from_secs(60)));
+        let results = interpreter.run_all(code);
+        assert_eq!(results[5], Ok(3.into()));
+        assert_eq!(results[6], Ok(NaslValue::Array(vec!["1".into()])));
+        match &results[7] {
+            Ok(NaslValue::Array(x)) => {
+                assert!(matches!(x[..], [NaslValue::Number(x)] if x > 0), "{x:?}")
+            }
+            x => panic!("expected an array, got: {x:?}"),
+        }
+    }
+
+    #[test]
+    fn callbacks_keep_errors_and_timeout() {
+        let code = r###"
+        function endless(x) {
+            while (TRUE) usleep(10000);
+        }
+        array_map(make_list(1), "endless");
+        "###;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(Register::default(), &context);
+        interpreter.set_timeout(Some(std::time::Duration::from_millis(100)));
+        let results = interpreter.run_all(code);
+        assert!(
+            matches!(
+                results.last(),
+                Some(Err(InterpretError {
+                    kind: InterpretErrorKind::Timeout(_),
+                    ..
+                }))
+            ),
+            "{results:?}"
+        );
+    }
+
+    #[test]
+    fn array_reduce() {
+        let code = r###"
+        function sum(acc, x) {
+            return acc + x;
+        }
+        function concat(acc, x) {
+            return acc + x + ";";
+        }
+        array_reduce(make_list(1, 2, 3, 4), "sum", 0);
+        array_reduce(make_list("a", "b", "c"), "concat", "");
+        array_reduce(make_array("b", 2, "a", 1), "concat", "");
+        array_reduce(make_list(5), "sum", 10);
+        array_reduce(make_list(), "sum", 42);
+        array_reduce(make_list(1), "undefined", 0);
+        array_reduce(make_list(1), "sum");
+        "###;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(10.into())));
+        assert_eq!(parser.next(), Some(Ok("a;b;c;".into())));
+        assert_eq!(parser.next(), Some(Ok("2;1;".into())));
+        assert_eq!(parser.next(), Some(Ok(15.into())));
+        assert_eq!(parser.next(), Some(Ok(42.into())));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn array_filter() {
+        let code = r###"
+        function even(x) {
+            return x % 2 == 0;
+        }
+        function not_empty(x) {
+            return x;
+        }
+        array_filter(make_list(1, 2, 3, 4), "even");
+        array_filter(make_list("a", "", "b", ""), "not_empty");
+        array_filter(make_array("a", 1, "b", 2, "c", 4), "even");
+        array_filter(make_list(), "even");
+        array_filter(make_list(1), "undefined");
+        array_filter(1, "even");
+        "###;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut parser = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Null)));
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Array(vec![2.into(), 4.into()])))
+        );
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Array(vec!["a".into(), "b".into()])))
+        );
+        assert_eq!(
+            parser.next(),
+            Some(Ok(NaslValue::Dict(NaslDict::from([
+                ("b".to_owned(), 2.into()),
+                ("c".to_owned(), 4.into())
+            ]))))
+        );
+        assert_eq!(parser.next(), Some(Ok(NaslValue::Array(vec![]))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+}
@@ -6,6 +6,8 @@
 mod tests {
     use std::collections::HashMap;
 
+    use nasl_syntax::NaslDict;
+
     use crate::*;
 
     struct FakeInclude {
@@ -24,6 +26,21 @@ mod tests {
         }
     }
 
+    fn factory(
+        loader: FakeInclude,
+    ) -> ContextFactory<FakeInclude, logger::DefaultLogger, storage::DefaultDispatcher> {
+        ContextFactory {
+            loader,
+            logger: logger::DefaultLogger::default(),
+            functions: nasl_std_functions(),
+            storage: storage::DefaultDispatcher::default(),
+            seed: None,
+            limits: Default::default(),
+            capabilities: Default::default(),
+            preferences: Default::default(),
+        }
+    }
+
     #[test]
     fn function_variable() {
         let example = r#"
@@ -42,22 +59,58 @@ mod tests {
         test();
         "#;
         let register = Register::default();
-        let context = ContextFactory {
-            loader,
-            logger: logger::DefaultLogger::default(),
-            functions: nasl_std_functions(),
-            storage: storage::DefaultDispatcher::default(),
-        };
+        let context = factory(loader);
         let ctx = context.build(Default::default(), Default::default());
         let mut interpreter = CodeInterpreter::new(code, register, &ctx);
         assert_eq!(interpreter.next(), Some(Ok(NaslValue::Null)));
         assert_eq!(interpreter.next(), Some(Ok(12.into())));
         assert_eq!(
             interpreter.next(),
-            Some(Ok(NaslValue::Dict(HashMap::from([(
+            Some(Ok(NaslValue::Dict(NaslDict::from([(
                 "hello".to_owned(),
                 NaslValue::Data("world".as_bytes().into())
             )]))))
         );
     }
+
+    #[test]
+    fn keeps_settings_of_including_script() {
+        use std::{
+            sync::{Arc, Mutex},
+            time::Duration,
+        };
+
+        let plugins = HashMap::from([
+            ("set.inc".to_string(), "a = 1; b[2] = 1;".to_string()),
+            (
+                "loop.inc".to_string(),
+                "while (TRUE) usleep(1000);".to_string(),
+            ),
+        ]);
+        let context = factory(FakeInclude { plugins });
+        let ctx = context.build(Default::default(), Default::default());
+        let code = r#"
+        include("set.inc");
+        include("loop.inc");
+        "#;
+        let mut interpreter = CodeInterpreter::new(code, Register::default(), &ctx);
+        let writes = Arc::new(Mutex::new(vec![]));
+        let observed = writes.clone();
+        interpreter.add_watch("a", move |_, new| {
+            observed.lock().unwrap().push(new.clone())
+        });
+        interpreter.set_max_array_size(2);
+        interpreter.set_timeout(Some(Duration::from_millis(10)));
+        let kind =
+            |x: Option<Result<NaslValue, InterpretError>>| x.and_then(|x| x.err()).map(|e| e.kind);
+        assert_eq!(
+            kind(interpreter.next()),
+            Some(InterpretErrorKind::InvalidIndex { index: 2, max: 2 })
+        );
+        assert_eq!(*writes.lock().unwrap(), vec![NaslValue::Number(1)]);
+        assert_eq!(
+            kind(interpreter.next()),
+            Some(InterpretErrorKind::Timeout(Duration::from_millis(10)))
+        );
+    }
 }
@@ -2,7 +2,12 @@
 //
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
-use std::{collections::HashMap, io};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    ops::Range,
+    time::{Duration, Instant},
+};
 
 use nasl_syntax::{
     IdentifierType, LoadError, NaslValue, Statement, StatementKind::*, Token, TokenCategory,
@@ -13,8 +18,9 @@ use crate::{
     assign::AssignExtension,
     call::CallExtension,
     declare::{DeclareFunctionExtension, DeclareVariableExtension},
+    fork_interpreter::{BreakpointHandler, StatementObserver, WatchCallback},
     loop_extension::LoopExtension,
-    operator::OperatorExtension,
+    operator::{Comparison, OperatorExtension},
     InterpretError, InterpretErrorKind,
 };
 
@@ -50,6 +56,21 @@ impl Position {
     fn root_index(&self) -> usize {
         *self.index.first().unwrap_or(&0)
     }
+
+    fn depth(&self) -> usize {
+        self.index.len()
+    }
+}
+
+/// Lines to stop at and the handler that is called when one is reached
+#[derive(Default)]
+pub(crate) struct Breakpoints {
+    lines: HashSet<usize>,
+    handler: Option<BreakpointHandler>,
+    /// Range and position depth of the statement that triggered the last breakpoint
+    ///
+    /// It is used to not trigger again for statements nested within it.
+    triggered: Option<(Range<usize>, usize)>,
 }
 
 /// Contains data that is specific for a single run
@@ -92,11 +113,55 @@ impl<'a, 'b> Iterator for StatementIter<'a, 'b> {
     }
 }
 
+/// Returns an error when a string or data exceeds the given maximum length
+pub(crate) fn check_length(value: NaslValue, max: usize) -> InterpretResult {
+    let length = match &value {
+        NaslValue::String(x) => x.len(),
+        NaslValue::Data(x) => x.len(),
+        _ => return Ok(value),
+    };
+    if length > max {
+        Err(InterpretError::too_large(length, max))
+    } else {
+        Ok(value)
+    }
+}
+
+/// Returns an error when an array exceeds the given maximum size
+pub(crate) fn check_size(value: NaslValue, max: usize) -> InterpretResult {
+    match &value {
+        NaslValue::Array(x) if x.len() > max => Err(InterpretError::too_large(x.len(), max)),
+        _ => Ok(value),
+    }
+}
+
+/// Returns the `timeout` preference of the context when it is a positive amount of seconds
+///
+/// The preference has the id 0 and is set via `script_timeout`.
+fn preferred_timeout(ctxconfigs: &Context) -> Option<Duration> {
+    ctxconfigs
+        .preferences()
+        .iter()
+        .find(|x| x.id() == Some(0))
+        .and_then(|x| x.default().trim().parse::<u64>().ok())
+        .filter(|x| *x > 0)
+        .map(Duration::from_secs)
+}
+
 /// Used to interpret a Statement
 pub struct Interpreter<'a> {
     pub(crate) run_specific: Vec<RunSpecific>,
     pub(crate) ctxconfigs: &'a Context<'a>,
     pub(crate) index: usize,
+    pub(crate) max_array_size: usize,
+    pub(crate) comparison: Comparison,
+    pub(crate) observer: Option<StatementObserver>,
+    pub(crate) breakpoints: Breakpoints,
+    pub(crate) watches: HashMap<String, Vec<WatchCallback>>,
+    /// Labels of the loops enclosing the currently resolved statement, None for loops without label
+    pub(crate) loops: Vec<Option<String>>,
+    /// Maximum wall-clock time of a run and the instant it ends, None for no limit
+    pub(crate) timeout: Option<(Duration, Instant)>,
 }
 
 /// Interpreter always returns a NaslValue or an InterpretError
@@ -112,13 +177,169 @@ impl<'a> Interpreter<'a> {
             position: Position::new(0),
             skip_until_return: None,
         };
-        Interpreter {
+        let mut result = Interpreter {
             run_specific: vec![root_run],
             ctxconfigs,
             index: 0,
+            max_array_size: ctxconfigs.limits().max_array_size,
+            comparison: Comparison::default(),
+            observer: None,
+            breakpoints: Breakpoints::default(),
+            watches: HashMap::new(),
+            loops: vec![],
+            timeout: None,
+        };
+        result.set_timeout(preferred_timeout(ctxconfigs));
+        result
+    }
+
+    /// Sets the maximum wall-clock time the script may run, None for no limit
+    ///
+    /// The time is measured from this call on and checked before each statement, so a single
+    /// long running builtin call is not interrupted. When it is exceeded the cached handles, like
+    /// open sockets, are cleared and each further statement returns a timeout error.
+    ///
+    /// Defaults to the `timeout` preference of the context.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout.map(|x| (x, Instant::now() + x));
+    }
+
+    /// Returns the maximum wall-clock time the script may run
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.map(|(x, _)| x)
+    }
+
+    /// Sets the maximum amount of elements an array may grow to on assignment
+    ///
+    /// Assigning to an index that is negative or not smaller than the maximum returns an
+    /// error instead of allocating the array. Defaults to the limit of the context.
+    pub fn set_max_array_size(&mut self, max: usize) {
+        self.max_array_size = max;
+    }
+
+    /// Sets how `==` and `!=` compare values of different types
+    ///
+    /// Defaults to `Comparison::Loose`, which converts the values like legacy NASL does.
+    pub fn set_comparison(&mut self, comparison: Comparison) {
+        self.comparison = comparison;
+    }
+
+    /// Sets an observer that is called before each statement is executed
+    ///
+    /// In difference to the statement callback of the `CodeInterpreter` it is also called for
+    /// nested statements, like the body of a loop or a function. Besides the statement, whose
+    /// span is available via `Statement::range`, it gets the scope depth, which is 0 on the top
+    /// level of a script. This allows e.g. a debugger to stop on breakpoints by line.
+    pub fn set_observer<F>(&mut self, observer: F)
+    where
+        F: Fn(&Statement, usize) + 'static,
+    {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Adds a breakpoint on the given line, starting at 1
+    ///
+    /// When a statement starting on that line is about to be executed the breakpoint handler is
+    /// called once; statements nested within it on the same line do not trigger again.
+    pub fn add_breakpoint(&mut self, line: usize) {
+        self.breakpoints.lines.insert(line);
+    }
+
+    /// Removes the breakpoint on the given line
+    pub fn remove_breakpoint(&mut self, line: usize) {
+        self.breakpoints.lines.remove(&line);
+    }
+
+    /// Sets the handler that is called when a breakpoint is reached
+    ///
+    /// It gets the statement and the register to inspect the current variables. When it returns
+    /// false the execution stops as if the script called `exit(0)`.
+    pub fn set_breakpoint_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(&Statement, &Register) -> bool + 'static,
+    {
+        self.breakpoints.handler = Some(Box::new(handler));
+    }
+
+    /// Adds a callback that is called on each assignment to the variable with the given name
+    ///
+    /// It gets the old and the new value; the old value is Null when the variable was not set
+    /// before. As the variable is watched by name, assignments within any scope are reported.
+    pub fn add_watch<F>(&mut self, name: &str, callback: F)
+    where
+        F: Fn(&NaslValue, &NaslValue) + 'static,
+    {
+        self.watches
+            .entry(name.to_owned())
+            .or_default()
+            .push(Box::new(callback));
+    }
+
+    /// Sets the port the script runs for
+    ///
+    /// It is returned by the builtin function `get_port`. The port is set for each run, forks
+    /// created afterwards inherit the port of the run they are created from.
+    pub fn set_port(&mut self, port: Option<u16>) {
+        for run in self.run_specific.iter_mut() {
+            run.register.set_port(port);
+        }
+    }
+
+    /// Returns the port the current run is executed for
+    pub fn port(&self) -> Option<u16> {
+        self.register().port()
+    }
+
+    /// Calls the breakpoint handler when the statement starts on a breakpoint line
+    ///
+    /// Returns false when the execution should stop.
+    fn check_breakpoint(&mut self, statement: &Statement) -> bool {
+        if !self
+            .breakpoints
+            .lines
+            .contains(&statement.start().line_column.0)
+        {
+            return true;
+        }
+        let range = statement.range();
+        let depth = self.position().depth();
+        if let Some((triggered, triggered_depth)) = &self.breakpoints.triggered {
+            if depth > *triggered_depth
+                && triggered.start <= range.start
+                && range.end <= triggered.end
+            {
+                return true;
+            }
+        }
+        self.breakpoints.triggered = Some((range, depth));
+        match &self.breakpoints.handler {
+            Some(handler) => handler(statement, self.register()),
+            None => true,
         }
     }
 
+    /// Resets the interpreter to be reused for another script
+    ///
+    /// All forked runs are dropped and the root run starts with a fresh register based on
+    /// `initial` and without a port. Cached handles of the previous context, like open sessions, are cleared before
+    /// the interpreter is bound to the given context, which identifies the next script. The
+    /// timeout restarts with the `timeout` preference and the maximum array size with the limits
+    /// of that context.
+    pub fn reset(&mut self, ctxconfigs: &'a Context<'a>, initial: &[(String, ContextType)]) {
+        self.ctxconfigs.executor().nasl_fn_cache_clear();
+        self.run_specific.truncate(1);
+        let root = &mut self.run_specific[0];
+        root.register = Register::root_initial(initial);
+        root.position = Position::new(0);
+        root.skip_until_return = None;
+        self.ctxconfigs = ctxconfigs;
+        self.index = 0;
+        self.max_array_size = ctxconfigs.limits().max_array_size;
+        self.loops.clear();
+        self.breakpoints.triggered = None;
+        self.set_timeout(preferred_timeout(ctxconfigs));
+    }
+
     pub(crate) fn identifier(token: &Token) -> Result<String, InterpretError> {
         match token.category() {
             TokenCategory::Identifier(IdentifierType::Undefined(x)) => Ok(x.to_owned()),
@@ -137,6 +358,26 @@ impl<'a> Interpreter<'a> {
         }
     }
 
+    /// Parses and resolves each top-level statement of the given code
+    ///
+    /// Returns the result of every statement in order; a syntax error is returned as the result
+    /// of the statement it occurs in. It stops after a statement returned `Exit`. Forked runs
+    /// are not followed, for scripts that fork use the CodeInterpreter instead.
+    pub fn run_all(&mut self, code: &str) -> Vec<InterpretResult> {
+        let mut results = Vec::new();
+        for stmt in nasl_syntax::parse(code) {
+            let result = stmt
+                .map_err(InterpretError::from)
+                .and_then(|stmt| self.resolve(&stmt));
+            let exit = matches!(result, Ok(NaslValue::Exit(_)));
+            results.push(result);
+            if exit {
+                break;
+            }
+        }
+        results
+    }
+
     /// May return the next interpreter to run against that statement
     ///
     /// When the interpreter are done a None will be returned. Afterwards it will begin at at 0
@@ -171,12 +412,21 @@ impl<'a> Interpreter<'a> {
                 let code = self.ctxconfigs.loader().load(&key)?;
 
                 let mut inter = Interpreter::new(self.register().clone(), self.ctxconfigs);
+                inter.comparison = self.comparison;
+                inter.max_array_size = self.max_array_size;
+                // the included script runs within the deadline of the current one and is seen by
+                // the same observer and watches
+                inter.timeout = self.timeout;
+                inter.observer = self.observer.take();
+                inter.watches = std::mem::take(&mut self.watches);
                 let result = nasl_syntax::parse(&code)
                     .map(|parsed| match parsed {
                         Ok(stmt) => inter.resolve(&stmt),
                         Err(err) => Err(InterpretError::include_syntax_error(&key, err)),
                     })
                     .find(|e| e.is_err());
+                self.observer = inter.observer.take();
+                self.watches = std::mem::take(&mut inter.watches);
                 match result {
                     Some(e) => e,
                     None => {
@@ -238,8 +488,11 @@ impl<'a> Interpreter<'a> {
         }
     }
 
-    /// Interprets a Statement
-    pub(crate) fn resolve(&mut self, statement: &Statement) -> InterpretResult {
+    /// Prepares the interpretation of a statement
+    ///
+    /// It increases the position and returns the result early when the statement is skipped on a
+    /// fork run or stopped by a breakpoint handler; in that case the position is already reset.
+    fn enter(&mut self, statement: &Statement) -> Option<InterpretResult> {
         self.position_mut().up();
         tracing::trace!(position=?self.position(), statement=statement.to_string(), "executing");
         // On a fork statement run we skip until the root index is reached. Between the root index
@@ -250,43 +503,109 @@ impl<'a> Interpreter<'a> {
             if self.position().root_index() < cp.root_index() {
                 tracing::trace!("skip execution");
                 self.position_mut().down();
-                return Ok(NaslValue::Null);
+                return Some(Ok(NaslValue::Null));
             }
             if cp == self.position() {
                 tracing::trace!(return=?rv, "skip execution and returning");
                 let rv = rv.clone();
                 self.set_skip_until_return(None);
                 self.position_mut().down();
-                return Ok(rv);
+                return Some(Ok(rv));
+            }
+        }
+
+        if let Some((timeout, end)) = self.timeout {
+            if Instant::now() >= end {
+                tracing::trace!(?timeout, "stopped by timeout");
+                self.ctxconfigs.executor().nasl_fn_cache_clear();
+                self.position_mut().down();
+                return Some(Err(InterpretError::timeout(timeout)));
+            }
+        }
+        if let Some(observer) = &self.observer {
+            observer(statement, self.register().index().saturating_sub(1));
+        }
+        if !self.check_breakpoint(statement) {
+            tracing::trace!("stopped by breakpoint handler");
+            self.position_mut().down();
+            return Some(Ok(NaslValue::Exit(0)));
+        }
+        None
+    }
+
+    /// Interprets an if statement
+    ///
+    /// An else if chain is interpreted within a loop instead of resolving each nested if, so that
+    /// the recursion depth does not grow with the length of the chain. The nested ifs are still
+    /// entered to keep the positions, observer calls and breakpoints of the recursive form.
+    fn resolve_if(
+        &mut self,
+        condition: &Statement,
+        if_block: &Statement,
+        else_block: Option<&Statement>,
+    ) -> InterpretResult {
+        let (mut condition, mut if_block, mut else_block) = (condition, if_block, else_block);
+        let mut entered = 0;
+        let result = loop {
+            match self.resolve(condition).map(bool::from) {
+                Ok(true) => break self.resolve(if_block),
+                Ok(false) => {}
+                Err(err) => break Err(err),
             }
+            let Some(stmt) = else_block else {
+                break Ok(NaslValue::Null);
+            };
+            let If(x) = stmt.kind() else {
+                break self.resolve(stmt);
+            };
+            if let Some(result) = self.enter(stmt) {
+                break result;
+            }
+            entered += 1;
+            (condition, if_block, else_block) = (&x.condition, &x.then, x.otherwise.as_ref());
+        };
+        for _ in 0..entered {
+            self.position_mut().down();
+        }
+        result
+    }
+
+    /// Interprets a Statement
+    pub(crate) fn resolve(&mut self, statement: &Statement) -> InterpretResult {
+        if let Some(result) = self.enter(statement) {
+            return result;
         }
 
         let results = {
             match statement.kind(){
             Array(position) => {
                 let name = Self::identifier(statement.start())?;
+                // Like the legacy engine the index is resolved before the lookup. This
+                // allows to borrow the array and to clone just the element.
+                let index = match position {
+                    Some(p) => Some((p, self.resolve(p)?)),
+                    None => None,
+                };
                 let val = self
                     .register()
                     .named(&name)
                     .unwrap_or(&ContextType::Value(NaslValue::Null));
-                let val = val.clone();
-
-                match (position, val) {
-                    (None, ContextType::Value(v)) => Ok(v),
-                    (Some(p), ContextType::Value(NaslValue::Array(x))) => {
-                        let p: &Statement = p;
-                        let position = self.resolve(p)?;
-                        let position = i64::from(&position) as usize;
-                        let result = x.get(position).unwrap_or(&NaslValue::Null);
+
+                match (index, val) {
+                    (None, ContextType::Value(v)) => Ok(v.clone()),
+                    (Some((_, position)), ContextType::Value(NaslValue::Array(x))) => {
+                        let result = usize::try_from(i64::from(&position))
+                            .ok()
+                            .and_then(|position| x.get(position))
+                            .unwrap_or(&NaslValue::Null);
                         Ok(result.clone())
                     }
-                    (Some(p), ContextType::Value(NaslValue::Dict(x))) => {
-                        let position = self.resolve(p)?.to_string();
-                        let result = x.get(&position).unwrap_or(&NaslValue::Null);
+                    (Some((_, position)), ContextType::Value(NaslValue::Dict(x))) => {
+                        let result = x.get(&position.to_string()).unwrap_or(&NaslValue::Null);
                         Ok(result.clone())
                     }
                     (Some(_), ContextType::Value(NaslValue::Null)) => Ok(NaslValue::Null),
-                    (Some(p), _) => Err(InterpretError::unsupported(p, "array")),
+                    (Some((p, _)), _) => Err(InterpretError::unsupported(p, "array")),
                     (None, ContextType::Function(_, _)) => {
                         Err(InterpretError::unsupported(statement, "variable"))
                     }
@@ -305,15 +624,17 @@ impl<'a> Interpreter<'a> {
             }
             Include(inc ) => self.include(inc),
             NamedParameter(..) => {
-                unreachable!("named parameter should not be an executable statement.")
+                // named parameter should not be an executable statement.
+                Err(InterpretError::unsupported(statement, "executable statement"))
             }
-            For(assignment, condition, update, body) => {
-                self.for_loop(assignment, condition, update, body)
+            For(l) => {
+                self.enclose_loop(None, |x| x.for_loop(&l.assignment, &l.condition, &l.update, &l.body, None))
             }
-            While(condition, body) => self.while_loop(condition, body),
-            Repeat(body, condition) => self.repeat_loop(body, condition),
-            ForEach(variable, iterable, body) => self.for_each_loop(variable, iterable, body),
-            FunctionDeclaration(name, args, exec) => self.declare_function(name, args.children(), exec),
+            While(condition, body) => self.enclose_loop(None, |x| x.while_loop(condition, body, None)),
+            Repeat(body, condition) => self.enclose_loop(None, |x| x.repeat_loop(body, condition, None)),
+            ForEach(l) => self.enclose_loop(None, |x| x.for_each_loop(&l.variable, &l.iterable, &l.body, None)),
+            Labeled(l) => self.labeled_loop(&l.label, &l.statement),
+            FunctionDeclaration(f) => self.declare_function(&f.name, f.parameters.children(), &f.body),
             Primitive => TryFrom::try_from(statement.as_token()).map_err(|e: TokenCategory| e.into()),
             Variable => {
                 let name: NaslValue = TryFrom::try_from(statement.as_token())?;
@@ -329,6 +650,9 @@ impl<'a> Interpreter<'a> {
             Declare(stmts) => self.declare_variable(statement.as_token(), stmts),
             // array creation
             Parameter(x) => {
+                if x.len() > self.max_array_size {
+                    return Err(InterpretError::too_large(x.len(), self.max_array_size));
+                }
                 let mut result = vec![];
                 for stmt in x {
                     let val = self.resolve(stmt)?;
@@ -337,18 +661,10 @@ impl<'a> Interpreter<'a> {
                 Ok(NaslValue::Array(result))
             }
             Assign(cat, order, left, right) => self.assign(cat, order, left, right),
-            Operator(sign, stmts) => self.operator(sign, stmts),
-            If(condition, if_block, _, else_block) => match self.resolve(condition) {
-                Ok(value) => {
-                    if bool::from(value) {
-                        return self.resolve(if_block);
-                    } else if let Some(else_block) = else_block {
-                        return self.resolve(else_block.as_ref());
-                    }
-                    Ok(NaslValue::Null)
-                }
-                Err(err) => Err(err),
-            },
+            Operator(sign, stmts) => self
+                .operator(sign, stmts)
+                .and_then(|x| check_length(x, self.ctxconfigs.limits().max_string_length)),
+            If(x) => self.resolve_if(&x.condition, &x.then, x.otherwise.as_ref()),
             Block(blocks) => {
                 self.register_mut().create_child(HashMap::default());
                 for stmt in blocks {
@@ -358,8 +674,8 @@ impl<'a> Interpreter<'a> {
                                 x,
                                 NaslValue::Exit(_)
                                     | NaslValue::Return(_)
-                                    | NaslValue::Break
-                                    | NaslValue::Continue
+                                    | NaslValue::Break(_)
+                                    | NaslValue::Continue(_)
                             ) {
                                 self.register_mut().drop_last();
                                 return Ok(x);
@@ -381,8 +697,8 @@ impl<'a> Interpreter<'a> {
 
                 }
             },
-            Continue => Ok(NaslValue::Continue),
-            Break => Ok(NaslValue::Break),
+            Continue(label) => self.enclosing_label(statement, label.as_deref()).map(NaslValue::Continue),
+            Break(label) => self.enclosing_label(statement, label.as_deref()).map(NaslValue::Break),
         }
         .map_err(|e| {
             if e.origin.is_none() {
@@ -428,3 +744,334 @@ impl<'a> Interpreter<'a> {
         self.run_specific[self.index].skip_until_return.as_ref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nasl_builtin_utils::NaslFunctionExecuter;
+
+    use crate::*;
+
+    #[test]
+    fn reset_behaves_like_new() {
+        let scripts = [
+            r###"
+            set_kb_item(name: "test", value: 1);
+            set_kb_item(name: "test", value: 2);
+            a = get_kb_item("test");
+            a;
+            "###,
+            r###"
+            a;
+            b = 12;
+            b;
+            "###,
+        ];
+        let run = |interpreter: &mut Interpreter, code: &str| {
+            let mut results = vec![];
+            for stmt in parse(code) {
+                results.extend(interpreter.resolve_all(stmt.expect("unexpected parse error")));
+            }
+            results
+        };
+
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let fresh = scripts
+            .iter()
+            .map(|code| run(&mut Interpreter::new(Register::default(), &context), code))
+            .collect::<Vec<_>>();
+
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(Register::default(), &context);
+        let mut reused = vec![];
+        for code in scripts {
+            interpreter.reset(&context, &[]);
+            reused.push(run(&mut interpreter, code));
+        }
+        assert_eq!(fresh, reused);
+        assert_eq!(reused[1][0], Ok(NaslValue::Null));
+
+        // handles opened by the previous script must be closed
+        run(&mut interpreter, "http2_handle();");
+        assert_eq!(binding.functions.nasl_fn_cache_clear(), Some(1));
+        run(&mut interpreter, "http2_handle(); http2_handle();");
+        interpreter.reset(&context, &[]);
+        assert_eq!(binding.functions.nasl_fn_cache_clear(), None);
+
+        // state left over by a script that stopped within a loop or a breakpoint
+        interpreter.set_max_array_size(1);
+        interpreter.loops.push(None);
+        interpreter.breakpoints.triggered = Some((0..1, 1));
+        interpreter.reset(&context, &[]);
+        assert_eq!(interpreter.max_array_size, context.limits().max_array_size);
+        assert!(interpreter.loops.is_empty());
+        assert!(interpreter.breakpoints.triggered.is_none());
+    }
+
+    #[test]
+    fn unexpected_statement_is_an_error() {
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(Register::default(), &context);
+        let call = parse("a(b: 1);").next().unwrap().unwrap();
+        let named_parameter = match call.kind() {
+            nasl_syntax::StatementKind::Call(args) => args.children()[0].clone(),
+            kind => panic!("expected call but got {kind:?}"),
+        };
+        let result = interpreter.resolve(&named_parameter);
+        assert!(matches!(
+            result,
+            Err(InterpretError {
+                kind: InterpretErrorKind::WrongType(_),
+                origin: Some(_)
+            })
+        ));
+    }
+
+    #[test]
+    fn stray_named_parameter_is_an_error() {
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        for code in [
+            "a: 1;",
+            "x = (a: 1);",
+            "x = [a: 1];",
+            "foreach x (a: 1) y = 1;",
+            "function f(p) { return p; } f(x: (a: 1));",
+        ] {
+            let mut interpreter = CodeInterpreter::new(code, Register::default(), &context);
+            let result = interpreter.find(|x| x.is_err());
+            assert!(
+                matches!(
+                    result,
+                    Some(Err(InterpretError {
+                        kind: InterpretErrorKind::WrongType(_),
+                        ..
+                    }))
+                ),
+                "{code}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn run_all_returns_every_top_level_result() {
+        let code = r#"
+        a = 1;
+        function inc(x) { return x + 1; }
+        b = inc(x: a);
+        c = );
+        b * 2;
+        exit(3);
+        a = 42;
+        "#;
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(Register::default(), &context);
+        let results = interpreter.run_all(code);
+
+        let mut manual = Interpreter::new(Register::default(), &context);
+        let mut expected = Vec::new();
+        for stmt in nasl_syntax::parse(code) {
+            let result = match stmt {
+                Ok(stmt) => manual.resolve(&stmt),
+                Err(e) => Err(e.into()),
+            };
+            let exit = matches!(result, Ok(NaslValue::Exit(_)));
+            expected.push(result);
+            if exit {
+                break;
+            }
+        }
+        assert_eq!(results, expected);
+        assert!(matches!(
+            &results[..],
+            [
+                Ok(NaslValue::Number(1)),
+                Ok(NaslValue::Null),
+                Ok(NaslValue::Number(2)),
+                Err(_),
+                Ok(NaslValue::Null),
+                Ok(NaslValue::Number(4)),
+                Ok(NaslValue::Exit(3)),
+            ]
+        ));
+        assert_eq!(
+            interpreter.register().named("a").cloned(),
+            Some(ContextType::Value(NaslValue::Number(1)))
+        );
+    }
+
+    #[test]
+    fn observer_gets_line_and_depth() {
+        use std::sync::{Arc, Mutex};
+
+        let code = "a = 1;\nif (a) {\n  b = 2;\n}\nfunction f() {\n  return 3;\n}\nf();\n";
+        let points = Arc::new(Mutex::new(vec![]));
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = CodeInterpreter::new(code, Register::default(), &context);
+        let observed = points.clone();
+        interpreter.set_observer(move |statement, depth| {
+            let mut points = observed.lock().unwrap();
+            let point = (statement.start().line_column.0, depth);
+            if points.last() != Some(&point) {
+                points.push(point);
+            }
+        });
+        assert_eq!(interpreter.count(), 4);
+        assert_eq!(
+            *points.lock().unwrap(),
+            vec![(1, 0), (2, 0), (3, 1), (5, 0), (8, 0), (5, 1), (6, 2)]
+        );
+    }
+
+    #[test]
+    fn breakpoint() {
+        use std::sync::{Arc, Mutex};
+
+        let code = "a = 1;\nb = a + 1;\nc = b + 1;\nd = c + 1;\n";
+        let hits = Arc::new(Mutex::new(vec![]));
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = CodeInterpreter::new(code, Register::default(), &context);
+        interpreter.add_breakpoint(3);
+        let observed = hits.clone();
+        interpreter.set_breakpoint_handler(move |statement, register| {
+            let value = |name| register.named(name).map(NaslValue::from);
+            observed.lock().unwrap().push((
+                statement.start().line_column.0,
+                value("b"),
+                value("c"),
+            ));
+            true
+        });
+        assert_eq!(interpreter.count(), 4);
+        assert_eq!(
+            *hits.lock().unwrap(),
+            vec![(3, Some(NaslValue::Number(2)), None)]
+        );
+    }
+
+    #[test]
+    fn breakpoint_stops_execution() {
+        let code = "a = 1;\nb = 2;\nc = 3;\n";
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = CodeInterpreter::new(code, Register::default(), &context);
+        interpreter.add_breakpoint(2);
+        interpreter.set_breakpoint_handler(|_, _| false);
+        assert_eq!(interpreter.next(), Some(Ok(NaslValue::Number(1))));
+        assert_eq!(interpreter.next(), Some(Ok(NaslValue::Exit(0))));
+    }
+
+    #[test]
+    fn long_else_if_chain() {
+        let mut code = "a = 9998; if (a == 0) b = 0;".to_owned();
+        for i in 1..10000 {
+            code.push_str(&format!(" else if (a == {i}) b = {i};"));
+        }
+        code.push_str(" else b = -1; b;");
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = CodeInterpreter::new(&code, Register::default(), &context);
+        assert_eq!(interpreter.next(), Some(Ok(NaslValue::Number(9998))));
+        assert_eq!(interpreter.next(), Some(Ok(NaslValue::Number(9998))));
+        assert_eq!(interpreter.next(), Some(Ok(NaslValue::Number(9998))));
+        assert_eq!(interpreter.next(), None);
+    }
+
+    #[test]
+    fn watch() {
+        use std::sync::{Arc, Mutex};
+
+        let code = r###"
+        a = 1;
+        b = 1;
+        a = "changed";
+        function f() {
+            local_var a;
+            a = 3;
+        }
+        f();
+        "###;
+        let writes = Arc::new(Mutex::new(vec![]));
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = CodeInterpreter::new(code, Register::default(), &context);
+        let observed = writes.clone();
+        interpreter.add_watch("a", move |old, new| {
+            observed.lock().unwrap().push((old.clone(), new.clone()));
+        });
+        assert_eq!(interpreter.count(), 5);
+        assert_eq!(
+            *writes.lock().unwrap(),
+            vec![
+                (NaslValue::Null, NaslValue::Number(1)),
+                (NaslValue::Number(1), NaslValue::String("changed".into())),
+                (NaslValue::Null, NaslValue::Number(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn timeout_terminates_sleeping_script() {
+        use std::time::{Duration, Instant};
+
+        let code = "a = 1;\nwhile (TRUE) usleep(100000);\nb = 2;\n";
+        let binding =
+            ContextFactory::default().preferences(vec![("0", "timeout", "entry", "1").into()]);
+        let context = binding.build(Default::default(), Default::default());
+        let start = Instant::now();
+        let mut interpreter = CodeInterpreter::new(code, Register::default(), &context);
+        assert_eq!(interpreter.timeout(), Some(Duration::from_secs(1)));
+        assert_eq!(interpreter.next(), Some(Ok(NaslValue::Number(1))));
+        assert!(matches!(
+            interpreter.next(),
+            Some(Err(InterpretError {
+                kind: InterpretErrorKind::Timeout(x),
+                ..
+            })) if x == Duration::from_secs(1)
+        ));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_secs(1) && elapsed < Duration::from_secs(2));
+        assert!(matches!(interpreter.next(), Some(Err(_))));
+    }
+
+    #[test]
+    fn timeout_preference_by_id() {
+        use std::time::Duration;
+
+        let binding = ContextFactory::default().preferences(vec![
+            ("1", "timeout", "entry", "5").into(),
+            ("0", "Timeout", "entry", "7").into(),
+        ]);
+        let context = binding.build(Default::default(), Default::default());
+        let interpreter = CodeInterpreter::new("1;", Register::default(), &context);
+        assert_eq!(interpreter.timeout(), Some(Duration::from_secs(7)));
+        let binding =
+            ContextFactory::default().preferences(vec![("1", "timeout", "entry", "5").into()]);
+        let context = binding.build(Default::default(), Default::default());
+        let interpreter = CodeInterpreter::new("1;", Register::default(), &context);
+        assert_eq!(interpreter.timeout(), None);
+    }
+
+    #[test]
+    fn set_timeout() {
+        use std::time::Duration;
+
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter =
+            CodeInterpreter::new("usleep(1000); 1;", Register::default(), &context);
+        assert_eq!(interpreter.timeout(), None);
+        interpreter.set_timeout(Some(Duration::ZERO));
+        assert_eq!(
+            interpreter.next().map(|x| x.map_err(|e| e.kind)),
+            Some(Err(InterpretErrorKind::Timeout(Duration::ZERO)))
+        );
+        interpreter.set_timeout(None);
+        assert_eq!(interpreter.next(), Some(Ok(NaslValue::Number(1))));
+    }
+}
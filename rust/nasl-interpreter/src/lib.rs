@@ -7,10 +7,13 @@
 
 mod error;
 
+mod arithmetic;
 mod assign;
+mod budget;
 mod call;
 mod declare;
 mod fork_interpreter;
+mod higher_order;
 mod include;
 mod interpreter;
 mod loop_extension;
@@ -18,18 +21,25 @@ mod operator;
 mod scan_interpreter;
 pub mod scheduling;
 
+pub use arithmetic::Arithmetic;
+pub use budget::Budget;
+pub use call::interpreter_fn_defined;
 pub use error::FunctionError;
 pub use error::InterpretError;
 pub use error::InterpretErrorKind;
 pub use fork_interpreter::*;
+pub use higher_order::HigherOrder;
 pub use interpreter::ContextLifeTimeCapture;
 pub use interpreter::Interpreter;
+pub use operator::Comparison;
 pub use scan_interpreter::*;
 
 // we expose the other libraries to allow users to use them without having to import them
 pub use nasl_builtin_std::{nasl_std_functions, ContextFactory, RegisterBuilder};
 pub use nasl_builtin_utils::{
-    Context, ContextType, FunctionErrorKind, NaslFunctionRegister, NaslVarRegister, Register,
+    Capabilities, Context, ContextType, FunctionErrorKind, Limits, NaslFunctionExecuter,
+    NaslFunctionRegister, NaslVarRegister, Register, DEFAULT_MAX_ARRAY_SIZE,
+    DEFAULT_MAX_STRING_LENGTH,
 };
 pub use nasl_syntax::{
     load_non_utf8_path, logger, parse, AsBufReader, FSPluginLoader, LoadError, Loader, NaslValue,
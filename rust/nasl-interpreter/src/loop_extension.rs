@@ -2,12 +2,41 @@
 //
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
-use nasl_syntax::{IdentifierType, Statement, Token, TokenCategory};
+use nasl_syntax::{IdentifierType, Statement, StatementKind, Token, TokenCategory};
 
 use crate::{interpreter::InterpretResult, InterpretError, Interpreter};
 use nasl_builtin_utils::ContextType;
 use nasl_syntax::NaslValue;
 
+/// Describes how a loop proceeds after resolving its body
+enum Flow {
+    /// Proceed with the next iteration
+    Next,
+    /// Stop the loop
+    Break,
+    /// Stop the loop and return the value to the enclosing statement
+    Leave(NaslValue),
+}
+
+impl Flow {
+    /// Determines the flow of a loop with the given label based on the result of its body.
+    ///
+    /// Break and continue without a label or with the label of the loop are handled by the loop
+    /// itself, all others are passed to the enclosing statement.
+    fn new(ret: NaslValue, label: Option<&str>) -> Self {
+        let own = |x: &Option<String>| x.is_none() || x.as_deref() == label;
+        match ret {
+            NaslValue::Break(ref x) if own(x) => Flow::Break,
+            NaslValue::Continue(ref x) if own(x) => Flow::Next,
+            NaslValue::Break(_)
+            | NaslValue::Continue(_)
+            | NaslValue::Exit(_)
+            | NaslValue::Return(_) => Flow::Leave(ret),
+            _ => Flow::Next,
+        }
+    }
+}
+
 /// Extension to handle the interpretation of NASL loops
 pub(crate) trait LoopExtension {
     /// Interpreting a NASL for loop. A NASL for loop is built up with the
@@ -17,24 +46,17 @@ pub(crate) trait LoopExtension {
     ///
     /// It first resolves the assignment and runs until the condition resolves
     /// into a `FALSE` NaslValue. The update statement is resolved after each
-    /// iteration.
+    /// iteration. Each of them may be omitted; an omitted condition is always
+    /// `TRUE`.
     fn for_loop(
         &mut self,
         assignment: &Statement,
         condition: &Statement,
         update: &Statement,
         body: &Statement,
+        label: Option<&str>,
     ) -> InterpretResult;
 
-    /// Interpreting a NASL foreach loop. A NASL foreach loop is built up with
-    /// the following:
-    ///
-    /// foreach variable(iterable) {body}
-    ///
-    /// The iterable is first transformed into an Array, then we iterate through
-    /// it and resolve the body for every value in the array.
-    fn while_loop(&mut self, condition: &Statement, body: &Statement) -> InterpretResult;
-
     /// Interpreting a NASL while loop. A NASL while loop is built up with the
     /// following:
     ///
@@ -42,7 +64,12 @@ pub(crate) trait LoopExtension {
     ///
     /// The condition is first checked, then the body resolved, as long as the
     /// condition resolves into a `TRUE` NaslValue.
-    fn repeat_loop(&mut self, body: &Statement, condition: &Statement) -> InterpretResult;
+    fn while_loop(
+        &mut self,
+        condition: &Statement,
+        body: &Statement,
+        label: Option<&str>,
+    ) -> InterpretResult;
 
     /// Interpreting a NASL repeat until loop. A NASL repeat until loop is built
     /// up with the following:
@@ -51,12 +78,51 @@ pub(crate) trait LoopExtension {
     ///
     /// It first resolves the body at least once. It keeps resolving the body,
     /// until the condition statement resolves into a `TRUE` NaslValue.
+    fn repeat_loop(
+        &mut self,
+        body: &Statement,
+        condition: &Statement,
+        label: Option<&str>,
+    ) -> InterpretResult;
+
+    /// Interpreting a NASL foreach loop. A NASL foreach loop is built up with
+    /// the following:
+    ///
+    /// foreach variable(iterable) {body}
+    ///
+    /// The iterable is first transformed into an Array, then we iterate through
+    /// it and resolve the body for every value in the array.
     fn for_each_loop(
         &mut self,
         variable: &Token,
         iterable: &Statement,
         body: &Statement,
+        label: Option<&str>,
     ) -> InterpretResult;
+
+    /// Interpreting a NASL loop with a label. A labeled loop is built up with
+    /// the following:
+    ///
+    /// label: loop
+    ///
+    /// `break label;` and `continue label;` within nested loops are passed up
+    /// until they reach the loop with that label.
+    fn labeled_loop(&mut self, label: &Token, stmt: &Statement) -> InterpretResult;
+
+    /// Resolves a loop while it is registered as enclosing loop with the given label.
+    fn enclose_loop<F>(&mut self, label: Option<&str>, f: F) -> InterpretResult
+    where
+        F: FnOnce(&mut Self) -> InterpretResult;
+
+    /// Returns the label of a break or continue statement.
+    ///
+    /// Returns an error when the statement is not within a loop or the label does not belong to
+    /// an enclosing loop.
+    fn enclosing_label(
+        &self,
+        statement: &Statement,
+        label: Option<&Token>,
+    ) -> Result<Option<String>, InterpretError>;
 }
 
 /// Implementation for the Loop extension. Note that for all loops, we do not
@@ -68,24 +134,26 @@ impl<'a> LoopExtension for Interpreter<'a> {
         condition: &Statement,
         update: &Statement,
         body: &Statement,
+        label: Option<&str>,
     ) -> InterpretResult {
         // Resolve assignment
         self.resolve(assignment)?;
 
         loop {
-            // Check condition statement
-            if !bool::from(self.resolve(condition)?) {
+            // Check condition statement, an omitted condition is always true
+            if !matches!(condition.kind(), StatementKind::NoOp)
+                && !bool::from(self.resolve(condition)?)
+            {
                 break;
             }
 
             // Execute loop body
             let ret = self.resolve(body)?;
             // Catch special values
-            match ret {
-                NaslValue::Break => break,
-                NaslValue::Exit(code) => return Ok(NaslValue::Exit(code)),
-                NaslValue::Return(val) => return Ok(NaslValue::Return(val)),
-                _ => (),
+            match Flow::new(ret, label) {
+                Flow::Break => break,
+                Flow::Leave(x) => return Ok(x),
+                Flow::Next => (),
             };
 
             // Execute update Statement
@@ -100,6 +168,7 @@ impl<'a> LoopExtension for Interpreter<'a> {
         variable: &Token,
         iterable: &Statement,
         body: &Statement,
+        label: Option<&str>,
     ) -> InterpretResult {
         // Get name of the iteration variable
         let iter_name = match variable.category() {
@@ -115,43 +184,50 @@ impl<'a> LoopExtension for Interpreter<'a> {
             // Execute loop body
             let ret = self.resolve(body)?;
             // Catch special values
-            match ret {
-                NaslValue::Break => break,
-                NaslValue::Exit(code) => return Ok(NaslValue::Exit(code)),
-                NaslValue::Return(val) => return Ok(NaslValue::Return(val)),
-                _ => (),
+            match Flow::new(ret, label) {
+                Flow::Break => break,
+                Flow::Leave(x) => return Ok(x),
+                Flow::Next => (),
             };
         }
 
         Ok(NaslValue::Null)
     }
 
-    fn while_loop(&mut self, condition: &Statement, body: &Statement) -> InterpretResult {
+    fn while_loop(
+        &mut self,
+        condition: &Statement,
+        body: &Statement,
+        label: Option<&str>,
+    ) -> InterpretResult {
         while bool::from(self.resolve(condition)?) {
             // Execute loop body
             let ret = self.resolve(body)?;
             // Catch special values
-            match ret {
-                NaslValue::Break => break,
-                NaslValue::Exit(code) => return Ok(NaslValue::Exit(code)),
-                NaslValue::Return(val) => return Ok(NaslValue::Return(val)),
-                _ => (),
+            match Flow::new(ret, label) {
+                Flow::Break => break,
+                Flow::Leave(x) => return Ok(x),
+                Flow::Next => (),
             };
         }
 
         Ok(NaslValue::Null)
     }
 
-    fn repeat_loop(&mut self, body: &Statement, condition: &Statement) -> InterpretResult {
+    fn repeat_loop(
+        &mut self,
+        body: &Statement,
+        condition: &Statement,
+        label: Option<&str>,
+    ) -> InterpretResult {
         loop {
             // Execute loop body
             let ret = self.resolve(body)?;
             // Catch special values
-            match ret {
-                NaslValue::Break => break,
-                NaslValue::Exit(code) => return Ok(NaslValue::Exit(code)),
-                NaslValue::Return(val) => return Ok(NaslValue::Return(val)),
-                _ => (),
+            match Flow::new(ret, label) {
+                Flow::Break => break,
+                Flow::Leave(x) => return Ok(x),
+                Flow::Next => (),
             };
 
             // Check condition statement
@@ -162,6 +238,50 @@ impl<'a> LoopExtension for Interpreter<'a> {
 
         Ok(NaslValue::Null)
     }
+
+    fn labeled_loop(&mut self, label: &Token, stmt: &Statement) -> InterpretResult {
+        let label = match label.category() {
+            TokenCategory::Identifier(IdentifierType::Undefined(name)) => Some(name.as_str()),
+            o => return Err(InterpretError::wrong_category(o)),
+        };
+        self.enclose_loop(label, |x| match stmt.kind() {
+            StatementKind::For(l) => {
+                x.for_loop(&l.assignment, &l.condition, &l.update, &l.body, label)
+            }
+            StatementKind::While(condition, body) => x.while_loop(condition, body, label),
+            StatementKind::Repeat(body, condition) => x.repeat_loop(body, condition, label),
+            StatementKind::ForEach(l) => x.for_each_loop(&l.variable, &l.iterable, &l.body, label),
+            // the lexer only allows labels on loops
+            _ => x.resolve(stmt),
+        })
+    }
+
+    fn enclose_loop<F>(&mut self, label: Option<&str>, f: F) -> InterpretResult
+    where
+        F: FnOnce(&mut Self) -> InterpretResult,
+    {
+        self.loops.push(label.map(|x| x.to_owned()));
+        let result = f(self);
+        self.loops.pop();
+        result
+    }
+
+    fn enclosing_label(
+        &self,
+        statement: &Statement,
+        label: Option<&Token>,
+    ) -> Result<Option<String>, InterpretError> {
+        let label = label.map(|x| x.category().to_string());
+        let enclosed = match &label {
+            None => !self.loops.is_empty(),
+            Some(_) => self.loops.contains(&label),
+        };
+        if enclosed {
+            Ok(label)
+        } else {
+            Err(InterpretError::not_in_loop(&statement.to_string()))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -208,6 +328,35 @@ mod tests {
         assert_eq!(interpreter.next(), Some(Ok(5.into())));
     }
 
+    #[test]
+    fn for_loop_with_omitted_clauses() {
+        let code = r###"
+        a = 0;
+        for (; a < 5;) a++;
+        a;
+        for (b = 0;; b++) {
+            if (b == 3) break;
+        }
+        b;
+        c = 0;
+        for (;;) {
+            c++;
+            if (c == 7) break;
+        }
+        c;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(register, &context);
+        let results = parse(code)
+            .map(|x| interpreter.resolve(&x.expect("unexpected parse error")))
+            .collect::<Vec<_>>();
+        assert_eq!(results[2], Ok(5.into()));
+        assert_eq!(results[4], Ok(3.into()));
+        assert_eq!(results[7], Ok(7.into()));
+    }
+
     #[test]
     fn for_each_loop_test() {
         let code = r###"
@@ -232,6 +381,48 @@ mod tests {
         assert_eq!(interpreter.next(), Some(Ok(8.into())));
     }
 
+    #[test]
+    fn for_each_iterables() {
+        let code = r###"
+        d = make_array("b", 2, "c", 3, "a", 1);
+        s = "";
+        foreach x (d) s += x;
+        s;
+        s = "";
+        foreach x (keys(d)) s += x;
+        s;
+        n = 0;
+        foreach x (5) n += x;
+        n;
+        n = 0;
+        foreach x ("abc") n++;
+        n;
+        n = 0;
+        foreach x (raw_string(1, 2, 3)) n++;
+        n;
+        n = 0;
+        foreach x (NULL) n++;
+        n;
+        n = 0;
+        foreach x (make_list()) n++;
+        n;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(register, &context);
+        let results = parse(code)
+            .map(|x| interpreter.resolve(&x.expect("unexpected parse error")))
+            .collect::<Vec<_>>();
+        assert_eq!(results[3], Ok("231".into()));
+        assert_eq!(results[6], Ok("bca".into()));
+        assert_eq!(results[9], Ok(5.into()));
+        assert_eq!(results[12], Ok(1.into()));
+        assert_eq!(results[15], Ok(1.into()));
+        assert_eq!(results[18], Ok(0.into()));
+        assert_eq!(results[21], Ok(0.into()));
+    }
+
     #[test]
     fn while_loop_test() {
         let code = r###"
@@ -285,6 +476,36 @@ mod tests {
         assert_eq!(interpreter.next(), Some(Ok(9.into())));
     }
 
+    #[test]
+    fn do_while_loop() {
+        let code = r###"
+        a = 0;
+        do {
+            a++;
+        } while (FALSE);
+        a;
+        i = 0;
+        do {
+            i++;
+        } while (i < 5);
+        i;
+        do = 2;
+        do + i;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(register, &context);
+        let results = parse(code)
+            .map(|x| interpreter.resolve(&x.expect("unexpected parse error")))
+            .collect::<Vec<_>>();
+        assert_eq!(results[1], Ok(NaslValue::Null));
+        assert_eq!(results[2], Ok(1.into()));
+        assert_eq!(results[5], Ok(5.into()));
+        // without a loop body do is a variable
+        assert_eq!(results[7], Ok(7.into()));
+    }
+
     #[test]
     fn control_flow() {
         let code = r###"
@@ -316,4 +537,121 @@ mod tests {
         assert_eq!(interpreter.next(), Some(Ok(10.into())));
         assert_eq!(interpreter.next(), Some(Ok(1.into())));
     }
+
+    #[test]
+    fn loops_honor_break() {
+        let code = r###"
+        a = 0;
+        for (i = 0; i < 10; i++) {
+            if (i == 3) break;
+            a++;
+        }
+        a;
+        b = 0;
+        while (TRUE) {
+            b++;
+            if (b == 4) break;
+        }
+        b;
+        c = 0;
+        repeat {
+            c++;
+            if (c == 5) break;
+        } until (c > 10);
+        c;
+        d = 0;
+        foreach x (make_list(1, 2, 3, 4)) {
+            if (x == 3) break;
+            d += x;
+        }
+        d;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(register, &context);
+        let results = parse(code)
+            .map(|x| interpreter.resolve(&x.expect("unexpected parse error")))
+            .collect::<Vec<_>>();
+        assert_eq!(results[2], Ok(3.into()));
+        assert_eq!(results[5], Ok(4.into()));
+        assert_eq!(results[8], Ok(5.into()));
+        assert_eq!(results[11], Ok(3.into()));
+    }
+
+    #[test]
+    fn labeled_break_and_continue() {
+        let code = r###"
+        a = 0;
+        outer: for (i = 0; i < 3; i++) {
+            for (j = 0; j < 3; j++) {
+                if (j == 2) break;
+                if (i == 1) break outer;
+                a++;
+            }
+        }
+        a;
+        b = 0;
+        outer: foreach x (make_list(1, 2, 3)) {
+            inner: while (TRUE) {
+                if (x == 2) continue outer;
+                b += x;
+                break inner;
+            }
+            b += 10;
+        }
+        b;
+        c = 0;
+        outer: repeat {
+            c++;
+            for (j = 0; j < 3; j++) {
+                if (c < 3) continue outer;
+                break;
+            }
+            break outer;
+        } until (c > 10);
+        c;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(register, &context);
+        let results = parse(code)
+            .map(|x| interpreter.resolve(&x.expect("unexpected parse error")))
+            .collect::<Vec<_>>();
+        assert_eq!(results[1], Ok(NaslValue::Null));
+        assert_eq!(results[2], Ok(2.into()));
+        assert_eq!(results[5], Ok(24.into()));
+        assert_eq!(results[8], Ok(3.into()));
+    }
+
+    #[test]
+    fn break_and_continue_outside_of_loop() {
+        let code = r###"
+        break;
+        continue;
+        while (TRUE) { break; }
+        function leave() { break; }
+        while (TRUE) { leave(); }
+        outer: while (TRUE) { break inner; }
+        a = 1;
+        "###;
+        let register = Register::default();
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let mut interpreter = Interpreter::new(register, &context);
+        let results = parse(code)
+            .map(|x| interpreter.resolve(&x.expect("unexpected parse error")))
+            .collect::<Vec<_>>();
+        let not_in_loop = |x: &super::InterpretResult, expected: &str| match x {
+            Err(e) => assert_eq!(e.kind, InterpretErrorKind::NotInLoop(expected.to_owned())),
+            x => panic!("expected an error, got: {x:?}"),
+        };
+        not_in_loop(&results[0], "break");
+        not_in_loop(&results[1], "continue");
+        assert_eq!(results[2], Ok(NaslValue::Null));
+        not_in_loop(&results[4], "break");
+        not_in_loop(&results[5], "break inner");
+        assert_eq!(results[6], Ok(1.into()));
+    }
 }
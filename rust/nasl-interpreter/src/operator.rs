@@ -2,13 +2,51 @@
 //
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
-use nasl_syntax::{Statement, TokenCategory};
+use nasl_syntax::{bytes_to_str, Statement, TokenCategory};
 use regex::Regex;
 
 use crate::{error::InterpretError, interpreter::InterpretResult, Interpreter};
 
 use nasl_syntax::NaslValue;
 
+/// Defines how `==` and `!=` compare values of different types
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Comparison {
+    /// Values of different types are never equal, e.g. `"1" == 1` is FALSE
+    Strict,
+    /// Values are converted like in legacy NASL before they are compared
+    ///
+    /// A number compared to a string is converted to a string, e.g. `"1" == 1` is TRUE but
+    /// `"abc" == 0` is FALSE. Booleans are the numbers 1 and 0 and NULL is either 0 or an empty
+    /// string, depending on the other value. Strings and data are compared by their content.
+    #[default]
+    Loose,
+}
+
+impl Comparison {
+    /// Returns true when both values are equal
+    pub fn equals(&self, a: &NaslValue, b: &NaslValue) -> bool {
+        match self {
+            Comparison::Strict => a == b,
+            Comparison::Loose => loose_equals(a, b),
+        }
+    }
+}
+
+fn loose_equals(a: &NaslValue, b: &NaslValue) -> bool {
+    use NaslValue::*;
+    match (a, b) {
+        (Boolean(a), b) => loose_equals(&Number(*a as i64), b),
+        (a, Boolean(b)) => loose_equals(a, &Number(*b as i64)),
+        (Null, Number(x)) | (Number(x), Null) => *x == 0,
+        (Null, String(x)) | (String(x), Null) => x.is_empty(),
+        (Null, Data(x)) | (Data(x), Null) => x.is_empty(),
+        (Number(_) | String(_) | Data(_), String(_) | Data(_))
+        | (String(_) | Data(_), Number(_)) => a.to_string() == b.to_string(),
+        _ => a == b,
+    }
+}
+
 /// Is a trait to handle operator within nasl.
 pub(crate) trait OperatorExtension {
     /// Returns result of an operator
@@ -65,6 +103,21 @@ macro_rules! num_expr {
     };
 }
 
+/// Applies an operation that is undefined for some numbers, e.g. a division by zero
+///
+/// Instead of panicking it returns an error when the operation returns None.
+fn checked(
+    a: NaslValue,
+    b: Option<NaslValue>,
+    op: &str,
+    f: impl Fn(i64, i64) -> Option<i64>,
+) -> InterpretResult {
+    let (left, right) = as_i64(a, b);
+    f(left, right)
+        .map(NaslValue::Number)
+        .ok_or_else(|| InterpretError::arithmetic(&format!("{left} {op} {right}")))
+}
+
 fn match_regex(a: NaslValue, matches: Option<NaslValue>) -> InterpretResult {
     let right = matches.map(|x| x.to_string()).unwrap_or_default();
     match Regex::new(&right) {
@@ -98,7 +151,7 @@ macro_rules! add_left_right_data {
     ($left: ident, $right:ident) => {{
         let right = $right.map(|x| x.to_string()).unwrap_or_default();
         let x: Vec<u8> = $left.into();
-        let x: String = x.into_iter().map(|b| b as char).collect();
+        let x = bytes_to_str(&x);
         Ok(NaslValue::Data(format!("{x}{right}").into()))
     }};
 }
@@ -107,65 +160,100 @@ macro_rules! minus_left_right_data {
     ($left: ident, $right:ident) => {{
         let right = $right.map(|x| x.to_string()).unwrap_or_default();
         let x: Vec<u8> = $left.into();
-        let x: String = x.into_iter().map(|b| b as char).collect();
+        let x = bytes_to_str(&x);
         Ok(NaslValue::Data(x.replacen(&right, "", 1).into()))
     }};
 }
 
+/// Adds right to left
+///
+/// When either side is a string or data the values are concatenated otherwise the numbers
+/// are added.
+pub(crate) fn plus(a: NaslValue, b: Option<NaslValue>) -> InterpretResult {
+    match a {
+        NaslValue::String(x) => add_left_right_string!(x, b),
+        NaslValue::Data(x) => add_left_right_data!(x, b),
+        left => match b {
+            Some(NaslValue::String(_)) => add_left_right_string!(left, b),
+            Some(NaslValue::Data(_)) => add_left_right_data!(left, b),
+            _ => checked(left, b, "+", i64::checked_add),
+        },
+    }
+}
+
+/// Removes the first occurrence of right from left or subtracts the numbers
+///
+/// Without right it negates left.
+pub(crate) fn minus(a: NaslValue, b: Option<NaslValue>) -> InterpretResult {
+    match a {
+        NaslValue::String(x) => minus_left_right_string!(x, b),
+        NaslValue::Data(x) => minus_left_right_data!(x, b),
+        left => match b {
+            Some(NaslValue::String(_)) => minus_left_right_string!(left, b),
+            Some(NaslValue::Data(_)) => minus_left_right_data!(left, b),
+            _ => match b {
+                Some(right) => checked(left, Some(right), "-", i64::checked_sub),
+                None => {
+                    let left = i64::from(&left);
+                    left.checked_neg()
+                        .map(NaslValue::Number)
+                        .ok_or_else(|| InterpretError::arithmetic(&format!("-{left}")))
+                }
+            },
+        },
+    }
+}
+
+/// Implementation of an operator based on the already resolved left and optional right value
+pub(crate) type OperatorFn = fn(NaslValue, Option<NaslValue>) -> InterpretResult;
+
+/// Returns the implementation of an arithmetic operator
+///
+/// It is shared with the compound assignments (e.g. `+=`) so that they behave exactly like
+/// their operator.
+pub(crate) fn arithmetic(category: &TokenCategory) -> Option<OperatorFn> {
+    let result: OperatorFn = match category {
+        // number and string
+        TokenCategory::Plus => plus,
+        TokenCategory::Minus => minus,
+        // number
+        TokenCategory::Star => |a, b| checked(a, b, "*", i64::checked_mul),
+        TokenCategory::Slash => |a, b| checked(a, b, "/", i64::checked_div),
+        TokenCategory::Percent => |a, b| checked(a, b, "%", i64::checked_rem),
+        TokenCategory::LessLess => {
+            |a, b| checked(a, b, "<<", |l, r| l.checked_shl(u32::try_from(r).ok()?))
+        }
+        TokenCategory::GreaterGreater => {
+            |a, b| checked(a, b, ">>", |l, r| l.checked_shr(u32::try_from(r).ok()?))
+        }
+        // let left_casted = left as u32; (left_casted >> right) as i64
+        TokenCategory::GreaterGreaterGreater => |a, b| {
+            checked(a, b, ">>>", |l, r| {
+                let result = (l as u32).checked_shr(u32::try_from(r).ok()?)?;
+                Some(result as i32 as i64)
+            })
+        },
+        _ => return None,
+    };
+    Some(result)
+}
+
 impl<'a> OperatorExtension for Interpreter<'a> {
     fn operator(&mut self, category: &TokenCategory, stmts: &[Statement]) -> InterpretResult {
+        if let Some(op) = arithmetic(category) {
+            return self.execute(stmts, op);
+        }
         match category {
-            // number and string
-            TokenCategory::Plus => self.execute(stmts, |a, b| match a {
-                NaslValue::String(x) => add_left_right_string!(x, b),
-                NaslValue::Data(x) => add_left_right_data!(x, b),
-                left => match b {
-                    Some(NaslValue::String(_)) => add_left_right_string!(left, b),
-                    Some(NaslValue::Data(_)) => add_left_right_data!(left, b),
-                    _ => {
-                        let right = b.map(|x| i64::from(&x)).unwrap_or_default();
-                        Ok(NaslValue::Number(i64::from(&left) + right))
-                    }
-                },
-            }),
-            TokenCategory::Minus => self.execute(stmts, |a, b| match a {
-                NaslValue::String(x) => minus_left_right_string!(x, b),
-                NaslValue::Data(x) => minus_left_right_data!(x, b),
-                left => match b {
-                    Some(NaslValue::String(_)) => minus_left_right_string!(left, b),
-                    Some(NaslValue::Data(_)) => minus_left_right_data!(left, b),
-                    _ => {
-                        let result = match b {
-                            Some(right) => i64::from(&left) - i64::from(&right),
-                            None => -i64::from(&left),
-                        };
-                        Ok(NaslValue::Number(result))
-                    }
-                },
-            }),
             // number
-            TokenCategory::Star => self.execute(stmts, |a, b| num_expr!(* a b)),
-            TokenCategory::Slash => self.execute(stmts, |a, b| num_expr!(/ a b)),
-            TokenCategory::Percent => self.execute(stmts, |a, b| num_expr!(% a b)),
-            TokenCategory::LessLess => self.execute(stmts, |a, b| num_expr!(<< a b)),
-            TokenCategory::GreaterGreater => self.execute(stmts, |a, b| num_expr!(>> a b)),
-            // let left_casted = left as u32; (left_casted >> right) as i64
-            TokenCategory::GreaterGreaterGreater => self.execute(
-                stmts,
-                //|a, b| num_expr!(|a, b| ((a as u32) >> b) as i32 => a b),
-                |a, b| {
-                    let (left, right) = as_i64(a, b);
-                    let result = ((left as u32) >> right) as i32;
-                    Ok(NaslValue::Number(result as i64))
-                },
-            ),
             TokenCategory::Ampersand => self.execute(stmts, |a, b| num_expr!(& a b)),
             TokenCategory::Pipe => self.execute(stmts, |a, b| num_expr!(| a b)),
             TokenCategory::Caret => self.execute(stmts, |a, b| num_expr!(^ a b)),
+            // the base is a 32 bit integer like in openvas
             TokenCategory::StarStar => self.execute(stmts, |a, b| {
-                let (a, b) = as_i64(a, b);
-                let result = (a as u32).pow(b as u32);
-                Ok(NaslValue::Number(result as i64))
+                checked(a, b, "**", |l, r| {
+                    let result = (l as u32).checked_pow(u32::try_from(r).ok()?)?;
+                    Some(result.into())
+                })
             }),
             TokenCategory::Tilde => self.execute(stmts, |a, _| Ok((!i64::from(&a)).into())),
             // string
@@ -191,14 +279,20 @@ impl<'a> OperatorExtension for Interpreter<'a> {
                 let right = b.map(bool::from).unwrap_or_default();
                 Ok(NaslValue::Boolean(bool::from(a) || right))
             }),
-            TokenCategory::EqualEqual => self.execute(stmts, |a, b| {
-                let right = b.unwrap_or(NaslValue::Null);
-                Ok(NaslValue::Boolean(a == right))
-            }),
-            TokenCategory::BangEqual => self.execute(stmts, |a, b| {
-                let right = b.unwrap_or(NaslValue::Null);
-                Ok(NaslValue::Boolean(a != right))
-            }),
+            TokenCategory::EqualEqual => {
+                let comparison = self.comparison;
+                self.execute(stmts, |a, b| {
+                    let right = b.unwrap_or(NaslValue::Null);
+                    Ok(NaslValue::Boolean(comparison.equals(&a, &right)))
+                })
+            }
+            TokenCategory::BangEqual => {
+                let comparison = self.comparison;
+                self.execute(stmts, |a, b| {
+                    let right = b.unwrap_or(NaslValue::Null);
+                    Ok(NaslValue::Boolean(!comparison.equals(&a, &right)))
+                })
+            }
             TokenCategory::Greater => self.execute(stmts, |a, b| {
                 let right = b.map(|x| i64::from(&x)).unwrap_or_default();
                 Ok(NaslValue::Boolean(i64::from(&a) > right))
@@ -288,6 +382,8 @@ mod tests {
         or: "-2 | 2;" => NaslValue::Number(-2),
         xor: "-2 ^ 2;" => NaslValue::Number(-4),
         pow: "2 ** 2;" => 4.into(),
+        pow_is_right_associative: "2 ** 3 ** 2;" => 512.into(),
+        chained_assignment: "a = b = 3; a + b;" => 6.into(),
         not: "~2;" => NaslValue::Number(-3),
         r_match: "'hello' =~ 'hell';" => NaslValue::Boolean(true),
         r_not_match: "'hello' !~ 'hell';" => NaslValue::Boolean(false),
@@ -300,10 +396,78 @@ mod tests {
         equals_string: "'1' == '1';" => NaslValue::Boolean(true),
         equals_number: "1 == 1;" => NaslValue::Boolean(true),
         unequal: "1 != 1;" => NaslValue::Boolean(false),
+        loose_string_number: "\"1\" == 1;" => NaslValue::Boolean(true),
+        loose_data_number: "'1' == 1;" => NaslValue::Boolean(true),
+        loose_string_zero: "\"abc\" == 0;" => NaslValue::Boolean(false),
+        loose_boolean_number: "TRUE == 1;" => NaslValue::Boolean(true),
+        loose_null_zero: "NULL == 0;" => NaslValue::Boolean(true),
+        loose_string_data: "\"a\" == 'a';" => NaslValue::Boolean(true),
+        loose_unequal: "\"1\" != 1;" => NaslValue::Boolean(false),
         greater: "1 > 0;" => NaslValue::Boolean(true),
         less: "1 < 2;" => NaslValue::Boolean(true),
         greater_equal: "1 >= 1;" => NaslValue::Boolean(true),
         less_equal: "1 <= 1;" => NaslValue::Boolean(true),
         x_gonna_give_it_ya: "function test() { }; test('hi') x 200;" => NaslValue::Null
     }
+
+    #[test]
+    fn comparison_modes() {
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let cases = [
+            ("\"1\" == 1;", true, false),
+            ("\"abc\" == 0;", false, false),
+            ("TRUE == 1;", true, false),
+            ("FALSE == NULL;", true, false),
+            ("\"1\" != 1;", false, true),
+            ("1 == 1;", true, true),
+        ];
+        for (code, loose, strict) in cases {
+            for (comparison, expected) in [(Comparison::Loose, loose), (Comparison::Strict, strict)]
+            {
+                let mut interpreter = Interpreter::new(Register::default(), &context);
+                interpreter.set_comparison(comparison);
+                assert_eq!(
+                    interpreter.run_all(code),
+                    vec![Ok(NaslValue::Boolean(expected))],
+                    "{code} {comparison:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn undefined_arithmetic() {
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        for code in [
+            "1 / 0;",
+            "1 % 0;",
+            "(-9223372036854775807 - 1) / -1;",
+            "1 << 64;",
+            "1 >> -1;",
+            "1 >>> 32;",
+            "a = 1; a /= 0;",
+            "9223372036854775807 + 1;",
+            "-9223372036854775807 - 2;",
+            "-(-9223372036854775807 - 1);",
+            "4294967296 * 4294967296;",
+            "2 ** 40;",
+            "2 ** -1;",
+            "a = 9223372036854775807; a += 1;",
+        ] {
+            let mut interpreter = Interpreter::new(Register::default(), &context);
+            let result = interpreter.run_all(code).pop();
+            assert!(
+                matches!(
+                    result,
+                    Some(Err(InterpretError {
+                        kind: InterpretErrorKind::Arithmetic(_),
+                        ..
+                    }))
+                ),
+                "{code}: {result:?}"
+            );
+        }
+    }
 }
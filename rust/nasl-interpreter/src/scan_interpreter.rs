@@ -4,15 +4,41 @@
 
 //! scan-interpreter interprets models::Scan
 
+use std::time::Duration;
+
 use nasl_builtin_utils::NaslFunctionExecuter;
 use nasl_syntax::{
     logger::{DefaultLogger, NaslLogger},
-    Loader, NaslValue,
+    Loader, NaslValue, ACT,
 };
-use storage::{ContextKey, Storage};
+use storage::{ContextKey, Field, Retrieve, Storage};
 
 use crate::{scheduling::ExecutionPlaner, InterpretError};
 
+/// Timeout of a script in seconds when neither the script nor the scan sets one
+const NVT_TIMEOUT: u64 = 320;
+/// Timeout of an ACT_SCANNER script in seconds when neither the script nor the scan sets one
+const SCANNER_NVT_TIMEOUT: u64 = 36000;
+
+/// Returns the timeout of a script that does not declare one via `script_timeout`
+///
+/// Like openvas it uses the `plugins_timeout` preference of the scan or, for ACT_SCANNER
+/// scripts, the `scanner_plugins_timeout` preference.
+fn default_timeout(scan: &models::Scan, category: ACT) -> Duration {
+    let (id, default) = match category {
+        ACT::Scanner => ("scanner_plugins_timeout", SCANNER_NVT_TIMEOUT),
+        _ => ("plugins_timeout", NVT_TIMEOUT),
+    };
+    let seconds = scan
+        .scan_preferences
+        .iter()
+        .find(|x| x.id == id)
+        .and_then(|x| x.value.trim().parse::<u64>().ok())
+        .filter(|x| *x > 0)
+        .unwrap_or(default);
+    Duration::from_secs(seconds)
+}
+
 /// Runs a scan in a synchronous mode
 ///
 /// As a Scan is able to configure the behavior of scripts (e.g. consider_alive means that each
@@ -133,6 +159,48 @@ where
         Err(ExecuteError::Parameter(parameter.clone()))
     }
 
+    /// Returns the port a VT runs for
+    ///
+    /// It is the first required port of the VT that is open on the host. A required port is
+    /// either a port number that is stored as `Ports/tcp/<port>` or a KB item like
+    /// `Services/www` containing the ports of a service. Returns None when the VT does not
+    /// require a port or none of them is open.
+    fn required_port(&self, vt: &storage::item::Nvt) -> Result<Option<u16>, ExecuteError> {
+        let key = ContextKey::Scan(self.scan.scan_id.clone());
+        let kb_values = |name: String| -> Result<Vec<storage::types::Primitive>, ExecuteError> {
+            Ok(self
+                .storage
+                .retrieve(&key, Retrieve::KB(name))?
+                .filter_map(|x| match x {
+                    Field::KB(kb) => Some(kb.value),
+                    _ => None,
+                })
+                .collect())
+        };
+        for required in vt.required_ports.iter() {
+            let ports = match required.trim().parse::<u16>() {
+                Ok(port) => {
+                    if kb_values(format!("Ports/tcp/{port}"))?.is_empty() {
+                        continue;
+                    }
+                    vec![port]
+                }
+                Err(_) => kb_values(required.to_string())?
+                    .into_iter()
+                    .filter_map(|x| match x {
+                        storage::types::Primitive::Number(x) => u16::try_from(x).ok(),
+                        storage::types::Primitive::String(x) => x.trim().parse().ok(),
+                        _ => None,
+                    })
+                    .collect(),
+            };
+            if let Some(port) = ports.into_iter().next() {
+                return Ok(Some(port));
+            }
+        }
+        Ok(None)
+    }
+
     fn execute(
         &mut self,
         stage: crate::scheduling::Stage,
@@ -140,6 +208,7 @@ where
         param: Option<Vec<models::Parameter>>,
     ) -> Result<ScriptResult, ExecuteError> {
         let code = self.loader.load(&vt.filename)?;
+        let port = self.required_port(&vt)?;
         let target = match self.current_host {
             None => unreachable!("host check must be done in the iterator implementation"),
             Some(i) => self.scan.target.hosts[i].to_string(),
@@ -158,6 +227,7 @@ where
             oid = &vt.oid,
             %stage,
             target,
+            port,
         )
         .entered();
 
@@ -169,8 +239,14 @@ where
             self.loader,
             self.logger,
             self.executor,
-        );
+        )
+        .with_preferences(vt.preferences.clone());
         let mut interpret = crate::CodeInterpreter::new(&code, register, &context);
+        // the interpreter uses the timeout declared by the script
+        if interpret.timeout().is_none() {
+            interpret.set_timeout(Some(default_timeout(self.scan, vt.category)));
+        }
+        interpret.set_port(port);
         tracing::debug!("running");
         let kind = interpret
             .find_map(|r| match r {
@@ -309,6 +385,7 @@ where
     ///             oid: "0".to_string(),
     ///             parameters: vec![],
     ///         }],
+    ///     metadata: None,
     /// };
     /// let schedule = store
     ///   .execution_plan::<WaveExecutionPlan>(&scan)
@@ -385,6 +462,7 @@ where
     ///             oid: "0".to_string(),
     ///             parameters: vec![],
     ///         }],
+    ///     metadata: None,
     /// };
     /// let interpreter = SyncScanInterpreter::with_default_function_executor(
     ///        &store, &loader,
@@ -407,6 +485,10 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::ScriptResultKind;
+    use crate::{InterpretError, InterpretErrorKind};
 
     fn create_script(id: &str, rc: usize, dependencies: &[&str]) -> (String, storage::item::Nvt) {
         let mut dependencies = dependencies.iter().fold(String::default(), |acc, e| {
@@ -474,6 +556,7 @@ exit({rc});
                     parameters: vec![],
                 })
                 .collect(),
+            metadata: None,
         };
         let interpreter =
             super::SyncScanInterpreter::with_default_function_executor(&dispatcher, &loader);
@@ -485,4 +568,177 @@ exit({rc});
             .collect::<Vec<_>>();
         assert_eq!(result.len(), 3);
     }
+
+    #[test]
+    fn default_timeout() {
+        let mut scan = models::Scan::default();
+        let timeout = |scan: &models::Scan, category| super::default_timeout(scan, category);
+        assert_eq!(
+            timeout(&scan, nasl_syntax::ACT::GatherInfo),
+            Duration::from_secs(320)
+        );
+        assert_eq!(
+            timeout(&scan, nasl_syntax::ACT::Scanner),
+            Duration::from_secs(36000)
+        );
+        scan.scan_preferences = vec![
+            models::ScanPreference {
+                id: "plugins_timeout".to_string(),
+                value: "7".to_string(),
+            },
+            models::ScanPreference {
+                id: "scanner_plugins_timeout".to_string(),
+                value: "0".to_string(),
+            },
+        ];
+        assert_eq!(
+            timeout(&scan, nasl_syntax::ACT::GatherInfo),
+            Duration::from_secs(7)
+        );
+        assert_eq!(
+            timeout(&scan, nasl_syntax::ACT::Scanner),
+            Duration::from_secs(36000)
+        );
+    }
+
+    #[test]
+    fn script_timeout_bounds_execution() {
+        use storage::Dispatcher;
+        // the first script declares a short timeout, the second one uses the one of the scan
+        let declared = storage::item::Nvt {
+            preferences: vec![storage::item::NvtPreference {
+                id: Some(0),
+                class: storage::item::PreferenceType::Entry,
+                name: "timeout".to_string(),
+                default: "1".to_string(),
+            }],
+            ..create_script("0", 0, &[]).1
+        };
+        let scripts = [declared, create_script("1", 0, &[]).1];
+        let dispatcher = storage::DefaultDispatcher::new(true);
+        for nvt in scripts.iter() {
+            dispatcher
+                .dispatch(
+                    &storage::ContextKey::FileName(nvt.filename.clone()),
+                    storage::Field::NVT(storage::item::NVTField::Nvt(nvt.clone())),
+                )
+                .expect("sending");
+        }
+        let loader = |_: &str| "while (TRUE) usleep(100000);".to_string();
+        let mut scan = models::Scan {
+            scan_id: "sid".to_string(),
+            target: models::Target {
+                hosts: vec!["test.host".to_string()],
+                ..Default::default()
+            },
+            scan_preferences: vec![],
+            vts: vec![models::VT {
+                oid: "0".to_string(),
+                parameters: vec![],
+            }],
+            metadata: None,
+        };
+        let interpreter =
+            super::SyncScanInterpreter::with_default_function_executor(&dispatcher, &loader);
+        let run = |scan: &models::Scan| {
+            let start = Instant::now();
+            let result = interpreter
+                .run::<crate::scheduling::WaveExecutionPlan>(scan)
+                .expect("success")
+                .map(|x| x.expect("script result").kind)
+                .collect::<Vec<_>>();
+            (result, start.elapsed())
+        };
+        let is_timeout = |kind: &ScriptResultKind| {
+            matches!(
+                kind,
+                ScriptResultKind::Error(InterpretError {
+                    kind: InterpretErrorKind::Timeout(x),
+                    ..
+                }) if *x == Duration::from_secs(1)
+            )
+        };
+
+        scan.scan_preferences = vec![models::ScanPreference {
+            id: "plugins_timeout".to_string(),
+            value: "3600".to_string(),
+        }];
+        let (result, elapsed) = run(&scan);
+        assert!(matches!(&result[..], [x] if is_timeout(x)), "{result:?}");
+        assert!(elapsed < Duration::from_secs(10));
+
+        scan.vts[0].oid = "1".to_string();
+        scan.scan_preferences[0].value = "1".to_string();
+        let (result, elapsed) = run(&scan);
+        assert!(matches!(&result[..], [x] if is_timeout(x)), "{result:?}");
+        assert!(elapsed < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn port_of_required_ports() {
+        use storage::Dispatcher;
+        let script = |id: &str, required_ports: &[&str]| storage::item::Nvt {
+            required_ports: required_ports.iter().map(|x| x.to_string()).collect(),
+            ..create_script(id, 0, &[]).1
+        };
+        let scripts = [
+            script("0", &[]),
+            script("1", &["Services/www", "22"]),
+            script("2", &["Services/www", "22"]),
+            script("3", &["21"]),
+        ];
+        let dispatcher = storage::DefaultDispatcher::new(true);
+        for nvt in scripts.iter() {
+            dispatcher
+                .dispatch(
+                    &storage::ContextKey::FileName(nvt.filename.clone()),
+                    storage::Field::NVT(storage::item::NVTField::Nvt(nvt.clone())),
+                )
+                .expect("sending");
+        }
+        let set_kb = |key: &str, value: i64| {
+            dispatcher
+                .dispatch(
+                    &storage::ContextKey::Scan("sid".to_string()),
+                    storage::Field::KB(storage::Kb {
+                        key: key.to_string(),
+                        value: value.into(),
+                        expire: None,
+                    }),
+                )
+                .expect("sending");
+        };
+        set_kb("Ports/tcp/22", 1);
+        let loader =
+            |_: &str| "port = get_port(); if (isnull(port)) exit(-1); exit(port);".to_string();
+        let mut scan = models::Scan {
+            scan_id: "sid".to_string(),
+            target: models::Target {
+                hosts: vec!["test.host".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let interpreter =
+            super::SyncScanInterpreter::with_default_function_executor(&dispatcher, &loader);
+        let mut run = |oid: &str| {
+            scan.vts = vec![models::VT {
+                oid: oid.to_string(),
+                parameters: vec![],
+            }];
+            interpreter
+                .run::<crate::scheduling::WaveExecutionPlan>(&scan)
+                .expect("success")
+                .map(|x| match x.expect("script result").kind {
+                    ScriptResultKind::ReturnCode(x) => x,
+                    ScriptResultKind::Error(e) => panic!("unexpected error: {e}"),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run("0"), vec![-1]);
+        assert_eq!(run("1"), vec![22]);
+        set_kb("Services/www", 8080);
+        assert_eq!(run("2"), vec![8080]);
+        assert_eq!(run("3"), vec![-1]);
+    }
 }
@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Compares the results of the interpreter with the legacy engine
+//!
+//! The corpus within `tests/legacy` contains small snippets together with the value of their last
+//! statement as displayed by the legacy engine. Each divergence is reported with the snippet as
+//! well as both results, unless it is documented as known divergence within the corpus.
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use nasl_interpreter::*;
+
+    /// A snippet with the expected output of the legacy engine
+    struct Case {
+        file: String,
+        name: String,
+        code: String,
+        expected: String,
+        /// The output of this engine when it knowingly differs
+        divergence: Option<String>,
+    }
+
+    /// Parses the cases of a corpus file
+    ///
+    /// A case starts with a `# case: <name>` comment followed by the code and ends with the
+    /// `# expect: <output>` comment. It may be followed by a `# divergence: <output>` comment
+    /// documenting a known divergence. Comments outside of a case are ignored.
+    fn cases(path: &Path) -> Vec<Case> {
+        let file = path.file_name().unwrap().to_string_lossy().to_string();
+        let content = std::fs::read_to_string(path).unwrap();
+        let mut result = vec![];
+        let mut current: Option<(String, String)> = None;
+        for line in content.lines() {
+            if let Some(name) = line.strip_prefix("# case:") {
+                current = Some((name.trim().to_owned(), String::new()));
+            } else if let Some(expected) = line.strip_prefix("# expect:") {
+                let (name, code) = current
+                    .take()
+                    .unwrap_or_else(|| panic!("{file}: expect without case: {line}"));
+                result.push(Case {
+                    file: file.clone(),
+                    name,
+                    code,
+                    expected: expected.trim().to_owned(),
+                    divergence: None,
+                });
+            } else if let Some(divergence) = line.strip_prefix("# divergence:") {
+                let case = result
+                    .last_mut()
+                    .filter(|_| current.is_none())
+                    .unwrap_or_else(|| panic!("{file}: divergence without expect: {line}"));
+                case.divergence = Some(divergence.trim().to_owned());
+            } else if let Some((_, code)) = current.as_mut() {
+                code.push_str(line);
+                code.push('\n');
+            }
+        }
+        assert!(current.is_none(), "{file}: case without expect");
+        result
+    }
+
+    /// Returns the value of the last statement as displayed by the engine
+    fn run(code: &str) -> String {
+        let binding = ContextFactory::default();
+        let context = binding.build(Default::default(), Default::default());
+        let interpreter = CodeInterpreter::new(code, Register::default(), &context);
+        match interpreter.last() {
+            Some(Ok(x)) => x.to_string(),
+            Some(Err(e)) => format!("error: {e}"),
+            None => "nothing".to_owned(),
+        }
+    }
+
+    #[test]
+    fn corpus() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/legacy");
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|x| x.unwrap().path())
+            .filter(|x| x.extension().is_some_and(|x| x == "nasl"))
+            .collect();
+        files.sort();
+        let cases: Vec<_> = files.iter().flat_map(|x| cases(x)).collect();
+        assert!(!cases.is_empty());
+        let divergences: Vec<_> = cases
+            .iter()
+            .filter_map(|case| {
+                let actual = run(&case.code);
+                let reason = match &case.divergence {
+                    Some(_) if actual == case.expected => "the known divergence is fixed",
+                    Some(x) if x != &actual => "the known divergence changed",
+                    None if actual != case.expected => "diverges",
+                    _ => return None,
+                };
+                Some(format!(
+                    "{} - {} {reason}:\n{}legacy: {}\nrust:   {}\n",
+                    case.file, case.name, case.code, case.expected, actual
+                ))
+            })
+            .collect();
+        assert!(
+            divergences.is_empty(),
+            "{} of {} cases differ from the corpus:\n\n{}",
+            divergences.len(),
+            cases.len(),
+            divergences.join("\n")
+        );
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Generates the documentation of the builtin functions from the manual
+//!
+//! The manual is not part of the crate, without it the builtin functions are known but not
+//! documented. The location can be overridden via the environment variable `NASL_MANUAL`.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// The location of the manual relative to the crate within the repository
+const MANUAL: &str = "../../doc/manual/nasl/built-in-functions";
+
+/// Returns each markdown file describing a single function
+fn pages(dir: &Path, result: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|x| x.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            pages(&path, result);
+        } else if path.extension().is_some_and(|x| x == "md")
+            && path.file_name().is_some_and(|x| x != "index.md")
+        {
+            result.push(path);
+        }
+    }
+}
+
+/// Returns the name, signature, summary and description of the function described in page
+fn describe(page: &str) -> Option<(String, String, String, String)> {
+    let name = page.lines().next()?.strip_prefix("# ")?.trim().to_owned();
+    let bold = format!("**{name}**");
+    let signature = page
+        .lines()
+        .find(|x| x.contains(&format!("{bold}(")))?
+        .replace('*', "");
+    let summary = page
+        .lines()
+        .find_map(|x| x.strip_prefix(&format!("{bold} - ")))
+        .unwrap_or_default()
+        .trim()
+        .to_owned();
+    let description = page
+        .split_once("## DESCRIPTION")
+        .map(|(_, x)| x.trim_start())
+        .and_then(|x| x.split("\n\n").next())
+        .filter(|x| !x.starts_with('#'))
+        .unwrap_or_default()
+        .trim()
+        .to_owned();
+    Some((name, signature.trim().to_owned(), summary, description))
+}
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=NASL_MANUAL");
+    let manual = match std::env::var_os("NASL_MANUAL") {
+        Some(x) => PathBuf::from(x),
+        None => PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join(MANUAL),
+    };
+    println!("cargo:rerun-if-changed={}", manual.display());
+    let mut files = vec![];
+    pages(&manual, &mut files);
+    files.sort();
+    let mut builtins = BTreeMap::new();
+    for file in files {
+        println!("cargo:rerun-if-changed={}", file.display());
+        let Ok(page) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        if let Some((name, signature, summary, description)) = describe(&page) {
+            builtins
+                .entry(name)
+                .or_insert((signature, summary, description));
+        }
+    }
+    let mut code = String::from("&[\n");
+    for (name, (signature, summary, description)) in builtins {
+        writeln!(
+            code,
+            "    Documentation {{ name: {name:?}, signature: {signature:?}, summary: {summary:?}, description: {description:?} }},"
+        )
+        .unwrap();
+    }
+    code.push(']');
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("builtins.rs");
+    std::fs::write(out, code).unwrap();
+}
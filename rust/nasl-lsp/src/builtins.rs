@@ -0,0 +1,141 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Contains the builtin functions and their documentation
+//!
+//! A function is a builtin when the interpreter defines it. The documentation is generated from
+//! the manual in `doc/manual/nasl/built-in-functions` at build time, when the manual is not
+//! available the builtin functions are known but not documented.
+
+use nasl_interpreter::{
+    interpreter_fn_defined, nasl_std_functions, NaslFunctionExecuter, NaslFunctionRegister,
+};
+use nasl_syntax::{IdentifierType, TokenCategory, Tokenizer};
+use serde_json::{json, Value};
+
+use crate::document::Document;
+
+/// The documentation of a builtin function
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Documentation {
+    /// The name of the function
+    pub name: &'static str,
+    /// The signature, e.g. `int strlen(0: string);`
+    pub signature: &'static str,
+    /// A single line describing the function
+    pub summary: &'static str,
+    /// The first paragraph of the description
+    pub description: &'static str,
+}
+
+/// The documentation of the builtin functions sorted by name
+pub const DOCUMENTATION: &[Documentation] = include!(concat!(env!("OUT_DIR"), "/builtins.rs"));
+
+thread_local! {
+    static FUNCTIONS: NaslFunctionRegister = nasl_std_functions();
+}
+
+/// A builtin function of the interpreter
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Builtin {
+    /// The name of the function
+    pub name: String,
+    /// The documentation of the function, None when the manual does not describe it
+    pub documentation: Option<&'static Documentation>,
+}
+
+/// Returns the builtin function called name
+pub fn builtin(name: &str) -> Option<Builtin> {
+    let defined = interpreter_fn_defined(name) || FUNCTIONS.with(|x| x.nasl_fn_defined(name));
+    defined.then(|| Builtin {
+        name: name.to_owned(),
+        documentation: DOCUMENTATION
+            .binary_search_by(|x| x.name.cmp(name))
+            .ok()
+            .map(|x| &DOCUMENTATION[x]),
+    })
+}
+
+/// Returns the documented builtin functions sorted by name
+///
+/// Functions that are described in the manual but not defined by the interpreter are skipped.
+pub fn documented() -> impl Iterator<Item = Builtin> {
+    DOCUMENTATION.iter().filter_map(|x| builtin(x.name))
+}
+
+impl Builtin {
+    /// Returns the documentation as markdown or only the name when it is not documented
+    pub fn markdown(&self) -> String {
+        match self.documentation {
+            Some(x) => x.markdown(),
+            None => format!(
+                "```nasl\n{}\n```\n\nbuiltin function without documentation",
+                self.name
+            ),
+        }
+    }
+}
+
+impl Documentation {
+    /// Returns the documentation as markdown
+    pub fn markdown(&self) -> String {
+        let mut result = format!("```nasl\n{}\n```", self.signature);
+        for text in [self.summary, self.description] {
+            if !text.is_empty() {
+                result.push_str("\n\n");
+                result.push_str(text);
+            }
+        }
+        result
+    }
+}
+
+/// Returns the hover of the builtin function whose name is at the byte offset
+///
+/// Returns None when there is no identifier at the offset or it is not a builtin function. A
+/// builtin function that is not described in the manual is shown without documentation.
+pub fn hover(document: &Document, offset: usize) -> Option<Value> {
+    let token = Tokenizer::new(document.text())
+        .find(|x| x.position.0 <= offset && offset <= x.position.1)?;
+    let TokenCategory::Identifier(IdentifierType::Undefined(name)) = token.category() else {
+        return None;
+    };
+    let builtin = builtin(name)?;
+    Some(json!({
+        "contents": { "kind": "markdown", "value": builtin.markdown() },
+        "range": document.range(token.position.0, token.position.1),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{builtin, documented, DOCUMENTATION};
+
+    #[test]
+    fn generated_from_manual() {
+        assert!(DOCUMENTATION.len() > 100);
+        assert!(DOCUMENTATION.windows(2).all(|x| x[0].name < x[1].name));
+        let strlen = builtin("strlen").unwrap().documentation.unwrap();
+        assert_eq!(strlen.signature, "int strlen(0: string);");
+        assert_eq!(strlen.summary, "get the length of a string");
+        assert!(strlen
+            .description
+            .starts_with("This function calculates the length"));
+        assert_eq!(builtin("undefined_function"), None);
+    }
+
+    #[test]
+    fn defined_by_interpreter() {
+        for name in [
+            "get_port",
+            "array_map",
+            "eval_arithmetic",
+            "get_time_remaining",
+        ] {
+            assert_eq!(builtin(name).map(|x| x.name), Some(name.to_owned()));
+        }
+        assert!(documented().all(|x| x.documentation.is_some()));
+        assert!(documented().any(|x| x.name == "strlen"));
+    }
+}
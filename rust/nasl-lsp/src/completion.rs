@@ -0,0 +1,158 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Completes builtin functions, keywords and the variables in scope
+
+use std::{collections::BTreeMap, ops::Range};
+
+use nasl_syntax::{IdentifierType, Statement, StatementKind, Token, TokenCategory};
+use serde_json::{json, Value};
+
+use crate::{builtins::documented, document::Document};
+
+/// The kind of a completion item as defined by the language server protocol
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Function = 3,
+    Variable = 6,
+    Keyword = 14,
+}
+
+fn identifier(token: &Token) -> Option<&str> {
+    match token.category() {
+        TokenCategory::Identifier(IdentifierType::Undefined(x)) => Some(x),
+        _ => None,
+    }
+}
+
+/// Returns the identifier characters directly before the byte offset
+fn prefix(text: &str, offset: usize) -> &str {
+    let before = text.get(..offset).unwrap_or_default();
+    let start = before
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map(|x| x + 1)
+        .unwrap_or(0);
+    &before[start..]
+}
+
+/// Returns the variables and functions that are declared before the byte offset
+///
+/// Variables declared within a function are only in scope within that function, the parameters
+/// of a function are in scope within the whole body of the function.
+fn in_scope(statements: &[Statement], offset: usize) -> Vec<(String, Kind)> {
+    let mut excluded: Vec<Range<usize>> = vec![];
+    let mut result = vec![];
+    for statement in statements {
+        statement.walk(&mut |s| {
+            if let StatementKind::FunctionDeclaration(f) = s.kind() {
+                result.extend(identifier(&f.name).map(|x| (x.to_owned(), Kind::Function)));
+                if f.body.range().contains(&offset) {
+                    for parameter in f.parameters.children() {
+                        let name = identifier(parameter.as_token());
+                        result.extend(name.map(|x| (x.to_owned(), Kind::Variable)));
+                    }
+                } else {
+                    excluded.push(f.body.range());
+                }
+            }
+        });
+    }
+    for statement in statements {
+        statement.walk(&mut |s| {
+            let start = s.as_token().position.0;
+            if start >= offset || excluded.iter().any(|x| x.contains(&start)) {
+                return;
+            }
+            let tokens = match s.kind() {
+                StatementKind::Assign(_, _, target, _) => vec![target.as_token()],
+                StatementKind::Declare(x) => x.iter().map(|x| x.as_token()).collect(),
+                StatementKind::ForEach(x) => vec![&x.variable],
+                _ => vec![],
+            };
+            for token in tokens {
+                result.extend(identifier(token).map(|x| (x.to_owned(), Kind::Variable)));
+            }
+        });
+    }
+    result
+}
+
+/// Returns the completion items for the identifier before the byte offset
+///
+/// The candidates are the variables and functions in scope, the keywords and the documented
+/// builtin functions starting with the identifier.
+pub fn completion(document: &Document, offset: usize) -> Value {
+    let prefix = prefix(document.text(), offset);
+    let statements: Vec<Statement> = nasl_syntax::parse(document.text())
+        .filter_map(|x| x.ok())
+        .collect();
+    let mut candidates: BTreeMap<String, (Kind, Option<&str>)> = BTreeMap::new();
+    for builtin in documented() {
+        let signature = builtin.documentation.map(|x| x.signature);
+        candidates.insert(builtin.name, (Kind::Function, signature));
+    }
+    for keyword in IdentifierType::keywords() {
+        candidates.insert(keyword.to_string(), (Kind::Keyword, None));
+    }
+    for (name, kind) in in_scope(&statements, offset) {
+        candidates.entry(name).or_insert((kind, None));
+    }
+    let items: Vec<Value> = candidates
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(prefix) && name != prefix)
+        .map(|(label, (kind, detail))| {
+            let mut item = json!({ "label": label, "kind": kind as u8 });
+            if let Some(detail) = detail {
+                item["detail"] = detail.into();
+            }
+            item
+        })
+        .collect();
+    json!({ "isIncomplete": false, "items": items })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{in_scope, prefix};
+
+    #[test]
+    fn prefix_before_cursor() {
+        assert_eq!(prefix("a = str", 7), "str");
+        assert_eq!(prefix("a = str", 5), "s");
+        assert_eq!(prefix("a = ", 4), "");
+        assert_eq!(prefix("my_var1", 7), "my_var1");
+    }
+
+    #[test]
+    fn scope() {
+        let code = r#"
+        global_var g;
+        a = 1;
+        function f(p) {
+            local_var l;
+            x = p;
+            return x;
+        }
+        foreach item (make_list(1)) b = item;
+        c = 2;
+        "#;
+        let statements: Vec<_> = nasl_syntax::parse(code).map(|x| x.unwrap()).collect();
+        let names = |offset| {
+            let mut result: Vec<_> = in_scope(&statements, offset)
+                .into_iter()
+                .map(|(name, kind)| format!("{name}:{}", kind as u8))
+                .collect();
+            result.sort();
+            result
+        };
+        assert_eq!(
+            names(code.find("return").unwrap()),
+            ["a:6", "f:3", "g:6", "l:6", "p:6", "x:6"]
+        );
+        assert_eq!(
+            names(code.find("c = 2").unwrap()),
+            ["a:6", "b:6", "f:3", "g:6", "item:6"]
+        );
+    }
+}
@@ -0,0 +1,222 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Resolves the definition of user defined functions and included files
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use nasl_syntax::{
+    FSPluginLoader, IdentifierType, Loader, Statement, StatementKind, Token, TokenCategory,
+};
+use serde_json::{json, Value};
+
+use crate::document::Document;
+
+/// Converts a `file://` uri into a path
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .and_then(|x| u8::from_str_radix(x, 16).ok());
+        match escaped {
+            Some(b) => {
+                result.push(b);
+                i += 3;
+            }
+            None => {
+                result.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(result).ok().map(PathBuf::from)
+}
+
+/// Converts a path into a `file://` uri
+pub fn path_to_uri(path: &Path) -> String {
+    let mut result = "file://".to_owned();
+    for b in path.to_string_lossy().bytes() {
+        match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                result.push(b as char)
+            }
+            b => result.push_str(&format!("%{b:02X}")),
+        }
+    }
+    result
+}
+
+/// Returns the name of the included file of an include statement
+fn include_name(statement: &Statement) -> Option<String> {
+    let StatementKind::Include(x) = statement.kind() else {
+        return None;
+    };
+    match x.as_token().category() {
+        TokenCategory::String(x) => Some(x.clone()),
+        TokenCategory::Data(x) => Some(x.iter().map(|&b| b as char).collect()),
+        _ => None,
+    }
+}
+
+/// Returns the name of an identifier token that is not a keyword
+fn identifier(token: &Token) -> Option<&str> {
+    match token.category() {
+        TokenCategory::Identifier(IdentifierType::Undefined(x)) => Some(x),
+        _ => None,
+    }
+}
+
+fn contains(token: &Token, offset: usize) -> bool {
+    token.position.0 <= offset && offset <= token.position.1
+}
+
+/// What is at the position of the cursor
+enum Target {
+    /// The name of a called function
+    Call(String),
+    /// The name of an included file
+    Include(String),
+}
+
+/// Returns the called function or the included file at the given byte offset
+fn target(statements: &[Statement], offset: usize) -> Option<Target> {
+    let mut result = None;
+    for statement in statements {
+        statement.walk(&mut |s| match s.kind() {
+            StatementKind::Call(_) if contains(s.as_token(), offset) => {
+                if let Some(name) = identifier(s.as_token()) {
+                    result = Some(Target::Call(name.to_owned()));
+                }
+            }
+            StatementKind::Include(x)
+                if contains(s.as_token(), offset) || contains(x.as_token(), offset) =>
+            {
+                result = include_name(s).map(Target::Include);
+            }
+            _ => {}
+        });
+    }
+    result
+}
+
+/// Returns the name token of the declaration of the function called name
+fn declaration<'a>(statements: &'a [Statement], name: &str) -> Option<&'a Token> {
+    let mut result = None;
+    for statement in statements {
+        statement.walk(&mut |s| {
+            if let StatementKind::FunctionDeclaration(f) = s.kind() {
+                if result.is_none() && identifier(&f.name) == Some(name) {
+                    result = Some(&f.name);
+                }
+            }
+        });
+    }
+    result
+}
+
+fn parse(code: &str) -> Vec<Statement> {
+    nasl_syntax::parse(code).filter_map(|x| x.ok()).collect()
+}
+
+fn location(uri: &str, document: &Document, token: &Token) -> Value {
+    json!({ "uri": uri, "range": document.range(token.position.0, token.position.1) })
+}
+
+/// Resolves definitions within a document and the files it includes
+pub struct Resolver {
+    loader: FSPluginLoader<PathBuf>,
+}
+
+impl Resolver {
+    /// Creates a resolver that loads included files relative to the given root
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            loader: FSPluginLoader::new(root),
+        }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.loader.root().join(name)
+    }
+
+    /// Searches the declaration of the function in the included files and their includes
+    fn included_declaration(
+        &self,
+        statements: &[Statement],
+        name: &str,
+        visited: &mut HashSet<String>,
+    ) -> Option<Value> {
+        let includes: Vec<String> = statements
+            .iter()
+            .flat_map(|x| x.find(&|s| matches!(s.kind(), StatementKind::Include(_))))
+            .filter_map(include_name)
+            .collect();
+        for include in includes {
+            if !visited.insert(include.clone()) {
+                continue;
+            }
+            let Ok(code) = self.loader.load(&include) else {
+                continue;
+            };
+            let included = parse(&code);
+            if let Some(token) = declaration(&included, name) {
+                let document = Document::new(code, 0);
+                let uri = path_to_uri(&self.path(&include));
+                return Some(location(&uri, &document, token));
+            }
+            if let Some(x) = self.included_declaration(&included, name, visited) {
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    /// Returns the location of the definition at the byte offset within the document
+    ///
+    /// For a call of a user defined function it is the name of the function declaration within
+    /// the document or an included file; for an include it is the start of the included file.
+    pub fn definition(&self, uri: &str, document: &Document, offset: usize) -> Option<Value> {
+        let statements = parse(document.text());
+        match target(&statements, offset)? {
+            Target::Call(name) => match declaration(&statements, &name) {
+                Some(token) => Some(location(uri, document, token)),
+                None => self.included_declaration(&statements, &name, &mut HashSet::new()),
+            },
+            Target::Include(name) => {
+                let path = self.path(&name);
+                path.is_file().then(|| {
+                    json!({
+                        "uri": path_to_uri(&path),
+                        "range": Document::default().range(0, 0),
+                    })
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{path_to_uri, uri_to_path};
+
+    #[test]
+    fn uri_conversion() {
+        let path = Path::new("/tmp/my feed/ä.nasl");
+        let uri = path_to_uri(path);
+        assert_eq!(uri, "file:///tmp/my%20feed/%C3%A4.nasl");
+        assert_eq!(uri_to_path(&uri).unwrap(), path);
+        assert_eq!(uri_to_path("untitled:1"), None);
+    }
+}
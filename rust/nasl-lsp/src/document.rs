@@ -0,0 +1,305 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Contains the state of an opened document
+
+use nasl_syntax::{css_class, SyntaxError, Tokenizer};
+use serde_json::{json, Value};
+
+/// The token types of the semantic tokens in the order of their index
+pub const TOKEN_TYPES: [&str; 6] = [
+    "keyword", "variable", "string", "number", "comment", "operator",
+];
+
+/// Returns the index within [TOKEN_TYPES] of the given highlighting class
+fn token_type(class: &str) -> Option<u32> {
+    match class {
+        "keyword" => Some(0),
+        "identifier" => Some(1),
+        "string" => Some(2),
+        "number" => Some(3),
+        "comment" => Some(4),
+        "operator" => Some(5),
+        // punctuation and faulty tokens are left to the editor
+        _ => None,
+    }
+}
+
+/// A position within a document as used by the language server protocol
+///
+/// Both line and character start at 0; the character is counted in UTF-16 code units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    /// The line starting at 0
+    pub line: u32,
+    /// The UTF-16 code unit offset within the line
+    pub character: u32,
+}
+
+impl Position {
+    /// Reads a position from its JSON representation
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            line: value.get("line")?.as_u64()? as u32,
+            character: value.get("character")?.as_u64()? as u32,
+        })
+    }
+
+    /// Returns the JSON representation of the position
+    pub fn to_json(self) -> Value {
+        json!({ "line": self.line, "character": self.character })
+    }
+}
+
+/// Maps byte offsets to line and column based positions and back
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineIndex {
+    /// The byte offset of the start of each line
+    starts: Vec<usize>,
+}
+
+impl Default for LineIndex {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl LineIndex {
+    /// Creates the index of the given text
+    pub fn new(text: &str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self { starts }
+    }
+
+    /// Returns the position of the byte offset within text
+    ///
+    /// Offsets after the end of the text are mapped to the end of the text.
+    pub fn position(&self, text: &str, offset: usize) -> Position {
+        let mut offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.starts.partition_point(|&x| x <= offset) - 1;
+        let character = text[self.starts[line]..offset].encode_utf16().count();
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    /// Returns the byte offset of the position within text
+    ///
+    /// Characters after the end of a line are mapped to the end of that line, lines after the
+    /// last line to the end of the text.
+    pub fn offset(&self, text: &str, position: Position) -> usize {
+        let start = match self.starts.get(position.line as usize) {
+            Some(x) => *x,
+            None => return text.len(),
+        };
+        let line = &text[start..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        let mut units = 0;
+        for (i, c) in line.char_indices() {
+            if units >= position.character as usize {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        start + line.len()
+    }
+}
+
+/// A text document opened by the client
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Document {
+    text: String,
+    version: i64,
+    lines: LineIndex,
+}
+
+impl Document {
+    /// Creates a document with the given content
+    pub fn new(text: String, version: i64) -> Self {
+        let lines = LineIndex::new(&text);
+        Self {
+            text,
+            version,
+            lines,
+        }
+    }
+
+    /// Returns the current content
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the version of the last change
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Applies a change of the client
+    ///
+    /// When range is None the whole content is replaced, otherwise only the text between start
+    /// and end.
+    pub fn change(&mut self, range: Option<(Position, Position)>, text: &str, version: i64) {
+        match range {
+            Some((start, end)) => {
+                let start = self.lines.offset(&self.text, start);
+                let end = self.lines.offset(&self.text, end).max(start);
+                self.text.replace_range(start..end, text);
+            }
+            None => self.text = text.to_owned(),
+        }
+        self.lines = LineIndex::new(&self.text);
+        self.version = version;
+    }
+
+    /// Returns the byte offset of the position
+    pub fn offset(&self, position: Position) -> usize {
+        self.lines.offset(&self.text, position)
+    }
+
+    /// Returns the range of the byte offsets as JSON
+    pub(crate) fn range(&self, start: usize, end: usize) -> Value {
+        json!({
+            "start": self.lines.position(&self.text, start).to_json(),
+            "end": self.lines.position(&self.text, end).to_json(),
+        })
+    }
+
+    /// Returns a diagnostic of the error
+    ///
+    /// Errors without a location, e.g. an unexpected end, are reported at the end of the text.
+    fn diagnostic(&self, error: &SyntaxError) -> Value {
+        let range = error.range().unwrap_or(self.text.len()..self.text.len());
+        json!({
+            "range": self.range(range.start, range.end),
+            "severity": 1,
+            "code": error.kind().code(),
+            "source": "nasl",
+            "message": error.to_string(),
+        })
+    }
+
+    /// Returns the syntax errors as diagnostics of the language server protocol
+    pub fn diagnostics(&self) -> Vec<Value> {
+        nasl_syntax::parse(&self.text)
+            .filter_map(|x| x.err())
+            .map(|e| self.diagnostic(&e))
+            .collect()
+    }
+
+    /// Returns the encoded semantic tokens of the document
+    ///
+    /// Each token is encoded as five numbers: the line relative to the previous token, the
+    /// start character relative to the previous token when in the same line, the length, the
+    /// index of the type within [TOKEN_TYPES] and the modifiers. Tokens spanning multiple lines
+    /// are split into one token per line.
+    pub fn semantic_tokens(&self) -> Vec<u32> {
+        let mut result = vec![];
+        let mut previous = Position::default();
+        for token in Tokenizer::new(&self.text) {
+            let token_type = match token_type(css_class(&token)) {
+                Some(x) => x,
+                None => continue,
+            };
+            let mut start = token.position.0;
+            for part in token.lexeme(&self.text).split_inclusive('\n') {
+                let length = part.trim_end_matches(['\r', '\n']).encode_utf16().count() as u32;
+                let position = self.lines.position(&self.text, start);
+                start += part.len();
+                if length == 0 {
+                    continue;
+                }
+                let delta_start = if position.line == previous.line {
+                    position.character - previous.character
+                } else {
+                    position.character
+                };
+                result.extend([
+                    position.line - previous.line,
+                    delta_start,
+                    length,
+                    token_type,
+                    0,
+                ]);
+                previous = position;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_index() {
+        let text = "a = 1;\nb = \"ä𝄞\";\n";
+        let lines = LineIndex::new(text);
+        let semicolon = text.rfind(';').unwrap();
+        let position = lines.position(text, semicolon);
+        assert_eq!(
+            position,
+            Position {
+                line: 1,
+                character: 9
+            }
+        );
+        assert_eq!(lines.offset(text, position), semicolon);
+        assert_eq!(
+            lines.offset(
+                text,
+                Position {
+                    line: 0,
+                    character: 100
+                }
+            ),
+            6
+        );
+        assert_eq!(
+            lines.offset(
+                text,
+                Position {
+                    line: 5,
+                    character: 0
+                }
+            ),
+            text.len()
+        );
+    }
+
+    #[test]
+    fn incremental_change() {
+        let mut document = Document::new("a = 1;\nb = 2;\n".to_owned(), 1);
+        let at = |line, character| Position { line, character };
+        document.change(Some((at(1, 4), at(1, 5))), "a + 1", 2);
+        assert_eq!(document.text(), "a = 1;\nb = a + 1;\n");
+        document.change(Some((at(2, 0), at(2, 0))), "c;", 3);
+        assert_eq!(document.text(), "a = 1;\nb = a + 1;\nc;");
+        document.change(None, "d;", 4);
+        assert_eq!(document.text(), "d;");
+        assert_eq!(document.version(), 4);
+    }
+
+    #[test]
+    fn semantic_tokens() {
+        let document = Document::new("if (a)\n  b = \"x\ny\"; # c".to_owned(), 1);
+        assert_eq!(
+            document.semantic_tokens(),
+            vec![
+                0, 0, 2, 0, 0, // if
+                0, 4, 1, 1, 0, // a
+                1, 2, 1, 1, 0, // b
+                0, 2, 1, 5, 0, // =
+                0, 2, 2, 2, 0, // "x
+                1, 0, 2, 2, 0, // y"
+                0, 4, 3, 4, 0, // # c
+            ]
+        );
+    }
+}
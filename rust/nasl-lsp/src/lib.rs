@@ -0,0 +1,18 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+#![doc = include_str!("../README.md")]
+#![warn(missing_docs)]
+mod builtins;
+mod completion;
+mod definition;
+mod document;
+mod protocol;
+mod server;
+
+pub use builtins::{builtin, documented, Builtin, Documentation, DOCUMENTATION};
+pub use definition::{path_to_uri, uri_to_path};
+pub use document::{Document, LineIndex, Position, TOKEN_TYPES};
+pub use protocol::{read_message, write_message};
+pub use server::Server;
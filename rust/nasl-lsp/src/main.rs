@@ -0,0 +1,14 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+use nasl_lsp::Server;
+
+fn main() {
+    let mut server = Server::default();
+    if let Err(e) = server.run(std::io::stdin().lock(), std::io::stdout().lock()) {
+        eprintln!("nasl-lsp: {e}");
+        std::process::exit(1);
+    }
+    std::process::exit(server.exit_code());
+}
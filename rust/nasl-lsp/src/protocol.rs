@@ -0,0 +1,56 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Reads and writes the base protocol messages, a `Content-Length` header followed by JSON
+
+use std::io::{self, BufRead, Write};
+
+use serde_json::Value;
+
+fn invalid_data(reason: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.into())
+}
+
+/// Reads the next message
+///
+/// Returns None when the reader is at the end before a new message starts. Content that is not
+/// valid JSON is returned as inner error, as the next message can still be read.
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<serde_json::Result<Value>>> {
+    let mut length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return match length {
+                None => Ok(None),
+                Some(_) => Err(invalid_data("unexpected end within header")),
+            };
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|e| invalid_data(e.to_string()))?,
+                );
+            }
+        }
+    }
+    let length = length.ok_or_else(|| invalid_data("missing Content-Length header"))?;
+    let mut content = vec![0; length];
+    reader.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)))
+}
+
+/// Writes the message including its header and flushes the writer
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    writer.flush()
+}
@@ -0,0 +1,283 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Handles the messages of a language client
+
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    builtins, completion,
+    definition::{uri_to_path, Resolver},
+    document::{Document, Position, TOKEN_TYPES},
+    protocol::{read_message, write_message},
+};
+
+/// The request method is not supported
+const METHOD_NOT_FOUND: i64 = -32601;
+/// The parameters of a request are invalid, e.g. an unknown document
+const INVALID_PARAMS: i64 = -32602;
+/// A request was sent after shutdown
+const INVALID_REQUEST: i64 = -32600;
+/// The content of a message is not valid JSON
+const PARSE_ERROR: i64 = -32700;
+
+/// A language server for NASL
+///
+/// It keeps the opened documents in memory, publishes the syntax errors of a document as
+/// diagnostics on each change and provides semantic tokens for highlighting.
+///
+/// Included files are resolved relative to the root folder sent by the client on initialize;
+/// without it relative to the folder of the including document.
+#[derive(Debug, Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    root: Option<PathBuf>,
+    shutdown: bool,
+    exit: bool,
+}
+
+/// Returns the uri of the text document within the params
+fn uri(params: &Value) -> Option<&str> {
+    params.get("textDocument")?.get("uri")?.as_str()
+}
+
+fn response(id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error(id: &Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+impl Server {
+    /// Returns true after the client sent `exit`
+    pub fn exited(&self) -> bool {
+        self.exit
+    }
+
+    /// Returns the exit code of the process, 0 when the client sent `shutdown` before `exit`
+    pub fn exit_code(&self) -> i32 {
+        if self.shutdown {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Returns the document of the given uri
+    pub fn document(&self, uri: &str) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+
+    fn capabilities() -> Value {
+        json!({
+            "capabilities": {
+                "textDocumentSync": { "openClose": true, "change": 2 },
+                "definitionProvider": true,
+                "hoverProvider": true,
+                "completionProvider": {},
+                "semanticTokensProvider": {
+                    "legend": { "tokenTypes": TOKEN_TYPES, "tokenModifiers": [] },
+                    "full": true
+                }
+            },
+            "serverInfo": { "name": "nasl-lsp", "version": env!("CARGO_PKG_VERSION") }
+        })
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Value {
+        let (diagnostics, version) = match self.documents.get(uri) {
+            Some(x) => (x.diagnostics(), Some(x.version())),
+            None => (vec![], None),
+        };
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "version": version, "diagnostics": diagnostics }
+        })
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        self.root = params
+            .get("rootUri")
+            .and_then(|x| x.as_str())
+            .and_then(uri_to_path)
+            .or_else(|| params.get("rootPath")?.as_str().map(PathBuf::from));
+        Self::capabilities()
+    }
+
+    /// Returns the uri, the document and the position of a text document position request
+    fn document_position<'a>(
+        &'a self,
+        id: &Value,
+        params: &'a Value,
+    ) -> Result<(&'a str, &'a Document, Position), Value> {
+        let document = uri(params).and_then(|x| Some((x, self.documents.get(x)?)));
+        match (
+            document,
+            params.get("position").and_then(Position::from_json),
+        ) {
+            (Some((uri, document)), Some(position)) => Ok((uri, document, position)),
+            _ => Err(error(
+                id,
+                INVALID_PARAMS,
+                "unknown document or position".to_owned(),
+            )),
+        }
+    }
+
+    fn hover(&self, id: &Value, params: &Value) -> Value {
+        match self.document_position(id, params) {
+            Ok((_, document, position)) => response(
+                id,
+                builtins::hover(document, document.offset(position)).unwrap_or_default(),
+            ),
+            Err(e) => e,
+        }
+    }
+
+    fn completion(&self, id: &Value, params: &Value) -> Value {
+        match self.document_position(id, params) {
+            Ok((_, document, position)) => response(
+                id,
+                completion::completion(document, document.offset(position)),
+            ),
+            Err(e) => e,
+        }
+    }
+
+    fn definition(&self, id: &Value, params: &Value) -> Value {
+        let (uri, document, position) = match self.document_position(id, params) {
+            Ok(x) => x,
+            Err(e) => return e,
+        };
+        let root = self.root.clone().unwrap_or_else(|| {
+            uri_to_path(uri)
+                .and_then(|x| x.parent().map(|x| x.to_path_buf()))
+                .unwrap_or_default()
+        });
+        let location = Resolver::new(root).definition(uri, document, document.offset(position));
+        response(id, location.unwrap_or_default())
+    }
+
+    fn did_open(&mut self, params: &Value) -> Option<String> {
+        let document = params.get("textDocument")?;
+        let text = document.get("text")?.as_str()?;
+        let version = document.get("version").and_then(|x| x.as_i64());
+        let uri = uri(params)?.to_owned();
+        self.documents.insert(
+            uri.clone(),
+            Document::new(text.to_owned(), version.unwrap_or_default()),
+        );
+        Some(uri)
+    }
+
+    fn did_change(&mut self, params: &Value) -> Option<String> {
+        let uri = uri(params)?;
+        let version = params
+            .get("textDocument")?
+            .get("version")
+            .and_then(|x| x.as_i64());
+        let document = self.documents.get_mut(uri)?;
+        let version = version.unwrap_or(document.version());
+        for change in params.get("contentChanges")?.as_array()? {
+            let text = change.get("text")?.as_str()?;
+            let range = match change.get("range") {
+                Some(range) => Some((
+                    Position::from_json(range.get("start")?)?,
+                    Position::from_json(range.get("end")?)?,
+                )),
+                None => None,
+            };
+            document.change(range, text, version);
+        }
+        Some(uri.to_owned())
+    }
+
+    fn did_close(&mut self, params: &Value) -> Option<String> {
+        let uri = uri(params)?;
+        self.documents.remove(uri);
+        Some(uri.to_owned())
+    }
+
+    fn request(&mut self, id: &Value, method: &str, params: &Value) -> Value {
+        if self.shutdown {
+            return error(id, INVALID_REQUEST, format!("{method} after shutdown"));
+        }
+        match method {
+            "initialize" => response(id, self.initialize(params)),
+            "shutdown" => {
+                self.shutdown = true;
+                response(id, Value::Null)
+            }
+            "textDocument/semanticTokens/full" => {
+                match uri(params).and_then(|x| self.documents.get(x)) {
+                    Some(document) => response(id, json!({ "data": document.semantic_tokens() })),
+                    None => error(id, INVALID_PARAMS, "unknown document".to_owned()),
+                }
+            }
+            "textDocument/definition" => self.definition(id, params),
+            "textDocument/hover" => self.hover(id, params),
+            "textDocument/completion" => self.completion(id, params),
+            _ => error(id, METHOD_NOT_FOUND, format!("{method} is not supported")),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let changed = match method {
+            "exit" => {
+                self.exit = true;
+                None
+            }
+            "textDocument/didOpen" => self.did_open(params),
+            "textDocument/didChange" => self.did_change(params),
+            "textDocument/didClose" => self.did_close(params),
+            // e.g. initialized, cancellations or configuration changes
+            _ => None,
+        };
+        changed
+            .map(|uri| vec![self.publish_diagnostics(&uri)])
+            .unwrap_or_default()
+    }
+
+    /// Handles a message of the client and returns the messages to send back
+    ///
+    /// Responses of the client to requests of the server are ignored.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = match message.get("method").and_then(|x| x.as_str()) {
+            Some(x) => x,
+            None => return vec![],
+        };
+        let params = message.get("params").unwrap_or(&Value::Null);
+        match message.get("id") {
+            Some(id) => vec![self.request(id, method, params)],
+            None => self.notification(method, params),
+        }
+    }
+
+    /// Handles messages of the reader and writes the answers to the writer until the client
+    /// sent `exit` or the reader is at the end.
+    ///
+    /// A message that is not valid JSON is answered with a parse error, only errors of the
+    /// reader or writer end it.
+    pub fn run<R: BufRead, W: Write>(&mut self, mut reader: R, mut writer: W) -> io::Result<()> {
+        while !self.exit {
+            let answers = match read_message(&mut reader)? {
+                Some(Ok(message)) => self.handle(&message),
+                Some(Err(e)) => vec![error(&Value::Null, PARSE_ERROR, e.to_string())],
+                None => break,
+            };
+            for answer in answers {
+                write_message(&mut writer, &answer)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use nasl_lsp::{path_to_uri, read_message, write_message, Server};
+    use serde_json::{json, Value};
+
+    const URI: &str = "file:///test.nasl";
+
+    /// Runs the server with the given messages and returns the written messages
+    fn drive(server: &mut Server, messages: &[Value]) -> Vec<Value> {
+        let mut input = vec![];
+        for message in messages {
+            write_message(&mut input, message).unwrap();
+        }
+        let mut output = vec![];
+        server.run(Cursor::new(input), &mut output).unwrap();
+        let mut output = Cursor::new(output);
+        let mut result = vec![];
+        while let Some(message) = read_message(&mut output).unwrap() {
+            result.push(message.unwrap());
+        }
+        result
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn request(id: i64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> Value {
+        json!({
+            "start": { "line": start.0, "character": start.1 },
+            "end": { "line": end.0, "character": end.1 }
+        })
+    }
+
+    /// Returns the diagnostics of each publishDiagnostics notification
+    fn published(messages: &[Value]) -> Vec<&Vec<Value>> {
+        messages
+            .iter()
+            .filter(|x| x["method"] == "textDocument/publishDiagnostics")
+            .map(|x| {
+                assert_eq!(x["params"]["uri"], URI);
+                x["params"]["diagnostics"].as_array().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn open_and_change() {
+        let mut server = Server::default();
+        let messages = drive(
+            &mut server,
+            &[
+                request(1, "initialize", json!({ "capabilities": {} })),
+                notification("initialized", json!({})),
+                notification(
+                    "textDocument/didOpen",
+                    json!({ "textDocument": {
+                        "uri": URI, "languageId": "nasl", "version": 1,
+                        "text": "a = 1;\nb = 'c;\n"
+                    }}),
+                ),
+                // fixes the unclosed string
+                notification(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": URI, "version": 2 },
+                        "contentChanges": [{ "range": range((1, 6), (1, 6)), "text": "'" }]
+                    }),
+                ),
+                // replaces the whole content with an unclosed block
+                notification(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": URI, "version": 3 },
+                        "contentChanges": [{ "text": "if (a) {\n  b = 1;\n" }]
+                    }),
+                ),
+                request(2, "shutdown", Value::Null),
+                notification("exit", Value::Null),
+            ],
+        );
+        assert_eq!(messages[0]["id"], 1);
+        let capabilities = &messages[0]["result"]["capabilities"];
+        assert_eq!(capabilities["textDocumentSync"]["change"], 2);
+        assert_eq!(capabilities["semanticTokensProvider"]["full"], true);
+
+        let diagnostics = published(&messages);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].len(), 1);
+        assert_eq!(diagnostics[0][0]["code"], "unexpected-token");
+        assert_eq!(diagnostics[0][0]["severity"], 1);
+        assert_eq!(
+            diagnostics[0][0]["range"]["start"],
+            json!({ "line": 1, "character": 4 })
+        );
+        assert!(diagnostics[1].is_empty());
+        assert_eq!(diagnostics[2].len(), 1);
+        assert_eq!(diagnostics[2][0]["code"], "unclosed-token");
+
+        assert_eq!(messages.last().unwrap()["id"], 2);
+        assert!(server.exited());
+        assert_eq!(server.exit_code(), 0);
+        assert_eq!(server.document(URI).unwrap().text(), "if (a) {\n  b = 1;\n");
+    }
+
+    #[test]
+    fn semantic_tokens_and_close() {
+        let mut server = Server::default();
+        let messages = drive(
+            &mut server,
+            &[
+                notification(
+                    "textDocument/didOpen",
+                    json!({ "textDocument": {
+                        "uri": URI, "languageId": "nasl", "version": 1, "text": "a = 1;"
+                    }}),
+                ),
+                request(
+                    1,
+                    "textDocument/semanticTokens/full",
+                    json!({ "textDocument": { "uri": URI } }),
+                ),
+                request(
+                    2,
+                    "textDocument/codeLens",
+                    json!({ "textDocument": { "uri": URI } }),
+                ),
+                notification(
+                    "textDocument/didClose",
+                    json!({ "textDocument": { "uri": URI } }),
+                ),
+                request(
+                    3,
+                    "textDocument/semanticTokens/full",
+                    json!({ "textDocument": { "uri": URI } }),
+                ),
+            ],
+        );
+        assert_eq!(
+            messages[1]["result"]["data"],
+            json!([0, 0, 1, 1, 0, 0, 2, 1, 5, 0, 0, 2, 1, 3, 0])
+        );
+        assert_eq!(messages[2]["error"]["code"], -32601);
+        assert!(published(&messages)[1].is_empty());
+        assert_eq!(messages[4]["error"]["code"], -32602);
+        assert!(server.document(URI).is_none());
+        assert!(!server.exited());
+        assert_eq!(server.exit_code(), 1);
+    }
+
+    #[test]
+    fn definition() {
+        let root = std::env::temp_dir().join(format!("nasl-lsp-definition-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("lib.inc"),
+            "function helper(a) {\n  return a;\n}\n",
+        )
+        .unwrap();
+        let uri = path_to_uri(&root.join("test.nasl"));
+        let library = path_to_uri(&root.join("lib.inc"));
+        let code =
+            "include(\"lib.inc\");\nfunction local(x) {\n  return x;\n}\nlocal(1);\nhelper(2);\n";
+        let definition = |id, line, character| {
+            request(
+                id,
+                "textDocument/definition",
+                json!({
+                    "textDocument": { "uri": uri },
+                    "position": { "line": line, "character": character }
+                }),
+            )
+        };
+        let mut server = Server::default();
+        let messages = drive(
+            &mut server,
+            &[
+                request(1, "initialize", json!({ "rootUri": path_to_uri(&root) })),
+                notification(
+                    "textDocument/didOpen",
+                    json!({ "textDocument": {
+                        "uri": uri, "languageId": "nasl", "version": 1, "text": code
+                    }}),
+                ),
+                definition(2, 4, 1),
+                definition(3, 5, 2),
+                definition(4, 0, 10),
+                definition(5, 2, 9),
+            ],
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            messages[0]["result"]["capabilities"]["definitionProvider"],
+            true
+        );
+        assert_eq!(
+            messages[2]["result"],
+            json!({ "uri": uri, "range": range((1, 9), (1, 14)) })
+        );
+        assert_eq!(
+            messages[3]["result"],
+            json!({ "uri": library, "range": range((0, 9), (0, 15)) })
+        );
+        assert_eq!(
+            messages[4]["result"],
+            json!({ "uri": library, "range": range((0, 0), (0, 0)) })
+        );
+        assert_eq!(messages[5]["result"], Value::Null);
+    }
+
+    #[test]
+    fn hover() {
+        let hover = |id, character| {
+            request(
+                id,
+                "textDocument/hover",
+                json!({
+                    "textDocument": { "uri": URI },
+                    "position": { "line": 0, "character": character }
+                }),
+            )
+        };
+        let mut server = Server::default();
+        let messages = drive(
+            &mut server,
+            &[
+                notification(
+                    "textDocument/didOpen",
+                    json!({ "textDocument": {
+                        "uri": URI, "languageId": "nasl", "version": 1,
+                        "text": "a = strlen(unknown) + eval_arithmetic(\"1\");"
+                    }}),
+                ),
+                hover(1, 6),
+                hover(2, 13),
+                hover(3, 0),
+                hover(4, 24),
+            ],
+        );
+        let result = &messages[1]["result"];
+        assert_eq!(result["contents"]["kind"], "markdown");
+        let value = result["contents"]["value"].as_str().unwrap();
+        assert!(value.starts_with("```nasl\nint strlen(0: string);\n```"));
+        assert!(value.contains("get the length of a string"));
+        assert_eq!(result["range"], range((0, 4), (0, 10)));
+        assert_eq!(messages[2]["result"], Value::Null);
+        assert_eq!(messages[3]["result"], Value::Null);
+        // defined by the interpreter but not described in the manual
+        let result = &messages[4]["result"];
+        assert_eq!(
+            result["contents"]["value"],
+            "```nasl\neval_arithmetic\n```\n\nbuiltin function without documentation"
+        );
+        assert_eq!(result["range"], range((0, 22), (0, 37)));
+    }
+
+    #[test]
+    fn completion() {
+        let mut server = Server::default();
+        let messages = drive(
+            &mut server,
+            &[
+                notification(
+                    "textDocument/didOpen",
+                    json!({ "textDocument": {
+                        "uri": URI, "languageId": "nasl", "version": 1,
+                        "text": "result = 1;\nre"
+                    }}),
+                ),
+                request(
+                    1,
+                    "textDocument/completion",
+                    json!({
+                        "textDocument": { "uri": URI },
+                        "position": { "line": 1, "character": 2 }
+                    }),
+                ),
+            ],
+        );
+        let items = messages[1]["result"]["items"].as_array().unwrap();
+        let kind = |label: &str| {
+            items
+                .iter()
+                .find(|x| x["label"] == label)
+                .map(|x| x["kind"].as_u64().unwrap())
+        };
+        assert_eq!(kind("repeat"), Some(14));
+        assert_eq!(kind("return"), Some(14));
+        assert_eq!(kind("replace_kb_item"), Some(3));
+        // documented but not defined by the interpreter
+        assert_eq!(kind("recv"), None);
+        assert_eq!(kind("result"), Some(6));
+        assert_eq!(kind("strlen"), None);
+        assert!(items
+            .iter()
+            .all(|x| x["label"].as_str().unwrap().starts_with("re")));
+    }
+
+    #[test]
+    fn parse_error() {
+        let mut server = Server::default();
+        let mut input = b"Content-Length: 5\r\n\r\n{\"id\"".to_vec();
+        write_message(&mut input, &request(1, "shutdown", json!(null))).unwrap();
+        let mut output = vec![];
+        server.run(Cursor::new(input), &mut output).unwrap();
+        let mut output = Cursor::new(output);
+        let parse_error = read_message(&mut output).unwrap().unwrap().unwrap();
+        assert_eq!(parse_error["id"], Value::Null);
+        assert_eq!(parse_error["error"]["code"], -32700);
+        // the following message is still handled
+        let shutdown = read_message(&mut output).unwrap().unwrap().unwrap();
+        assert_eq!(shutdown["id"], 1);
+        assert_eq!(shutdown["result"], Value::Null);
+    }
+}
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use nasl_syntax::parse;
+use nasl_syntax::{parse, Tokenizer};
 
 pub fn simple_parse_benchmark(c: &mut Criterion) {
     let code = include_str!("simple_parse.nasl");
@@ -27,5 +27,17 @@ pub fn parse_large_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, simple_parse_benchmark, parse_large_benchmark);
+pub fn tokenize_large_benchmark(c: &mut Criterion) {
+    let code = include_str!("smb_nt.inc");
+    c.bench_function(&format!("tokenize smb_nt.inc {}", code.len()), |b| {
+        b.iter(|| Tokenizer::new(black_box(code)).count())
+    });
+}
+
+criterion_group!(
+    benches,
+    simple_parse_benchmark,
+    parse_large_benchmark,
+    tokenize_large_benchmark
+);
 criterion_main!(benches);
@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nasl_syntax::Tokenizer;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(code) = std::str::from_utf8(data) {
+        let mut previous = (0, 0);
+        let mut line_column = (0, 0);
+        for token in Tokenizer::new(code) {
+            let (start, end) = token.position;
+            // each token starts after the previous one and is within the code
+            assert!(start >= previous.1, "{token:?} overlaps {previous:?}");
+            assert!(start <= end, "{token:?} ends before it starts");
+            assert!(end <= code.len(), "{token:?} exceeds {}", code.len());
+            assert!(code.is_char_boundary(start) && code.is_char_boundary(end));
+            assert!(token.line_column >= line_column, "{token:?} is before {line_column:?}");
+            previous = token.position;
+            line_column = token.line_column;
+        }
+    }
+});
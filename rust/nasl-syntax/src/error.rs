@@ -5,7 +5,7 @@
 //! Defines TokenError and its companion macros.
 
 use core::fmt;
-use std::{error::Error, io};
+use std::{error::Error, io, ops::Range};
 
 use crate::{token::Token, Statement};
 
@@ -55,6 +55,67 @@ impl SyntaxError {
             ErrorKind::MaxRecursionDepth(_) => None,
         }
     }
+
+    /// Returns the byte range of the offending token or statement within the parsed code
+    pub fn range(&self) -> Option<Range<usize>> {
+        match &self.kind {
+            ErrorKind::UnexpectedToken(t) | ErrorKind::UnclosedToken(t) => {
+                Some(t.position.0..t.position.1)
+            }
+            ErrorKind::UnexpectedStatement(s)
+            | ErrorKind::MissingSemicolon(s)
+            | ErrorKind::UnclosedStatement(s) => Some(s.range()),
+            ErrorKind::EoF | ErrorKind::IOError(_) | ErrorKind::MaxRecursionDepth(_) => None,
+        }
+    }
+
+    /// Returns the line and column, both starting at 1, of the offending token
+    pub fn line_column(&self) -> Option<(usize, usize)> {
+        self.as_token()
+            .map(|t| t.line_column)
+            .filter(|(line, _)| *line > 0)
+    }
+
+    /// Renders the error with a caret underlined snippet of the given code
+    ///
+    /// The code must be the code that was parsed. The result contains the filename with the
+    /// line and column, the affected source line and `^` markers under the offending part:
+    ///
+    /// ```text
+    /// test.nasl:1:5: unexpected token: 1:5 ;
+    /// a = ;
+    ///     ^
+    /// ```
+    ///
+    /// When there is no location available only the filename and the error is returned.
+    pub fn render(&self, filename: &str, code: &str) -> String {
+        let (line, column) = match self.line_column() {
+            Some(lc) => lc,
+            None => return format!("{filename}: {self}"),
+        };
+        let header = format!("{filename}:{line}:{column}: {self}");
+        let range = match self.range() {
+            Some(r) if code.is_char_boundary(r.start) && r.start <= code.len() => r,
+            _ => return header,
+        };
+        let line_start = code[..range.start].rfind('\n').map(|x| x + 1).unwrap_or(0);
+        let line_end = code[range.start..]
+            .find('\n')
+            .map(|x| x + range.start)
+            .unwrap_or(code.len());
+        let source = code[line_start..line_end].trim_end_matches('\r');
+        // keep tabs so that the marker is aligned the same way as the source line
+        let indent: String = code[line_start..range.start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let marked = code
+            .get(range.start..range.end.min(line_end))
+            .map(|x| x.chars().count())
+            .unwrap_or_default()
+            .max(1);
+        format!("{header}\n{source}\n{indent}{}", "^".repeat(marked))
+    }
 }
 
 /// Creates an SyntaxError.
@@ -205,6 +266,22 @@ impl fmt::Display for SyntaxError {
     }
 }
 
+impl ErrorKind {
+    /// Returns a stable machine readable identifier of the kind, e.g. `unclosed-token`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::UnexpectedToken(_) => "unexpected-token",
+            ErrorKind::UnclosedToken(_) => "unclosed-token",
+            ErrorKind::UnexpectedStatement(_) => "unexpected-statement",
+            ErrorKind::UnclosedStatement(_) => "unclosed-statement",
+            ErrorKind::MissingSemicolon(_) => "missing-semicolon",
+            ErrorKind::EoF => "unexpected-eof",
+            ErrorKind::IOError(_) => "io-error",
+            ErrorKind::MaxRecursionDepth(_) => "max-recursion-depth",
+        }
+    }
+}
+
 impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -304,4 +381,21 @@ mod tests {
         test_for_unclosed_token("{ a = 2;", TokenCategory::LeftCurlyBracket);
         test_for_unclosed_token("function a() { a = 2;", TokenCategory::LeftCurlyBracket);
     }
+
+    #[test]
+    fn render_with_caret() {
+        let code = "a = 1;\ncalled(me;\n";
+        let err = parse(code).find_map(|x| x.err()).unwrap();
+        assert_eq!(err.line_column(), Some((2, 7)));
+        assert_eq!(
+            err.render("test.nasl", code),
+            format!("test.nasl:2:7: {err}\ncalled(me;\n      ^")
+        );
+    }
+
+    #[test]
+    fn render_without_location() {
+        let err = crate::SyntaxError::new(ErrorKind::EoF, 0, "".to_owned());
+        assert_eq!(err.render("test.nasl", ""), "test.nasl: end of file.");
+    }
 }
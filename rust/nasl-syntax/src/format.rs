@@ -0,0 +1,766 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Formats NASL source code with canonical whitespace
+
+use std::ops::Range;
+
+use crate::{
+    token::{Category, IdentifierType, Token},
+    SyntaxError, Tokenizer,
+};
+
+/// The placement of the opening brace of a block
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BraceStyle {
+    /// The opening brace is kept in the line of its statement, e.g. `if (a) {`
+    KAndR,
+    /// The opening brace is put into its own line
+    #[default]
+    Allman,
+}
+
+/// Options to configure the result of [format_code_with]
+///
+/// The default options match the style of the feed: two spaces per indentation level, no space
+/// between a keyword and its condition and the opening brace in its own line, e.g.
+/// `if(description)\n{`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// The amount of spaces per indentation level, ignored when `use_tabs` is set
+    pub indent_width: usize,
+    /// Indents with one tab per level instead of spaces
+    pub use_tabs: bool,
+    /// The placement of opening braces
+    pub brace_style: BraceStyle,
+    /// Separates `if`, `for`, `foreach`, `while` and `until` from their parenthesis by a space
+    pub keyword_space: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            use_tabs: false,
+            brace_style: BraceStyle::Allman,
+            keyword_space: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Returns the text of a single indentation level
+    fn indent(&self) -> String {
+        if self.use_tabs {
+            "\t".to_owned()
+        } else {
+            " ".repeat(self.indent_width)
+        }
+    }
+}
+
+/// Returns the byte range of the token within the code
+fn range(token: &Token) -> Range<usize> {
+    token.position.0..token.position.1
+}
+
+/// The last token written by the formatter
+struct Previous {
+    category: Category,
+    range: Range<usize>,
+    /// The token ends a value, e.g. a variable, a literal or `)`
+    value: bool,
+    /// The token is a prefix operator, e.g. `!` or `-` in `-1`
+    prefix: bool,
+    /// The token is the variable of a foreach loop
+    foreach_variable: bool,
+}
+
+/// Returns true when the category ends a value so that a following operator is binary
+fn ends_value(category: &Category) -> bool {
+    matches!(
+        category,
+        Category::Identifier(
+            IdentifierType::Undefined(_)
+                | IdentifierType::True
+                | IdentifierType::False
+                | IdentifierType::Null
+                | IdentifierType::FCTAnonArgs
+                | IdentifierType::ACT(_)
+        ) | Category::Number(_)
+            | Category::String(_)
+            | Category::Data(_)
+            | Category::IPv4Address(_)
+            | Category::RightParen
+            | Category::RightBrace
+    )
+}
+
+/// Returns true when the category may be used as prefix operator
+fn may_be_prefix(category: &Category) -> bool {
+    matches!(
+        category,
+        Category::Minus
+            | Category::Plus
+            | Category::Bang
+            | Category::Tilde
+            | Category::PlusPlus
+            | Category::MinusMinus
+    )
+}
+
+struct Formatter<'a> {
+    code: &'a str,
+    /// The indentation per block level
+    indent: String,
+    brace_style: BraceStyle,
+    keyword_space: bool,
+    result: String,
+    /// The amount of open `{`
+    depth: usize,
+    /// The amount of open `(` and `[`
+    parens: usize,
+    previous: Option<Previous>,
+    /// The end of the last token including comments
+    last_end: usize,
+    /// The last token is a comment
+    after_comment: bool,
+    /// The next token starts in a new line
+    line_break: bool,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(code: &'a str, options: &FormatOptions) -> Self {
+        Self {
+            code,
+            indent: options.indent(),
+            brace_style: options.brace_style,
+            keyword_space: options.keyword_space,
+            result: String::with_capacity(code.len()),
+            depth: 0,
+            parens: 0,
+            previous: None,
+            last_end: 0,
+            after_comment: false,
+            line_break: false,
+        }
+    }
+
+    fn text(&self, range: Range<usize>) -> &'a str {
+        self.code.get(range).unwrap_or_default()
+    }
+
+    /// Returns the text of a token without surrounding whitespace, e.g. the repetition `x`
+    /// contains the following space.
+    fn token_text(&self, range: Range<usize>) -> &'a str {
+        self.text(range).trim()
+    }
+
+    /// Starts a new line, keeps up to one empty line when the source contains it
+    fn new_line(&mut self, keep_empty_line: bool) {
+        if self.result.is_empty() {
+            return;
+        }
+        self.result.push('\n');
+        if keep_empty_line {
+            self.result.push('\n');
+        }
+        for _ in 0..self.depth + self.parens {
+            self.result.push_str(&self.indent);
+        }
+    }
+
+    /// Returns true when the previous and the given token need to be separated by a space
+    fn needs_space(&self, previous: &Previous, category: &Category) -> bool {
+        use Category::*;
+        if matches!(
+            category,
+            Semicolon | Comma | RightParen | RightBrace | DoublePoint | Dot
+        ) || matches!(previous.category, LeftParen | LeftBrace | Dot)
+            || previous.prefix
+        {
+            return false;
+        }
+        match category {
+            LeftParen => match &previous.category {
+                Identifier(IdentifierType::Undefined(_)) => {
+                    previous.foreach_variable && self.keyword_space
+                }
+                Identifier(
+                    IdentifierType::If
+                    | IdentifierType::For
+                    | IdentifierType::While
+                    | IdentifierType::Until,
+                ) => self.keyword_space,
+                Identifier(IdentifierType::Exit | IdentifierType::Include) => false,
+                RightParen | RightBrace => false,
+                _ => true,
+            },
+            LeftBrace => !previous.value,
+            PlusPlus | MinusMinus => !previous.value,
+            _ => true,
+        }
+    }
+
+    /// Returns true when the texts of both tokens would be tokenized differently without a space
+    fn merges(&self, previous: &Previous, token: &Token) -> bool {
+        let joined = format!(
+            "{}{}",
+            self.token_text(previous.range.clone()),
+            self.token_text(range(token))
+        );
+        let categories: Vec<_> = Tokenizer::new(&joined).map(|x| x.category).collect();
+        categories != [previous.category.clone(), token.category.clone()]
+    }
+
+    fn push(&mut self, token: Token) {
+        let gap = self.text(self.last_end..token.position.0);
+        let newlines = gap.matches('\n').count();
+        if token.category() == &Category::Comment {
+            if self.previous.is_some() && newlines == 0 {
+                self.result.push(' ');
+            } else {
+                self.new_line(newlines > 1 && !self.after_open_block());
+            }
+            self.result.push_str(self.token_text(range(&token)));
+            self.last_end = token.position.1;
+            self.after_comment = true;
+            self.line_break = true;
+            return;
+        }
+        let category = token.category().clone();
+        if category == Category::RightCurlyBracket {
+            self.depth = self.depth.saturating_sub(1);
+        }
+        let continues_block = !self.after_comment
+            && matches!(
+                self.previous.as_ref().map(|x| &x.category),
+                Some(Category::RightCurlyBracket)
+            )
+            && match category {
+                Category::Semicolon => true,
+                Category::Identifier(IdentifierType::Else | IdentifierType::Until) => {
+                    self.brace_style == BraceStyle::KAndR
+                }
+                _ => false,
+            };
+        let own_line = self.brace_style == BraceStyle::Allman
+            && category == Category::LeftCurlyBracket
+            && self.previous.is_some();
+        if (self.line_break || own_line) && !continues_block {
+            let keep_empty_line =
+                newlines > 1 && category != Category::RightCurlyBracket && !self.after_open_block();
+            self.new_line(keep_empty_line);
+        } else if let Some(previous) = &self.previous {
+            if self.needs_space(previous, &category) || self.merges(previous, &token) {
+                self.result.push(' ');
+            }
+        }
+        self.result.push_str(self.token_text(range(&token)));
+
+        let previous_value = self.previous.as_ref().map(|x| x.value).unwrap_or_default();
+        let postfix =
+            matches!(category, Category::PlusPlus | Category::MinusMinus) && previous_value;
+        let foreach_variable =
+            matches!(category, Category::Identifier(IdentifierType::Undefined(_)))
+                && matches!(
+                    self.previous.as_ref().map(|x| &x.category),
+                    Some(Category::Identifier(IdentifierType::ForEach))
+                );
+        match category {
+            Category::LeftCurlyBracket => self.depth += 1,
+            Category::LeftParen | Category::LeftBrace => self.parens += 1,
+            Category::RightParen | Category::RightBrace => {
+                self.parens = self.parens.saturating_sub(1)
+            }
+            _ => {}
+        }
+        self.line_break = match category {
+            Category::LeftCurlyBracket | Category::RightCurlyBracket => true,
+            Category::Semicolon => self.parens == 0,
+            _ => false,
+        };
+        self.previous = Some(Previous {
+            value: ends_value(&category) || postfix,
+            prefix: may_be_prefix(&category) && !previous_value,
+            foreach_variable,
+            range: range(&token),
+            category,
+        });
+        self.last_end = token.position.1;
+        self.after_comment = false;
+    }
+
+    /// Returns true when the last token opened a block
+    fn after_open_block(&self) -> bool {
+        !self.after_comment
+            && matches!(
+                self.previous.as_ref().map(|x| &x.category),
+                Some(Category::LeftCurlyBracket)
+            )
+    }
+
+    fn finish(mut self) -> String {
+        let len = self.result.trim_end().len();
+        self.result.truncate(len);
+        if !self.result.is_empty() {
+            self.result.push('\n');
+        }
+        self.result
+    }
+}
+
+/// Formats the given code with canonical whitespace using the default [FormatOptions].
+///
+/// Statements are put into separate lines, blocks are indented by two spaces and the opening
+/// brace is put into its own line. Keywords are directly followed by their parenthesis. Binary operators are surrounded by spaces while
+/// prefix and postfix operators are attached to their operand. Comments as well as up to one
+/// empty line between statements are kept.
+///
+/// Returns the first syntax error when the code cannot be parsed.
+///
+/// # Examples
+/// Basic usage:
+///
+/// ```
+/// let code = nasl_syntax::format_code("if (a==1){b=-a;}else b++;").unwrap();
+/// assert_eq!(code, "if(a == 1)\n{\n  b = -a;\n}\nelse b++;\n");
+/// ```
+pub fn format_code(code: &str) -> Result<String, SyntaxError> {
+    format_code_with(code, &FormatOptions::default())
+}
+
+/// Formats the given code with canonical whitespace using the given options.
+///
+/// With [BraceStyle::Allman] opening braces as well as `else` and `until` after a block are put
+/// into their own line, with [BraceStyle::KAndR] they are kept in the line of the statement.
+///
+/// # Examples
+/// Basic usage:
+///
+/// ```
+/// use nasl_syntax::{format_code_with, BraceStyle, FormatOptions};
+/// let options = FormatOptions {
+///     indent_width: 4,
+///     brace_style: BraceStyle::KAndR,
+///     keyword_space: true,
+///     ..Default::default()
+/// };
+/// let code = format_code_with("if(a){b;}", &options).unwrap();
+/// assert_eq!(code, "if (a) {\n    b;\n}\n");
+/// ```
+pub fn format_code_with(code: &str, options: &FormatOptions) -> Result<String, SyntaxError> {
+    if let Some(err) = crate::parse(code).find_map(|x| x.err()) {
+        return Err(err);
+    }
+    let mut formatter = Formatter::new(code, options);
+    for token in Tokenizer::new(code) {
+        formatter.push(token);
+    }
+    Ok(formatter.finish())
+}
+
+/// The amount of unchanged lines shown around changed lines within a diff
+const DIFF_CONTEXT: usize = 3;
+
+/// The maximum amount of changed lines for which the shortest edit script is searched
+///
+/// The memory needed grows quadratically with it, above it all lines are replaced instead.
+const DIFF_MAX_EDITS: usize = 1000;
+
+/// Returns the lines that are kept (` `), removed (`-`) or added (`+`) to change a into b
+///
+/// Uses the algorithm of Myers ("An O(ND) Difference Algorithm and Its Variations") to find the
+/// shortest edit script. When more than [DIFF_MAX_EDITS] lines differ, all lines of a are removed
+/// and all lines of b are added.
+fn edit_script<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(char, &'a str)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(DIFF_MAX_EDITS) as isize;
+    // v[offset + k] is the furthest x reached on diagonal k = x - y
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // the diagonals -d..=d of v after each step d, used to backtrack the path
+    let mut trace: Vec<Vec<isize>> = vec![];
+    let mut found = false;
+    'search: for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+                found = true;
+                break 'search;
+            }
+        }
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+    }
+    if !found {
+        return a
+            .iter()
+            .map(|x| ('-', *x))
+            .chain(b.iter().map(|x| ('+', *x)))
+            .collect();
+    }
+
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let previous = &trace[d as usize - 1];
+        // previous contains the diagonals -(d - 1)..=(d - 1)
+        let at = |k: isize| previous[(k + d - 1) as usize];
+        let k = x - y;
+        let previous_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = at(previous_k);
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push((' ', a[x as usize]));
+        }
+        if x == previous_x {
+            y -= 1;
+            edits.push(('+', b[y as usize]));
+        } else {
+            x -= 1;
+            edits.push(('-', a[x as usize]));
+        }
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        edits.push((' ', a[x as usize]));
+    }
+    edits.reverse();
+    edits
+}
+
+/// Returns a unified diff of the lines of both texts without file headers
+///
+/// Returns an empty string when both texts are equal.
+fn unified_diff(original: &str, changed: &str) -> String {
+    let a: Vec<&str> = original.split_inclusive('\n').collect();
+    let b: Vec<&str> = changed.split_inclusive('\n').collect();
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let mut edits: Vec<(char, &str)> = a[..prefix].iter().map(|x| (' ', *x)).collect();
+    edits.extend(edit_script(
+        &a[prefix..a.len() - suffix],
+        &b[prefix..b.len() - suffix],
+    ));
+    edits.extend(a[a.len() - suffix..].iter().map(|x| (' ', *x)));
+
+    let changes: Vec<usize> = (0..edits.len()).filter(|&i| edits[i].0 != ' ').collect();
+    let mut result = String::new();
+    let mut index = 0;
+    while index < changes.len() {
+        let start = changes[index].saturating_sub(DIFF_CONTEXT);
+        let mut end = changes[index];
+        while index + 1 < changes.len() && changes[index + 1] - end <= 2 * DIFF_CONTEXT + 1 {
+            index += 1;
+            end = changes[index];
+        }
+        index += 1;
+        let end = (end + DIFF_CONTEXT + 1).min(edits.len());
+        let count = |skip: char| edits[..start].iter().filter(|x| x.0 != skip).count();
+        let (a_start, b_start) = (count('+'), count('-'));
+        let hunk = &edits[start..end];
+        let a_len = hunk.iter().filter(|x| x.0 != '+').count();
+        let b_len = hunk.iter().filter(|x| x.0 != '-').count();
+        let line = |start: usize, len: usize| if len == 0 { start } else { start + 1 };
+        result.push_str(&format!(
+            "@@ -{},{a_len} +{},{b_len} @@\n",
+            line(a_start, a_len),
+            line(b_start, b_len)
+        ));
+        for (kind, text) in hunk {
+            result.push(*kind);
+            result.push_str(text);
+            if !text.ends_with('\n') {
+                result.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    result
+}
+
+/// Verifies that the given code is formatted according to the given options without changing it.
+///
+/// Returns `None` when the code is already canonical, otherwise a unified diff from the given
+/// to the formatted code. This is meant for CI pipelines, similar to `rustfmt --check`.
+///
+/// Returns the first syntax error when the code cannot be parsed.
+///
+/// # Examples
+/// Basic usage:
+///
+/// ```
+/// use nasl_syntax::{check_format, FormatOptions};
+/// let options = FormatOptions::default();
+/// assert_eq!(check_format("a = 1;\n", &options).unwrap(), None);
+/// assert_eq!(
+///     check_format("a=1;\n", &options).unwrap(),
+///     Some("@@ -1,1 +1,1 @@\n-a=1;\n+a = 1;\n".to_owned())
+/// );
+/// ```
+pub fn check_format(code: &str, options: &FormatOptions) -> Result<Option<String>, SyntaxError> {
+    let formatted = format_code_with(code, options)?;
+    if formatted == code {
+        Ok(None)
+    } else {
+        Ok(Some(unified_diff(code, &formatted)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_format, edit_script, format_code, format_code_with, unified_diff, BraceStyle,
+        FormatOptions, DIFF_MAX_EDITS,
+    };
+
+    #[test]
+    fn whitespace() {
+        let code = r#"
+# SPDX header
+
+function  add( a,b ){return a+b;}
+if(!isnull(a)&&a>-1)
+{
+  foreach item(make_list(1,2)) { x[item]++; --y; }
+}
+else if (a) exit( 0 ); # trailing
+
+
+outer: for(i=0;i<10;i++){ send(socket:soc,data:"a") x 2; break outer;}
+repeat{a=a-1;}until(a<0);
+"#;
+        let expected = r#"# SPDX header
+
+function add(a, b)
+{
+  return a + b;
+}
+if(!isnull(a) && a > -1)
+{
+  foreach item(make_list(1, 2))
+  {
+    x[item]++;
+    --y;
+  }
+}
+else if(a) exit(0); # trailing
+
+outer: for(i = 0; i < 10; i++)
+{
+  send(socket: soc, data: "a") x 2;
+  break outer;
+}
+repeat
+{
+  a = a - 1;
+}
+until(a < 0);
+"#;
+        assert_eq!(format_code(code).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_tokens_apart() {
+        assert_eq!(format_code("a = - -1;").unwrap(), "a = - -1;\n");
+        assert_eq!(format_code("a = !(b);").unwrap(), "a = !(b);\n");
+        assert_eq!(format_code("for (;;) break;").unwrap(), "for(;;) break;\n");
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert!(format_code("if (a { b; }").is_err());
+    }
+
+    #[test]
+    fn indentation() {
+        let code = "if(a){while(b){c;}}";
+        let options = |indent_width, use_tabs| FormatOptions {
+            indent_width,
+            use_tabs,
+            brace_style: BraceStyle::KAndR,
+            keyword_space: true,
+        };
+        assert_eq!(
+            format_code_with(code, &options(2, false)).unwrap(),
+            "if (a) {\n  while (b) {\n    c;\n  }\n}\n"
+        );
+        assert_eq!(
+            format_code_with(code, &options(4, false)).unwrap(),
+            "if (a) {\n    while (b) {\n        c;\n    }\n}\n"
+        );
+        assert_eq!(
+            format_code_with(code, &options(2, true)).unwrap(),
+            "if (a) {\n\twhile (b) {\n\t\tc;\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn brace_style() {
+        let code = "if(a){b;}else{c;}\nrepeat{d;}until(e);\nfunction f(){return;}";
+        let options = |brace_style| FormatOptions {
+            indent_width: 4,
+            brace_style,
+            keyword_space: true,
+            ..Default::default()
+        };
+        let k_and_r = r#"if (a) {
+    b;
+} else {
+    c;
+}
+repeat {
+    d;
+} until (e);
+function f() {
+    return;
+}
+"#;
+        let allman = r#"if (a)
+{
+    b;
+}
+else
+{
+    c;
+}
+repeat
+{
+    d;
+}
+until (e);
+function f()
+{
+    return;
+}
+"#;
+        for (style, expected) in [(BraceStyle::KAndR, k_and_r), (BraceStyle::Allman, allman)] {
+            let formatted = format_code_with(code, &options(style)).unwrap();
+            assert_eq!(formatted, expected);
+            assert_eq!(
+                format_code_with(&formatted, &options(style)).unwrap(),
+                formatted
+            );
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let code = "if(description){script_oid(\"1.2.3\");exit(0);}";
+        let expected = "if(description)\n{\n  script_oid(\"1.2.3\");\n  exit(0);\n}\n";
+        assert_eq!(format_code(code).unwrap(), expected);
+        assert_eq!(
+            format_code_with(code, &FormatOptions::default()).unwrap(),
+            expected
+        );
+        assert_eq!(BraceStyle::default(), BraceStyle::Allman);
+    }
+
+    #[test]
+    fn keyword_space() {
+        let options = FormatOptions {
+            keyword_space: true,
+            ..Default::default()
+        };
+        let code = "if(a)b();while(c)d();for(;;)e();foreach f(g)h();repeat i();until(j);x = (k);";
+        let expected = "if (a) b();\nwhile (c) d();\nfor (;;) e();\nforeach f (g) h();\nrepeat i();\nuntil (j);\nx = (k);\n";
+        assert_eq!(format_code_with(code, &options).unwrap(), expected);
+        assert_eq!(
+            format_code(expected).unwrap(),
+            "if(a) b();\nwhile(c) d();\nfor(;;) e();\nforeach f(g) h();\nrepeat i();\nuntil(j);\nx = (k);\n"
+        );
+    }
+
+    #[test]
+    fn check() {
+        let options = FormatOptions::default();
+        let formatted = "if(a)\n{\n  b = 1;\n}\nc = 2;\nd = 3;\n";
+        assert_eq!(check_format(formatted, &options).unwrap(), None);
+        let unformatted = "if(a)\n{\nb=1;\n}\nc = 2;\nd = 3;";
+        assert_eq!(
+            check_format(unformatted, &options).unwrap().unwrap(),
+            concat!(
+                "@@ -1,6 +1,6 @@\n",
+                " if(a)\n",
+                " {\n",
+                "-b=1;\n",
+                "+  b = 1;\n",
+                " }\n",
+                " c = 2;\n",
+                "-d = 3;\n",
+                "\\ No newline at end of file\n",
+                "+d = 3;\n",
+            )
+        );
+        assert!(check_format("if (a { b; }", &options).is_err());
+    }
+
+    #[test]
+    fn shortest_edit_script() {
+        let a = ["a", "b", "c", "a", "b", "b", "a"];
+        let b = ["c", "b", "a", "b", "a", "c"];
+        let edits = edit_script(&a, &b);
+        // the edit distance of both is 5
+        assert_eq!(edits.iter().filter(|x| x.0 != ' ').count(), 5);
+        let kept = |skip: char| {
+            edits
+                .iter()
+                .filter(|x| x.0 != skip)
+                .map(|x| x.1)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(kept('+'), a);
+        assert_eq!(kept('-'), b);
+        assert_eq!(edit_script(&[], &["a"]), vec![('+', "a")]);
+        assert_eq!(edit_script(&["a"], &[]), vec![('-', "a")]);
+    }
+
+    #[test]
+    fn large_diff() {
+        let lines = DIFF_MAX_EDITS * 2;
+        let original: String = (0..lines).map(|x| format!("{x}\n")).collect();
+        // every other line changed exceeds the limit so that all lines are replaced
+        let changed: String = (0..lines)
+            .map(|x| match x % 2 {
+                0 => format!("{x}\n"),
+                _ => format!("changed {x}\n"),
+            })
+            .collect();
+        let diff = unified_diff(&original, &changed);
+        assert!(diff.starts_with(&format!("@@ -1,{lines} +1,{lines} @@\n 0\n-1\n-2\n")));
+        assert_eq!(
+            diff.lines().filter(|x| x.starts_with('-')).count(),
+            lines - 1
+        );
+        // few changes within many lines are still found exactly
+        let changed = original.replacen("10\n", "ten\n", 1);
+        assert_eq!(
+            unified_diff(&original, &changed),
+            "@@ -8,7 +8,7 @@\n 7\n 8\n 9\n-10\n+ten\n 11\n 12\n 13\n"
+        );
+    }
+}
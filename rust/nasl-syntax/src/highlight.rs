@@ -0,0 +1,131 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Converts NASL source code into HTML with highlighted tokens
+
+use crate::{
+    token::{Category, IdentifierType, Token},
+    Tokenizer,
+};
+
+/// Returns the CSS class of the given token
+///
+/// The classes are:
+/// - `keyword` for reserved words like `if`, `function` or `TRUE`
+/// - `identifier` for names of variables and functions
+/// - `string` for strings and data
+/// - `number` for numbers and IPv4 addresses
+/// - `comment` for comments
+/// - `punctuation` for brackets, `,`, `;` and `:`
+/// - `operator` for all other symbols
+/// - `error` for faulty tokens, e.g. unclosed strings
+pub fn css_class(token: &Token) -> &'static str {
+    if token.is_faulty() {
+        return "error";
+    }
+    match token.category() {
+        Category::Identifier(IdentifierType::Undefined(_)) => "identifier",
+        Category::Identifier(_) => "keyword",
+        Category::String(_) | Category::Data(_) => "string",
+        Category::Number(_) | Category::IPv4Address(_) => "number",
+        Category::Comment => "comment",
+        Category::LeftParen
+        | Category::RightParen
+        | Category::LeftBrace
+        | Category::RightBrace
+        | Category::LeftCurlyBracket
+        | Category::RightCurlyBracket
+        | Category::Comma
+        | Category::Semicolon
+        | Category::DoublePoint => "punctuation",
+        _ => "operator",
+    }
+}
+
+/// Escapes the characters that have a special meaning within HTML
+fn escape(text: &str, result: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+}
+
+/// Converts the given code into HTML.
+///
+/// Each token is wrapped in a `span` with the class returned by [css_class]. The text between
+/// the tokens, e.g. whitespace, is kept as is so that the result is meant to be placed within
+/// a `pre` element.
+///
+/// # Examples
+/// Basic usage:
+///
+/// ```
+/// let html = nasl_syntax::highlight_html("a = 1;");
+/// assert_eq!(
+///     html,
+///     r#"<span class="identifier">a</span> <span class="operator">=</span> <span class="number">1</span><span class="punctuation">;</span>"#
+/// );
+/// ```
+pub fn highlight_html(code: &str) -> String {
+    let mut result = String::with_capacity(code.len() * 2);
+    let mut last = 0;
+    for token in Tokenizer::new(code) {
+        let (start, end) = token.position;
+        escape(code.get(last..start).unwrap_or_default(), &mut result);
+        result.push_str("<span class=\"");
+        result.push_str(css_class(&token));
+        result.push_str("\">");
+        escape(token.lexeme(code), &mut result);
+        result.push_str("</span>");
+        last = end;
+    }
+    escape(code.get(last..).unwrap_or_default(), &mut result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::highlight_html;
+
+    #[test]
+    fn classed_spans() {
+        let code = "if (a >< \"<b>\") exit(0x2); # done\n";
+        assert_eq!(
+            highlight_html(code),
+            concat!(
+                r#"<span class="keyword">if</span> "#,
+                r#"<span class="punctuation">(</span>"#,
+                r#"<span class="identifier">a</span> "#,
+                r#"<span class="operator">&gt;&lt;</span> "#,
+                r#"<span class="string">&quot;&lt;b&gt;&quot;</span>"#,
+                r#"<span class="punctuation">)</span> "#,
+                r#"<span class="keyword">exit</span>"#,
+                r#"<span class="punctuation">(</span>"#,
+                r#"<span class="number">0x2</span>"#,
+                r#"<span class="punctuation">)</span>"#,
+                r#"<span class="punctuation">;</span> "#,
+                r#"<span class="comment"># done</span>"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn faulty_tokens() {
+        assert_eq!(
+            highlight_html("a = 'b;"),
+            concat!(
+                r#"<span class="identifier">a</span> "#,
+                r#"<span class="operator">=</span> "#,
+                r#"<span class="error">&#39;b;</span>"#
+            )
+        );
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     lexer::{End, Lexer},
     token::{Category, IdentifierType, Token},
     unclosed_statement, unclosed_token, unexpected_end, unexpected_statement, unexpected_token,
-    Statement, StatementKind,
+    Conditional, ForEachLoop, ForLoop, FunctionDefinition, Statement, StatementKind,
 };
 
 pub(crate) trait Keywords {
@@ -38,7 +38,8 @@ impl<'a> Lexer<'a> {
             End::Continue => Err(unexpected_end!("expected a finished statement.")),
         }
     }
-    fn parse_if(&mut self, kw: Token) -> Result<Statement, SyntaxError> {
+    /// Parses the condition and the body of an if statement and returns them with the end token
+    fn parse_if_branch(&mut self) -> Result<(Statement, Statement, Token), SyntaxError> {
         let ptoken = self.token().ok_or_else(|| unexpected_end!("if parsing"))?;
         let condition = match ptoken.category() {
             Category::LeftParen => self.parse_paren(ptoken.clone())?,
@@ -46,41 +47,63 @@ impl<'a> Lexer<'a> {
         }
         .as_returnable_or_err()?;
         let (end, body) = self.statement(0, &|cat| cat == &Category::Semicolon)?;
-        let end = {
-            match end {
-                End::Done(end) => end,
-                End::Continue => return Err(unclosed_token!(ptoken)),
-            }
-        };
+        match end {
+            End::Done(end) => Ok((condition, body, end)),
+            End::Continue => Err(unclosed_token!(ptoken)),
+        }
+    }
 
-        let (ekw, r#else, end) = {
+    /// Parses an if statement
+    ///
+    /// An else if chain is collected iteratively and nested afterwards, so that the recursion
+    /// depth does not grow with the length of the chain.
+    fn parse_if(&mut self, kw: Token) -> Result<Statement, SyntaxError> {
+        let mut branches = vec![];
+        let mut kw = kw;
+        let (r#else, end) = loop {
+            let (condition, body, end) = self.parse_if_branch()?;
+            let ekw = match self.peek() {
+                Some(token) if token.category() == &Category::Identifier(IdentifierType::Else) => {
+                    self.token();
+                    token
+                }
+                _ => {
+                    branches.push((kw, condition, body, None));
+                    break (None, end);
+                }
+            };
             match self.peek() {
-                Some(token) => match token.category() {
-                    Category::Identifier(IdentifierType::Else) => {
-                        self.token();
-                        let (end, stmt) = self.statement(0, &|cat| cat == &Category::Semicolon)?;
-
-                        match end {
-                            End::Done(end) => (Some(token), Some(stmt), end),
-                            End::Continue => return Err(unexpected_statement!(stmt)),
+                Some(token) if token.category() == &Category::Identifier(IdentifierType::If) => {
+                    self.token();
+                    branches.push((kw, condition, body, Some(ekw)));
+                    kw = token;
+                }
+                _ => {
+                    let (end, stmt) = self.statement(0, &|cat| cat == &Category::Semicolon)?;
+                    match end {
+                        End::Done(end) => {
+                            branches.push((kw, condition, body, Some(ekw)));
+                            break (Some(stmt), end);
                         }
+                        End::Continue => return Err(unexpected_statement!(stmt)),
                     }
-                    _ => (None, None, end),
-                },
-                None => (None, None, end),
+                }
             }
         };
-        let result = Statement::with_start_end_token(
-            kw,
-            end.clone(),
-            StatementKind::If(
-                Box::new(condition),
-                Box::new(body),
-                ekw,
-                r#else.map(Box::new),
-            ),
-        );
-        Ok(result)
+        let mut statement = r#else;
+        for (kw, condition, body, ekw) in branches.into_iter().rev() {
+            statement = Some(Statement::with_start_end_token(
+                kw,
+                end.clone(),
+                StatementKind::If(Box::new(Conditional {
+                    condition,
+                    then: body,
+                    else_token: ekw,
+                    otherwise: statement,
+                })),
+            ));
+        }
+        Ok(statement.expect("expected at least one if branch"))
     }
 
     fn jump_to_left_parenthesis(&mut self) -> Result<(), SyntaxError> {
@@ -174,7 +197,11 @@ impl<'a> Lexer<'a> {
         Ok(Statement::with_start_end_token(
             token,
             block.end().clone(),
-            StatementKind::FunctionDeclaration(id, Box::new(parameter), Box::new(block)),
+            StatementKind::FunctionDeclaration(Box::new(FunctionDefinition {
+                name: id,
+                parameters: parameter,
+                body: block,
+            })),
         ))
     }
 
@@ -200,37 +227,36 @@ impl<'a> Lexer<'a> {
             End::Continue => Err(unclosed_statement!(parameter)),
         }
     }
-    fn parse_continue(&mut self, kw: Token) -> Result<Statement, SyntaxError> {
-        let token = self.peek();
-        if let Some(token) = token {
-            if matches!(token.category(), Category::Semicolon) {
-                self.token();
-                return Ok(Statement::with_start_end_token(
-                    kw,
-                    token,
-                    StatementKind::Continue,
-                ));
-            } else {
-                return Err(unexpected_token!(token));
-            }
+    /// Parses the optional label of break or continue and the closing semicolon
+    fn parse_jump_label(&mut self) -> Result<(Option<Token>, Token), SyntaxError> {
+        let token = self
+            .token()
+            .ok_or_else(|| unexpected_end!("expected a label or a semicolon."))?;
+        match token.category() {
+            Category::Semicolon => Ok((None, token)),
+            Category::Identifier(IdentifierType::Undefined(_)) => match self.token() {
+                Some(end) if end.category() == &Category::Semicolon => Ok((Some(token), end)),
+                Some(end) => Err(unexpected_token!(end)),
+                None => Err(unexpected_end!("expected a semicolon.")),
+            },
+            _ => Err(unexpected_token!(token)),
         }
-        Err(unexpected_end!("exit"))
+    }
+    fn parse_continue(&mut self, kw: Token) -> Result<Statement, SyntaxError> {
+        let (label, end) = self.parse_jump_label()?;
+        Ok(Statement::with_start_end_token(
+            kw,
+            end,
+            StatementKind::Continue(label.map(Box::new)),
+        ))
     }
     fn parse_break(&mut self, kw: Token) -> Result<Statement, SyntaxError> {
-        let token = self.peek();
-        if let Some(token) = token {
-            if matches!(token.category(), Category::Semicolon) {
-                self.token();
-                return Ok(Statement::with_start_end_token(
-                    kw,
-                    token,
-                    StatementKind::Break,
-                ));
-            } else {
-                return Err(unexpected_token!(token));
-            }
-        }
-        Err(unexpected_end!("exit"))
+        let (label, end) = self.parse_jump_label()?;
+        Ok(Statement::with_start_end_token(
+            kw,
+            end,
+            StatementKind::Break(label.map(Box::new)),
+        ))
     }
 
     fn map_syntax_error_to_unclosed_left_paren(e: SyntaxError) -> SyntaxError {
@@ -265,7 +291,11 @@ impl<'a> Lexer<'a> {
         }
         // `for (i = 0; i < 10; i++) display("hi");`
         let (end, condition) = self.statement(0, &|c| c == &Category::Semicolon)?;
-        let condition = condition.as_returnable_or_err()?;
+        // an omitted condition is a NoOp and treated as always true
+        let condition = match condition.kind() {
+            StatementKind::NoOp => condition,
+            _ => condition.as_returnable_or_err()?,
+        };
         if end == End::Continue {
             return Err(unclosed_statement!(condition));
         }
@@ -304,12 +334,12 @@ impl<'a> Lexer<'a> {
             End::Done(end) => Ok(Statement::with_start_end_token(
                 kw,
                 end.clone(),
-                StatementKind::For(
-                    Box::new(assignment),
-                    Box::new(condition),
-                    Box::new(update),
-                    Box::new(body),
-                ),
+                StatementKind::For(Box::new(ForLoop {
+                    assignment,
+                    condition,
+                    update,
+                    body,
+                })),
             )),
         }
     }
@@ -367,6 +397,47 @@ impl<'a> Lexer<'a> {
         ))
     }
 
+    /// Parses `do body while (condition);` as repeat loop with the negated condition
+    fn parse_do(&mut self, token: Token) -> Result<Statement, SyntaxError> {
+        let (end, body) = self.statement(0, &|c| c == &Category::Semicolon)?;
+        if !end {
+            return Err(unclosed_token!(token));
+        }
+        match self.token() {
+            Some(x) if x.category() == &Category::Identifier(IdentifierType::While) => {}
+            Some(x) => return Err(unexpected_token!(x)),
+            None => return Err(unexpected_end!("in do")),
+        }
+        self.jump_to_left_parenthesis()?;
+        let (end, condition) = self
+            .statement(0, &|c| c == &Category::RightParen)
+            .map_err(Self::map_syntax_error_to_unclosed_left_paren)?;
+        if !Self::is_end_of_category(&end, Category::RightParen) {
+            let ct = condition.as_token();
+            return Err(unclosed_token!(Token {
+                category: Category::LeftParen,
+                line_column: ct.line_column,
+                position: ct.position,
+            }));
+        }
+        let condition = condition.as_returnable_or_err()?;
+        let end = match self.token() {
+            Some(x) if x.category() == &Category::Semicolon => x,
+            Some(x) => return Err(unexpected_token!(x)),
+            None => return Err(unexpected_end!("in do")),
+        };
+        let until = Statement::with_start_end_token(
+            condition.start().clone(),
+            condition.end().clone(),
+            StatementKind::Operator(Category::Bang, vec![condition]),
+        );
+        Ok(Statement::with_start_end_token(
+            token,
+            end,
+            StatementKind::Repeat(Box::new(body), Box::new(until)),
+        ))
+    }
+
     fn parse_foreach(&mut self, token: Token) -> Result<Statement, SyntaxError> {
         let variable: Token = {
             match self.token() {
@@ -391,7 +462,11 @@ impl<'a> Lexer<'a> {
             End::Done(end) => Ok(Statement::with_start_end_token(
                 token,
                 end,
-                StatementKind::ForEach(variable, Box::new(r#in), Box::new(block)),
+                StatementKind::ForEach(Box::new(ForEachLoop {
+                    variable,
+                    iterable: r#in,
+                    body: block,
+                })),
             )),
 
             End::Continue => Err(unclosed_token!(token)),
@@ -447,6 +522,9 @@ impl<'a> Keywords for Lexer<'a> {
                 .parse_repeat(token)
                 .map(|stmt| (End::Done(stmt.end().clone()), stmt)),
             IdentifierType::Until => Err(unexpected_token!(token)), // handled in repeat
+            IdentifierType::Do => self
+                .parse_do(token)
+                .map(|stmt| (End::Done(stmt.end().clone()), stmt)),
             IdentifierType::LocalVar | IdentifierType::GlobalVar => self
                 .parse_declaration(token)
                 .map(|stmt| (End::Done(stmt.end().clone()), stmt)),
@@ -504,7 +582,7 @@ mod test {
             .unwrap()
             .unwrap();
         match actual.kind() {
-            If(_, _, Some(_), Some(_)) => {}
+            If(x) if x.else_token.is_some() && x.otherwise.is_some() => {}
             _ => unreachable!("{actual} must be if with else stmt."),
         }
 
@@ -513,20 +591,59 @@ mod test {
             .unwrap()
             .unwrap();
         match actual.kind() {
-            If(_, _, None, None) => {}
+            If(x) if x.else_token.is_none() && x.otherwise.is_none() => {}
             _ => unreachable!("{actual} must be if without else stmt."),
         }
     }
 
+    #[test]
+    fn else_if_chain() {
+        let actual = parse("if (a) b; else if (c) d; else e;")
+            .next()
+            .unwrap()
+            .unwrap();
+        match actual.kind() {
+            If(x) if x.else_token.is_some() => {
+                let x = x.otherwise.as_ref().unwrap();
+                assert_eq!(x.end(), actual.end());
+                match x.kind() {
+                    If(x) if x.else_token.is_some() => {
+                        assert_eq!(x.otherwise.as_ref().unwrap().to_string(), "e")
+                    }
+                    _ => unreachable!("{x} must be an if with else stmt."),
+                }
+            }
+            _ => unreachable!("{actual} must be if with else stmt."),
+        }
+
+        let mut code = "if (a == 0) b = 0;".to_owned();
+        for i in 1..10000 {
+            code.push_str(&format!(" else if (a == {i}) b = {i};"));
+        }
+        let mut statements = parse(&code);
+        let actual = statements.next().unwrap().unwrap();
+        assert_eq!(statements.next(), None);
+        let mut current = &actual;
+        let mut chain = 1;
+        while let Some(x) = match current.kind() {
+            If(x) => x.otherwise.as_ref(),
+            _ => None,
+        } {
+            current = x;
+            chain += 1;
+        }
+        assert_eq!(chain, 10000);
+    }
+
     #[test]
     fn if_block() {
         let actual = parse("if (description) { ; }").next().unwrap().unwrap();
         match actual.kind() {
-            If(_, b, _, _) => match b.kind() {
+            If(x) => match x.then.kind() {
                 Block(v) => {
                     assert_eq!(v, &vec![]);
                 }
-                _ => unreachable!("{b} must be a block stmt."),
+                _ => unreachable!("{} must be a block stmt.", x.then),
             },
             _ => unreachable!("{actual} must be an if stmt."),
         }
@@ -618,7 +735,17 @@ mod test {
         assert!(matches!(
             parse(code).next().unwrap().unwrap().kind(),
             &For(..)
-        ))
+        ));
+        for code in [
+            "for (; i < 10;) i++;",
+            "for (i = 0;; i++) ;",
+            "for (;;) break;",
+        ] {
+            assert!(
+                matches!(parse(code).next().unwrap().unwrap().kind(), &For(..)),
+                "{code}"
+            );
+        }
     }
 
     #[test]
@@ -639,6 +766,70 @@ mod test {
         ))
     }
 
+    #[test]
+    fn labeled_loop() {
+        let result = parse("outer: for (i = 0; i < 10; i++) { break outer; }")
+            .next()
+            .unwrap()
+            .unwrap();
+        match result.kind() {
+            Labeled(x) => {
+                assert_eq!(
+                    x.label.category(),
+                    &Identifier(IdentifierType::Undefined("outer".to_owned()))
+                );
+                assert!(matches!(x.statement.kind(), &For(..)));
+            }
+            x => panic!("expected a labeled loop, got: {x:?}"),
+        }
+        let code = "outer: while (TRUE) { continue outer; continue; break; }";
+        let result = parse(code).next().unwrap().unwrap();
+        let jumps: Vec<_> = result
+            .find(&|x| matches!(x.kind(), Break(_) | Continue(_)))
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(jumps, vec!["continue outer", "continue", "break"]);
+        assert!(parse("break outer inner;").next().unwrap().is_err());
+        assert!(parse("continue 1;").next().unwrap().is_err());
+    }
+
+    #[test]
+    fn do_while_loop() {
+        let result = parse("do { a++; } while (a < 10);")
+            .next()
+            .unwrap()
+            .unwrap();
+        match result.kind() {
+            Repeat(_, until) => assert!(matches!(until.kind(), Operator(Bang, _))),
+            x => panic!("expected a repeat loop, got: {x:?}"),
+        }
+        assert!(parse("do a++; while (a < 10);").next().unwrap().is_ok());
+        assert!(parse("do { a++; } while (a < 10)").next().unwrap().is_err());
+        assert!(parse("do { a++; } until (a < 10);")
+            .next()
+            .unwrap()
+            .is_err());
+        // without a block or statement it is a variable
+        for code in [
+            "do = 1;",
+            "a = do + 1;",
+            "f(do, x: do);",
+            "do[1] = do;",
+            "do++;",
+            "++do;",
+        ] {
+            let result = parse(code).next().unwrap();
+            assert!(result.is_ok(), "{code}: {result:?}");
+            result.unwrap().walk(&mut |x| {
+                assert_ne!(
+                    x.as_token().category(),
+                    &Category::Identifier(IdentifierType::Do)
+                )
+            });
+        }
+    }
+
     #[test]
     fn foreach() {
         let test_cases = [
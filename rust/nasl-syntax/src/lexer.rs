@@ -24,6 +24,11 @@ pub struct Lexer<'a> {
     // implementation relies that the iterator implementation resets depth to 0
     // after a statement, or error, has been returned.
     pub(crate) depth: u8,
+    /// Returns a final EoF statement when the input ended after a complete statement
+    emit_eof: bool,
+    /// The previous result was an error
+    failed: bool,
+    finished: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -56,12 +61,18 @@ impl Not for End {
 ///
 /// The binding power is used to express the order of a statement.
 /// Because the binding power of e,g. Plus is lower than Star the Star operation gets calculate before.
-/// The first number represents the left hand, the second number the right hand binding power
+/// The first number represents the left hand, the second number the right hand binding power.
+/// When the right hand binding power is lower than the left hand one the operation is right
+/// associative, e.g. `a ** b ** c` is `a ** (b ** c)`.
+///
+/// The order follows the precedence table of the NASL grammar in
+/// `doc/manual/nasl/nasl-grammar/index.md`. Prefix operations are handled in prefix_extension
+/// and bind stronger than every infix operation but `**`.
 fn infix_binding_power(op: &Operation) -> Option<(u8, u8)> {
     use self::Operation::*;
     use Category::*;
     let res = match op {
-        Operator(Category::StarStar) => (22, 23),
+        Operator(Category::StarStar) => (23, 22),
         Operator(Category::Star | Category::Slash | Category::Percent) => (20, 21),
         Operator(Plus | Minus) => (18, 19),
         Operator(LessLess | GreaterGreater | GreaterGreaterGreater) => (16, 17),
@@ -75,7 +86,8 @@ fn infix_binding_power(op: &Operation) -> Option<(u8, u8)> {
         Operator(AmpersandAmpersand) => (6, 7),
         Operator(PipePipe) => (4, 5),
         // two is lowest since on block we can start with 1
-        Assign(_) | Operator(X) => (2, 3),
+        Assign(_) => (3, 2),
+        Operator(X) => (2, 3),
 
         _ => return None,
     };
@@ -91,8 +103,22 @@ enum InFixState {
 impl<'a> Lexer<'a> {
     /// Creates a Lexer
     pub fn new(tokenizer: Tokenizer<'a>) -> Lexer<'a> {
-        let depth = 0;
-        Lexer { tokenizer, depth }
+        Lexer {
+            tokenizer,
+            depth: 0,
+            emit_eof: false,
+            failed: false,
+            finished: false,
+        }
+    }
+
+    /// Returns a final EoF statement when the input ended cleanly
+    ///
+    /// The input ended cleanly when the last statement is complete. When it is truncated, e.g.
+    /// by an unterminated statement, the last result is an error and no EoF is returned.
+    pub fn with_eof(mut self) -> Self {
+        self.emit_eof = true;
+        self
     }
 
     /// Returns next token of tokenizer
@@ -117,6 +143,15 @@ impl<'a> Lexer<'a> {
         None
     }
 
+    /// Returns up to amount upcoming tokens of tokenizer without consuming them
+    pub(crate) fn peek_many(&mut self, amount: usize) -> Vec<Token> {
+        self.tokenizer
+            .clone()
+            .filter(|x| x.category() != &Category::Comment)
+            .take(amount)
+            .collect()
+    }
+
     pub(crate) fn parse_comma_group(
         &mut self,
         category: Category,
@@ -351,16 +386,21 @@ impl<'a> Iterator for Lexer<'a> {
     type Item = Result<Statement, SyntaxError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
         let result = self.statement(0, &|cat| cat == &Category::Semicolon);
         // simulate eof if end::continue is stuck in a recursive loop
         if self.depth >= MAX_DEPTH {
+            self.finished = true;
             return None;
         }
 
-        match result {
+        let result = match result {
             Ok((end, stmt)) => {
                 if matches!(stmt.kind(), &StatementKind::EoF) {
-                    return None;
+                    self.finished = true;
+                    return (self.emit_eof && !self.failed).then_some(Ok(stmt));
                 }
                 if matches!(stmt.kind(), &StatementKind::NoOp) {
                     return Some(Ok(stmt));
@@ -373,7 +413,9 @@ impl<'a> Iterator for Lexer<'a> {
                 }
             }
             Err(x) => Some(Err(x)),
-        }
+        };
+        self.failed = matches!(result, Some(Err(_)));
+        result
     }
 }
 
@@ -461,6 +503,72 @@ mod infix {
         calculated_test!("3 * 10 / 5 % 4;", 2);
     }
 
+    /// Renders the operations of a statement as s-expressions to make the parse tree visible
+    fn sexpr(stmt: &Statement) -> std::string::String {
+        match stmt.kind() {
+            StatementKind::Operator(op, args) => {
+                let args: Vec<_> = args.iter().map(sexpr).collect();
+                format!("({op} {})", args.join(" "))
+            }
+            StatementKind::Assign(op, AssignOrder::AssignReturn, l, r) => {
+                format!("({op} {} {})", sexpr(l), sexpr(r))
+            }
+            _ => stmt.to_string(),
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        let cases = [
+            // arithmetic
+            ("a + b * c;", "(+ a (* b c))"),
+            ("a * b + c;", "(+ (* a b) c)"),
+            ("a - b - c;", "(- (- a b) c)"),
+            ("a / b * c;", "(* (/ a b) c)"),
+            ("a % b * c;", "(* (% a b) c)"),
+            ("a * b ** c;", "(* a (** b c))"),
+            ("a ** b ** c;", "(** a (** b c))"),
+            // unary operators
+            ("-a ** b;", "(- (** a b))"),
+            ("-a * b;", "(* (- a) b)"),
+            ("~a + b;", "(+ (~ a) b)"),
+            ("!a * b;", "(* (! a) b)"),
+            ("!a == b;", "(== (! a) b)"),
+            ("!a && b;", "(&& (! a) b)"),
+            // shifts vs. arithmetic
+            ("a << b + c;", "(<< a (+ b c))"),
+            ("a + b >> c;", "(>> (+ a b) c)"),
+            ("a >>> b * c;", "(>>> a (* b c))"),
+            ("a << b >> c;", "(>> (<< a b) c)"),
+            ("a << b & c;", "(& (<< a b) c)"),
+            // bit operators
+            ("a & b ^ c;", "(^ (& a b) c)"),
+            ("a ^ b & c;", "(^ a (& b c))"),
+            ("a | b ^ c;", "(| a (^ b c))"),
+            ("a ^ b | c;", "(| (^ a b) c)"),
+            ("a | b & c;", "(| a (& b c))"),
+            // comparisons
+            ("a | b == c;", "(== (| a b) c)"),
+            ("a < b + c;", "(< a (+ b c))"),
+            ("a >< b + c;", "(>< a (+ b c))"),
+            ("a =~ b + c;", "(=~ a (+ b c))"),
+            ("a == b && c != d;", "(&& (== a b) (!= c d))"),
+            // logical operators
+            ("a && b || c;", "(|| (&& a b) c)"),
+            ("a || b && c;", "(|| a (&& b c))"),
+            ("a || b || c;", "(|| (|| a b) c)"),
+            // assignments
+            ("a = b + c;", "(= a (+ b c))"),
+            ("a = b || c;", "(= a (|| b c))"),
+            ("a = b = c;", "(= a (= b c))"),
+            ("a += b * c;", "(+= a (* b c))"),
+            ("a = b += c;", "(= a (+= b c))"),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(sexpr(&result(code)), expected, "{code}");
+        }
+    }
+
     #[test]
     fn grouping() {
         //calculated_test!("2 * (2 + 5);", 13);
@@ -530,6 +638,30 @@ mod infix {
         expected(result("x() x 2;"), X);
     }
 
+    #[test]
+    fn containment_operator() {
+        let cases = [
+            ("a >< b;", "(>< a b)"),
+            ("a ><b;", "(>< a b)"),
+            ("a >!< b;", "(>!< a b)"),
+            ("a>!<b;", "(>!< a b)"),
+            ("a >!b;", "(> a (! b))"),
+            // same precedence as comparisons, parsed from left to right
+            ("a >< b > c;", "(> (>< a b) c)"),
+            ("a > b >< c;", "(>< (> a b) c)"),
+            ("a >!< b == c;", "(== (>!< a b) c)"),
+            ("a >< b + c;", "(>< a (+ b c))"),
+            ("a >< b && c >!< d;", "(&& (>< a b) (>!< c d))"),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(sexpr(&result(code)), expected, "{code}");
+        }
+        // a separated `>` and `<` are two operators and not a containment check
+        for code in ["a > <b;", "a >>< b;", "a >! <b;", "a ><= b;"] {
+            assert!(crate::parse(code).next().unwrap().is_err(), "{code}");
+        }
+    }
+
     #[test]
     fn logical_operator() {
         fn expected(stmt: Statement, category: Category) {
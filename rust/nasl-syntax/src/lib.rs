@@ -6,7 +6,9 @@
 #![warn(missing_docs)]
 mod cursor;
 mod error;
+mod format;
 mod grouping_extension;
+mod highlight;
 mod keyword_extension;
 mod lexer;
 mod loader;
@@ -20,6 +22,8 @@ mod variable_extension;
 pub mod logger;
 
 pub use error::{ErrorKind, SyntaxError};
+pub use format::{check_format, format_code, format_code_with, BraceStyle, FormatOptions};
+pub use highlight::{css_class, highlight_html};
 pub use lexer::Lexer;
 pub use loader::*;
 pub use naslvalue::*;
@@ -33,6 +37,10 @@ pub use token::Tokenizer;
 
 /// Parses given code and returns found Statements and Errors
 ///
+/// The iterator yields until the input is exhausted; errors do not stop it, so that all errors
+/// of a file can be found. It ends without a marker, use [parse_with_eof] to distinguish complete
+/// from truncated input.
+///
 /// # Examples
 /// Basic usage:
 ///
@@ -46,6 +54,25 @@ pub fn parse(code: &str) -> impl Iterator<Item = Result<Statement, SyntaxError>>
     Lexer::new(tokenizer)
 }
 
+/// Parses given code like [parse] but ends with an EoF statement when the input ended cleanly
+///
+/// [parse] yields statements and errors until the input is exhausted. A streaming consumer
+/// cannot tell from that whether the input ended after a complete statement. This iterator
+/// returns a final `Ok` statement of kind [StatementKind::EoF] only when the last statement is
+/// complete; on truncated input, e.g. an unterminated statement, the last item is an error.
+///
+/// # Examples
+/// ```
+/// use nasl_syntax::{parse_with_eof, StatementKind};
+/// let last = parse_with_eof("a = 23;").last().unwrap().unwrap();
+/// assert_eq!(last.kind(), &StatementKind::EoF);
+/// assert!(parse_with_eof("a = 23").last().unwrap().is_err());
+/// ```
+pub fn parse_with_eof(code: &str) -> impl Iterator<Item = Result<Statement, SyntaxError>> + '_ {
+    let tokenizer = Tokenizer::new(code);
+    Lexer::new(tokenizer).with_eof()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
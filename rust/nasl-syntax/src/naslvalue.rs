@@ -4,21 +4,39 @@
 
 use std::{cmp::Ordering, collections::HashMap, fmt::Display};
 
+use indexmap::IndexMap;
+
 use crate::{IdentifierType, Token, TokenCategory, ACT};
 
+/// The entries of a dict in the order their keys were first inserted
+///
+/// Use `shift_remove` to remove an entry without changing the order of the others.
+pub type NaslDict = IndexMap<String, NaslValue>;
+
+/// Maps each byte to the char of the same code point, like Latin-1
+///
+/// This is binary-safe, every byte results in exactly one char, but mangles UTF-8 encoded text.
+/// It is used whenever data is converted to a string, e.g. by `to_string`.
+pub fn bytes_to_str(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
 /// Represents a valid Value of NASL
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub enum NaslValue {
     /// String value
     String(String),
     /// Data value
+    ///
+    /// In a boolean context data is TRUE when it is not empty, as a number it is always 1 and as
+    /// a string each byte is mapped to a char via `bytes_to_str`.
     Data(Vec<u8>),
     /// Number value
     Number(i64),
     /// Array value
     Array(Vec<NaslValue>),
-    /// Array value
-    Dict(HashMap<String, NaslValue>),
+    /// Array value with string keys, it keeps the insertion order of its keys
+    Dict(NaslDict),
     /// Boolean value
     Boolean(bool),
     /// Attack category keyword
@@ -44,10 +62,10 @@ pub enum NaslValue {
     /// ```
     /// to print each kb_item within test.
     Fork(Vec<NaslValue>),
-    /// Signals continuing a loop
-    Continue,
-    /// Signals a break of a control structure
-    Break,
+    /// Signals continuing a loop, contains the label of the loop to continue if given
+    Continue(Option<String>),
+    /// Signals a break of a control structure, contains the label of the loop to break if given
+    Break(Option<String>),
     /// Exit value of the script
     Exit(i64),
 }
@@ -66,6 +84,55 @@ impl NaslValue {
             _ => Null,
         }
     }
+
+    /// Parses the leading number of a string like `strtol` with a base of 0
+    ///
+    /// Leading whitespace and a sign are skipped, a `0x` prefix is read as hexadecimal and a
+    /// leading `0` as octal. Everything after the number is ignored, so `"  42abc"` is 42, and a
+    /// string without a leading number is 0. Numbers that do not fit are clamped to `i64::MIN` or
+    /// `i64::MAX`.
+    ///
+    /// Unlike `i64::from`, which treats every string as true, this is meant for numeric contexts
+    /// like `int()`. Values that are not strings or data are converted via `i64::from`.
+    pub fn to_number(&self) -> i64 {
+        let s = match self {
+            NaslValue::String(x) => x.as_str(),
+            NaslValue::Data(x) => return NaslValue::String(bytes_to_str(x)).to_number(),
+            x => return i64::from(x),
+        };
+        let s = s.trim_start();
+        let (negative, s) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (radix, digits) = if let Some(hex) = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .filter(|x| x.starts_with(|c: char| c.is_ascii_hexdigit()))
+        {
+            (16, hex)
+        } else if s.starts_with('0') {
+            (8, s)
+        } else {
+            (10, s)
+        };
+        let mut result: i64 = 0;
+        for digit in digits.chars().map_while(|c| c.to_digit(radix)) {
+            let digit = i64::from(digit);
+            result = result
+                .checked_mul(i64::from(radix))
+                .and_then(|x| {
+                    if negative {
+                        x.checked_sub(digit)
+                    } else {
+                        x.checked_add(digit)
+                    }
+                })
+                .unwrap_or(if negative { i64::MIN } else { i64::MAX });
+        }
+        result
+    }
 }
 
 impl PartialOrd for NaslValue {
@@ -96,7 +163,7 @@ impl Display for NaslValue {
                     .collect::<Vec<String>>()
                     .join(",")
             ),
-            NaslValue::Data(x) => write!(f, "{}", x.iter().map(|x| *x as char).collect::<String>()),
+            NaslValue::Data(x) => write!(f, "{}", bytes_to_str(x)),
             NaslValue::Dict(x) => write!(
                 f,
                 "{}",
@@ -108,13 +175,17 @@ impl Display for NaslValue {
             NaslValue::Boolean(true) => write!(f, "1"),
             NaslValue::Boolean(false) => write!(f, "0"),
             NaslValue::Null => write!(f, "\0"),
-            NaslValue::Exit(rc) => write!(f, "exit({})", rc),
+            // the exit code is used when an exit value is concatenated, `Exit(n)` is only shown in
+            // the debug output
+            NaslValue::Exit(rc) => write!(f, "{rc}"),
             NaslValue::AttackCategory(category) => {
                 write!(f, "{}", IdentifierType::ACT(*category))
             }
             NaslValue::Return(rc) => write!(f, "return({:?})", *rc),
-            NaslValue::Continue => write!(f, "continue"),
-            NaslValue::Break => write!(f, "break"),
+            NaslValue::Continue(None) => write!(f, "continue"),
+            NaslValue::Continue(Some(x)) => write!(f, "continue {x}"),
+            NaslValue::Break(None) => write!(f, "break"),
+            NaslValue::Break(Some(x)) => write!(f, "break {x}"),
             NaslValue::Fork(x) => write!(
                 f,
                 "Creates {} runs, for {}",
@@ -178,6 +249,12 @@ impl From<usize> for NaslValue {
 
 impl From<HashMap<String, NaslValue>> for NaslValue {
     fn from(x: HashMap<String, NaslValue>) -> Self {
+        NaslValue::Dict(x.into_iter().collect())
+    }
+}
+
+impl From<NaslDict> for NaslValue {
+    fn from(x: NaslDict) -> Self {
         NaslValue::Dict(x)
     }
 }
@@ -191,37 +268,47 @@ impl From<NaslValue> for Vec<u8> {
                 .iter()
                 .flat_map(<&NaslValue as Into<Vec<u8>>>::into)
                 .collect(),
-            NaslValue::Boolean(_) | NaslValue::Number(_) | NaslValue::Dict(_) => {
-                value.to_string().as_bytes().into()
-            }
+            NaslValue::Boolean(_)
+            | NaslValue::Number(_)
+            | NaslValue::Exit(_)
+            | NaslValue::Dict(_) => value.to_string().as_bytes().into(),
             NaslValue::AttackCategory(_)
             | NaslValue::Fork(_)
             | NaslValue::Null
             | NaslValue::Return(_)
-            | NaslValue::Continue
-            | NaslValue::Break
-            | NaslValue::Exit(_) => vec![],
+            | NaslValue::Continue(_)
+            | NaslValue::Break(_) => vec![],
         }
     }
 }
 
+/// Returns whether a value is TRUE in a boolean context, e.g. the condition of an if statement
+///
+/// Containers are TRUE when they are not empty, regardless of their content; an array
+/// containing only NULL elements is TRUE. This applies to arrays, dicts and data alike, so an
+/// empty data buffer is FALSE. Strings are FALSE when empty or "0", numbers when 0 and NULL is
+/// always FALSE.
+pub fn nasl_is_truthy(value: &NaslValue) -> bool {
+    match value {
+        NaslValue::String(string) => !string.is_empty() && string != "0",
+        NaslValue::Array(v) => !v.is_empty(),
+        NaslValue::Data(v) => !v.is_empty(),
+        NaslValue::Dict(v) => !v.is_empty(),
+        NaslValue::Boolean(boolean) => *boolean,
+        NaslValue::Null => false,
+        NaslValue::Number(number) => *number != 0,
+        NaslValue::Exit(number) => *number != 0,
+        NaslValue::AttackCategory(_) => true,
+        NaslValue::Return(_) => true,
+        NaslValue::Continue(_) => false,
+        NaslValue::Break(_) => false,
+        NaslValue::Fork(v) => v.is_empty(),
+    }
+}
+
 impl From<NaslValue> for bool {
     fn from(value: NaslValue) -> Self {
-        match value {
-            NaslValue::String(string) => !string.is_empty() && string != "0",
-            NaslValue::Array(v) => !v.is_empty(),
-            NaslValue::Data(v) => !v.is_empty(),
-            NaslValue::Boolean(boolean) => boolean,
-            NaslValue::Null => false,
-            NaslValue::Number(number) => number != 0,
-            NaslValue::Exit(number) => number != 0,
-            NaslValue::AttackCategory(_) => true,
-            NaslValue::Dict(v) => !v.is_empty(),
-            NaslValue::Return(_) => true,
-            NaslValue::Continue => false,
-            NaslValue::Break => false,
-            NaslValue::Fork(v) => v.is_empty(),
-        }
+        nasl_is_truthy(&value)
     }
 }
 
@@ -238,8 +325,8 @@ impl From<&NaslValue> for i64 {
             NaslValue::Null => 0,
             &NaslValue::Exit(x) => x,
             &NaslValue::Return(_) => -1,
-            &NaslValue::Continue => 0,
-            &NaslValue::Break => 0,
+            NaslValue::Continue(_) => 0,
+            NaslValue::Break(_) => 0,
             NaslValue::Fork(_) => 1,
         }
     }
@@ -283,19 +370,29 @@ impl TryFrom<&Token> for NaslValue {
     }
 }
 
-// is used for loops, maybe refactor
+/// Returns the values a foreach loop iterates over
+///
+/// - an array iterates over its elements
+/// - a dict iterates over its values in the order their keys were inserted, the order `keys`
+///   returns the keys in
+/// - NULL does not iterate at all
+/// - any other value, like a number, string or data, iterates once over itself
 impl From<NaslValue> for Vec<NaslValue> {
     fn from(value: NaslValue) -> Self {
         match value {
             NaslValue::Array(ret) => ret,
-            NaslValue::Dict(ret) => ret.values().cloned().collect(),
-            NaslValue::Boolean(_) | NaslValue::Number(_) => vec![value],
-            NaslValue::Data(ret) => ret.into_iter().map(|x| NaslValue::Data(vec![x])).collect(),
-            NaslValue::String(ret) => ret
-                .chars()
-                .map(|x| NaslValue::String(x.to_string()))
-                .collect(),
-            _ => vec![],
+            NaslValue::Dict(ret) => ret.into_values().collect(),
+            NaslValue::Boolean(_)
+            | NaslValue::Number(_)
+            | NaslValue::String(_)
+            | NaslValue::Data(_)
+            | NaslValue::AttackCategory(_) => vec![value],
+            NaslValue::Null
+            | NaslValue::Exit(_)
+            | NaslValue::Return(_)
+            | NaslValue::Continue(_)
+            | NaslValue::Break(_)
+            | NaslValue::Fork(_) => vec![],
         }
     }
 }
@@ -314,3 +411,113 @@ impl From<storage::types::Primitive> for NaslValue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{bytes_to_str, nasl_is_truthy, NaslValue};
+
+    #[test]
+    fn exit_as_number() {
+        let exit = NaslValue::Exit(2);
+        assert_eq!(exit.to_string(), "2");
+        assert_eq!(Vec::<u8>::from(exit.clone()), b"2");
+        assert_eq!(i64::from(&exit), 2);
+        assert_eq!(format!("{exit:?}"), "Exit(2)");
+    }
+
+    #[test]
+    fn to_number() {
+        let cases = [
+            ("0x1F", 31),
+            ("0X1f", 31),
+            ("  42abc", 42),
+            ("-7", -7),
+            ("+7", 7),
+            ("010", 8),
+            ("0", 0),
+            ("0x", 0),
+            ("abc", 0),
+            ("", 0),
+            ("-", 0),
+            ("99999999999999999999", i64::MAX),
+            ("-99999999999999999999", i64::MIN),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                NaslValue::String(input.into()).to_number(),
+                expected,
+                "{input}"
+            );
+        }
+        assert_eq!(NaslValue::Data(b"0x10".to_vec()).to_number(), 16);
+        assert_eq!(NaslValue::Number(3).to_number(), 3);
+        assert_eq!(NaslValue::Null.to_number(), 0);
+        // boolean context stays lenient
+        assert_eq!(i64::from(&NaslValue::String("abc".into())), 1);
+    }
+
+    #[test]
+    fn iterate() {
+        let dict = NaslValue::Dict(
+            [("b", 2), ("c", 3), ("a", 1)]
+                .into_iter()
+                .map(|(k, v)| (k.to_owned(), NaslValue::Number(v)))
+                .collect(),
+        );
+        let numbers = |x: &[i64]| x.iter().map(|x| NaslValue::Number(*x)).collect::<Vec<_>>();
+        assert_eq!(Vec::<NaslValue>::from(dict), numbers(&[2, 3, 1]));
+        assert_eq!(
+            Vec::<NaslValue>::from(NaslValue::Array(numbers(&[3, 1]))),
+            numbers(&[3, 1])
+        );
+        for scalar in [
+            NaslValue::Number(0),
+            NaslValue::Boolean(false),
+            NaslValue::String("abc".to_owned()),
+            NaslValue::Data(vec![1, 2]),
+        ] {
+            assert_eq!(Vec::<NaslValue>::from(scalar.clone()), vec![scalar]);
+        }
+        assert_eq!(Vec::<NaslValue>::from(NaslValue::Null), vec![]);
+        assert_eq!(Vec::<NaslValue>::from(NaslValue::Array(vec![])), vec![]);
+    }
+
+    #[test]
+    fn truthy_containers() {
+        let dict = |x: &[&str]| {
+            NaslValue::Dict(x.iter().map(|k| (k.to_string(), NaslValue::Null)).collect())
+        };
+        let cases = [
+            (NaslValue::Array(vec![]), false),
+            (NaslValue::Array(vec![NaslValue::Null]), true),
+            (NaslValue::Array(vec![NaslValue::Number(0)]), true),
+            (dict(&[]), false),
+            (dict(&["a"]), true),
+            (NaslValue::Data(vec![]), false),
+            (NaslValue::Data(vec![0]), true),
+            (NaslValue::String(String::new()), false),
+            (NaslValue::String("0".to_owned()), false),
+            (NaslValue::Null, false),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(nasl_is_truthy(&value), expected, "{value:?}");
+            assert_eq!(bool::from(value), expected);
+        }
+    }
+
+    #[test]
+    fn data_conversions() {
+        let empty = NaslValue::Data(vec![]);
+        assert!(!bool::from(empty.clone()));
+        assert_eq!(i64::from(&empty), 1);
+        assert_eq!(empty.to_string(), "");
+
+        let bytes = vec![b'a', 0, 0xe4, 0xff];
+        let data = NaslValue::Data(bytes.clone());
+        assert!(bool::from(data.clone()));
+        assert_eq!(i64::from(&data), 1);
+        assert_eq!(data.to_string(), "a\0\u{e4}\u{ff}");
+        assert_eq!(data.to_string(), bytes_to_str(&bytes));
+        assert_eq!(data.to_string().chars().count(), bytes.len());
+    }
+}
@@ -10,7 +10,8 @@ use crate::{
     lexer::{End, Lexer},
     operation::Operation,
     token::{Category, Token},
-    unclosed_token, unexpected_end, unexpected_token, Statement, {AssignOrder, StatementKind},
+    unclosed_token, unexpected_end, unexpected_statement, unexpected_token, Statement,
+    {AssignOrder, LabeledStatement, StatementKind},
 };
 pub(crate) trait Prefix {
     /// Handles statements before operation statements get handled.
@@ -32,13 +33,72 @@ fn prefix_binding_power(token: &Token) -> Result<u8, SyntaxError> {
 }
 
 impl<'a> Lexer<'a> {
+    /// Returns `do` as identifier when it does not start a loop
+    ///
+    /// A loop starts with a block or a statement beginning with an identifier, e.g.
+    /// `do { ... } while (...);` or `do a++; while (...);`. Otherwise, e.g. in `do = 1;` or
+    /// `f(do)`, it is a variable.
+    fn do_as_identifier(&mut self, token: Token) -> Token {
+        if token.category() != &Category::Identifier(crate::IdentifierType::Do) {
+            return token;
+        }
+        match self.peek().as_ref().map(|x| x.category()) {
+            Some(Category::LeftCurlyBracket | Category::Identifier(_)) => token,
+            _ => Token {
+                category: Category::Identifier(crate::IdentifierType::Undefined("do".to_owned())),
+                ..token
+            },
+        }
+    }
+
+    /// Returns true when the next tokens are a colon followed by a loop keyword
+    fn is_loop_label(&mut self) -> bool {
+        use crate::IdentifierType::*;
+        let mut next = self.peek_many(3).into_iter().map(|x| x.category);
+        matches!(next.next(), Some(Category::DoublePoint))
+            && match next.next() {
+                Some(Category::Identifier(For | ForEach | While | Repeat)) => true,
+                // a named argument may be the variable do, e.g. `f(a: do)`
+                Some(Category::Identifier(Do)) => matches!(
+                    next.next(),
+                    Some(Category::LeftCurlyBracket | Category::Identifier(_))
+                ),
+                _ => false,
+            }
+    }
+
+    /// Parses a loop with a label, e.g. `outer: for (...) { ... }`
+    fn parse_labeled_loop(&mut self, label: Token) -> Result<(End, Statement), SyntaxError> {
+        // skip the colon
+        self.token();
+        let (end, stmt) = self.statement(0, &|c| c == &Category::Semicolon)?;
+        match end {
+            End::Done(end) => Ok((
+                End::Done(end.clone()),
+                Statement::with_start_end_token(
+                    label.clone(),
+                    end,
+                    StatementKind::Labeled(Box::new(LabeledStatement {
+                        label,
+                        statement: stmt,
+                    })),
+                ),
+            )),
+            End::Continue => Err(unexpected_statement!(stmt)),
+        }
+    }
+
     fn parse_variable(&mut self, token: Token) -> Result<(End, Statement), SyntaxError> {
+        let token = self.do_as_identifier(token);
         if !matches!(
             token.category(),
             Category::Identifier(crate::IdentifierType::Undefined(_))
         ) {
             return Err(unexpected_token!(token));
         }
+        if self.is_loop_label() {
+            return self.parse_labeled_loop(token);
+        }
         use End::*;
         let (kind, end) = {
             if let Some(nt) = self.peek() {
@@ -114,6 +174,7 @@ impl<'a> Prefix for Lexer<'a> {
         abort: &impl Fn(&Category) -> bool,
     ) -> Result<(End, Statement), SyntaxError> {
         use End::*;
+        let token = self.do_as_identifier(token);
         let op = Operation::new(&token).ok_or_else(|| unexpected_token!(token.clone()))?;
         match op {
             Operation::Operator(kind) => {
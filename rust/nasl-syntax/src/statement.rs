@@ -33,10 +33,10 @@ pub enum StatementKind {
     Exit(Box<Statement>),
     /// Special Return statement
     Return(Box<Statement>),
-    /// Special Break statement
-    Break,
-    /// Special Continue statement
-    Continue,
+    /// Special Break statement, containing an optional label of the loop to break
+    Break(Option<Box<Token>>),
+    /// Special Continue statement, containing an optional label of the loop to continue
+    Continue(Option<Box<Token>>),
     /// Special include call
     Include(Box<Statement>),
     /// Declares a new variable in either global or local scope
@@ -50,37 +50,102 @@ pub enum StatementKind {
     /// An Operator (e.g. +, -, *)
     Operator(TokenCategory, Vec<Statement>),
     /// If statement, containing a condition, expression to be executed when the condition is true and an optional else expression
-    If(
-        Box<Statement>,
-        Box<Statement>,
-        Option<Token>,
-        Option<Box<Statement>>,
-    ),
+    If(Box<Conditional>),
     /// For statement, containing a declaration/assignment, a condition, a execution per round before body execution, body execution
     /// e.g. `for (i = 0; i < 10; i++) display("hi");`
-    For(
-        Box<Statement>,
-        Box<Statement>,
-        Box<Statement>,
-        Box<Statement>,
-    ),
+    For(Box<ForLoop>),
     /// While statement, containing a condition and a block
     While(Box<Statement>, Box<Statement>),
     /// repeat statement, containing a block and a condition
     Repeat(Box<Statement>, Box<Statement>),
     /// foreach statement, containing a variable in array and a block
-    ForEach(Token, Box<Statement>, Box<Statement>),
+    ForEach(Box<ForEachLoop>),
+    /// A loop with a label, containing the label and the loop
+    /// e.g. `outer: for (i = 0; i < 10; i++) { ... }`
+    Labeled(Box<LabeledStatement>),
     /// A set of expression within { ... }
     Block(Vec<Statement>),
     /// Function declaration; contains an identifier token, parameter statement and a block statement
-    // The third token can be deleted as it is end statement end token
-    FunctionDeclaration(Token, Box<Statement>, Box<Statement>),
+    FunctionDeclaration(Box<FunctionDefinition>),
     /// An empty operation, e.g. ;
     NoOp,
     /// End of File
     EoF,
 }
 
+// The variants containing tokens or more than two statements are boxed as a whole to keep
+// StatementKind small.
+
+/// The content of an if statement
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conditional {
+    /// The condition
+    pub condition: Statement,
+    /// Is executed when the condition is true
+    pub then: Statement,
+    /// The else keyword
+    pub else_token: Option<Token>,
+    /// Is executed when the condition is false
+    pub otherwise: Option<Statement>,
+}
+
+/// Drops a chain of else if branches iteratively to not overflow the stack on long chains
+impl Drop for Conditional {
+    fn drop(&mut self) {
+        let mut next = self.otherwise.take();
+        while let Some(stmt) = next {
+            next = match stmt.kind {
+                StatementKind::If(mut x) => x.otherwise.take(),
+                _ => None,
+            };
+        }
+    }
+}
+
+/// The content of a for statement
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForLoop {
+    /// Is executed once before the first round
+    pub assignment: Statement,
+    /// Is checked before each round
+    pub condition: Statement,
+    /// Is executed after each round
+    pub update: Statement,
+    /// Is executed each round
+    pub body: Statement,
+}
+
+/// The content of a foreach statement
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForEachLoop {
+    /// The variable the elements are assigned to
+    pub variable: Token,
+    /// The array to iterate over
+    pub iterable: Statement,
+    /// Is executed for each element
+    pub body: Statement,
+}
+
+/// The content of a labeled statement
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LabeledStatement {
+    /// The label
+    pub label: Token,
+    /// The labeled loop
+    pub statement: Statement,
+}
+
+/// The content of a function declaration
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionDefinition {
+    /// The name of the function
+    pub name: Token,
+    /// The parameter statement
+    pub parameters: Statement,
+    /// The block statement
+    pub body: Statement,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 /// Is the definition of a Statement
 ///
@@ -120,12 +185,15 @@ impl Statement {
             | StatementKind::AttackCategory
             | StatementKind::Variable
             | StatementKind::NoOp
-            | StatementKind::Break
-            | StatementKind::Continue
+            | StatementKind::Break(None)
+            | StatementKind::Continue(None)
             | StatementKind::Array(None)
             | StatementKind::EoF => {
                 // doesn't contain further statements
             }
+            StatementKind::Break(Some(x)) | StatementKind::Continue(Some(x)) => {
+                results.push(x);
+            }
             StatementKind::NamedParameter(x)
             | StatementKind::Exit(x)
             | StatementKind::Return(x)
@@ -134,6 +202,10 @@ impl Statement {
             | StatementKind::Array(Some(x)) => {
                 results.extend(x.as_tokens());
             }
+            StatementKind::Labeled(x) => {
+                results.push(&x.label);
+                results.extend(x.statement.as_tokens());
+            }
             StatementKind::Block(x)
             | StatementKind::Operator(_, x)
             | StatementKind::Parameter(x)
@@ -148,31 +220,31 @@ impl Statement {
                 results.extend(x.as_tokens());
                 results.extend(y.as_tokens());
             }
-            StatementKind::If(r, x, y, z) => {
-                results.extend(r.as_tokens());
-                results.extend(x.as_tokens());
-                if let Some(y) = y {
+            StatementKind::If(x) => {
+                results.extend(x.condition.as_tokens());
+                results.extend(x.then.as_tokens());
+                if let Some(y) = &x.else_token {
                     results.push(y);
                 }
-                if let Some(z) = z {
+                if let Some(z) = &x.otherwise {
                     results.extend(z.as_tokens());
                 }
             }
-            StatementKind::For(r, x, y, z) => {
-                results.extend(r.as_tokens());
-                results.extend(x.as_tokens());
-                results.extend(y.as_tokens());
-                results.extend(z.as_tokens());
+            StatementKind::For(x) => {
+                results.extend(x.assignment.as_tokens());
+                results.extend(x.condition.as_tokens());
+                results.extend(x.update.as_tokens());
+                results.extend(x.body.as_tokens());
             }
-            StatementKind::ForEach(x, y, z) => {
-                results.push(x);
-                results.extend(y.as_tokens());
-                results.extend(z.as_tokens());
+            StatementKind::ForEach(x) => {
+                results.push(&x.variable);
+                results.extend(x.iterable.as_tokens());
+                results.extend(x.body.as_tokens());
             }
-            StatementKind::FunctionDeclaration(x, y, w) => {
-                results.push(x);
-                results.extend(y.as_tokens());
-                results.extend(w.as_tokens());
+            StatementKind::FunctionDeclaration(x) => {
+                results.push(&x.name);
+                results.extend(x.parameters.as_tokens());
+                results.extend(x.body.as_tokens());
             }
         };
         if let Some(t) = self.end.as_ref() {
@@ -194,6 +266,7 @@ impl Statement {
             | StatementKind::ForEach(..)
             | StatementKind::While(..)
             | StatementKind::Repeat(..)
+            | StatementKind::Labeled(..)
             | StatementKind::Assign(..)
             | StatementKind::NamedParameter(_)
             | StatementKind::Exit(_)
@@ -204,12 +277,13 @@ impl Statement {
             | StatementKind::AttackCategory
             | StatementKind::Variable
             | StatementKind::NoOp
-            | StatementKind::Break
-            | StatementKind::Continue
+            | StatementKind::Break(_)
+            | StatementKind::Continue(_)
             | StatementKind::EoF => &[],
 
             // contains Parameter
-            StatementKind::Call(x) | StatementKind::FunctionDeclaration(_, x, _) => x.children(),
+            StatementKind::Call(x) => x.children(),
+            StatementKind::FunctionDeclaration(x) => x.parameters.children(),
 
             StatementKind::Block(x)
             | StatementKind::Operator(_, x)
@@ -235,6 +309,91 @@ impl Statement {
         Range { start, end }
     }
 
+    /// Compares two statements while ignoring the source positions
+    ///
+    /// In difference to `==` only the token categories and the structure are compared
+    /// so that differently formatted but equivalent code is considered equal.
+    pub fn semantic_eq(&self, other: &Statement) -> bool {
+        fn token_eq(a: &Token, b: &Token) -> bool {
+            a.category == b.category
+        }
+        fn all_eq(a: &[Statement], b: &[Statement]) -> bool {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.semantic_eq(b))
+        }
+        let same_end = match (&self.end, &other.end) {
+            (None, None) => true,
+            (Some(a), Some(b)) => token_eq(a, b),
+            _ => false,
+        };
+        if !token_eq(&self.start, &other.start) || !same_end {
+            return false;
+        }
+        use StatementKind::*;
+        match (self.kind(), other.kind()) {
+            (Primitive, Primitive)
+            | (AttackCategory, AttackCategory)
+            | (Variable, Variable)
+            | (NoOp, NoOp)
+            | (EoF, EoF)
+            | (Array(None), Array(None)) => true,
+            (Array(Some(a)), Array(Some(b)))
+            | (Call(a), Call(b))
+            | (Exit(a), Exit(b))
+            | (Return(a), Return(b))
+            | (Include(a), Include(b))
+            | (NamedParameter(a), NamedParameter(b)) => a.semantic_eq(b),
+            (Declare(a), Declare(b)) | (Parameter(a), Parameter(b)) | (Block(a), Block(b)) => {
+                all_eq(a, b)
+            }
+            (Break(a), Break(b)) | (Continue(a), Continue(b)) => match (a, b) {
+                (None, None) => true,
+                (Some(a), Some(b)) => token_eq(a, b),
+                _ => false,
+            },
+            (Labeled(a), Labeled(b)) => {
+                token_eq(&a.label, &b.label) && a.statement.semantic_eq(&b.statement)
+            }
+            (Operator(c1, a), Operator(c2, b)) => c1 == c2 && all_eq(a, b),
+            (Assign(c1, o1, l1, r1), Assign(c2, o2, l2, r2)) => {
+                c1 == c2 && o1 == o2 && l1.semantic_eq(l2) && r1.semantic_eq(r2)
+            }
+            (If(a), If(b)) => {
+                a.condition.semantic_eq(&b.condition)
+                    && a.then.semantic_eq(&b.then)
+                    && match (&a.else_token, &b.else_token) {
+                        (None, None) => true,
+                        (Some(a), Some(b)) => token_eq(a, b),
+                        _ => false,
+                    }
+                    && match (&a.otherwise, &b.otherwise) {
+                        (None, None) => true,
+                        (Some(a), Some(b)) => a.semantic_eq(b),
+                        _ => false,
+                    }
+            }
+            (For(a), For(b)) => {
+                a.assignment.semantic_eq(&b.assignment)
+                    && a.condition.semantic_eq(&b.condition)
+                    && a.update.semantic_eq(&b.update)
+                    && a.body.semantic_eq(&b.body)
+            }
+            (While(a1, b1), While(a2, b2)) | (Repeat(a1, b1), Repeat(a2, b2)) => {
+                a1.semantic_eq(a2) && b1.semantic_eq(b2)
+            }
+            (ForEach(a), ForEach(b)) => {
+                token_eq(&a.variable, &b.variable)
+                    && a.iterable.semantic_eq(&b.iterable)
+                    && a.body.semantic_eq(&b.body)
+            }
+            (FunctionDeclaration(a), FunctionDeclaration(b)) => {
+                token_eq(&a.name, &b.name)
+                    && a.parameters.semantic_eq(&b.parameters)
+                    && a.body.semantic_eq(&b.body)
+            }
+            _ => false,
+        }
+    }
+
     /// Finds all statements in itself or itself that matches the wanted function
     ///
     /// Example:
@@ -275,8 +434,8 @@ impl Statement {
                 | StatementKind::AttackCategory
                 | StatementKind::Variable
                 | StatementKind::NoOp
-                | StatementKind::Break
-                | StatementKind::Continue
+                | StatementKind::Break(_)
+                | StatementKind::Continue(_)
                 | StatementKind::Array(None)
                 | StatementKind::EoF => {
                     // doesn't contain further statements
@@ -289,6 +448,9 @@ impl Statement {
                 | StatementKind::Array(Some(x)) => {
                     results.extend(Self::find(x, wanted));
                 }
+                StatementKind::Labeled(x) => {
+                    results.extend(Self::find(&x.statement, wanted));
+                }
                 StatementKind::Block(x)
                 | StatementKind::Operator(_, x)
                 | StatementKind::Parameter(x)
@@ -303,27 +465,27 @@ impl Statement {
                     results.extend(Self::find(x, wanted));
                     results.extend(Self::find(y, wanted));
                 }
-                StatementKind::If(r, x, _, z) => {
-                    results.extend(Self::find(r, wanted));
-                    results.extend(Self::find(x, wanted));
+                StatementKind::If(x) => {
+                    results.extend(Self::find(&x.condition, wanted));
+                    results.extend(Self::find(&x.then, wanted));
 
-                    if let Some(z) = z {
+                    if let Some(z) = &x.otherwise {
                         results.extend(Self::find(z, wanted));
                     }
                 }
-                StatementKind::For(r, x, y, z) => {
-                    results.extend(Self::find(r, wanted));
-                    results.extend(Self::find(x, wanted));
-                    results.extend(Self::find(y, wanted));
-                    results.extend(Self::find(z, wanted));
+                StatementKind::For(x) => {
+                    results.extend(Self::find(&x.assignment, wanted));
+                    results.extend(Self::find(&x.condition, wanted));
+                    results.extend(Self::find(&x.update, wanted));
+                    results.extend(Self::find(&x.body, wanted));
                 }
-                StatementKind::ForEach(_, y, z) => {
-                    results.extend(Self::find(y, wanted));
-                    results.extend(Self::find(z, wanted));
+                StatementKind::ForEach(x) => {
+                    results.extend(Self::find(&x.iterable, wanted));
+                    results.extend(Self::find(&x.body, wanted));
                 }
-                StatementKind::FunctionDeclaration(_, y, w) => {
-                    results.extend(Self::find(y, wanted));
-                    results.extend(Self::find(w, wanted));
+                StatementKind::FunctionDeclaration(x) => {
+                    results.extend(Self::find(&x.parameters, wanted));
+                    results.extend(Self::find(&x.body, wanted));
                 }
             };
 
@@ -331,6 +493,71 @@ impl Statement {
         }
     }
 
+    /// Calls the given function for itself and each contained statement
+    ///
+    /// In difference to `find` it visits every statement, parents are visited before their
+    /// children.
+    pub fn walk<'a, F>(&'a self, f: &mut F)
+    where
+        F: FnMut(&'a Statement),
+    {
+        f(self);
+        match self.kind() {
+            StatementKind::Primitive
+            | StatementKind::AttackCategory
+            | StatementKind::Variable
+            | StatementKind::NoOp
+            | StatementKind::Break(_)
+            | StatementKind::Continue(_)
+            | StatementKind::Array(None)
+            | StatementKind::EoF => {
+                // doesn't contain further statements
+            }
+            StatementKind::NamedParameter(x)
+            | StatementKind::Exit(x)
+            | StatementKind::Return(x)
+            | StatementKind::Include(x)
+            | StatementKind::Call(x)
+            | StatementKind::Array(Some(x)) => x.walk(f),
+            StatementKind::Labeled(x) => x.statement.walk(f),
+            StatementKind::Block(x)
+            | StatementKind::Operator(_, x)
+            | StatementKind::Parameter(x)
+            | StatementKind::Declare(x) => {
+                for stmt in x {
+                    stmt.walk(f);
+                }
+            }
+            StatementKind::While(x, y)
+            | StatementKind::Repeat(x, y)
+            | StatementKind::Assign(_, _, x, y) => {
+                x.walk(f);
+                y.walk(f);
+            }
+            StatementKind::ForEach(x) => {
+                x.iterable.walk(f);
+                x.body.walk(f);
+            }
+            StatementKind::FunctionDeclaration(x) => {
+                x.parameters.walk(f);
+                x.body.walk(f);
+            }
+            StatementKind::If(x) => {
+                x.condition.walk(f);
+                x.then.walk(f);
+                if let Some(z) = &x.otherwise {
+                    z.walk(f);
+                }
+            }
+            StatementKind::For(x) => {
+                x.assignment.walk(f);
+                x.condition.walk(f);
+                x.update.walk(f);
+                x.body.walk(f);
+            }
+        }
+    }
+
     /// Returns the initial token of a Statement
     pub fn start(&self) -> &Token {
         &self.start
@@ -420,28 +647,46 @@ impl std::fmt::Display for Statement {
                 [l] => write!(f, "{o}{l}"),
                 _ => write!(f, "({o} ({}))", as_str_list(args)),
             },
-            StatementKind::If(c, x, _, e) => {
-                let r = write!(f, "if ({c}) {x}");
-                if let Some(e) = e {
+            StatementKind::If(x) => {
+                let r = write!(f, "if ({}) {}", x.condition, x.then);
+                if let Some(e) = &x.otherwise {
                     write!(f, " else {e}")
                 } else {
                     r
                 }
             }
-            StatementKind::For(i, c, u, e) => write!(f, "for ({i}; {c}; {u}) {{ {e} }}"),
+            StatementKind::For(x) => write!(
+                f,
+                "for ({}; {}; {}) {{ {} }}",
+                x.assignment, x.condition, x.update, x.body
+            ),
             StatementKind::While(c, e) => write!(f, "while ({c}) {{{e}}}"),
             StatementKind::Repeat(e, c) => write!(f, "repeat {e} until {c}"),
-            StatementKind::ForEach(v, a, e) => {
-                write!(f, "foreach {}({a}) {{{e}}}", v.category())
+            StatementKind::ForEach(x) => {
+                write!(
+                    f,
+                    "foreach {}({}) {{{}}}",
+                    x.variable.category(),
+                    x.iterable,
+                    x.body
+                )
             }
             StatementKind::Block(..) => write!(f, "{{ ... }}"),
-            StatementKind::FunctionDeclaration(n, p, _) => {
-                write!(f, "function {}({}) {{ ... }}", n.category(), p)
+            StatementKind::FunctionDeclaration(x) => {
+                write!(
+                    f,
+                    "function {}({}) {{ ... }}",
+                    x.name.category(),
+                    x.parameters
+                )
             }
             StatementKind::NoOp => write!(f, "NoOp"),
             StatementKind::EoF => write!(f, "EoF"),
-            StatementKind::Break => write!(f, "break"),
-            StatementKind::Continue => write!(f, "continue"),
+            StatementKind::Labeled(x) => write!(f, "{}: {}", x.label.category(), x.statement),
+            StatementKind::Break(None) => write!(f, "break"),
+            StatementKind::Break(Some(l)) => write!(f, "break {}", l.category()),
+            StatementKind::Continue(None) => write!(f, "continue"),
+            StatementKind::Continue(Some(l)) => write!(f, "continue {}", l.category()),
         }
     }
 }
@@ -535,3 +780,58 @@ mod position {
         assert_eq!(tests, expected.len());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Statement, StatementKind};
+
+    fn statements(code: &str) -> Vec<Statement> {
+        parse(code).map(|x| x.unwrap()).collect()
+    }
+
+    fn semantic_eq(a: &str, b: &str) -> bool {
+        let (a, b) = (statements(a), statements(b));
+        a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| a.semantic_eq(b))
+    }
+
+    #[test]
+    fn size() {
+        // large payloads are boxed so that moving a statement stays cheap
+        assert!(std::mem::size_of::<StatementKind>() <= 64);
+        assert!(std::mem::size_of::<Statement>() <= 192);
+    }
+
+    #[test]
+    fn semantic_eq_ignores_formatting() {
+        let a = r#"
+        function test(a, b) {
+            if (a > b) return a; else return b;
+        }
+        foreach x(make_list(1, 2)) { y[x] = test(a: x, b: 1); }
+        "#;
+        let b = "function test(a,b){if(a>b)return a;else return b;}\n\nforeach x (make_list(1,2)){y[x]=test(a:x,b:1);}";
+        assert!(semantic_eq(a, b));
+        assert_ne!(statements(a), statements(b));
+    }
+
+    #[test]
+    fn walk_visits_each_statement() {
+        let stmt = parse("if (a) { b = 1; } else c(d: 2);")
+            .next()
+            .unwrap()
+            .unwrap();
+        let mut amount = 0;
+        stmt.walk(&mut |_| amount += 1);
+        // if, a, block, assign, b, 1, call, parameter, named parameter, 2
+        assert_eq!(amount, 10);
+    }
+
+    #[test]
+    fn semantic_eq_detects_differences() {
+        assert!(!semantic_eq("a = 1;", "a = 2;"));
+        assert!(!semantic_eq("a = 1;", "b = 1;"));
+        assert!(!semantic_eq("a + 1;", "a - 1;"));
+        assert!(!semantic_eq("a++;", "++a;"));
+        assert!(!semantic_eq("if (a) b;", "if (a) b; else c;"));
+    }
+}
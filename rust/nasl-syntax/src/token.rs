@@ -101,6 +101,16 @@ impl IdentifierType {
          self.len() == 0
     }
 
+    /// Returns all reserved words
+    pub fn keywords() -> &'static [&'static str] {
+        &[$(stringify!($matcher)),*]
+    }
+
+    /// Returns true when the given string is a reserved word
+    pub fn is_keyword(keyword: &str) -> bool {
+        Self::keywords().contains(&keyword)
+    }
+
 }
 impl Display for IdentifierType {
 
@@ -145,6 +155,8 @@ pub enum IdentifierType {
     Repeat,
     /// until
     Until,
+    /// do
+    Do,
     /// local_var
     LocalVar,
     /// global_var
@@ -179,6 +191,7 @@ make_keyword_matcher! {
     while: IdentifierType::While,
     repeat: IdentifierType::Repeat,
     until: IdentifierType::Until,
+    do: IdentifierType::Do,
     local_var: IdentifierType::LocalVar,
     global_var: IdentifierType::GlobalVar,
     NULL: IdentifierType::Null,
@@ -461,6 +474,28 @@ impl Token {
                 | Category::UnknownSymbol
         )
     }
+
+    /// Returns the raw source text of the token within the given source
+    ///
+    /// In difference to the category the lexeme is not unescaped, strings and data contain
+    /// their quotes. The source must be the code the token was created from; when the position
+    /// is not within the source an empty string is returned.
+    ///
+    /// # Examples
+    /// Basic usage:
+    ///
+    /// ```
+    /// let code = r#"a = 'b\n';"#;
+    /// let lexemes: Vec<_> = nasl_syntax::Tokenizer::new(code)
+    ///     .map(|t| t.lexeme(code))
+    ///     .collect();
+    /// assert_eq!(lexemes, ["a", "=", r#"'b\n'"#, ";"]);
+    /// ```
+    pub fn lexeme<'a>(&self, source: &'a str) -> &'a str {
+        source
+            .get(self.position.0..self.position.1)
+            .unwrap_or_default()
+    }
 }
 
 /// Tokenizer uses a cursor to create tokens
@@ -885,6 +920,16 @@ mod tests {
         verify_tokens!(">>>=", [">>>="]);
     }
 
+    #[test]
+    fn greater_less_combinations() {
+        verify_tokens!("> <", [">", "<"]);
+        verify_tokens!(">><", [">>", "<"]);
+        verify_tokens!(">!", [">", "!"]);
+        verify_tokens!(">! <", [">", "!", "<"]);
+        verify_tokens!("><=", ["><", "="]);
+        verify_tokens!(">!<<", [">!<", "<"]);
+    }
+
     #[test]
     fn unquotable_string() {
         verify_tokens!(
@@ -938,6 +983,7 @@ mod tests {
         verify_tokens!("while", ["while"]);
         verify_tokens!("repeat", ["repeat"]);
         verify_tokens!("until", ["until"]);
+        verify_tokens!("do", ["do"]);
         verify_tokens!("local_var", ["local_var"]);
         verify_tokens!("global_var", ["global_var"]);
         verify_tokens!("NULL", ["NULL"]);
@@ -975,4 +1021,33 @@ mod tests {
     fn repeat_x_times() {
         verify_tokens!("x() x 10;", ["x", "(", ")", "X", "10", ";"]);
     }
+
+    #[test]
+    fn keyword_list() {
+        for keyword in IdentifierType::keywords() {
+            assert!(IdentifierType::is_keyword(keyword));
+            let identifier = IdentifierType::new(keyword);
+            assert!(!matches!(identifier, IdentifierType::Undefined(_)));
+            assert_eq!(&identifier.to_string(), keyword);
+        }
+        assert!(!IdentifierType::is_keyword("script_oid"));
+        assert!(!IdentifierType::is_keyword("For"));
+    }
+
+    #[test]
+    fn lexeme() {
+        let code = r#"a = "b\n" + 'c\n' + 0x1F; # d"#;
+        let lexemes: Vec<_> = Tokenizer::new(code).map(|t| t.lexeme(code)).collect();
+        assert_eq!(
+            lexemes,
+            ["a", "=", r#""b\n""#, "+", r"'c\n'", "+", "0x1F", ";", "# d"]
+        );
+        let unclosed = "a = 'b;";
+        let lexemes: Vec<_> = Tokenizer::new(unclosed)
+            .map(|t| t.lexeme(unclosed))
+            .collect();
+        assert_eq!(lexemes, ["a", "=", "'b;"]);
+        assert_eq!(Token::default().lexeme(code), "");
+        assert_eq!(Tokenizer::new(code).last().unwrap().lexeme("a"), "");
+    }
 }
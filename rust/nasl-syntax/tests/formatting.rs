@@ -0,0 +1,80 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use nasl_syntax::{format_code_with, parse, BraceStyle, FormatOptions, Statement};
+
+    /// Returns all nasl and inc files within the given directory and its subdirectories
+    fn corpus(dir: &Path, result: &mut Vec<PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                corpus(&path, result);
+            } else if matches!(
+                path.extension().and_then(|x| x.to_str()),
+                Some("nasl" | "inc")
+            ) {
+                result.push(path);
+            }
+        }
+    }
+
+    fn statements(code: &str) -> Vec<Statement> {
+        parse(code).map(|x| x.unwrap()).collect()
+    }
+
+    /// Returns each combination of indentation, brace style and keyword spacing
+    fn all_options() -> Vec<FormatOptions> {
+        let mut result = vec![];
+        for brace_style in [BraceStyle::KAndR, BraceStyle::Allman] {
+            for (indent_width, use_tabs) in [(2, false), (4, false), (4, true)] {
+                for keyword_space in [false, true] {
+                    result.push(FormatOptions {
+                        indent_width,
+                        use_tabs,
+                        brace_style,
+                        keyword_space,
+                    });
+                }
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn idempotent_and_equivalent() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let mut files = vec![];
+        corpus(&root.join("examples"), &mut files);
+        corpus(&root.join("../nasl/tests"), &mut files);
+        let mut formatted_files = 0;
+        for file in files {
+            let code = std::fs::read_to_string(&file).unwrap();
+            // the corpus contains files with intentional syntax errors
+            if parse(&code).any(|x| x.is_err()) {
+                continue;
+            }
+            formatted_files += 1;
+            let original = statements(&code);
+            for options in all_options() {
+                let formatted = format_code_with(&code, &options).unwrap();
+                assert_eq!(
+                    format_code_with(&formatted, &options).unwrap(),
+                    formatted,
+                    "{} is not idempotent with {options:?}",
+                    file.display()
+                );
+                let formatted = statements(&formatted);
+                assert_eq!(original.len(), formatted.len(), "{}", file.display());
+                for (a, b) in original.iter().zip(&formatted) {
+                    assert!(a.semantic_eq(b), "{}: {a} != {b}", file.display());
+                }
+            }
+        }
+        assert!(formatted_files > 10);
+    }
+}
@@ -5,7 +5,7 @@
 #[cfg(test)]
 mod test {
 
-    use nasl_syntax::parse;
+    use nasl_syntax::{parse, parse_with_eof, Statement, StatementKind, SyntaxError};
 
     #[test]
     fn change_to_peek() {
@@ -184,4 +184,40 @@ soc = open_sock_tcp(port);
             x.unwrap();
         }
     }
+
+    fn ends_with_eof(results: &[Result<Statement, SyntaxError>]) -> bool {
+        matches!(results.last(), Some(Ok(x)) if x.kind() == &StatementKind::EoF)
+    }
+
+    #[test]
+    fn eof_on_complete_file() {
+        let code = "a = 1;\nif (a) { b = 2; }\n# trailing comment\n";
+        let results: Vec<_> = parse_with_eof(code).collect();
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|x| x.is_ok()));
+        assert!(ends_with_eof(&results));
+        // errors in between do not prevent a clean end
+        let results: Vec<_> = parse_with_eof("}\nb = 1;").collect();
+        assert!(results[0].is_err());
+        assert!(ends_with_eof(&results));
+        let results: Vec<_> = parse_with_eof("").collect();
+        assert!(ends_with_eof(&results));
+        assert_eq!(results.len(), 1);
+        // parse itself does not return the marker
+        assert_eq!(parse(code).count(), 2);
+    }
+
+    #[test]
+    fn no_eof_on_truncated_file() {
+        for code in [
+            "a = 1;\nb = 2",
+            "a = 1;\nif (a) { b = 2;",
+            "a = 1;\nb = foo(1,",
+        ] {
+            let results: Vec<_> = parse_with_eof(code).collect();
+            assert!(results[0].is_ok(), "{code}");
+            assert!(!ends_with_eof(&results), "{code}");
+            assert!(results.last().unwrap().is_err(), "{code}");
+        }
+    }
 }
@@ -0,0 +1,194 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+//! Verifies that formatting generated scripts keeps their meaning
+//!
+//! The scripts are generated as trees of the common statements and rendered to source. Parsing
+//! the formatted source must result in the same statements, ignoring their positions, and
+//! formatting it again must not change it anymore.
+
+#[cfg(test)]
+mod tests {
+    use nasl_syntax::{format_code, parse, Statement};
+    use proptest::prelude::*;
+
+    #[derive(Clone, Debug)]
+    enum Expr {
+        Number(u32),
+        String(String),
+        Data(String),
+        Variable(String),
+        Index(String, Box<Expr>),
+        Unary(&'static str, Box<Expr>),
+        Binary(&'static str, Box<Expr>, Box<Expr>, bool),
+        Call(String, Vec<Expr>, Vec<(String, Expr)>),
+    }
+
+    #[derive(Clone, Debug)]
+    enum Stmt {
+        Assign(String, Expr),
+        Call(String, Vec<Expr>),
+        Return(Expr),
+        LocalVar(Vec<String>),
+        If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+        While(Expr, Box<Stmt>),
+        ForEach(String, Expr, Box<Stmt>),
+        Block(Vec<Stmt>),
+        Function(String, Vec<String>, Vec<Stmt>),
+    }
+
+    const BINARY: &[&str] = &[
+        "+", "-", "*", "/", "%", "**", "==", "!=", "<", ">", "<=", ">=", "&&", "||", "&", "|", "^",
+        "<<", ">>", ">>>", "=~", "!~",
+    ];
+    const UNARY: &[&str] = &["-", "!", "~"];
+
+    fn render_args(positional: &[Expr], named: &[(String, Expr)]) -> String {
+        positional
+            .iter()
+            .map(Expr::render)
+            .chain(named.iter().map(|(n, x)| format!("{n}:{}", x.render())))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    impl Expr {
+        /// Renders the expression with as few whitespace as possible
+        fn render(&self) -> String {
+            match self {
+                Expr::Number(x) => x.to_string(),
+                Expr::String(x) => format!("\"{x}\""),
+                Expr::Data(x) => format!("'{x}'"),
+                Expr::Variable(x) => x.clone(),
+                Expr::Index(x, i) => format!("{x}[{}]", i.render()),
+                Expr::Unary(o, x) => format!("{o}({})", x.render()),
+                // a prefix operator of the right side must stay separated
+                Expr::Binary(o, l, r, true) => format!("({}{o} {})", l.render(), r.render()),
+                Expr::Binary(o, l, r, false) => format!("{}{o} {}", l.render(), r.render()),
+                Expr::Call(n, p, named) => format!("{n}({})", render_args(p, named)),
+            }
+        }
+    }
+
+    impl Stmt {
+        fn render(&self) -> String {
+            match self {
+                Stmt::Assign(v, x) => format!("{v}={};", x.render()),
+                Stmt::Call(n, p) => format!("{n}({});", render_args(p, &[])),
+                Stmt::Return(x) => format!("return {};", x.render()),
+                Stmt::LocalVar(v) => format!("local_var {};", v.join(",")),
+                Stmt::If(c, x, None) => format!("if({}){}", c.render(), x.render()),
+                Stmt::If(c, x, Some(e)) => {
+                    format!("if({}){}else {}", c.render(), x.render(), e.render())
+                }
+                Stmt::While(c, x) => format!("while({}){}", c.render(), x.render()),
+                Stmt::ForEach(v, a, x) => format!("foreach {v}({}){}", a.render(), x.render()),
+                Stmt::Block(x) => format!("{{{}}}", render_block(x)),
+                Stmt::Function(n, p, x) => {
+                    format!("function {n}({}){{{}}}", p.join(","), render_block(x))
+                }
+            }
+        }
+    }
+
+    fn render_block(statements: &[Stmt]) -> String {
+        statements.iter().map(Stmt::render).collect()
+    }
+
+    /// Identifiers are prefixed so that they never collide with a keyword
+    fn identifier() -> impl Strategy<Value = String> {
+        "[a-z][a-z0-9_]{0,3}".prop_map(|x| format!("v_{x}"))
+    }
+
+    fn expression() -> impl Strategy<Value = Expr> {
+        let leaf = prop_oneof![
+            (0u32..1000).prop_map(Expr::Number),
+            identifier().prop_map(Expr::Variable),
+            "[a-zA-Z0-9 _.]{0,6}".prop_map(Expr::String),
+            r"([a-z ]|\\'|\\n|\\\\){0,4}".prop_map(Expr::Data),
+        ];
+        leaf.prop_recursive(4, 24, 3, |inner| {
+            prop_oneof![
+                (identifier(), inner.clone()).prop_map(|(v, i)| Expr::Index(v, Box::new(i))),
+                (prop::sample::select(UNARY), inner.clone())
+                    .prop_map(|(o, x)| Expr::Unary(o, Box::new(x))),
+                (
+                    prop::sample::select(BINARY),
+                    inner.clone(),
+                    inner.clone(),
+                    any::<bool>()
+                )
+                    .prop_map(|(o, l, r, p)| Expr::Binary(
+                        o,
+                        Box::new(l),
+                        Box::new(r),
+                        p
+                    )),
+                (
+                    identifier(),
+                    prop::collection::vec(inner.clone(), 0..3),
+                    prop::collection::vec((identifier(), inner), 0..2)
+                )
+                    .prop_map(|(n, p, named)| Expr::Call(n, p, named)),
+            ]
+        })
+    }
+
+    fn statement() -> impl Strategy<Value = Stmt> {
+        let leaf = prop_oneof![
+            (identifier(), expression()).prop_map(|(v, x)| Stmt::Assign(v, x)),
+            (identifier(), prop::collection::vec(expression(), 0..3))
+                .prop_map(|(n, p)| Stmt::Call(n, p)),
+            expression().prop_map(Stmt::Return),
+            prop::collection::vec(identifier(), 1..3).prop_map(Stmt::LocalVar),
+        ];
+        leaf.prop_recursive(3, 16, 3, |inner| {
+            prop_oneof![
+                (expression(), inner.clone(), prop::option::of(inner.clone()))
+                    .prop_map(|(c, x, e)| Stmt::If(c, Box::new(x), e.map(Box::new))),
+                (expression(), inner.clone()).prop_map(|(c, x)| Stmt::While(c, Box::new(x))),
+                (identifier(), expression(), inner.clone()).prop_map(|(v, a, x)| Stmt::ForEach(
+                    v,
+                    a,
+                    Box::new(x)
+                )),
+                prop::collection::vec(inner.clone(), 0..3).prop_map(Stmt::Block),
+                (
+                    identifier(),
+                    prop::collection::vec(identifier(), 0..3),
+                    prop::collection::vec(inner, 0..3)
+                )
+                    .prop_map(|(n, p, x)| Stmt::Function(n, p, x)),
+            ]
+        })
+    }
+
+    fn statements(code: &str) -> Vec<Statement> {
+        parse(code)
+            .map(|x| x.unwrap_or_else(|e| panic!("{e} in:\n{code}")))
+            .collect()
+    }
+
+    /// Returns true when both codes parse to the same statements, ignoring their positions
+    fn semantic_eq(a: &str, b: &str) -> bool {
+        let (a, b) = (statements(a), statements(b));
+        a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| a.semantic_eq(b))
+    }
+
+    proptest! {
+        #[test]
+        fn format_keeps_statements(statements in prop::collection::vec(statement(), 1..4)) {
+            let code = render_block(&statements);
+            let formatted = format_code(&code).unwrap();
+            prop_assert!(semantic_eq(&code, &formatted), "formatted:\n{}", formatted);
+            prop_assert_eq!(format_code(&formatted).unwrap(), formatted);
+        }
+    }
+
+    #[test]
+    fn positions_are_ignored() {
+        assert!(semantic_eq("a = 1 + b;", "a=1+\n  b ;"));
+        assert!(!semantic_eq("a = 1 + b;", "a = 1 - b;"));
+    }
+}
@@ -718,6 +718,12 @@ where
         kbs.push(kb);
         Ok(())
     }
+    fn replace_kb(&self, _: &ContextKey, kb: storage::Kb) -> Result<(), StorageError> {
+        let mut kbs = self.kbs.lock().map_err(StorageError::from)?;
+        kbs.retain(|x| x.key != kb.key);
+        kbs.push(kb);
+        Ok(())
+    }
     fn dispatch_advisory(&self, key: &str, adv: Option<NotusAdvisory>) -> Result<(), StorageError> {
         let mut cache = Arc::as_ref(&self.cache).lock()?;
         cache.redis_add_advisory(key, adv).map_err(|e| e.into())
@@ -744,6 +750,13 @@ where
                     .filter(move |x| x.key == s)
                     .map(move |x| storage::Field::KB(x.clone()))
             }),
+            storage::Retrieve::KBPattern(s) => Box::new({
+                let kbs = self.kbs.lock().map_err(StorageError::from)?;
+                let kbs = kbs.clone();
+                kbs.into_iter()
+                    .filter(move |x| storage::kb_key_matches(&s, &x.key))
+                    .map(storage::Field::KB)
+            }),
         })
     }
 
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
 pub mod update;
+pub mod verify;
 use std::{io, path::PathBuf};
 
 use clap::{arg, value_parser, ArgAction, Command};
@@ -31,6 +32,11 @@ pub fn extend_args(cmd: Command) -> Command {
                 .arg(arg!(-x --"signature-check" "Enable NASL signature check.").required(false).action(ArgAction::SetTrue))
                 .arg(arg!(-r --redis <VALUE> "Redis url. Must either start `unix://` or `redis://`.").required(false))
                 )
+                .subcommand(crate::add_format(Command::new("verify")
+                .about("Verifies the hashsums of each file within the sha256sums file of the feed. Exits with the amount of failed files capped at 255.")
+                .arg(arg!(-p --path <FILE> "Path to the feed.") .required(false)
+                    .value_parser(value_parser!(PathBuf)))
+                ))
                 .subcommand(Command::new("transform")
                 .about("Runs nasl scripts in description mode and returns it as a json array into stdout")
                 .arg(arg!(-p --path <FILE> "Path to the feed.") .required(false)
@@ -149,6 +155,10 @@ pub fn run(root: &clap::ArgMatches) -> Option<Result<(), CliError>> {
             }
             ret
         }
+        Some(("verify", args)) => {
+            let path = get_vts_path("path", args);
+            Some(verify::run(path, crate::get_format(args)))
+        }
         Some(("transform", args)) => {
             let path = get_vts_path("path", args);
 
@@ -0,0 +1,36 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+use std::path::PathBuf;
+
+use nasl_interpreter::FSPluginLoader;
+
+use crate::{print_diagnostics, CliError, OutputFormat};
+
+pub fn run(path: PathBuf, format: OutputFormat) -> Result<(), CliError> {
+    tracing::debug!("verifying hashsums in {path:?}.");
+    let loader = FSPluginLoader::new(path);
+    let verifier = feed::HashSumNameLoader::sha256(&loader)?;
+    let report = verifier.verify_all();
+    match format {
+        OutputFormat::Text => {
+            for e in &report.errors {
+                eprintln!("{e}");
+            }
+        }
+        OutputFormat::Json | OutputFormat::Sarif => {
+            let diagnostics: Vec<_> = report
+                .errors
+                .iter()
+                .map(feed::Diagnostic::from_verify_error)
+                .collect();
+            print_diagnostics(&diagnostics, format)?;
+        }
+    }
+    eprintln!("{report}");
+    if !report.is_success() {
+        std::process::exit(report.exit_code());
+    }
+    Ok(())
+}
@@ -0,0 +1,157 @@
+// SPDX-FileCopyrightText: 2024 Greenbone AG
+//
+// SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
+
+use std::path::{Path, PathBuf};
+
+use clap::{arg, value_parser, Arg, ArgAction, Command};
+use nasl_interpreter::load_non_utf8_path;
+use nasl_syntax::{BraceStyle, FormatOptions};
+use walkdir::WalkDir;
+
+use crate::{syntax::check::is_nasl, CliError, CliErrorKind};
+
+pub fn run(root: &clap::ArgMatches) -> Option<Result<(), CliError>> {
+    let (args, _) = crate::get_args_set_logging(root, "fmt")?;
+    let path = match args.get_one::<PathBuf>("path").cloned() {
+        Some(path) => path,
+        _ => unreachable!("path is set to required"),
+    };
+    let check = args.get_one::<bool>("check").cloned().unwrap_or_default();
+    let options = FormatOptions {
+        indent_width: args.get_one::<usize>("indent-width").cloned().unwrap_or(2),
+        use_tabs: args.get_one::<bool>("tabs").cloned().unwrap_or_default(),
+        brace_style: match args.get_one::<String>("brace-style").map(|x| x as &str) {
+            Some("k&r") => BraceStyle::KAndR,
+            _ => BraceStyle::Allman,
+        },
+        keyword_space: args
+            .get_one::<bool>("keyword-space")
+            .cloned()
+            .unwrap_or_default(),
+    };
+    Some(format(&path, &options, check))
+}
+
+pub fn extend_args(cmd: Command) -> Command {
+    cmd.subcommand(crate::add_verbose(
+        Command::new("fmt")
+            .about("Formats NASL files in given dir or file.")
+            .arg(
+                Arg::new("path")
+                    .required(true)
+                    .value_parser(value_parser!(PathBuf)),
+            )
+            .arg(
+                arg!(--check "Prints a diff of each file that is not formatted instead of rewriting it.")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                arg!(--"indent-width" <WIDTH> "Amount of spaces per indentation level.")
+                    .required(false)
+                    .value_parser(value_parser!(usize))
+                    .default_value("2"),
+            )
+            .arg(
+                arg!(--tabs "Indents with tabs instead of spaces.")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                arg!(--"brace-style" <STYLE> "Placement of opening braces.")
+                    .required(false)
+                    .value_parser(["k&r", "allman"])
+                    .default_value("allman"),
+            )
+            .arg(
+                arg!(--"keyword-space" "Separates keywords like if and while from their parenthesis by a space.")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            ),
+    ))
+}
+
+/// The outcome of formatting a single file
+enum Outcome {
+    /// The file is already formatted
+    Unchanged,
+    /// The file is not formatted, contains the diff in check mode
+    Changed(Option<String>),
+    /// The file contains syntax errors and is not formatted
+    Invalid,
+}
+
+fn format_file(path: &Path, options: &FormatOptions, check: bool) -> Result<Outcome, CliError> {
+    let code = load_non_utf8_path(path).map_err(|e| CliError {
+        filename: format!("{path:?}"),
+        kind: CliErrorKind::LoadError(e),
+    })?;
+    if check {
+        return match nasl_syntax::check_format(&code, options) {
+            Ok(None) => Ok(Outcome::Unchanged),
+            Ok(Some(diff)) => Ok(Outcome::Changed(Some(diff))),
+            Err(err) => {
+                eprintln!("{}", err.render(&path.to_string_lossy(), &code));
+                Ok(Outcome::Invalid)
+            }
+        };
+    }
+    match nasl_syntax::format_code_with(&code, options) {
+        Ok(formatted) if formatted == code => Ok(Outcome::Unchanged),
+        Ok(formatted) => {
+            // the code is loaded byte wise, the formatter only adds ASCII characters
+            let bytes: Vec<u8> = formatted.chars().map(|c| c as u8).collect();
+            std::fs::write(path, bytes)?;
+            Ok(Outcome::Changed(None))
+        }
+        Err(err) => {
+            eprintln!("{}", err.render(&path.to_string_lossy(), &code));
+            Ok(Outcome::Invalid)
+        }
+    }
+}
+
+/// Formats each NASL file in path.
+///
+/// In check mode the files are not rewritten; instead a diff is printed for each file that is
+/// not formatted and the process exits with the amount of those files capped at `255`.
+fn format(path: &Path, options: &FormatOptions, check: bool) -> Result<(), CliError> {
+    let files: Vec<PathBuf> = if path.is_dir() {
+        WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .map(|e| e.into_path())
+            .filter(|p| is_nasl(p))
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let (mut changed, mut invalid) = (0usize, 0usize);
+    for file in &files {
+        match format_file(file, options, check)? {
+            Outcome::Unchanged => {}
+            Outcome::Changed(diff) => {
+                changed += 1;
+                match diff {
+                    Some(diff) => println!("Diff in {}:\n{diff}", file.display()),
+                    None => println!("Formatted {}", file.display()),
+                }
+            }
+            Outcome::Invalid => invalid += 1,
+        }
+    }
+    if check {
+        eprintln!(
+            "{changed} of {} files are not formatted, {invalid} contain syntax errors.",
+            files.len()
+        );
+        let failed = changed + invalid;
+        if failed > 0 {
+            std::process::exit(failed.min(255) as i32);
+        }
+    } else if invalid > 0 {
+        eprintln!("{invalid} files contain syntax errors and are not formatted.");
+    }
+    Ok(())
+}
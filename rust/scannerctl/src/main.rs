@@ -6,6 +6,7 @@
 mod error;
 mod execute;
 mod feed;
+mod format;
 mod interpret;
 mod notusupdate;
 mod scanconfig;
@@ -59,6 +60,7 @@ fn main() {
             .subcommand_required(true),
     );
     let matches = syntax::extend_args(matches);
+    let matches = format::extend_args(matches);
     let matches = scanconfig::extend_args(matches);
     let matches = execute::extend_args(matches);
     let matches = notusupdate::scanner::extend_args(matches);
@@ -85,6 +87,7 @@ fn run(matches: &ArgMatches) -> Result<(), CliError> {
     let functions = [
         feed::run,
         syntax::run,
+        format::run,
         execute::run,
         scanconfig::run,
         notusupdate::scanner::run,
@@ -125,6 +128,56 @@ pub fn add_verbose(cmd: Command) -> Command {
     )
 }
 
+/// Output format of diagnostics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable messages
+    Text,
+    /// JSON array of `::feed::Diagnostic`
+    Json,
+    /// SARIF 2.1.0 log
+    Sarif,
+}
+
+pub fn add_format(cmd: Command) -> Command {
+    cmd.arg(
+        arg!(-f --format <FORMAT> "Output format of the diagnostics.")
+            .required(false)
+            .value_parser(["text", "json", "sarif"])
+            .default_value("text"),
+    )
+}
+
+pub fn get_format(args: &ArgMatches) -> OutputFormat {
+    match args.get_one::<String>("format").map(|x| x as &str) {
+        Some("json") => OutputFormat::Json,
+        Some("sarif") => OutputFormat::Sarif,
+        _ => OutputFormat::Text,
+    }
+}
+
+/// Prints the diagnostics either as a JSON array or as a SARIF log to stdout
+///
+/// On `OutputFormat::Text` nothing is printed, the caller prints human readable messages instead.
+pub fn print_diagnostics(
+    diagnostics: &[::feed::Diagnostic],
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    let json = match format {
+        OutputFormat::Text => return Ok(()),
+        OutputFormat::Json => serde_json::to_string_pretty(diagnostics),
+        OutputFormat::Sarif => {
+            serde_json::to_string_pretty(&::feed::SarifLog::new("scannerctl", diagnostics))
+        }
+    };
+    let json = json.map_err(|e| CliError {
+        filename: "".to_string(),
+        kind: CliErrorKind::Corrupt(e.to_string()),
+    })?;
+    println!("{json}");
+    Ok(())
+}
+
 pub fn get_args_set_logging<'a>(
     root: &'a ArgMatches,
     name: &'a str,
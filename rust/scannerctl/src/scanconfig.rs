@@ -3,13 +3,21 @@
 // SPDX-License-Identifier: GPL-2.0-or-later WITH x11vnc-openssl-exception
 
 use std::fmt::{Display, Formatter};
-use std::{io::BufReader, path::PathBuf, sync::Arc};
+use std::{
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use clap::{arg, value_parser, Arg, ArgAction, Command};
-use serde::Deserialize;
+use nasl_interpreter::FSPluginLoader;
+use serde::{Deserialize, Serialize};
+
+use storage::item::{NvtPreference, PreferenceType};
 
 use crate::{get_path_from_openvas, read_openvas_config, CliError, CliErrorKind};
-use std::collections::HashMap;
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use std::collections::{BTreeMap, HashMap};
 use std::io::BufRead;
 
 pub fn extend_args(cmd: Command) -> Command {
@@ -22,6 +30,10 @@ When piping a scan json it is enriched with the scan-config xml and may the port
                 .arg(Arg::new("scan-config").required(true).action(ArgAction::Append))
                 .arg(arg!(-i --input "Parses scan json from stdin.").required(false).action(ArgAction::SetTrue))
                 .arg(arg!(-l --portlist <FILE> "Path to the port list xml") .required(false))
+                .arg(arg!(--"with-defaults" "Adds the default values of parameters that are not set by the scan-config.").required(false).action(ArgAction::SetTrue))
+                .arg(arg!(--"require-feed-hash" <HASH> "Fails when the sha256 hash of the sums file of the feed differs.").required(false))
+                .arg(arg!(--diff <FILE> "Prints the changes against a previously generated scan json instead of the scan.").required(false)
+                    .value_parser(value_parser!(PathBuf)))
         )
     )
 }
@@ -38,7 +50,265 @@ pub fn run(root: &clap::ArgMatches) -> Option<Result<(), CliError>> {
     let port_list = args.get_one::<String>("portlist").cloned();
     tracing::debug!("port_list: {port_list:?}");
     let stdin = args.get_one::<bool>("input").cloned().unwrap_or_default();
-    Some(execute(feed.as_ref(), &config, port_list.as_ref(), stdin))
+    let feed_hash = args.get_one::<String>("require-feed-hash").cloned();
+    let with_defaults = args
+        .get_one::<bool>("with-defaults")
+        .cloned()
+        .unwrap_or_default();
+    let diff = args.get_one::<PathBuf>("diff").cloned();
+    Some(execute(
+        feed.as_ref(),
+        &config,
+        port_list.as_ref(),
+        stdin,
+        feed_hash.as_deref(),
+        with_defaults,
+        diff.as_ref(),
+    ))
+}
+
+/// Returns the version and the hash of the feed
+///
+/// The hash is the sha256 hash of the sums file, as it contains the hash of each file of the
+/// feed. When a hash is required and it differs an error is returned.
+fn feed_metadata(
+    feed: &Path,
+    required_hash: Option<&str>,
+) -> Result<models::ScanMetadata, CliError> {
+    let loader = FSPluginLoader::new(feed);
+    let feed_hash = feed::HashSumNameLoader::sha256(&loader)?.sumfile_hash()?;
+    if let Some(required) = required_hash {
+        if !required.eq_ignore_ascii_case(&feed_hash) {
+            return Err(CliError {
+                filename: feed::Hasher::Sha256.sum_file().to_string(),
+                kind: CliErrorKind::Corrupt(format!(
+                    "feed hash {feed_hash} is not as required ({required})."
+                )),
+            });
+        }
+    }
+    let feed_version =
+        feed::version(&loader, &storage::DefaultDispatcher::default()).map_err(|e| CliError {
+            filename: "plugin_feed_info.inc".to_string(),
+            kind: CliErrorKind::Corrupt(format!("{e:?}")),
+        })?;
+    Ok(models::ScanMetadata {
+        feed_version,
+        feed_hash,
+    })
+}
+
+fn map_error(f: &str, e: Error) -> CliError {
+    CliError {
+        filename: f.to_string(),
+        kind: CliErrorKind::Corrupt(format!("{e:?}")),
+    }
+}
+
+fn as_bufreader(f: &str) -> Result<BufReader<std::fs::File>, CliError> {
+    let file = std::fs::File::open(f).map_err(|e| CliError {
+        filename: f.to_string(),
+        kind: CliErrorKind::Corrupt(format!("{e:?}")),
+    })?;
+    Ok(BufReader::new(file))
+}
+
+/// Parses the VTs of each scan-config file and merges them by OID
+///
+/// When parallel is set the files are parsed within a thread pool. The result does not depend on
+/// it: the VTs are ordered by OID and for a VT contained in multiple files the one of the first
+/// file is used.
+fn parse_configs<S>(
+    config: &[String],
+    storage: &S,
+    vts: &[models::VT],
+    parallel: bool,
+) -> Result<Vec<models::VT>, CliError>
+where
+    S: storage::Retriever + Sync,
+{
+    let parse = |f: &String| {
+        as_bufreader(f).and_then(|r| parse_vts(r, storage, vts).map_err(|e| map_error(f, e)))
+    };
+    let parsed: Vec<Vec<models::VT>> = if parallel {
+        config.par_iter().map(parse).collect::<Result<_, _>>()?
+    } else {
+        config.iter().map(parse).collect::<Result<_, _>>()?
+    };
+    let mut merged = BTreeMap::new();
+    for vt in parsed.into_iter().flatten() {
+        merged.entry(vt.oid.clone()).or_insert(vt);
+    }
+    Ok(merged.into_values().collect())
+}
+
+/// Returns the default value of a preference
+///
+/// The default of a radio preference lists each option separated by `;`, the first one is used.
+fn default_value(preference: &NvtPreference) -> String {
+    match preference.class() {
+        PreferenceType::Radio => preference.default().split(';').next().unwrap_or_default(),
+        _ => preference.default(),
+    }
+    .to_owned()
+}
+
+/// Adds the preferences of the loaded feed as parameters with their default value
+///
+/// Parameters that are already set are kept, the defaults are appended ordered by id.
+fn add_default_parameters(
+    vts: &mut [models::VT],
+    retriever: &dyn storage::Retriever,
+) -> Result<(), Error> {
+    use storage::item::{NVTField, NVTKey};
+    use storage::{Field, Retrieve};
+    for vt in vts.iter_mut() {
+        let nvts = retriever.retrieve_by_field(
+            Field::NVT(NVTField::Oid(vt.oid.clone())),
+            Retrieve::NVT(Some(NVTKey::Nvt)),
+        )?;
+        let mut preferences: Vec<_> = nvts
+            .flat_map(|(_, f)| match f {
+                Field::NVT(NVTField::Nvt(nvt)) => nvt.preferences,
+                _ => vec![],
+            })
+            .filter_map(|p| Some((u16::try_from(p.id()?).ok()?, default_value(&p))))
+            .filter(|(id, _)| !vt.parameters.iter().any(|x| x.id == *id))
+            .collect();
+        preferences.sort_by_key(|(id, _)| *id);
+        preferences.dedup_by_key(|(id, _)| *id);
+        vt.parameters.extend(
+            preferences
+                .into_iter()
+                .map(|(id, value)| models::Parameter { id, value }),
+        );
+    }
+    Ok(())
+}
+
+/// A changed parameter of a VT, a missing value means the parameter was added or removed
+#[derive(Clone, Debug, Serialize, PartialEq)]
+struct ParameterChange {
+    oid: String,
+    id: u16,
+    old: Option<String>,
+    new: Option<String>,
+}
+
+/// The port ranges of a protocol that were added or removed
+#[derive(Clone, Debug, Serialize, PartialEq)]
+struct PortChange {
+    protocol: Option<models::Protocol>,
+    added: Vec<models::PortRange>,
+    removed: Vec<models::PortRange>,
+}
+
+/// The changes of a scan compared to a previously generated one
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+struct ScanDiff {
+    added_vts: Vec<String>,
+    removed_vts: Vec<String>,
+    changed_parameters: Vec<ParameterChange>,
+    changed_ports: Vec<PortChange>,
+}
+
+impl ScanDiff {
+    /// Compares the VTs and ports of the new scan against the old one
+    ///
+    /// VTs and parameters are ordered by OID and id, the order within the scans is ignored.
+    fn new(old: &models::Scan, new: &models::Scan) -> Self {
+        let by_oid = |scan: &models::Scan| -> BTreeMap<String, BTreeMap<u16, String>> {
+            scan.vts
+                .iter()
+                .map(|vt| {
+                    let parameters = vt.parameters.iter().map(|p| (p.id, p.value.clone()));
+                    (vt.oid.clone(), parameters.collect())
+                })
+                .collect()
+        };
+        let (old_vts, new_vts) = (by_oid(old), by_oid(new));
+        let mut result = ScanDiff {
+            added_vts: new_vts
+                .keys()
+                .filter(|x| !old_vts.contains_key(*x))
+                .cloned()
+                .collect(),
+            removed_vts: old_vts
+                .keys()
+                .filter(|x| !new_vts.contains_key(*x))
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        for (oid, new_parameters) in &new_vts {
+            let old_parameters = match old_vts.get(oid) {
+                Some(x) => x,
+                None => continue,
+            };
+            let ids: std::collections::BTreeSet<_> =
+                old_parameters.keys().chain(new_parameters.keys()).collect();
+            for id in ids {
+                let (old, new) = (old_parameters.get(id), new_parameters.get(id));
+                if old != new {
+                    result.changed_parameters.push(ParameterChange {
+                        oid: oid.clone(),
+                        id: *id,
+                        old: old.cloned(),
+                        new: new.cloned(),
+                    });
+                }
+            }
+        }
+        let mut protocols: Vec<_> = old
+            .target
+            .ports
+            .iter()
+            .map(|x| x.protocol.clone())
+            .collect();
+        for port in &new.target.ports {
+            if !protocols.contains(&port.protocol) {
+                protocols.push(port.protocol.clone());
+            }
+        }
+        for protocol in protocols {
+            let ranges = |scan: &models::Scan| -> Vec<models::PortRange> {
+                scan.target
+                    .ports
+                    .iter()
+                    .filter(|x| x.protocol == protocol)
+                    .flat_map(|x| x.range.clone())
+                    .collect()
+            };
+            let (old_ranges, new_ranges) = (ranges(old), ranges(new));
+            let change = PortChange {
+                protocol,
+                added: new_ranges
+                    .iter()
+                    .filter(|x| !old_ranges.contains(x))
+                    .cloned()
+                    .collect(),
+                removed: old_ranges
+                    .iter()
+                    .filter(|x| !new_ranges.contains(x))
+                    .cloned()
+                    .collect(),
+            };
+            if !change.added.is_empty() || !change.removed.is_empty() {
+                result.changed_ports.push(change);
+            }
+        }
+        result
+    }
+}
+
+/// Reads a previously generated scan json
+fn read_scan(path: &Path) -> Result<models::Scan, CliError> {
+    let filename = path.to_string_lossy().to_string();
+    let reader = as_bufreader(&filename)?;
+    serde_json::from_reader(reader).map_err(|e| CliError {
+        filename,
+        kind: CliErrorKind::Corrupt(format!("{e:?}")),
+    })
 }
 
 fn execute(
@@ -46,19 +316,11 @@ fn execute(
     config: &[String],
     port_list: Option<&String>,
     stdin: bool,
+    required_feed_hash: Option<&str>,
+    with_defaults: bool,
+    diff: Option<&PathBuf>,
 ) -> Result<(), CliError> {
-    let map_error = |f: &str, e: Error| CliError {
-        filename: f.to_string(),
-        kind: CliErrorKind::Corrupt(format!("{e:?}")),
-    };
-    let as_bufreader = |f: &str| {
-        let file = std::fs::File::open(f).map_err(|e| CliError {
-            filename: f.to_string(),
-            kind: CliErrorKind::Corrupt(format!("{e:?}")),
-        })?;
-        let reader = BufReader::new(file);
-        Ok::<BufReader<std::fs::File>, CliError>(reader)
-    };
+    let previous = diff.map(|x| read_scan(x)).transpose()?;
     let storage = Arc::new(storage::DefaultDispatcher::new(true));
     let mut scan = {
         if stdin {
@@ -81,6 +343,7 @@ fn execute(
             })?,
     };
 
+    let metadata = feed_metadata(&feed, required_feed_hash)?;
     tracing::info!("loading feed. This may take a while.");
     crate::feed::update::run(Arc::clone(&storage), feed.to_owned(), false)?;
     tracing::info!("feed loaded.");
@@ -92,17 +355,19 @@ fn execute(
         }
         None => vec![],
     };
-    let mut vts = vec![];
-    for a in config.iter().map(|f| {
-        as_bufreader(f)
-            .map_err(CliError::from)
-            .and_then(|r| parse_vts(r, storage.as_ref(), &scan.vts).map_err(|e| map_error(f, e)))
-    }) {
-        vts.extend(a?);
+    let mut vts = parse_configs(config, storage.as_ref(), &scan.vts, true)?;
+    if with_defaults {
+        add_default_parameters(&mut vts, storage.as_ref())
+            .map_err(|e| map_error(&config.join(","), e))?;
     }
     scan.vts.extend(vts);
     scan.target.ports = ports;
-    let out = serde_json::to_string_pretty(&scan).map_err(|e| CliError {
+    scan.metadata = Some(metadata);
+    let out = match previous {
+        Some(previous) => serde_json::to_string_pretty(&ScanDiff::new(&previous, &scan)),
+        None => serde_json::to_string_pretty(&scan),
+    }
+    .map_err(|e| CliError {
         filename: config.join(","),
         kind: CliErrorKind::Corrupt(format!("{e:?}")),
     })?;
@@ -489,4 +754,211 @@ mod tests {
         let result = super::parse_vts(sc.as_bytes(), &shop, &exists).unwrap();
         assert_eq!(result.len(), 4);
     }
+
+    #[test]
+    fn parallel_parsing() {
+        let config = |id: &str, oid: &str, family: &str| {
+            format!(
+                r#"
+        <config id="{id}">
+  <name>{id}</name>
+  <type>0</type>
+  <usage_type>scan</usage_type>
+  <preferences>
+    <preference>
+      <nvt oid="{oid}">
+        <name>{oid}</name>
+      </nvt>
+      <name>Report about unreachable Hosts</name>
+      <type>checkbox</type>
+      <value>{id}</value>
+      <id>6</id>
+    </preference>
+  </preferences>
+  <nvt_selectors>
+    <nvt_selector>
+      <include>1</include>
+      <type>2</type>
+      <family_or_nvt>{oid}</family_or_nvt>
+    </nvt_selector>
+    <nvt_selector>
+      <include>1</include>
+      <type>1</type>
+      <family_or_nvt>{family}</family_or_nvt>
+    </nvt_selector>
+    </nvt_selectors>
+    </config>"#
+            )
+        };
+        let shop = storage::DefaultDispatcher::default();
+        for (oid, family) in [("1", "a"), ("2", "a"), ("3", "b"), ("4", "b"), ("5", "c")] {
+            let key = storage::ContextKey::FileName(format!("{oid}.nasl"));
+            let dispatch = |field| shop.as_dispatcher().dispatch(&key, field).unwrap();
+            dispatch(storage::Field::NVT(storage::item::NVTField::Oid(
+                oid.to_owned(),
+            )));
+            dispatch(storage::Field::NVT(storage::item::NVTField::Family(
+                family.to_owned(),
+            )));
+        }
+        let dir = std::env::temp_dir().join(format!("scanconfig-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let configs: Vec<String> = [("x", "9", "a"), ("y", "1", "b"), ("z", "9", "c")]
+            .iter()
+            .map(|(id, oid, family)| {
+                let path = dir.join(format!("{id}.xml"));
+                std::fs::write(&path, config(id, oid, family)).unwrap();
+                path.to_string_lossy().to_string()
+            })
+            .collect();
+        let exists = vec![models::VT {
+            oid: "2".to_string(),
+            parameters: vec![],
+        }];
+
+        let serial = super::parse_configs(&configs, &shop, &exists, false).unwrap();
+        let parallel = super::parse_configs(&configs, &shop, &exists, true).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(serial, parallel);
+        let oids: Vec<_> = parallel.iter().map(|x| x.oid.as_str()).collect();
+        assert_eq!(oids, ["1", "3", "4", "5", "9"]);
+        let nine = parallel.iter().find(|x| x.oid == "9").unwrap();
+        assert_eq!(nine.parameters[0].value, "x");
+    }
+
+    #[test]
+    fn with_defaults() {
+        let sc = r#"
+        <config id="8715c877-47a0-438d-98a3-27c7a6ab2196">
+  <name>Discovery</name>
+  <type>0</type>
+  <usage_type>scan</usage_type>
+  <preferences>
+    <preference>
+      <nvt oid="1">
+        <name>Ping Host</name>
+      </nvt>
+      <name>Report about unreachable Hosts</name>
+      <type>checkbox</type>
+      <value>yes</value>
+      <id>6</id>
+    </preference>
+  </preferences>
+  <nvt_selectors>
+    <nvt_selector>
+      <include>1</include>
+      <type>2</type>
+      <family_or_nvt>1</family_or_nvt>
+    </nvt_selector>
+    </nvt_selectors>
+    </config>"#;
+        let shop = storage::DefaultDispatcher::default();
+        let key = storage::ContextKey::FileName("1.nasl".to_owned());
+        let fields = [
+            storage::item::NVTField::Oid("1".to_owned()),
+            storage::item::NVTField::Preference(("6", "Report", "checkbox", "no").into()),
+            storage::item::NVTField::Preference(("2", "Protocol", "radio", "tcp;udp").into()),
+            storage::item::NVTField::Preference(("1", "Timeout", "entry", "5").into()),
+        ];
+        for field in fields {
+            shop.as_dispatcher()
+                .dispatch(&key, storage::Field::NVT(field))
+                .unwrap();
+        }
+        let parameter = |id, value: &str| models::Parameter {
+            id,
+            value: value.to_owned(),
+        };
+
+        let mut result = super::parse_vts(sc.as_bytes(), &shop, &[]).unwrap();
+        assert_eq!(result[0].parameters, vec![parameter(6, "yes")]);
+        super::add_default_parameters(&mut result, &shop).unwrap();
+        assert_eq!(
+            result[0].parameters,
+            vec![parameter(6, "yes"), parameter(1, "5"), parameter(2, "tcp")]
+        );
+    }
+
+    #[test]
+    fn feed_metadata() {
+        let feed = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../feed/tests");
+        let metadata = super::feed_metadata(&feed, None).unwrap();
+        assert_eq!(metadata.feed_hash.len(), 64);
+        assert_eq!(metadata.feed_version, "202302011009");
+        let matching = super::feed_metadata(&feed, Some(&metadata.feed_hash)).unwrap();
+        assert_eq!(matching, metadata);
+        let mismatch = super::feed_metadata(&feed, Some(&"0".repeat(64))).unwrap_err();
+        assert_eq!(mismatch.filename, "sha256sums");
+        assert!(matches!(mismatch.kind, CliErrorKind::Corrupt(_)));
+    }
+    #[test]
+    fn diff() {
+        let vt = |oid: &str, parameters: &[(u16, &str)]| models::VT {
+            oid: oid.to_owned(),
+            parameters: parameters
+                .iter()
+                .map(|(id, value)| models::Parameter {
+                    id: *id,
+                    value: value.to_string(),
+                })
+                .collect(),
+        };
+        let port = |protocol, start| models::Port {
+            protocol,
+            range: vec![models::PortRange { start, end: None }],
+        };
+        let mut old = models::Scan {
+            vts: vec![vt("1", &[(1, "yes")]), vt("2", &[])],
+            ..Default::default()
+        };
+        old.target.ports = vec![
+            port(Some(models::Protocol::TCP), 22),
+            port(Some(models::Protocol::UDP), 53),
+        ];
+        let mut new = old.clone();
+        new.vts.insert(0, vt("3", &[]));
+        new.target.ports[0] = port(Some(models::Protocol::TCP), 80);
+
+        let result = ScanDiff::new(&old, &new);
+        assert_eq!(result.added_vts, vec!["3"]);
+        assert!(result.removed_vts.is_empty());
+        assert!(result.changed_parameters.is_empty());
+        assert_eq!(
+            result.changed_ports,
+            vec![PortChange {
+                protocol: Some(models::Protocol::TCP),
+                added: vec![models::PortRange {
+                    start: 80,
+                    end: None
+                }],
+                removed: vec![models::PortRange {
+                    start: 22,
+                    end: None
+                }],
+            }]
+        );
+        assert_eq!(ScanDiff::new(&old, &old), ScanDiff::default());
+
+        new.vts = vec![vt("1", &[(1, "no"), (2, "5")])];
+        let result = ScanDiff::new(&old, &new);
+        assert!(result.added_vts.is_empty());
+        assert_eq!(result.removed_vts, vec!["2"]);
+        assert_eq!(
+            result.changed_parameters,
+            vec![
+                ParameterChange {
+                    oid: "1".to_owned(),
+                    id: 1,
+                    old: Some("yes".to_owned()),
+                    new: Some("no".to_owned()),
+                },
+                ParameterChange {
+                    oid: "1".to_owned(),
+                    id: 2,
+                    old: None,
+                    new: Some("5".to_owned()),
+                },
+            ]
+        );
+    }
 }
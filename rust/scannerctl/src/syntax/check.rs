@@ -8,21 +8,25 @@ use nasl_interpreter::load_non_utf8_path;
 use nasl_syntax::{Statement, SyntaxError};
 use walkdir::WalkDir;
 
-use crate::{CliError, CliErrorKind};
+use crate::{print_diagnostics, CliError, CliErrorKind, OutputFormat};
 
-fn read_errors<P: AsRef<Path>>(path: P) -> Result<Vec<SyntaxError>, CliErrorKind> {
+type ReadResult<T> = Result<(String, Vec<T>), CliErrorKind>;
+
+fn read_errors<P: AsRef<Path>>(path: P) -> ReadResult<SyntaxError> {
     let code = load_non_utf8_path(path.as_ref())?;
-    Ok(nasl_syntax::parse(&code)
+    let errors = nasl_syntax::parse(&code)
         .filter_map(|r| match r {
             Ok(_) => None,
             Err(err) => Some(err),
         })
-        .collect())
+        .collect();
+    Ok((code, errors))
 }
 
-fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Result<Statement, SyntaxError>>, CliErrorKind> {
+fn read<P: AsRef<Path>>(path: P) -> ReadResult<Result<Statement, SyntaxError>> {
     let code = load_non_utf8_path(path.as_ref())?;
-    Ok(nasl_syntax::parse(&code).collect())
+    let results = nasl_syntax::parse(&code).collect();
+    Ok((code, results))
 }
 
 fn print_results(path: &Path, verbose: bool) -> Result<usize, CliError> {
@@ -30,18 +34,18 @@ fn print_results(path: &Path, verbose: bool) -> Result<usize, CliError> {
 
     if verbose {
         println!("# {path:?}");
-        let results = read(path).map_err(|kind| CliError {
+        let (code, results) = read(path).map_err(|kind| CliError {
             kind,
             filename: format!("{path:?}"),
         })?;
         for r in results {
             match r {
                 Ok(stmt) => println!("{stmt:?}"),
-                Err(err) => eprintln!("{err}"),
+                Err(err) => eprintln!("{}", err.render(&path.to_string_lossy(), &code)),
             }
         }
     } else {
-        let err = read_errors(path).map_err(|kind| CliError {
+        let (code, err) = read_errors(path).map_err(|kind| CliError {
             kind,
             filename: format!("{path:?}"),
         })?;
@@ -49,12 +53,59 @@ fn print_results(path: &Path, verbose: bool) -> Result<usize, CliError> {
             eprintln!("# Error in {path:?}");
         }
         errors += err.len();
-        err.iter().for_each(|r| eprintln!("{r}"));
+        err.iter()
+            .for_each(|r| eprintln!("{}", r.render(&path.to_string_lossy(), &code)));
     }
     Ok(errors)
 }
 
-pub fn run(path: &PathBuf, verbose: bool, no_progress: bool) -> Result<(), CliError> {
+pub fn is_nasl(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|x| x.to_str()),
+        Some("nasl" | "inc")
+    )
+}
+
+fn run_structured(path: &Path, format: OutputFormat) -> Result<(), CliError> {
+    let files: Vec<PathBuf> = if path.is_dir() {
+        WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .map(|e| e.into_path())
+            .filter(|p| is_nasl(p))
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut diagnostics = vec![];
+    for file in files {
+        let (_, errors) = read_errors(&file).map_err(|kind| CliError {
+            kind,
+            filename: format!("{file:?}"),
+        })?;
+        let filename = file.to_string_lossy();
+        diagnostics.extend(
+            errors
+                .iter()
+                .map(|e| feed::Diagnostic::from_syntax_error(&filename, e)),
+        );
+    }
+    print_diagnostics(&diagnostics, format)?;
+    if !diagnostics.is_empty() {
+        std::process::exit(diagnostics.len().min(255) as i32);
+    }
+    Ok(())
+}
+
+pub fn run(
+    path: &PathBuf,
+    verbose: bool,
+    no_progress: bool,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    if format != OutputFormat::Text {
+        return run_structured(path, format);
+    }
     let mut parsed: usize = 0;
     let mut skipped: usize = 0;
     let mut errors: usize = 0;
@@ -64,14 +115,7 @@ pub fn run(path: &PathBuf, verbose: bool, no_progress: bool) -> Result<(), CliEr
             if !no_progress {
                 print!("\rparsing {parsed}th file");
             }
-            let ext = {
-                if let Some(ext) = entry.path().extension() {
-                    ext.to_str().unwrap().to_owned()
-                } else {
-                    "".to_owned()
-                }
-            };
-            if !matches!(ext.as_str(), "nasl" | "inc") {
+            if !is_nasl(entry.path()) {
                 skipped += 1;
             } else {
                 errors += print_results(entry.path(), verbose)?;
@@ -85,7 +129,8 @@ pub fn run(path: &PathBuf, verbose: bool, no_progress: bool) -> Result<(), CliEr
     }
     println!("skipped: {skipped} files; parsed: {parsed} files; errors: {errors}");
     if errors > 0 {
-        std::process::exit(errors as i32);
+        // capped so that 256 errors do not wrap around to success
+        std::process::exit(errors.min(255) as i32);
     }
     Ok(())
 }
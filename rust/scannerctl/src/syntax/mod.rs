@@ -6,7 +6,7 @@ use std::path::PathBuf;
 
 use clap::{arg, value_parser, Arg, ArgAction, Command};
 
-use crate::{add_verbose, CliError};
+use crate::{add_format, add_verbose, get_format, CliError};
 
 pub mod check;
 
@@ -18,11 +18,11 @@ pub fn run(root: &clap::ArgMatches) -> Option<Result<(), CliError>> {
     };
     let quiet = args.get_one::<bool>("quiet").cloned().unwrap_or_default();
 
-    Some(check::run(&path, verbose > 0, quiet))
+    Some(check::run(&path, verbose > 0, quiet, get_format(args)))
 }
 
 pub fn extend_args(cmd: Command) -> Command {
-    cmd.subcommand(add_verbose(
+    cmd.subcommand(add_format(add_verbose(
         Command::new("syntax")
             .about("Verifies syntax of NASL files in given dir or file.")
             .arg(
@@ -35,5 +35,5 @@ pub fn extend_args(cmd: Command) -> Command {
                     .required(false)
                     .action(ArgAction::SetTrue),
             ),
-    ))
+    )))
 }
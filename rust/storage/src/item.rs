@@ -846,9 +846,17 @@ pub trait ItemDispatcher {
     /// accordingly.
     /// The default is set to return `Ok(())` without doing something to net enforce every
     /// ItemDispatcher to implement it.
+    ///
+    /// Expiring the item is best effort, an implementation that cannot expire items may keep it.
     fn dispatch_kb(&self, _: &ContextKey, _: Kb) -> Result<(), StorageError> {
         Ok(())
     }
+    /// Replaces all knowledge base items with the key of the given item by it.
+    ///
+    /// The default just dispatches the item via `dispatch_kb`.
+    fn replace_kb(&self, key: &ContextKey, kb: Kb) -> Result<(), StorageError> {
+        self.dispatch_kb(key, kb)
+    }
     /// Stores an advisory
     fn dispatch_advisory(&self, _: &str, _: Option<NotusAdvisory>) -> Result<(), StorageError>;
 }
@@ -921,6 +929,13 @@ where
         }
     }
 
+    fn dispatch_replace(&self, key: &ContextKey, scope: crate::Field) -> Result<(), StorageError> {
+        match scope {
+            Field::KB(kb) => self.dispatcher.replace_kb(key, kb),
+            x => self.dispatch(key, x),
+        }
+    }
+
     fn on_exit(&self) -> Result<(), StorageError> {
         let mut data = Arc::as_ref(&self.nvt)
             .lock()
@@ -14,7 +14,11 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::Display,
     io,
-    sync::{Arc, PoisonError, RwLock},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex, PoisonError, RwLock,
+    },
+    time::{Duration, Instant},
 };
 
 use item::NVTField;
@@ -79,11 +83,22 @@ pub struct Kb {
     pub value: Primitive,
     /// If set it is the unix timestamp the KB entry will expire
     ///
+    /// The timestamp is compared to [Dispatcher::now] of the storage.
     /// When an entry expires `get_kb` will not find that entry anymore.
     /// When it is Null the KB entry will stay the whole run.
+    ///
+    /// Expiring is best effort: the DefaultDispatcher removes expired entries, storages that
+    /// cannot expire entries keep them for the whole run.
     pub expire: Option<u64>,
 }
 
+impl Kb {
+    /// Returns true when the entry is expired at the given unix timestamp
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expire.is_some_and(|x| x <= now)
+    }
+}
+
 /// Redefine Vulnerability so that other libraries using that don't have to include models
 pub type NotusAdvisory = models::VulnerabilityData;
 
@@ -211,6 +226,22 @@ pub trait Dispatcher: Sync + Send {
     /// Some database require a cleanup therefore this method is called when a script finishes.
     fn on_exit(&self) -> Result<(), StorageError>;
 
+    /// Distributes given field under a key and replaces previously distributed values
+    ///
+    /// Currently only KB items can be replaced, all other fields are dispatched as usual. The
+    /// default does not remove anything and just dispatches the field.
+    fn dispatch_replace(&self, key: &ContextKey, scope: Field) -> Result<(), StorageError> {
+        self.dispatch(key, scope)
+    }
+
+    /// Returns the current unix timestamp in seconds that is used to expire KB entries
+    ///
+    /// The expiration of a KB entry must be based on this timestamp. The default is the system
+    /// time.
+    fn now(&self) -> u64 {
+        system_time()
+    }
+
     /// Retries a dispatch for the amount of retries when a retrievable error occurs.
     fn retry_dispatch(
         &self,
@@ -242,6 +273,14 @@ where
     fn on_exit(&self) -> Result<(), StorageError> {
         self.as_ref().on_exit()
     }
+
+    fn dispatch_replace(&self, key: &ContextKey, scope: Field) -> Result<(), StorageError> {
+        self.as_ref().dispatch_replace(key, scope)
+    }
+
+    fn now(&self) -> u64 {
+        self.as_ref().now()
+    }
 }
 
 /// Convenience trait to use a dispatcher and retriever implementation
@@ -269,12 +308,125 @@ where
 ///
 /// To make lookups easier KB items are fetched by a scan_id, followed by the kb key this should
 /// make required_key verifications relatively simple.
-type Kbs = HashMap<String, HashMap<String, Vec<Kb>>>;
+///
+/// Each entry is stored with a sequence number of its insertion to find the oldest entry.
+type Kbs = HashMap<String, ScanKbs>;
+
+/// The amount of dispatched KB entries after which all expired entries are removed
+///
+/// Expired entries are filtered on retrieve, removing them is only necessary to free memory.
+const KB_SWEEP_INTERVAL: u64 = 1024;
+
+/// The KB entries of a single scan
+#[derive(Default)]
+struct ScanKbs {
+    entries: HashMap<String, Vec<(u64, Kb)>>,
+    /// The amount of entries of all keys
+    count: usize,
+}
+
+impl ScanKbs {
+    fn get(&self, key: &str) -> Option<&Vec<(u64, Kb)>> {
+        self.entries.get(key)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&String, &Vec<(u64, Kb)>)> {
+        self.entries.iter()
+    }
+
+    fn push(&mut self, sequence: u64, kb: Kb) {
+        self.entries
+            .entry(kb.key.clone())
+            .or_default()
+            .push((sequence, kb));
+        self.count += 1;
+    }
+
+    /// Removes all entries of a key, returns true when there were any
+    fn remove(&mut self, key: &str) -> bool {
+        match self.entries.remove(key) {
+            Some(kbs) => {
+                self.count -= kbs.len();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the expired entries of a key, returns true when there were any
+    fn expire_key(&mut self, key: &str, now: u64) -> bool {
+        let Some(kbs) = self.entries.get_mut(key) else {
+            return false;
+        };
+        let before = kbs.len();
+        kbs.retain(|(_, x)| !x.is_expired(now));
+        let removed = before - kbs.len();
+        if kbs.is_empty() {
+            self.entries.remove(key);
+        }
+        self.count -= removed;
+        removed > 0
+    }
+
+    /// Removes all expired entries, returns true when there were any
+    fn expire(&mut self, now: u64) -> bool {
+        let before = self.count;
+        self.entries.retain(|_, kbs| {
+            kbs.retain(|(_, x)| !x.is_expired(now));
+            !kbs.is_empty()
+        });
+        self.count = self.entries.values().map(|x| x.len()).sum();
+        self.count < before
+    }
+
+    /// Removes the entry with the lowest sequence number
+    fn remove_oldest(&mut self) {
+        // entries of a key are stored in order of insertion
+        let oldest = self
+            .entries
+            .iter()
+            .filter_map(|(k, x)| x.first().map(|(s, _)| (*s, k.clone())))
+            .min();
+        if let Some((_, key)) = oldest {
+            if let Some(kbs) = self.entries.get_mut(&key) {
+                kbs.remove(0);
+                self.count -= 1;
+                if kbs.is_empty() {
+                    self.entries.remove(&key);
+                }
+            }
+        }
+    }
+}
 
 /// Vts are using a relative file path as a key. This should make includes, script_dependency
 /// lookups relative simple.
 type Vts = HashMap<String, item::Nvt>;
 
+/// Returns the current unix timestamp in seconds
+pub type Clock = Arc<dyn Fn() -> u64 + Send + Sync>;
+
+/// Returns the current unix timestamp of the system in seconds
+fn system_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or_default()
+}
+
+/// Defines what happens when a KB item is dispatched into a full KB
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KbOverflow {
+    /// Waits until entries are removed by expiring or replacing them
+    ///
+    /// Returns StorageError::Retry when no entry got removed within the given duration.
+    Block(Duration),
+    /// Removes the oldest entry of the scan
+    DropOldest,
+    /// Returns StorageError::Retry
+    Reject,
+}
+
 /// Is a in-memory dispatcher that behaves like a Storage.
 #[derive(Default)]
 pub struct DefaultDispatcher {
@@ -284,6 +436,15 @@ pub struct DefaultDispatcher {
     feed_version: Arc<RwLock<String>>,
     advisories: Arc<RwLock<HashSet<NotusAdvisory>>>,
     kbs: Arc<RwLock<Kbs>>,
+    /// Used to expire KB entries, when None the system time is used
+    clock: Option<Clock>,
+    /// The maximum amount of KB entries per scan and how to handle overflows
+    kb_limit: Option<(usize, KbOverflow)>,
+    sequence: AtomicU64,
+    /// Notifies blocked dispatches when KB entries are removed
+    kb_freed: Condvar,
+    /// The amount of dispatches waiting for kb_freed
+    kb_waiting: Mutex<usize>,
 }
 
 impl DefaultDispatcher {
@@ -295,6 +456,23 @@ impl DefaultDispatcher {
         }
     }
 
+    /// Sets the clock used to expire KB entries
+    ///
+    /// Without a clock the system time is used.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Limits the amount of KB entries per scan
+    ///
+    /// When a KB is full the overflow policy decides if a dispatch blocks until entries are
+    /// removed, drops the oldest entry or is rejected. The capacity is at least one entry.
+    pub fn with_kb_limit(mut self, capacity: usize, overflow: KbOverflow) -> Self {
+        self.kb_limit = Some((capacity.max(1), overflow));
+        self
+    }
+
     /// Cleanses stored data.
     pub fn cleanse(&self) -> Result<(), StorageError> {
         // TODO cleanse at least kbs, may rest?
@@ -329,20 +507,72 @@ impl DefaultDispatcher {
         Ok(())
     }
 
-    fn cache_kb(&self, scan_id: &str, kb: Kb) -> Result<(), StorageError> {
-        let mut data = self.kbs.as_ref().write()?;
-        if let Some(scan_entry) = data.get_mut(scan_id) {
-            if let Some(kb_entry) = scan_entry.get_mut(&kb.key) {
-                kb_entry.push(kb);
+    /// Stores a KB entry, when replace is set the previous entries of the key are removed
+    ///
+    /// Removing the previous entries and storing the new one happen under the same write lock.
+    fn cache_kb(&self, scan_id: &str, kb: Kb, replace: bool) -> Result<(), StorageError> {
+        let started = Instant::now();
+        loop {
+            let now = self.now();
+            let mut data = self.kbs.as_ref().write()?;
+            let scan_entry = data.entry(scan_id.to_string()).or_default();
+            // expired entries are filtered on retrieve, so they are only removed when their key
+            // is written, when the KB is full or occasionally to free memory
+            let sweep = self
+                .sequence
+                .load(Ordering::Relaxed)
+                .is_multiple_of(KB_SWEEP_INTERVAL);
+            let mut freed = if sweep {
+                scan_entry.expire(now)
             } else {
-                scan_entry.insert(kb.key.clone(), vec![kb]);
+                scan_entry.expire_key(&kb.key, now)
+            };
+            if replace {
+                freed |= scan_entry.remove(&kb.key);
             }
-        } else {
-            let mut scan_entry = HashMap::new();
-            scan_entry.insert(kb.key.clone(), vec![kb]);
-            data.insert(scan_id.to_string(), scan_entry);
+            if let Some((capacity, overflow)) = self.kb_limit {
+                if scan_entry.count >= capacity && !sweep {
+                    freed |= scan_entry.expire(now);
+                }
+                if freed {
+                    self.kb_freed.notify_all();
+                }
+                if scan_entry.count >= capacity {
+                    match overflow {
+                        KbOverflow::Reject => {
+                            return Err(StorageError::Retry(format!(
+                                "the KB of {scan_id} is full with {capacity} entries"
+                            )))
+                        }
+                        KbOverflow::DropOldest => {
+                            while scan_entry.count >= capacity {
+                                scan_entry.remove_oldest();
+                            }
+                        }
+                        KbOverflow::Block(max_wait) => {
+                            drop(data);
+                            let remaining = max_wait.saturating_sub(started.elapsed());
+                            if remaining.is_zero() {
+                                return Err(StorageError::Retry(format!(
+                                    "the KB of {scan_id} is still full with {capacity} entries after {max_wait:?}"
+                                )));
+                            }
+                            let mut waiting = self.kb_waiting.lock()?;
+                            *waiting += 1;
+                            // the timeout rechecks for expired entries and missed notifications
+                            let (mut waiting, _) = self
+                                .kb_freed
+                                .wait_timeout(waiting, remaining.min(Duration::from_millis(100)))?;
+                            *waiting -= 1;
+                            continue;
+                        }
+                    }
+                }
+            }
+            let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+            scan_entry.push(sequence, kb);
+            return Ok(());
         }
-        Ok(())
     }
 
     fn cache_notus_advisory(&self, adv: NotusAdvisory) -> Result<(), StorageError> {
@@ -368,7 +598,7 @@ impl Dispatcher for DefaultDispatcher {
     fn dispatch(&self, key: &ContextKey, scope: Field) -> Result<(), StorageError> {
         match scope {
             Field::NVT(x) => self.cache_nvt_field(key.as_ref(), x)?,
-            Field::KB(x) => self.cache_kb(key.as_ref(), x)?,
+            Field::KB(x) => self.cache_kb(key.as_ref(), x, false)?,
             Field::NotusAdvisory(x) => {
                 if let Some(x) = *x {
                     self.cache_notus_advisory(x)?
@@ -378,6 +608,20 @@ impl Dispatcher for DefaultDispatcher {
         Ok(())
     }
 
+    fn dispatch_replace(&self, key: &ContextKey, scope: Field) -> Result<(), StorageError> {
+        match scope {
+            Field::KB(x) => self.cache_kb(key.as_ref(), x, true),
+            x => self.dispatch(key, x),
+        }
+    }
+
+    fn now(&self) -> u64 {
+        match &self.clock {
+            Some(clock) => clock(),
+            None => system_time(),
+        }
+    }
+
     fn on_exit(&self) -> Result<(), StorageError> {
         if !self.dirty {
             self.cleanse()?;
@@ -443,18 +687,40 @@ impl Retriever for DefaultDispatcher {
                 Ok(Box::new(data.into_iter()))
             }
             Retrieve::KB(x) => {
+                let now = self.now();
                 let kbs = self.kbs.as_ref().read()?;
                 // TODO: maybe return all when x is empty?
                 if let Some(kbs) = kbs.get(key.as_ref()) {
                     if let Some(kbs) = kbs.get(&x) {
+                        let kbs: Vec<Field> = kbs
+                            .iter()
+                            .filter(|(_, x)| !x.is_expired(now))
+                            .map(|(_, x)| x.clone().into())
+                            .collect();
                         let data = InMemoryDataWrapper {
-                            inner: Box::new(kbs.clone().into_iter().map(|x| x.into())),
+                            inner: Box::new(kbs.into_iter()),
                         };
                         return Ok(Box::new(data.into_iter()));
                     }
                 }
                 Ok(Box::new(vec![].into_iter()))
             }
+            Retrieve::KBPattern(x) => {
+                let now = self.now();
+                let kbs = self.kbs.as_ref().read()?;
+                let kbs = kbs
+                    .get(key.as_ref())
+                    .map(|kbs| {
+                        kbs.iter()
+                            .filter(|(k, _)| kb_key_matches(&x, k))
+                            .flat_map(|(_, v)| v.iter().map(|(_, x)| x))
+                            .filter(|x| !x.is_expired(now))
+                            .map(|x| x.clone().into())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                Ok(Box::new(InMemoryDataWrapper::new(kbs)))
+            }
             Retrieve::NotusAdvisory(x) => {
                 let data = self.advisories.as_ref().read()?.clone();
                 match x {
@@ -529,7 +795,7 @@ impl Retriever for DefaultDispatcher {
                 tracing::warn!(kb=?x, "currently it is assumed that notus advisories are handled as vt, please use Retrieve::NVT for now.");
                 Ok(Box::new(vec![].into_iter()))
             }
-            Retrieve::KB(x) => {
+            Retrieve::KB(x) | Retrieve::KBPattern(x) => {
                 // are there use cases to get a KB outside of a scan?
                 tracing::warn!(
                     kb = x,
@@ -589,4 +855,149 @@ mod tests {
         );
         Ok(())
     }
+
+    fn port(x: i64) -> Field {
+        Kb {
+            key: "Ports/tcp".to_owned(),
+            value: Primitive::Number(x),
+            expire: None,
+        }
+        .into()
+    }
+
+    fn ports(storage: &DefaultDispatcher, key: &ContextKey) -> Vec<i64> {
+        let mut result: Vec<_> = storage
+            .retrieve(key, Retrieve::KB("Ports/tcp".to_owned()))
+            .unwrap()
+            .map(|x| match x {
+                KB(Kb {
+                    value: Primitive::Number(x),
+                    ..
+                }) => x,
+                x => panic!("unexpected field {x:?}"),
+            })
+            .collect();
+        result.sort();
+        result
+    }
+
+    #[test]
+    pub fn kb_limit_reject() {
+        let storage = DefaultDispatcher::default().with_kb_limit(2, KbOverflow::Reject);
+        let key = ContextKey::Scan("full".to_owned());
+        storage.dispatch(&key, port(1)).unwrap();
+        storage.dispatch(&key, port(2)).unwrap();
+        assert!(matches!(
+            storage.dispatch(&key, port(3)),
+            Err(StorageError::Retry(_))
+        ));
+        assert_eq!(ports(&storage, &key), vec![1, 2]);
+        // other scans have their own limit
+        let other = ContextKey::Scan("other".to_owned());
+        storage.dispatch(&other, port(3)).unwrap();
+        assert_eq!(ports(&storage, &other), vec![3]);
+    }
+
+    #[test]
+    pub fn kb_limit_drop_oldest() {
+        let storage = DefaultDispatcher::default().with_kb_limit(2, KbOverflow::DropOldest);
+        let key = ContextKey::Scan("full".to_owned());
+        for x in 1..=2 {
+            storage.dispatch(&key, port(x)).unwrap();
+        }
+        let banner = Kb {
+            key: "banner".to_owned(),
+            value: Primitive::String("ssh".to_owned()),
+            expire: None,
+        };
+        storage.dispatch(&key, banner.into()).unwrap();
+        assert_eq!(ports(&storage, &key), vec![2]);
+        storage.dispatch(&key, port(3)).unwrap();
+        assert_eq!(ports(&storage, &key), vec![3]);
+        assert_eq!(
+            storage
+                .retrieve(&key, Retrieve::KB("banner".to_owned()))
+                .unwrap()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    pub fn kb_limit_block() {
+        let storage = DefaultDispatcher::default()
+            .with_kb_limit(2, KbOverflow::Block(Duration::from_secs(10)));
+        let key = ContextKey::Scan("full".to_owned());
+        storage.dispatch(&key, port(1)).unwrap();
+        storage.dispatch(&key, port(2)).unwrap();
+        std::thread::scope(|s| {
+            let blocked = s.spawn(|| storage.dispatch(&key, port(3)));
+            // waits until the dispatch is blocked by the full KB
+            while *storage.kb_waiting.lock().unwrap() == 0 {
+                std::thread::yield_now();
+            }
+            assert!(!blocked.is_finished());
+            assert_eq!(ports(&storage, &key), vec![1, 2]);
+            // replacing removes both entries and adds one, so one slot is free
+            storage.dispatch_replace(&key, port(4)).unwrap();
+            blocked.join().unwrap().unwrap();
+        });
+        assert_eq!(ports(&storage, &key), vec![3, 4]);
+    }
+
+    #[test]
+    pub fn kb_limit_block_gives_up() {
+        let storage = DefaultDispatcher::default()
+            .with_kb_limit(2, KbOverflow::Block(Duration::from_millis(150)));
+        let key = ContextKey::Scan("full".to_owned());
+        storage.dispatch(&key, port(1)).unwrap();
+        storage.dispatch(&key, port(2)).unwrap();
+        let started = Instant::now();
+        assert!(matches!(
+            storage.dispatch(&key, port(3)),
+            Err(StorageError::Retry(_))
+        ));
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(ports(&storage, &key), vec![1, 2]);
+    }
+
+    #[test]
+    pub fn kb_limit_expired_entries_are_freed() {
+        let now = Arc::new(AtomicU64::new(100));
+        let clock = now.clone();
+        let storage = DefaultDispatcher::default()
+            .with_clock(Arc::new(move || clock.load(Ordering::SeqCst)))
+            .with_kb_limit(2, KbOverflow::Reject);
+        assert_eq!(storage.now(), 100);
+        let key = ContextKey::Scan("full".to_owned());
+        let transient = Kb {
+            key: "transient".to_owned(),
+            value: Primitive::Number(0),
+            expire: Some(110),
+        };
+        storage.dispatch(&key, transient.into()).unwrap();
+        storage.dispatch(&key, port(1)).unwrap();
+        assert!(storage.dispatch(&key, port(2)).is_err());
+        now.store(110, Ordering::SeqCst);
+        storage.dispatch(&key, port(2)).unwrap();
+        assert_eq!(ports(&storage, &key), vec![1, 2]);
+    }
+
+    #[test]
+    pub fn dispatch_from_multiple_threads() -> Result<(), StorageError> {
+        let storage = DefaultDispatcher::default();
+        let key = ContextKey::Scan("shared".to_owned());
+        std::thread::scope(|s| {
+            for t in 0..2 {
+                let (storage, key) = (&storage, &key);
+                s.spawn(move || {
+                    for i in 0..100 {
+                        storage.dispatch(key, port(t * 100 + i)).unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(ports(&storage, &key), (0..200).collect::<Vec<_>>());
+        Ok(())
+    }
 }
@@ -16,6 +16,10 @@ pub enum Retrieve {
     NVT(Option<NVTKey>),
     /// Knowledge Base item
     KB(String),
+    /// Knowledge Base items whose keys match the pattern
+    ///
+    /// The pattern is anchored and `*` matches any sequence of characters, see [kb_key_matches].
+    KBPattern(String),
     /// Metadata of the Notus advisory
     NotusAdvisory(Option<String>),
 }
@@ -25,7 +29,7 @@ impl Retrieve {
     pub fn scope(&self) -> &str {
         match self {
             Retrieve::NVT(_) => "nvt",
-            Retrieve::KB(_) => "kb",
+            Retrieve::KB(_) | Retrieve::KBPattern(_) => "kb",
             Retrieve::NotusAdvisory(_) => "notus",
         }
     }
@@ -77,11 +81,45 @@ impl Retrieve {
                 }
             }
 
+            Retrieve::KBPattern(p) => {
+                if let Field::KB(kb) = field {
+                    kb_key_matches(p, &kb.key)
+                } else {
+                    false
+                }
+            }
+
             Retrieve::NotusAdvisory(_) => matches!(field, Field::NotusAdvisory(_)),
         }
     }
 }
 
+/// Returns true when the key of a knowledge base item matches the pattern
+///
+/// The whole key must match the pattern. `*` matches any sequence of characters including `/`,
+/// all other characters match themselves. E.g. `Ports/tcp/*` matches `Ports/tcp/22` but not
+/// `Ports/udp/53`.
+pub fn kb_key_matches(pattern: &str, key: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // split always returns at least one element
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = key.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    let Some(last) = parts.pop() else {
+        // no wildcard within the pattern
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
 /// Result of a heap stored iterator or StorageError
 pub type FieldResult = Result<Box<dyn Iterator<Item = Field>>, StorageError>;
 
@@ -135,3 +173,25 @@ impl Retriever for NoOpRetriever {
         Ok(Box::new(vec![].into_iter()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::kb_key_matches;
+
+    #[test]
+    fn kb_pattern() {
+        assert!(kb_key_matches("Ports/tcp/*", "Ports/tcp/22"));
+        assert!(kb_key_matches("Ports/tcp/*", "Ports/tcp/"));
+        assert!(kb_key_matches("Ports/*/22", "Ports/tcp/22"));
+        assert!(kb_key_matches("*/22", "Ports/tcp/22"));
+        assert!(kb_key_matches("*", "Ports/tcp/22"));
+        assert!(kb_key_matches("Ports/tcp/22", "Ports/tcp/22"));
+        assert!(kb_key_matches("a*b*b", "abb"));
+        assert!(!kb_key_matches("a*b*b", "ab"));
+        assert!(!kb_key_matches("Ports/tcp/*", "Ports/udp/53"));
+        assert!(!kb_key_matches("Ports/tcp/*", "Host/Ports/tcp/22"));
+        assert!(!kb_key_matches("Ports/tcp/2", "Ports/tcp/22"));
+        assert!(!kb_key_matches("Ports/tcp/?2", "Ports/tcp/22"));
+        assert!(!kb_key_matches("Ports/tcp/[0-9]*", "Ports/tcp/22"));
+    }
+}